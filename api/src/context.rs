@@ -41,6 +41,7 @@ use aptos_types::{
         state_value::StateValue,
     },
     transaction::{SignedTransaction, TransactionWithProof, Version},
+    write_set::WriteOp,
 };
 use aptos_utils::aptos_try;
 use aptos_vm::data_cache::AsMoveResolver;
@@ -50,12 +51,15 @@ use move_core_types::{
     move_resource::MoveResource,
     resolver::ModuleResolver,
 };
+use once_cell::sync::OnceCell;
 use std::{
+    cmp::Ordering,
     collections::{BTreeMap, HashMap},
     ops::{Bound::Included, Deref},
     sync::{Arc, RwLock, RwLockWriteGuard},
-    time::Instant,
+    time::{Duration, Instant},
 };
+use tokio::sync::{watch, OwnedSemaphorePermit, Semaphore};
 
 // Context holds application scope context
 #[derive(Clone)]
@@ -67,6 +71,8 @@ pub struct Context {
     gas_schedule_cache: Arc<RwLock<GasScheduleCache>>,
     gas_estimation_cache: Arc<RwLock<GasEstimationCache>>,
     gas_limit_cache: Arc<RwLock<GasLimitCache>>,
+    ledger_updates: Arc<OnceCell<watch::Receiver<Version>>>,
+    event_stream_connections: Arc<Semaphore>,
 }
 
 impl std::fmt::Debug for Context {
@@ -82,6 +88,7 @@ impl Context {
         mp_sender: MempoolClientSender,
         node_config: NodeConfig,
     ) -> Self {
+        let max_event_stream_connections = node_config.api.max_event_stream_connections;
         Self {
             chain_id,
             db,
@@ -102,6 +109,8 @@ impl Context {
                 block_executor_onchain_config: OnChainExecutionConfig::default_if_missing()
                     .block_executor_onchain_config(),
             })),
+            ledger_updates: Arc::new(OnceCell::new()),
+            event_stream_connections: Arc::new(Semaphore::new(max_event_stream_connections)),
         }
     }
 
@@ -121,6 +130,14 @@ impl Context {
         self.node_config.api.max_account_modules_page_size
     }
 
+    pub fn max_view_function_versions(&self) -> usize {
+        self.node_config.api.max_view_function_versions
+    }
+
+    pub fn max_resource_query_selectors(&self) -> usize {
+        self.node_config.api.max_resource_query_selectors
+    }
+
     pub fn latest_state_view(&self) -> Result<DbStateView> {
         self.db.latest_state_checkpoint_view()
     }
@@ -171,6 +188,65 @@ impl Context {
         self.node_config.api.failpoints_enabled
     }
 
+    pub fn event_stream_enabled(&self) -> bool {
+        self.node_config.api.event_stream_enabled
+    }
+
+    pub fn max_event_stream_subscriptions(&self) -> usize {
+        self.node_config.api.max_event_stream_subscriptions
+    }
+
+    pub fn max_event_stream_backfill_versions(&self) -> u64 {
+        self.node_config.api.max_event_stream_backfill_versions
+    }
+
+    /// Attempts to reserve a slot for a new event stream connection. The
+    /// slot is released when the returned permit is dropped.
+    pub fn try_acquire_event_stream_permit(&self) -> Option<OwnedSemaphorePermit> {
+        self.event_stream_connections
+            .clone()
+            .try_acquire_owned()
+            .ok()
+    }
+
+    /// Returns a receiver that is notified with the latest committed version
+    /// whenever the ledger is updated. The first call spawns a single task
+    /// (on the current runtime) that watches the ledger on behalf of all
+    /// subscribers, so this must be called from within a tokio runtime.
+    pub fn subscribe_to_ledger_updates(&self) -> Result<watch::Receiver<Version>> {
+        self.ledger_updates
+            .get_or_try_init(|| {
+                let latest_version = self.db.get_latest_version()?;
+                let (sender, receiver) = watch::channel(latest_version);
+
+                let db = self.db.clone();
+                let poll_interval =
+                    Duration::from_millis(self.node_config.api.event_stream_poll_interval_ms);
+                tokio::spawn(async move {
+                    let mut interval = tokio::time::interval(poll_interval);
+                    loop {
+                        interval.tick().await;
+                        match db.get_latest_version() {
+                            Ok(latest_version) => {
+                                if *sender.borrow() != latest_version {
+                                    let _ = sender.send(latest_version);
+                                }
+                            },
+                            Err(error) => {
+                                warn!(
+                                    "Failed to get the latest version for ledger updates: {}",
+                                    error
+                                );
+                            },
+                        }
+                    }
+                });
+
+                Ok(receiver)
+            })
+            .map(|receiver| receiver.clone())
+    }
+
     pub fn max_submit_transaction_batch_size(&self) -> usize {
         self.node_config.api.max_submit_transaction_batch_size
     }
@@ -457,6 +533,89 @@ impl Context {
         Ok((kvs, next_key))
     }
 
+    /// Returns the changes to the state items of the account between the start and end
+    /// versions, in storage order, starting at `start_state_key`. At most `limit` changes are
+    /// returned and at most `MAX_REQUEST_LIMIT` unchanged state items are scanned per request,
+    /// so a page can hold fewer than `limit` changes. The returned state key is the cursor
+    /// for the next page (if any).
+    pub fn get_state_diff_by_pagination(
+        &self,
+        address: AccountAddress,
+        start_state_key: Option<&StateKey>,
+        start_version: u64,
+        end_version: u64,
+        limit: u64,
+    ) -> Result<(Vec<(StateKey, WriteOp)>, Option<StateKey>)> {
+        // Returns the next state item of the iterator, with its encoded key for ordering
+        fn next_item(
+            iter: &mut dyn Iterator<Item = Result<(StateKey, StateValue)>>,
+        ) -> Result<Option<(Vec<u8>, StateKey, StateValue)>> {
+            iter.next()
+                .transpose()?
+                .map(|(state_key, state_value)| Ok((state_key.encode()?, state_key, state_value)))
+                .transpose()
+        }
+
+        let key_prefix = StateKeyPrefix::from(address);
+        let mut start_iter = self.db.get_prefixed_state_value_iterator(
+            &key_prefix,
+            start_state_key,
+            start_version,
+        )?;
+        let mut end_iter =
+            self.db
+                .get_prefixed_state_value_iterator(&key_prefix, start_state_key, end_version)?;
+        let mut next_start_item = next_item(&mut start_iter)?;
+        let mut next_end_item = next_item(&mut end_iter)?;
+
+        // Merge the (ordered) state items at both versions
+        let mut changes = vec![];
+        let mut num_scanned_items = 0;
+        loop {
+            let ordering = match (&next_start_item, &next_end_item) {
+                (None, None) => return Ok((changes, None)),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((start_key, _, _)), Some((end_key, _, _))) => start_key.cmp(end_key),
+            };
+            let (state_key, write_op) = match ordering {
+                Ordering::Less => {
+                    let (_, state_key, _) = next_start_item.take().expect("Item must exist!");
+                    next_start_item = next_item(&mut start_iter)?;
+                    (state_key, Some(WriteOp::Deletion))
+                },
+                Ordering::Greater => {
+                    let (_, state_key, end_value) = next_end_item.take().expect("Item must exist!");
+                    next_end_item = next_item(&mut end_iter)?;
+                    (
+                        state_key,
+                        Some(WriteOp::Creation(end_value.bytes().clone())),
+                    )
+                },
+                Ordering::Equal => {
+                    let (_, _, start_value) = next_start_item.take().expect("Item must exist!");
+                    let (_, state_key, end_value) = next_end_item.take().expect("Item must exist!");
+                    next_start_item = next_item(&mut start_iter)?;
+                    next_end_item = next_item(&mut end_iter)?;
+                    let write_op = (start_value.bytes() != end_value.bytes())
+                        .then(|| WriteOp::Modification(end_value.bytes().clone()));
+                    (state_key, write_op)
+                },
+            };
+
+            // Stop once the page is full, or too many items have been scanned
+            if num_scanned_items >= MAX_REQUEST_LIMIT
+                || (write_op.is_some() && changes.len() as u64 >= limit)
+            {
+                return Ok((changes, Some(state_key)));
+            }
+            num_scanned_items += 1;
+            if let Some(write_op) = write_op {
+                changes.push((state_key, write_op));
+            }
+        }
+    }
+
     pub fn get_modules_by_pagination(
         &self,
         address: AccountAddress,
@@ -0,0 +1,432 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::context::Context;
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_api_types::{AsConverter, TransactionOnChainData};
+use aptos_logger::warn;
+use aptos_types::{
+    account_address::AccountAddress,
+    contract_event::{ContractEvent, EventWithVersion},
+    event::EventKey,
+    state_store::state_key::StateKeyInner,
+    transaction::Version,
+};
+use aptos_vm::data_cache::AsMoveResolver;
+use futures::{Stream, StreamExt};
+use move_core_types::{language_storage::TypeTag, parser::parse_type_tag};
+use poem::{
+    handler,
+    http::StatusCode,
+    web::{
+        sse::{Event, SSE},
+        Data,
+    },
+    Request,
+};
+use std::{
+    cmp::min,
+    collections::{HashSet, VecDeque},
+    str::FromStr,
+    sync::Arc,
+    time::Duration,
+};
+use tokio::sync::{watch, OwnedSemaphorePermit};
+
+/// The interval at which keep-alive messages are sent on idle event streams
+const KEEP_ALIVE_INTERVAL_SECS: u64 = 15;
+
+// The query parameters accepted by the event stream endpoint
+const ADDRESS_PARAM: &str = "address";
+const EVENT_HANDLE_PARAM: &str = "event_handle";
+const EVENT_TYPE_PARAM: &str = "event_type";
+const START_VERSION_PARAM: &str = "start_version";
+
+// The types of items sent on an event stream
+pub const ERROR_ITEM_TYPE: &str = "error";
+pub const EVENT_ITEM_TYPE: &str = "event";
+pub const TRANSACTION_ITEM_TYPE: &str = "transaction";
+
+/// Streams committed events and transactions to the client using Server-Sent
+/// Events. Clients subscribe using the (repeatable) query parameters:
+/// - `event_handle`: an event handle, given as `{address}/{creation_number}`.
+/// - `event_type`: a Move event type, e.g., `0x1::coin::DepositEvent`.
+/// - `address`: an account address. All committed transactions sent by, or
+///   writing resources under, the address are streamed.
+///
+/// The stream starts at the `start_version` query parameter (or the next
+/// committed version, if not specified). The start version can be at most
+/// `max_event_stream_backfill_versions` behind the latest committed version.
+/// Every item carries the version it was committed at as its id, so clients
+/// can resume from the last version they observed (items at that version
+/// may be delivered again).
+#[handler]
+pub fn event_stream_poem(context: Data<&Arc<Context>>, request: &Request) -> poem::Result<SSE> {
+    if !context.event_stream_enabled() {
+        return Err(poem::Error::from_string(
+            "The event stream API is disabled on this node",
+            StatusCode::FORBIDDEN,
+        ));
+    }
+
+    // Parse the subscriptions
+    let query_pairs: Vec<(String, String)> =
+        url::form_urlencoded::parse(request.uri().query().unwrap_or("").as_bytes())
+            .into_owned()
+            .collect();
+    let filter =
+        EventStreamFilter::from_query_pairs(&query_pairs, context.max_event_stream_subscriptions())
+            .map_err(|error| {
+                poem::Error::from_string(error.to_string(), StatusCode::BAD_REQUEST)
+            })?;
+    let start_version = parse_start_version(&query_pairs)
+        .map_err(|error| poem::Error::from_string(error.to_string(), StatusCode::BAD_REQUEST))?;
+
+    // Reserve a connection slot for the stream
+    let connection_permit = context.try_acquire_event_stream_permit().ok_or_else(|| {
+        poem::Error::from_string(
+            "Too many event stream connections! Try again later.",
+            StatusCode::SERVICE_UNAVAILABLE,
+        )
+    })?;
+
+    // Subscribe to ledger updates and create the stream
+    let ledger_updates = context.subscribe_to_ledger_updates().map_err(|error| {
+        poem::Error::from_string(error.to_string(), StatusCode::INTERNAL_SERVER_ERROR)
+    })?;
+    let next_version = *ledger_updates.borrow() + 1;
+    let start_version = match start_version {
+        Some(start_version) => {
+            check_start_version(
+                start_version,
+                next_version,
+                context.max_event_stream_backfill_versions(),
+            )
+            .map_err(|error| {
+                poem::Error::from_string(error.to_string(), StatusCode::BAD_REQUEST)
+            })?;
+            start_version
+        },
+        None => next_version,
+    };
+    let event_stream = create_event_stream(
+        context.0.clone(),
+        filter,
+        start_version,
+        ledger_updates,
+        connection_permit,
+    )
+    .map(Event::from);
+
+    Ok(SSE::new(event_stream).keep_alive(Duration::from_secs(KEEP_ALIVE_INTERVAL_SECS)))
+}
+
+/// Returns the `start_version` query parameter (if specified)
+fn parse_start_version(query_pairs: &[(String, String)]) -> Result<Option<Version>> {
+    query_pairs
+        .iter()
+        .find(|(key, _)| key == START_VERSION_PARAM)
+        .map(|(_, value)| {
+            Version::from_str(value)
+                .map_err(|error| format_err!("Invalid start_version {}: {}", value, error))
+        })
+        .transpose()
+}
+
+/// Verifies that the start version is within the backfill range, i.e., at most
+/// `max_backfill_versions` behind (and not after) the next version to be committed
+pub fn check_start_version(
+    start_version: Version,
+    next_version: Version,
+    max_backfill_versions: u64,
+) -> Result<()> {
+    let min_start_version = next_version.saturating_sub(max_backfill_versions);
+    ensure!(
+        start_version >= min_start_version,
+        "start_version {} is too old! The oldest version that can be streamed is {}",
+        start_version,
+        min_start_version
+    );
+    ensure!(
+        start_version <= next_version,
+        "start_version {} is in the future! The next version to be committed is {}",
+        start_version,
+        next_version
+    );
+    Ok(())
+}
+
+/// The set of event handles, event types and addresses a stream subscribes to
+#[derive(Clone, Debug, Default)]
+pub struct EventStreamFilter {
+    event_keys: HashSet<EventKey>,
+    event_types: HashSet<TypeTag>,
+    addresses: HashSet<AccountAddress>,
+}
+
+impl EventStreamFilter {
+    /// Parses the filter from the given query parameters. Returns an error
+    /// if there are no subscriptions, or more than `max_subscriptions`.
+    pub fn from_query_pairs(
+        query_pairs: &[(String, String)],
+        max_subscriptions: usize,
+    ) -> Result<Self> {
+        let mut filter = Self::default();
+        for (key, value) in query_pairs {
+            match key.as_str() {
+                ADDRESS_PARAM => {
+                    let address = AccountAddress::from_str(value)
+                        .map_err(|error| format_err!("Invalid address {}: {}", value, error))?;
+                    filter.addresses.insert(address);
+                },
+                EVENT_HANDLE_PARAM => {
+                    filter.event_keys.insert(parse_event_handle(value)?);
+                },
+                EVENT_TYPE_PARAM => {
+                    let event_type = parse_type_tag(value)
+                        .map_err(|error| format_err!("Invalid event type {}: {}", value, error))?;
+                    filter.event_types.insert(event_type);
+                },
+                START_VERSION_PARAM => {}, // Not a subscription
+                _ => bail!("Unknown query parameter: {}", key),
+            }
+        }
+
+        let num_subscriptions = filter.num_subscriptions();
+        ensure!(
+            num_subscriptions > 0,
+            "At least one {}, {} or {} must be specified!",
+            EVENT_HANDLE_PARAM,
+            EVENT_TYPE_PARAM,
+            ADDRESS_PARAM
+        );
+        ensure!(
+            num_subscriptions <= max_subscriptions,
+            "Too many subscriptions! Found: {}, max: {}",
+            num_subscriptions,
+            max_subscriptions
+        );
+
+        Ok(filter)
+    }
+
+    /// Returns the total number of subscriptions held by the filter
+    pub fn num_subscriptions(&self) -> usize {
+        self.event_keys.len() + self.event_types.len() + self.addresses.len()
+    }
+
+    /// Returns true iff the event matches a subscribed event handle or type
+    fn matches_event(&self, event: &ContractEvent) -> bool {
+        event
+            .event_key()
+            .map(|event_key| self.event_keys.contains(event_key))
+            .unwrap_or(false)
+            || self.event_types.contains(event.type_tag())
+    }
+
+    /// Returns true iff the transaction was sent by, or wrote
+    /// resources under, a subscribed address.
+    fn matches_transaction(&self, transaction: &TransactionOnChainData) -> bool {
+        if self.addresses.is_empty() {
+            return false;
+        }
+
+        let sent_by_address = transaction
+            .transaction
+            .try_as_signed_user_txn()
+            .map(|signed_transaction| self.addresses.contains(&signed_transaction.sender()))
+            .unwrap_or(false);
+        sent_by_address
+            || transaction
+                .changes
+                .iter()
+                .any(|(state_key, _)| match state_key.inner() {
+                    StateKeyInner::AccessPath(access_path) => {
+                        self.addresses.contains(&access_path.address)
+                    },
+                    _ => false,
+                })
+    }
+}
+
+/// Parses an event handle of the form `{address}/{creation_number}`
+fn parse_event_handle(event_handle: &str) -> Result<EventKey> {
+    let (address, creation_number) = event_handle.split_once('/').ok_or_else(|| {
+        format_err!(
+            "Invalid event handle {}, expected {{address}}/{{creation_number}}",
+            event_handle
+        )
+    })?;
+    let address = AccountAddress::from_str(address)
+        .map_err(|error| format_err!("Invalid event handle address {}: {}", address, error))?;
+    let creation_number = u64::from_str(creation_number).map_err(|error| {
+        format_err!(
+            "Invalid event handle creation number {}: {}",
+            creation_number,
+            error
+        )
+    })?;
+    Ok(EventKey::new(creation_number, address))
+}
+
+/// A single item sent on an event stream
+#[derive(Clone, Debug)]
+pub struct EventStreamItem {
+    pub version: Version,        // The version the item was committed at
+    pub item_type: &'static str, // The type of the item (e.g., event or transaction)
+    pub data: String,            // The JSON representation of the item
+}
+
+impl From<EventStreamItem> for Event {
+    fn from(item: EventStreamItem) -> Self {
+        Event::message(item.data)
+            .event_type(item.item_type)
+            .id(item.version.to_string())
+    }
+}
+
+/// Creates a stream of the events and transactions matching the filter,
+/// starting at `start_version`. The stream follows the ledger as new
+/// versions are committed, and ends after the first error.
+pub fn create_event_stream(
+    context: Arc<Context>,
+    filter: EventStreamFilter,
+    start_version: Version,
+    ledger_updates: watch::Receiver<Version>,
+    connection_permit: OwnedSemaphorePermit,
+) -> impl Stream<Item = EventStreamItem> {
+    let state = EventStreamState {
+        context,
+        filter: Arc::new(filter),
+        next_version: start_version,
+        ledger_updates,
+        pending_items: VecDeque::new(),
+        terminated: false,
+        _connection_permit: connection_permit,
+    };
+    futures::stream::unfold(state, |mut state| async move {
+        state.next_item().await.map(|item| (item, state))
+    })
+}
+
+/// The state of a single event stream connection
+struct EventStreamState {
+    context: Arc<Context>,
+    filter: Arc<EventStreamFilter>,
+    next_version: Version,
+    ledger_updates: watch::Receiver<Version>,
+    pending_items: VecDeque<EventStreamItem>,
+    terminated: bool,
+    _connection_permit: OwnedSemaphorePermit, // Released when the stream is dropped
+}
+
+impl EventStreamState {
+    /// Returns the next item on the stream, waiting for
+    /// ledger updates if all committed versions have been read.
+    async fn next_item(&mut self) -> Option<EventStreamItem> {
+        loop {
+            if let Some(item) = self.pending_items.pop_front() {
+                return Some(item);
+            }
+            if self.terminated {
+                return None;
+            }
+
+            // Wait for new versions to be committed
+            let latest_version = *self.ledger_updates.borrow_and_update();
+            if self.next_version > latest_version {
+                if self.ledger_updates.changed().await.is_err() {
+                    return None; // The ledger is no longer being watched
+                }
+                continue;
+            }
+
+            // Read the next batch of transactions from storage
+            let context = self.context.clone();
+            let filter = self.filter.clone();
+            let start_version = self.next_version;
+            let result = tokio::task::spawn_blocking(move || {
+                read_items(&context, &filter, start_version, latest_version)
+            })
+            .await
+            .map_err(|error| format_err!("Failed to join the event stream task: {}", error))
+            .and_then(|result| result);
+            match result {
+                Ok((items, next_version)) => {
+                    self.pending_items.extend(items);
+                    self.next_version = next_version;
+                },
+                Err(error) => {
+                    warn!(
+                        "Failed to read items for the event stream at version {}: {}",
+                        start_version, error
+                    );
+                    self.pending_items.push_back(EventStreamItem {
+                        version: start_version,
+                        item_type: ERROR_ITEM_TYPE,
+                        data: serde_json::Value::String(error.to_string()).to_string(),
+                    });
+                    self.terminated = true;
+                },
+            }
+        }
+    }
+}
+
+/// Reads the items matching the filter from a batch of transactions starting
+/// at `start_version`. Returns the items and the next version to read from.
+fn read_items(
+    context: &Context,
+    filter: &EventStreamFilter,
+    start_version: Version,
+    latest_version: Version,
+) -> Result<(Vec<EventStreamItem>, Version)> {
+    let limit = min(
+        latest_version - start_version + 1,
+        context.max_transactions_page_size() as u64,
+    ) as u16;
+    let transactions = context.get_transactions(start_version, limit, latest_version)?;
+    ensure!(
+        !transactions.is_empty(),
+        "No transactions found at version {}",
+        start_version
+    );
+    let next_version = start_version + transactions.len() as u64;
+
+    let state_view = context.latest_state_view()?;
+    let resolver = state_view.as_move_resolver();
+    let converter = resolver.as_converter(context.db.clone());
+
+    let mut items = vec![];
+    for transaction in transactions {
+        let version = transaction.version;
+
+        // Add the matching events
+        let events: Vec<_> = transaction
+            .events
+            .iter()
+            .filter(|event| filter.matches_event(event))
+            .map(|event| EventWithVersion::new(version, event.clone()))
+            .collect();
+        for event in converter.try_into_versioned_events(&events)? {
+            items.push(EventStreamItem {
+                version,
+                item_type: EVENT_ITEM_TYPE,
+                data: serde_json::to_string(&event)?,
+            });
+        }
+
+        // Add the transaction (if it matches)
+        if filter.matches_transaction(&transaction) {
+            let timestamp = context.db.get_block_timestamp(version)?;
+            let transaction = converter.try_into_onchain_transaction(timestamp, transaction)?;
+            items.push(EventStreamItem {
+                version,
+                item_type: TRANSACTION_ITEM_TYPE,
+                data: serde_json::to_string(&transaction)?,
+            });
+        }
+    }
+
+    Ok((items, next_version))
+}
@@ -12,6 +12,7 @@ mod blocks;
 mod check_size;
 pub mod context;
 mod error_converter;
+mod event_stream;
 mod events;
 mod failpoint;
 mod index;
@@ -4,9 +4,9 @@
 
 use crate::{
     accounts::AccountsApi, basic::BasicApi, blocks::BlocksApi, check_size::PostSizeLimit,
-    context::Context, error_converter::convert_error, events::EventsApi, index::IndexApi,
-    log::middleware_log, set_failpoints, state::StateApi, transactions::TransactionsApi,
-    view_function::ViewFunctionApi,
+    context::Context, error_converter::convert_error, event_stream::event_stream_poem,
+    events::EventsApi, index::IndexApi, log::middleware_log, set_failpoints, state::StateApi,
+    transactions::TransactionsApi, view_function::ViewFunctionApi,
 };
 use anyhow::Context as AnyhowContext;
 use aptos_api_types::X_APTOS_CLIENT;
@@ -192,6 +192,12 @@ pub fn attach_poem_to_runtime(
                     .at(
                         "/set_failpoint",
                         poem::get(set_failpoints::set_failpoint_poem).data(context.clone()),
+                    )
+                    // The event stream uses Server-Sent Events, which aren't
+                    // supported by the OpenAPI spec, so we add it manually too.
+                    .at(
+                        "/stream",
+                        poem::get(event_stream_poem).data(context.clone()),
                     ),
             )
             .with(cors)
@@ -5,6 +5,7 @@ use crate::{
     accept_type::AcceptType,
     context::api_spawn_blocking,
     failpoint::fail_point_poem,
+    page::determine_limit,
     response::{
         api_forbidden, build_not_found, module_not_found, resource_not_found, table_item_not_found,
         BadRequestError, BasicErrorWith404, BasicResponse, BasicResponseStatus, BasicResultWith404,
@@ -16,7 +17,8 @@ use anyhow::Context as AnyhowContext;
 use aptos_api_types::{
     verify_module_identifier, Address, AptosErrorCode, AsConverter, IdentifierWrapper,
     MoveModuleBytecode, MoveResource, MoveStructTag, MoveValue, RawStateValueRequest,
-    RawTableItemRequest, TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
+    RawTableItemRequest, ResourceQueryRequest, ResourceQueryResult, StateDiff, StateKeyWrapper,
+    TableItemRequest, VerifyInput, VerifyInputWithRecursion, U64,
 };
 use aptos_state_view::TStateView;
 use aptos_types::{
@@ -25,6 +27,7 @@ use aptos_types::{
 };
 use aptos_vm::data_cache::AsMoveResolver;
 use move_core_types::{
+    identifier::Identifier,
     language_storage::{ModuleId, StructTag},
     resolver::MoveResolver,
 };
@@ -272,6 +275,109 @@ impl StateApi {
         let api = self.clone();
         api_spawn_blocking(move || api.raw_value(&accept_type, request.0, ledger_version.0)).await
     }
+
+    /// Get account state diff
+    ///
+    /// Retrieves the changes to the state (i.e., resources, modules and other state items)
+    /// of a given account between two ledger versions. The changes are returned as write set
+    /// changes that, applied to the account state at the start version, result in the account
+    /// state at the end version. If the end version is not specified in the request, the latest
+    /// ledger version is used.
+    ///
+    /// The changes are returned in pages. A page can hold fewer changes than the limit, so
+    /// keep requesting pages until no cursor is returned in the X-Aptos-Cursor header.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If either of the requested ledger versions has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/accounts/:address/state_diff",
+        method = "get",
+        operation_id = "get_account_state_diff",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn get_account_state_diff(
+        &self,
+        accept_type: AcceptType,
+        /// Address of account with or without a `0x` prefix
+        address: Path<Address>,
+        /// Ledger version of the account state to diff from
+        start_version: Query<U64>,
+        /// Ledger version of the account state to diff to
+        ///
+        /// If not provided, it will be the latest version
+        end_version: Query<Option<U64>>,
+        /// Cursor specifying where to start for pagination
+        ///
+        /// This cursor cannot be derived manually client-side. Instead, you must
+        /// call this endpoint once without this query parameter specified, and
+        /// then use the cursor returned in the X-Aptos-Cursor header in the
+        /// response.
+        start: Query<Option<StateKeyWrapper>>,
+        /// Max number of changes to retrieve
+        ///
+        /// If not provided, defaults to default page size.
+        limit: Query<Option<u16>>,
+    ) -> BasicResultWith404<StateDiff> {
+        fail_point_poem("endpoint_get_account_state_diff")?;
+        self.context
+            .check_api_output_enabled("Get account state diff", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || {
+            api.state_diff(
+                &accept_type,
+                address.0,
+                start_version.0 .0,
+                end_version.0.map(|inner| inner.0),
+                start.0.map(StateKey::from),
+                limit.0,
+            )
+        })
+        .await
+    }
+
+    /// Query account resources
+    ///
+    /// Retrieves a batch of resources, or fields of resources, from any number of accounts at a
+    /// single ledger version. Each selector names an account, a resource type and an optional
+    /// path of field names into the resource, and only the selected values are returned, in the
+    /// same order as the selectors. If an account doesn't hold the selected resource, its value
+    /// is null. If the ledger version is not specified in the request, the latest ledger version
+    /// is used.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If the requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/resources/query",
+        method = "post",
+        operation_id = "query_account_resources",
+        tag = "ApiTags::Accounts"
+    )]
+    async fn query_account_resources(
+        &self,
+        accept_type: AcceptType,
+        /// Selectors of the resources or fields to retrieve
+        request: Json<ResourceQueryRequest>,
+        /// Ledger version to get state of the accounts
+        ///
+        /// If not provided, it will be the latest version
+        ledger_version: Query<Option<U64>>,
+    ) -> BasicResultWith404<Vec<ResourceQueryResult>> {
+        fail_point_poem("endpoint_query_account_resources")?;
+
+        if AcceptType::Bcs == accept_type {
+            return Err(api_forbidden(
+                "Query account resources",
+                "Only JSON is supported as an AcceptType.",
+            ));
+        }
+        self.context
+            .check_api_output_enabled("Query account resources", &accept_type)?;
+
+        let api = self.clone();
+        api_spawn_blocking(move || api.query_resources(&accept_type, request.0, ledger_version.0))
+            .await
+    }
 }
 
 impl StateApi {
@@ -607,4 +713,217 @@ impl StateApi {
             },
         }
     }
+
+    /// Diff the state of an account between two ledger versions
+    ///
+    /// JSON: Convert to WriteSetChanges
+    /// BCS: Leave it as (StateKey, WriteOp) pairs
+    pub fn state_diff(
+        &self,
+        accept_type: &AcceptType,
+        address: Address,
+        start_version: u64,
+        end_version: Option<u64>,
+        start: Option<StateKey>,
+        limit: Option<u16>,
+    ) -> BasicResultWith404<StateDiff> {
+        let (ledger_info, end_version) = self
+            .context
+            .get_latest_ledger_info_and_verify_lookup_version(end_version)?;
+        self.context
+            .get_latest_ledger_info_and_verify_lookup_version::<BasicErrorWith404>(Some(
+                start_version,
+            ))?;
+        if start_version > end_version {
+            return Err(BasicErrorWith404::bad_request_with_code(
+                format!(
+                    "Start version ({}) is greater than end version ({})",
+                    start_version, end_version
+                ),
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            ));
+        }
+
+        let max_page_size = self.context.max_account_resources_page_size();
+        let limit = determine_limit(limit, max_page_size, max_page_size, &ledger_info)?;
+        let (write_ops, next_state_key) = self
+            .context
+            .get_state_diff_by_pagination(
+                address.into(),
+                start.as_ref(),
+                start_version,
+                end_version,
+                limit as u64,
+            )
+            .context(format!(
+                "Failed to diff account state of {} between versions {} and {}",
+                address, start_version, end_version
+            ))
+            .map_err(|err| {
+                BasicErrorWith404::internal_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        match accept_type {
+            AcceptType::Json => {
+                // Deleted resources are decoded with the state at the end version, the same
+                // as the write sets of the transactions that deleted them
+                let state_view =
+                    self.context
+                        .state_view_at_version(end_version)
+                        .map_err(|err| {
+                            BasicErrorWith404::internal_with_code(
+                                err,
+                                AptosErrorCode::InternalError,
+                                &ledger_info,
+                            )
+                        })?;
+                let resolver = state_view.as_move_resolver();
+                let converter = resolver.as_converter(self.context.db.clone());
+                let changes = write_ops
+                    .into_iter()
+                    .map(|(state_key, op)| converter.try_into_write_set_changes(state_key, op))
+                    .collect::<anyhow::Result<Vec<_>>>()
+                    .context("Failed to convert account state diff to write set changes")
+                    .map_err(|err| {
+                        BasicErrorWith404::internal_with_code(
+                            err,
+                            AptosErrorCode::InternalError,
+                            &ledger_info,
+                        )
+                    })?
+                    .into_iter()
+                    .flatten()
+                    .collect();
+
+                let state_diff = StateDiff {
+                    start_version: start_version.into(),
+                    end_version: end_version.into(),
+                    changes,
+                };
+                BasicResponse::try_from_json((state_diff, &ledger_info, BasicResponseStatus::Ok))
+                    .map(|v| v.with_cursor(next_state_key))
+            },
+            AcceptType::Bcs => {
+                BasicResponse::try_from_bcs((write_ops, &ledger_info, BasicResponseStatus::Ok))
+                    .map(|v| v.with_cursor(next_state_key))
+            },
+        }
+    }
+
+    /// Read the selected resources (or fields of resources) at the ledger version
+    ///
+    /// JSON: Convert the selected values to MoveValues
+    /// BCS: Not supported, as fields can't be selected from the encoded resources
+    pub fn query_resources(
+        &self,
+        accept_type: &AcceptType,
+        request: ResourceQueryRequest,
+        ledger_version: Option<U64>,
+    ) -> BasicResultWith404<Vec<ResourceQueryResult>> {
+        let max_selectors = self.context.max_resource_query_selectors();
+        if request.selectors.len() > max_selectors {
+            return Err(BasicErrorWith404::bad_request_with_code_no_info(
+                format!(
+                    "Too many resource selectors! Found: {}, max: {}",
+                    request.selectors.len(),
+                    max_selectors
+                ),
+                AptosErrorCode::InvalidInput,
+            ));
+        }
+
+        // All selectors are read from the same state view, so the values are consistent
+        let (ledger_info, _, state_view) = self
+            .context
+            .state_view(ledger_version.map(|inner| inner.0))?;
+        let resolver = state_view.as_move_resolver();
+        let converter = resolver.as_converter(self.context.db.clone());
+
+        let mut results = Vec::with_capacity(request.selectors.len());
+        for selector in request.selectors {
+            selector
+                .resource_type
+                .verify(0)
+                .context("'resource_type' invalid")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    )
+                })?;
+            let resource_type: StructTag = selector
+                .resource_type
+                .clone()
+                .try_into()
+                .context("Failed to parse given resource type")
+                .map_err(|err| {
+                    BasicErrorWith404::bad_request_with_code(
+                        err,
+                        AptosErrorCode::InvalidInput,
+                        &ledger_info,
+                    )
+                })?;
+            let field_path: Vec<Identifier> = selector
+                .field_path
+                .unwrap_or_default()
+                .into_iter()
+                .map(Identifier::from)
+                .collect();
+
+            let bytes = resolver
+                .get_resource(&selector.address.into(), &resource_type)
+                .context(format!(
+                    "Failed to query DB to check for {} at {}",
+                    resource_type, selector.address
+                ))
+                .map_err(|err| {
+                    BasicErrorWith404::internal_with_code(
+                        err,
+                        AptosErrorCode::InternalError,
+                        &ledger_info,
+                    )
+                })?;
+            let value = match bytes {
+                Some(bytes) => Some(
+                    converter
+                        .try_into_resource_field(&resource_type, &bytes, &field_path)
+                        .context(format!(
+                            "Failed to select value from {} at {}",
+                            resource_type, selector.address
+                        ))
+                        .map_err(|err| {
+                            BasicErrorWith404::bad_request_with_code(
+                                err,
+                                AptosErrorCode::InvalidInput,
+                                &ledger_info,
+                            )
+                        })?,
+                ),
+                None => None,
+            };
+
+            results.push(ResourceQueryResult {
+                address: selector.address,
+                resource_type: selector.resource_type,
+                field_path: field_path.into_iter().map(IdentifierWrapper).collect(),
+                value,
+            });
+        }
+
+        match accept_type {
+            AcceptType::Json => {
+                BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+            },
+            AcceptType::Bcs => Err(api_forbidden(
+                "Query account resources",
+                "This serves only JSON. Use other APIs for BCS.",
+            )),
+        }
+    }
 }
@@ -0,0 +1,168 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{new_test_context, new_test_context_with_config};
+use crate::{
+    context::Context,
+    event_stream::{
+        check_start_version, create_event_stream, EventStreamFilter, EventStreamItem,
+        EVENT_ITEM_TYPE, TRANSACTION_ITEM_TYPE,
+    },
+};
+use aptos_api_test_context::current_function_name;
+use aptos_config::config::{ApiConfig, NodeConfig};
+use aptos_types::transaction::Version;
+use futures::{Stream, StreamExt};
+use std::{sync::Arc, time::Duration};
+use tokio::time::timeout;
+
+const MAX_WAIT_SECS: u64 = 30;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_event_stream_genesis_events() {
+    let context = new_test_context(current_function_name!());
+    let api_context = Arc::new(context.context.clone());
+
+    // Stream the events of the root account from genesis
+    let filter = create_filter(&[("event_handle", "0xa550c18/0")]);
+    let event_stream = create_test_event_stream(api_context, filter, 0);
+    futures::pin_mut!(event_stream);
+
+    // Verify the first event is committed at genesis
+    let item = get_next_item(&mut event_stream).await;
+    assert_eq!(item.version, 0);
+    assert_eq!(item.item_type, EVENT_ITEM_TYPE);
+    let event: serde_json::Value = serde_json::from_str(&item.data).unwrap();
+    assert_eq!(event["guid"]["account_address"], "0xa550c18");
+    assert_eq!(event["guid"]["creation_number"], "0");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_event_stream_new_transactions() {
+    let mut context = new_test_context(current_function_name!());
+    let api_context = Arc::new(context.context.clone());
+
+    // Subscribe to the transactions touching a new account
+    let account = context.gen_account();
+    let filter = create_filter(&[("address", &account.address().to_hex_literal())]);
+    let start_version = context.get_latest_ledger_info().version() + 1;
+    let event_stream = create_test_event_stream(api_context, filter, start_version);
+    futures::pin_mut!(event_stream);
+
+    // Create the account
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&[txn]).await;
+
+    // Verify the account creation is streamed
+    let item = get_next_item(&mut event_stream).await;
+    assert!(item.version >= start_version);
+    assert_eq!(item.item_type, TRANSACTION_ITEM_TYPE);
+    let transaction: serde_json::Value = serde_json::from_str(&item.data).unwrap();
+    assert_eq!(transaction["type"], "user_transaction");
+    assert_eq!(transaction["version"], item.version.to_string());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_event_stream_connection_limit() {
+    let node_config = NodeConfig {
+        api: ApiConfig {
+            max_event_stream_connections: 1,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let context = new_test_context_with_config(current_function_name!(), node_config);
+
+    // Verify only a single connection can be held at once
+    let connection_permit = context.context.try_acquire_event_stream_permit().unwrap();
+    assert!(context.context.try_acquire_event_stream_permit().is_none());
+
+    // Release the connection and verify a new one can be created
+    drop(connection_permit);
+    assert!(context.context.try_acquire_event_stream_permit().is_some());
+}
+
+#[test]
+fn test_event_stream_filter() {
+    // Verify that valid subscriptions are parsed
+    let filter = create_filter(&[
+        ("address", "0x1"),
+        ("event_handle", "0x1/2"),
+        ("event_type", "0x1::coin::DepositEvent"),
+        ("start_version", "10"),
+    ]);
+    assert_eq!(filter.num_subscriptions(), 3);
+
+    // Verify that invalid subscriptions are rejected
+    for query_pairs in [
+        vec![],
+        vec![("start_version", "10")],
+        vec![("address", "invalid")],
+        vec![("event_handle", "0x1")],
+        vec![("event_handle", "0x1/invalid")],
+        vec![("event_type", "0x1::coin")],
+        vec![("unknown", "0x1")],
+        vec![("address", "0x1"), ("address", "0x2"), ("address", "0x3")],
+    ] {
+        let query_pairs = to_query_pairs(&query_pairs);
+        EventStreamFilter::from_query_pairs(&query_pairs, 2).unwrap_err();
+    }
+}
+
+#[test]
+fn test_event_stream_start_version() {
+    // Verify that start versions within the backfill range are accepted
+    check_start_version(100, 100, 10).unwrap();
+    check_start_version(90, 100, 10).unwrap();
+    check_start_version(0, 5, 10).unwrap();
+
+    // Verify that start versions outside the backfill range are rejected
+    check_start_version(89, 100, 10).unwrap_err();
+    check_start_version(0, 100, 10).unwrap_err();
+    check_start_version(101, 100, 10).unwrap_err();
+}
+
+#[test]
+fn test_event_stream_disabled_by_default() {
+    assert!(!ApiConfig::default().event_stream_enabled);
+}
+
+/// Creates an event stream filter from the given query parameters
+fn create_filter(query_pairs: &[(&str, &str)]) -> EventStreamFilter {
+    EventStreamFilter::from_query_pairs(&to_query_pairs(query_pairs), 10).unwrap()
+}
+
+/// Creates an event stream for the given filter, starting at the specified version
+fn create_test_event_stream(
+    context: Arc<Context>,
+    filter: EventStreamFilter,
+    start_version: Version,
+) -> impl Stream<Item = EventStreamItem> {
+    let ledger_updates = context.subscribe_to_ledger_updates().unwrap();
+    let connection_permit = context.try_acquire_event_stream_permit().unwrap();
+    create_event_stream(
+        context,
+        filter,
+        start_version,
+        ledger_updates,
+        connection_permit,
+    )
+}
+
+/// Waits for the next item on the given event stream
+async fn get_next_item(
+    event_stream: &mut (impl Stream<Item = EventStreamItem> + Unpin),
+) -> EventStreamItem {
+    timeout(Duration::from_secs(MAX_WAIT_SECS), event_stream.next())
+        .await
+        .expect("Timed out waiting for the next event stream item!")
+        .expect("The event stream ended unexpectedly!")
+}
+
+/// Converts the given query parameters into owned strings
+fn to_query_pairs(query_pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+    query_pairs
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
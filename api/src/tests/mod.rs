@@ -5,6 +5,7 @@
 mod accounts_test;
 mod blocks_test;
 mod converter_test;
+mod event_stream_test;
 mod events_test;
 mod index_test;
 mod invalid_post_request_test;
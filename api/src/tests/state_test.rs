@@ -298,6 +298,175 @@ async fn test_get_table_item() {
     assert_table_item(ctx, &nested_table, "u8", "u8", 2, 3).await;
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_state_diff() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let start_version = context.get_latest_ledger_info().version();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+    let end_version = context.get_latest_ledger_info().version();
+
+    // The account is created between the two versions
+    let resp = context
+        .get(&get_account_state_diff(
+            &account.address().to_hex_literal(),
+            start_version,
+            Some(end_version),
+        ))
+        .await;
+    assert_eq!(resp["start_version"], json!(start_version.to_string()));
+    assert_eq!(resp["end_version"], json!(end_version.to_string()));
+    let changes = resp["changes"].as_array().unwrap();
+    assert!(changes
+        .iter()
+        .all(|change| change["type"] == "write_resource"));
+    assert!(changes
+        .iter()
+        .any(|change| change["data"]["type"] == "0x1::account::Account"));
+
+    // Diffing a version with itself yields no changes
+    let resp = context
+        .get(&get_account_state_diff(
+            &account.address().to_hex_literal(),
+            end_version,
+            None,
+        ))
+        .await;
+    assert_eq!(resp["changes"], json!([]));
+
+    // The start version can't be after the end version
+    context
+        .expect_status_code(400)
+        .get(&get_account_state_diff(
+            &account.address().to_hex_literal(),
+            end_version,
+            Some(start_version),
+        ))
+        .await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_get_account_state_diff_with_pagination() {
+    let mut context = new_test_context(current_function_name!());
+    let account = context.gen_account();
+    let start_version = context.get_latest_ledger_info().version();
+    let txn = context.create_user_account(&account).await;
+    context.commit_block(&vec![txn]).await;
+    let address = account.address().to_hex_literal();
+    let state_diff_path = get_account_state_diff(&address, start_version, None);
+
+    // Get the full diff, there should be no cursor
+    let req = warp::test::request()
+        .method("GET")
+        .path(&format!("/v1{}", state_diff_path));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers().contains_key("X-Aptos-Cursor"));
+    let all_changes = serde_json::from_slice::<Value>(resp.body()).unwrap()["changes"].clone();
+    let all_changes = all_changes.as_array().unwrap();
+    assert!(all_changes.len() >= 2);
+
+    // Get the first change, there should be a cursor for the rest
+    let req = warp::test::request()
+        .method("GET")
+        .path(&format!("/v1{}&limit=1", state_diff_path));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    let cursor_header = resp
+        .headers()
+        .get("X-Aptos-Cursor")
+        .expect("Cursor header was missing")
+        .to_str()
+        .unwrap()
+        .to_string();
+    let changes = serde_json::from_slice::<Value>(resp.body()).unwrap()["changes"].clone();
+    assert_eq!(changes, json!(all_changes[0..1]));
+
+    // Get the rest of the changes using the cursor, there should be no cursor now
+    let req = warp::test::request()
+        .method("GET")
+        .path(&format!("/v1{}&start={}", state_diff_path, cursor_header));
+    let resp = context.reply(req).await;
+    assert_eq!(resp.status(), 200);
+    assert!(!resp.headers().contains_key("X-Aptos-Cursor"));
+    let changes = serde_json::from_slice::<Value>(resp.body()).unwrap()["changes"].clone();
+    assert_eq!(changes, json!(all_changes[1..]));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_query_account_resources() {
+    let mut context = new_test_context(current_function_name!());
+    let resource = context
+        .get(&get_account_resource("0xA550C18", "0x1::account::Account"))
+        .await;
+
+    let resp = context
+        .post(
+            "/resources/query",
+            json!({
+                "selectors": [
+                    {
+                        "address": "0xA550C18",
+                        "resource_type": "0x1::account::Account",
+                        "field_path": ["sequence_number"],
+                    },
+                    {
+                        "address": "0xA550C18",
+                        "resource_type": "0x1::account::Account",
+                    },
+                    {
+                        "address": "0xA550C19",
+                        "resource_type": "0x1::account::Account",
+                        "field_path": ["sequence_number"],
+                    },
+                ],
+            }),
+        )
+        .await;
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len(), 3);
+    assert_eq!(results[0]["field_path"], json!(["sequence_number"]));
+    assert_eq!(results[0]["value"], resource["data"]["sequence_number"]);
+    assert_eq!(results[1]["field_path"], json!([]));
+    assert_eq!(results[1]["value"], resource["data"]);
+    assert_eq!(results[2]["value"], Value::Null);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_query_account_resources_invalid() {
+    let context = new_test_context(current_function_name!());
+
+    // The field doesn't exist in the resource
+    context
+        .expect_status_code(400)
+        .post(
+            "/resources/query",
+            json!({
+                "selectors": [{
+                    "address": "0xA550C18",
+                    "resource_type": "0x1::account::Account",
+                    "field_path": ["sequence_number", "value"],
+                }],
+            }),
+        )
+        .await;
+
+    // Too many selectors
+    let selector = json!({
+        "address": "0xA550C18",
+        "resource_type": "0x1::account::Account",
+    });
+    let max_selectors = context.context.max_resource_query_selectors();
+    context
+        .expect_status_code(400)
+        .post(
+            "/resources/query",
+            json!({ "selectors": vec![selector; max_selectors + 1] }),
+        )
+        .await;
+}
+
 fn get_account_resource(address: &str, struct_tag: &str) -> String {
     format!("/accounts/{}/resource/{}", address, struct_tag)
 }
@@ -309,6 +478,19 @@ fn get_account_resource_with_version(address: &str, struct_tag: &str, version: u
     )
 }
 
+fn get_account_state_diff(address: &str, start_version: u64, end_version: Option<u64>) -> String {
+    match end_version {
+        Some(end_version) => format!(
+            "/accounts/{}/state_diff?start_version={}&end_version={}",
+            address, start_version, end_version
+        ),
+        None => format!(
+            "/accounts/{}/state_diff?start_version={}",
+            address, start_version
+        ),
+    }
+}
+
 fn get_account_module(address: &str, name: &str) -> String {
     format!("/accounts/{}/module/{}", address, name)
 }
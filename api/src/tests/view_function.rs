@@ -111,3 +111,113 @@ async fn test_view_tuple() {
         .await;
     context.check_golden_output_no_prune(resp);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_at_versions() {
+    let mut context = new_test_context(current_function_name!());
+    let creator = &mut context.gen_account();
+    let owner = &mut context.gen_account();
+    let txn = context.mint_user_account(creator).await;
+    context.commit_block(&vec![txn]).await;
+    let start_version = context.get_latest_ledger_info().version();
+
+    let txn = context.account_transfer(creator, owner, 100_000);
+    context.commit_block(&vec![txn]).await;
+    let end_version = context.get_latest_ledger_info().version();
+
+    let resp = context
+        .post(
+            "/view/versions",
+            json!({
+                "function":"0x1::coin::balance",
+                "arguments": vec![owner.address().to_string()],
+                "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+                "ledger_versions": vec![end_version.to_string()],
+            }),
+        )
+        .await;
+    assert_eq!(
+        resp,
+        json!([{"ledger_version": end_version.to_string(), "result": ["100000"]}])
+    );
+
+    // The owner account doesn't exist before the transfer, so query the creator instead
+    let resp = context
+        .post(
+            "/view/versions",
+            json!({
+                "function":"0x1::coin::balance",
+                "arguments": vec![creator.address().to_string()],
+                "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+                "start_version": start_version.to_string(),
+                "end_version": end_version.to_string(),
+            }),
+        )
+        .await;
+    let results = resp.as_array().unwrap();
+    assert_eq!(results.len() as u64, end_version - start_version + 1);
+    for (result, version) in results.iter().zip(start_version..=end_version) {
+        assert_eq!(result["ledger_version"], json!(version.to_string()));
+        let single_resp = context
+            .post(
+                &format!("/view?ledger_version={}", version),
+                json!({
+                    "function":"0x1::coin::balance",
+                    "arguments": vec![creator.address().to_string()],
+                    "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+                }),
+            )
+            .await;
+        assert_eq!(result["result"], single_resp);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_view_at_versions_invalid() {
+    let context = new_test_context(current_function_name!());
+    let request = |versions: serde_json::Value| {
+        let mut request = json!({
+            "function":"0x1::coin::balance",
+            "arguments": vec!["0x1"],
+            "type_arguments": vec!["0x1::aptos_coin::AptosCoin"],
+        });
+        request
+            .as_object_mut()
+            .unwrap()
+            .extend(versions.as_object().unwrap().clone());
+        request
+    };
+
+    // Neither a list nor a range of versions
+    context
+        .expect_status_code(400)
+        .post("/view/versions", request(json!({})))
+        .await;
+
+    // Both a list and a range of versions
+    context
+        .expect_status_code(400)
+        .post(
+            "/view/versions",
+            request(json!({"ledger_versions": ["0"], "start_version": "0", "end_version": "0"})),
+        )
+        .await;
+
+    // A zero step
+    context
+        .expect_status_code(400)
+        .post(
+            "/view/versions",
+            request(json!({"start_version": "0", "end_version": "0", "step": "0"})),
+        )
+        .await;
+
+    // Too many versions
+    context
+        .expect_status_code(400)
+        .post(
+            "/view/versions",
+            request(json!({"start_version": "0", "end_version": "1000000"})),
+        )
+        .await;
+}
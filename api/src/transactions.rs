@@ -1057,6 +1057,10 @@ impl TransactionsApi {
                 format!("Transaction was rejected with status {}", mempool_status,),
                 AptosErrorCode::InternalError,
             )),
+            MempoolStatusCode::RejectedByFilter => Err(AptosError::new_with_error_code(
+                "Transaction was rejected by the node's transaction filter",
+                AptosErrorCode::InvalidInput,
+            )),
         }
     }
 
@@ -14,10 +14,11 @@ use crate::{
 };
 use anyhow::Context as anyhowContext;
 use aptos_api_types::{
-    AptosErrorCode, AsConverter, MoveValue, ViewFunction, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED,
-    U64,
+    AptosErrorCode, AsConverter, LedgerInfo, MoveValue, MultiVersionViewRequest,
+    VersionedViewResult, ViewFunction, ViewRequest, MAX_RECURSIVE_TYPES_ALLOWED, U64,
 };
 use aptos_bcs_utils::serialize_uleb128;
+use aptos_storage_interface::state_view::DbStateView;
 use aptos_vm::{data_cache::AsMoveResolver, AptosVM};
 use itertools::Itertools;
 use move_core_types::language_storage::TypeTag;
@@ -71,6 +72,37 @@ impl ViewFunctionApi {
         api_spawn_blocking(move || view_request(context, accept_type, request, ledger_version))
             .await
     }
+
+    /// Execute view function of a module at multiple ledger versions
+    ///
+    /// Execute the Move function with the given parameters at each of the requested ledger
+    /// versions and return the execution result at each version. This is useful for tracking
+    /// a value (e.g., a balance or a price) over time with a single request.
+    ///
+    /// The ledger versions are given either as a list, or as an inclusive range with an
+    /// optional step. The number of versions per request is limited by the node.
+    ///
+    /// The Aptos nodes prune account state history, via a configurable time window.
+    /// If any requested ledger version has been pruned, the server responds with a 410.
+    #[oai(
+        path = "/view/versions",
+        method = "post",
+        operation_id = "view_at_versions",
+        tag = "ApiTags::View"
+    )]
+    async fn view_function_at_versions(
+        &self,
+        accept_type: AcceptType,
+        /// View function request with type and position arguments, and the ledger versions
+        request: Json<MultiVersionViewRequest>,
+    ) -> BasicResultWith404<Vec<VersionedViewResult>> {
+        fail_point_poem("endpoint_view_function_at_versions")?;
+        self.context
+            .check_api_output_enabled("View function at versions", &accept_type)?;
+
+        let context = self.context.clone();
+        api_spawn_blocking(move || view_request_at_versions(context, accept_type, request.0)).await
+    }
 }
 
 fn view_request(
@@ -154,41 +186,145 @@ fn view_request(
             BasicResponse::try_from_encoded((ret, &ledger_info, BasicResponseStatus::Ok))
         },
         AcceptType::Json => {
-            let resolver = state_view.as_move_resolver();
-            let return_types = resolver
-                .as_converter(context.db.clone())
-                .function_return_types(&view_function)
-                .and_then(|tys| {
-                    tys.into_iter()
-                        .map(TypeTag::try_from)
-                        .collect::<anyhow::Result<Vec<_>>>()
-                })
-                .map_err(|err| {
-                    BasicErrorWith404::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InternalError,
-                        &ledger_info,
-                    )
-                })?;
-
-            let move_vals = return_vals
-                .into_iter()
-                .zip(return_types.into_iter())
-                .map(|(v, ty)| {
-                    resolver
-                        .as_converter(context.db.clone())
-                        .try_into_move_value(&ty, &v)
-                })
-                .collect::<anyhow::Result<Vec<_>>>()
-                .map_err(|err| {
-                    BasicErrorWith404::bad_request_with_code(
-                        err,
-                        AptosErrorCode::InternalError,
-                        &ledger_info,
-                    )
-                })?;
-
+            let move_vals = convert_return_values(
+                &context,
+                &state_view,
+                &view_function,
+                return_vals,
+                &ledger_info,
+            )?;
             BasicResponse::try_from_json((move_vals, &ledger_info, BasicResponseStatus::Ok))
         },
     }
 }
+
+/// Executes the view function at each of the requested ledger versions.
+///
+/// JSON: a list of the decoded return values at each version
+/// BCS: a list of (version, return values) pairs, with each return value BCS encoded
+fn view_request_at_versions(
+    context: Arc<Context>,
+    accept_type: AcceptType,
+    request: MultiVersionViewRequest,
+) -> BasicResultWith404<Vec<VersionedViewResult>> {
+    let ledger_info = context.get_latest_ledger_info()?;
+    let ledger_versions = request
+        .ledger_versions(context.max_view_function_versions())
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InvalidInput,
+                &ledger_info,
+            )
+        })?;
+    let view_request = request.view_request();
+
+    let mut results = vec![];
+    let mut encoded_results = vec![];
+    for ledger_version in ledger_versions {
+        let (_, requested_version) =
+            context.get_latest_ledger_info_and_verify_lookup_version(Some(ledger_version))?;
+        let state_view = context
+            .state_view_at_version(requested_version)
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InternalError,
+                    &ledger_info,
+                )
+            })?;
+
+        // The function is converted at each version, as its signature may change across upgrades
+        let view_function = state_view
+            .as_move_resolver()
+            .as_converter(context.db.clone())
+            .convert_view_function(view_request.clone())
+            .map_err(|err| {
+                BasicErrorWith404::bad_request_with_code(
+                    err,
+                    AptosErrorCode::InvalidInput,
+                    &ledger_info,
+                )
+            })?;
+        let return_vals = AptosVM::execute_view_function(
+            &state_view,
+            view_function.module.clone(),
+            view_function.function.clone(),
+            view_function.ty_args.clone(),
+            view_function.args.clone(),
+            context.node_config.api.max_gas_view_function,
+        )
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code_no_info(err, AptosErrorCode::InvalidInput)
+        })?;
+
+        match accept_type {
+            AcceptType::Bcs => encoded_results.push((requested_version, return_vals)),
+            AcceptType::Json => {
+                let move_vals = convert_return_values(
+                    &context,
+                    &state_view,
+                    &view_function,
+                    return_vals,
+                    &ledger_info,
+                )?;
+                results.push(VersionedViewResult {
+                    ledger_version: requested_version.into(),
+                    result: move_vals,
+                });
+            },
+        }
+    }
+
+    match accept_type {
+        AcceptType::Bcs => {
+            BasicResponse::try_from_bcs((encoded_results, &ledger_info, BasicResponseStatus::Ok))
+        },
+        AcceptType::Json => {
+            BasicResponse::try_from_json((results, &ledger_info, BasicResponseStatus::Ok))
+        },
+    }
+}
+
+/// Decodes the BCS encoded return values of the view function into Move values
+fn convert_return_values(
+    context: &Context,
+    state_view: &DbStateView,
+    view_function: &ViewFunction,
+    return_vals: Vec<Vec<u8>>,
+    ledger_info: &LedgerInfo,
+) -> Result<Vec<MoveValue>, BasicErrorWith404> {
+    let resolver = state_view.as_move_resolver();
+    let return_types = resolver
+        .as_converter(context.db.clone())
+        .function_return_types(view_function)
+        .and_then(|tys| {
+            tys.into_iter()
+                .map(TypeTag::try_from)
+                .collect::<anyhow::Result<Vec<_>>>()
+        })
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                ledger_info,
+            )
+        })?;
+
+    return_vals
+        .into_iter()
+        .zip(return_types.into_iter())
+        .map(|(v, ty)| {
+            resolver
+                .as_converter(context.db.clone())
+                .try_into_move_value(&ty, &v)
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|err| {
+            BasicErrorWith404::bad_request_with_code(
+                err,
+                AptosErrorCode::InternalError,
+                ledger_info,
+            )
+        })
+}
@@ -43,7 +43,7 @@ use move_core_types::{
     resolver::MoveResolver,
     value::{LayoutTag, MoveStructLayout, MoveTypeLayout},
 };
-use move_resource_viewer::MoveValueAnnotator;
+use move_resource_viewer::{AnnotatedMoveValue, MoveValueAnnotator};
 use serde_json::Value;
 use std::{
     convert::{TryFrom, TryInto},
@@ -101,6 +101,40 @@ impl<'a, R: MoveResolver + ?Sized> MoveConverter<'a, R> {
             .collect::<Result<Vec<_>>>()
     }
 
+    /// Converts the value at the end of the given field path in the resource. If the
+    /// path is empty, the whole resource is converted.
+    pub fn try_into_resource_field(
+        &self,
+        typ: &StructTag,
+        bytes: &'_ [u8],
+        field_path: &[Identifier],
+    ) -> Result<MoveValue> {
+        let mut value = AnnotatedMoveValue::Struct(self.inner.view_resource(typ, bytes)?);
+        for field in field_path {
+            value = match value {
+                AnnotatedMoveValue::Struct(annotated_struct)
+                    if !MoveValue::is_utf8_string(&annotated_struct.type_) =>
+                {
+                    let struct_type = annotated_struct.type_;
+                    annotated_struct
+                        .value
+                        .into_iter()
+                        .find(|(name, _)| name == field)
+                        .map(|(_, field_value)| field_value)
+                        .ok_or_else(|| {
+                            format_err!("Struct {} has no field named '{}'", struct_type, field)
+                        })?
+                },
+                _ => bail!(
+                    "Field '{}' of {} can't be selected from a non-struct value",
+                    field,
+                    typ
+                ),
+            };
+        }
+        value.try_into()
+    }
+
     pub fn move_struct_fields(
         &self,
         typ: &StructTag,
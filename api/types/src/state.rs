@@ -1,7 +1,9 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::HexEncodedBytes;
+use crate::{
+    Address, HexEncodedBytes, IdentifierWrapper, MoveStructTag, MoveValue, WriteSetChange, U64,
+};
 use poem_openapi::Object;
 use serde::{Deserialize, Serialize};
 
@@ -10,3 +12,46 @@ use serde::{Deserialize, Serialize};
 pub struct RawStateValueRequest {
     pub key: HexEncodedBytes,
 }
+
+/// The changes to the resources and modules of an account between two ledger versions
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct StateDiff {
+    /// Ledger version the changes start from (exclusive)
+    pub start_version: U64,
+    /// Ledger version the changes end at (inclusive)
+    pub end_version: U64,
+    /// Changes to the account's state, with the final values at `end_version`
+    pub changes: Vec<WriteSetChange>,
+}
+
+/// Request for the values of a batch of resources (or fields of resources) at a single ledger version
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceQueryRequest {
+    /// Selectors of the values to retrieve. The values are returned in the same order.
+    pub selectors: Vec<ResourceSelector>,
+}
+
+/// Selects a resource of an account, or a (possibly nested) field of the resource
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct ResourceSelector {
+    /// Address of the account holding the resource
+    pub address: Address,
+    /// Type of the resource e.g. `0x1::coin::CoinStore<0x1::aptos_coin::AptosCoin>`
+    pub resource_type: MoveStructTag,
+    /// Names of the fields to follow from the resource to the selected value
+    /// e.g. `["coin", "value"]`. If empty or not provided, the whole resource is selected.
+    pub field_path: Option<Vec<IdentifierWrapper>>,
+}
+
+/// The value selected by a [`ResourceSelector`]
+#[derive(Clone, Debug, PartialEq, Serialize, Object)]
+pub struct ResourceQueryResult {
+    /// Address of the account holding the resource
+    pub address: Address,
+    /// Type of the resource
+    pub resource_type: MoveStructTag,
+    /// Names of the fields followed from the resource to the value
+    pub field_path: Vec<IdentifierWrapper>,
+    /// The selected value, or none if the account doesn't hold the resource
+    pub value: Option<MoveValue>,
+}
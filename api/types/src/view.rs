@@ -1,7 +1,8 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{EntryFunctionId, MoveType};
+use crate::{EntryFunctionId, MoveType, MoveValue, U64};
+use anyhow::{bail, ensure};
 use aptos_types::serde_helper::vec_bytes;
 use move_core_types::{
     identifier::Identifier,
@@ -20,6 +21,100 @@ pub struct ViewRequest {
     pub arguments: Vec<serde_json::Value>,
 }
 
+/// View request for executing a view function at multiple ledger versions
+///
+/// The versions are given either as a list (`ledger_versions`), or as a range
+/// (`start_version` to `end_version`, inclusive, every `step` versions).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Object)]
+pub struct MultiVersionViewRequest {
+    pub function: EntryFunctionId,
+    /// Type arguments of the function
+    pub type_arguments: Vec<MoveType>,
+    /// Arguments of the function
+    pub arguments: Vec<serde_json::Value>,
+    /// Ledger versions to execute the function at
+    pub ledger_versions: Option<Vec<U64>>,
+    /// First ledger version of the range to execute the function at
+    pub start_version: Option<U64>,
+    /// Last ledger version (inclusive) of the range to execute the function at
+    pub end_version: Option<U64>,
+    /// Number of versions between executions in the range. Defaults to 1.
+    pub step: Option<U64>,
+}
+
+impl MultiVersionViewRequest {
+    /// Returns the view request to execute at each ledger version
+    pub fn view_request(&self) -> ViewRequest {
+        ViewRequest {
+            function: self.function.clone(),
+            type_arguments: self.type_arguments.clone(),
+            arguments: self.arguments.clone(),
+        }
+    }
+
+    /// Returns the ledger versions to execute the function at. Returns an
+    /// error if the versions are invalid, or there are more than `max_versions`.
+    pub fn ledger_versions(&self, max_versions: usize) -> anyhow::Result<Vec<u64>> {
+        let ledger_versions: Vec<u64> = match (
+            &self.ledger_versions,
+            self.start_version,
+            self.end_version,
+        ) {
+            (Some(ledger_versions), None, None) => {
+                ensure!(
+                    self.step.is_none(),
+                    "'step' can only be used with 'start_version' and 'end_version'"
+                );
+                ensure!(
+                    ledger_versions.len() <= max_versions,
+                    "Too many ledger versions! Found: {}, max: {}",
+                    ledger_versions.len(),
+                    max_versions
+                );
+                ledger_versions.iter().map(|version| version.0).collect()
+            },
+            (None, Some(start_version), Some(end_version)) => {
+                let (start_version, end_version) = (start_version.0, end_version.0);
+                let step = self.step.map(|step| step.0).unwrap_or(1);
+                ensure!(
+                    start_version <= end_version,
+                    "'start_version' ({}) must be <= 'end_version' ({})",
+                    start_version,
+                    end_version
+                );
+                ensure!(step > 0, "'step' must be greater than 0");
+                let num_versions = (end_version - start_version) / step + 1;
+                ensure!(
+                    num_versions <= max_versions as u64,
+                    "Too many ledger versions! Found: {}, max: {}",
+                    num_versions,
+                    max_versions
+                );
+                (0..num_versions)
+                    .map(|index| start_version + index * step)
+                    .collect()
+            },
+            _ => bail!(
+                "Either 'ledger_versions', or both 'start_version' and 'end_version' must be provided"
+            ),
+        };
+        ensure!(
+            !ledger_versions.is_empty(),
+            "At least one ledger version must be provided"
+        );
+        Ok(ledger_versions)
+    }
+}
+
+/// The return values of a view function at a single ledger version
+#[derive(Clone, Debug, PartialEq, Serialize, Object)]
+pub struct VersionedViewResult {
+    /// Ledger version the function was executed at
+    pub ledger_version: U64,
+    /// Return values of the function
+    pub result: Vec<MoveValue>,
+}
+
 #[derive(Clone, Debug, Hash, Eq, PartialEq, Serialize, Deserialize)]
 pub struct ViewFunction {
     pub module: ModuleId,
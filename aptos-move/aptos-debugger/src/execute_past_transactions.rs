@@ -6,6 +6,9 @@ use anyhow::Result;
 use aptos_rest_client::Client;
 use aptos_vm::AptosVM;
 use clap::Parser;
+use move_cli::debug_adapter::{DebugAdapter, SourceIndex};
+use move_package::BuildConfig;
+use std::path::PathBuf;
 use url::Url;
 
 #[derive(Parser)]
@@ -21,11 +24,26 @@ pub struct Command {
 
     #[clap(long)]
     skip_result: bool,
+
+    /// Replay the transactions under a Debug Adapter Protocol server listening on this port.
+    /// The transactions are executed once a debugger (e.g., an editor) attaches.
+    #[clap(long)]
+    debug_adapter_port: Option<u16>,
+
+    /// Local Move packages whose sources are used to debug the replayed transactions. The
+    /// compiled modules must match the ones on chain for the source lines to be accurate.
+    #[clap(long, requires = "debug_adapter_port")]
+    package_dir: Vec<PathBuf>,
 }
 
 impl Command {
     pub async fn run(self) -> Result<()> {
-        AptosVM::set_concurrency_level_once(self.opts.concurrency_level);
+        // The VM debugger is global, so the transactions must be executed one at a time
+        if self.debug_adapter_port.is_some() {
+            AptosVM::set_concurrency_level_once(1);
+        } else {
+            AptosVM::set_concurrency_level_once(self.opts.concurrency_level);
+        }
 
         let debugger = if let Some(rest_endpoint) = self.opts.target.rest_endpoint {
             AptosDebugger::rest_client(Client::new(Url::parse(&rest_endpoint)?))?
@@ -35,9 +53,25 @@ impl Command {
             unreachable!("Must provide one target.");
         };
 
+        let debug_adapter = match self.debug_adapter_port {
+            Some(debug_adapter_port) => {
+                let mut source_index = SourceIndex::default();
+                for package_dir in &self.package_dir {
+                    let package = BuildConfig::default()
+                        .compile_package(package_dir, &mut std::io::stderr())?;
+                    source_index.add_compiled_package(&package);
+                }
+                Some(DebugAdapter::listen(debug_adapter_port, source_index)?.start())
+            },
+            None => None,
+        };
         let result = debugger
             .execute_past_transactions(self.begin_version, self.limit)
-            .await?;
+            .await;
+        if let Some(debug_adapter) = debug_adapter {
+            debug_adapter.finish();
+        }
+        let result = result?;
 
         if !self.skip_result {
             println!("{result:#?}",);
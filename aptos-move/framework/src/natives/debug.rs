@@ -9,7 +9,7 @@ use aptos_native_interface::{
     safely_pop_arg, RawSafeNative, SafeNativeBuilder, SafeNativeContext, SafeNativeError,
     SafeNativeResult,
 };
-use move_vm_runtime::native_functions::NativeFunction;
+use move_vm_runtime::{debug_output::print_debug_output, native_functions::NativeFunction};
 #[allow(unused_imports)]
 use move_vm_types::{
     loaded_data::runtime_types::Type,
@@ -36,10 +36,10 @@ fn native_print(
         let val = safely_pop_arg!(args, Struct);
         let bytes = val.unpack()?.next().unwrap();
 
-        println!(
+        print_debug_output(&format!(
             "[debug] {}",
             std::str::from_utf8(&bytes.value_as::<Vec<u8>>()?).unwrap()
-        );
+        ));
     }
 
     Ok(smallvec![])
@@ -79,10 +79,10 @@ fn native_old_debug_print(
         let x = safely_pop_arg!(args, Reference);
         let val = x.read_ref().map_err(SafeNativeError::InvariantViolation)?;
 
-        println!(
+        print_debug_output(&format!(
             "[debug] {}",
             native_format_debug(context, &ty_args[0], val)?
-        );
+        ));
     }
     Ok(smallvec![])
 }
@@ -99,7 +99,7 @@ fn native_old_print_stacktrace(
     if cfg!(feature = "testing") {
         let mut s = String::new();
         context.print_stack_trace(&mut s)?;
-        println!("{}", s);
+        print_debug_output(&s);
     }
     Ok(smallvec![])
 }
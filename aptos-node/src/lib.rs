@@ -618,6 +618,7 @@ pub fn setup_environment_and_start_node(
             event_subscription_service,
             db_rw.clone(),
         )?;
+    admin_service.set_aptos_data_client(aptos_data_client.clone());
 
     // Start the node inspection service
     services::start_node_inspection_service(
@@ -68,7 +68,7 @@ pub fn consensus_network_configuration(node_config: &NodeConfig) -> NetworkAppli
 /// Returns the network application config for the mempool client and service
 pub fn mempool_network_configuration(node_config: &NodeConfig) -> NetworkApplicationConfig {
     let direct_send_protocols = vec![ProtocolId::MempoolDirectSend];
-    let rpc_protocols = vec![]; // Mempool does not use RPC
+    let rpc_protocols = vec![ProtocolId::MempoolRpc]; // Used for pulling transactions
 
     let network_client_config =
         NetworkClientConfig::new(direct_send_protocols.clone(), rpc_protocols.clone());
@@ -106,7 +106,10 @@ pub fn peer_monitoring_network_configuration(node_config: &NodeConfig) -> Networ
 /// Returns the network application config for the storage service client and server
 pub fn storage_service_network_configuration(node_config: &NodeConfig) -> NetworkApplicationConfig {
     let direct_send_protocols = vec![]; // The storage service does not use direct send
-    let rpc_protocols = vec![ProtocolId::StorageServiceRpc];
+    let rpc_protocols = vec![
+        ProtocolId::StorageServiceRpcZstd, // Preferred, so that zstd can be negotiated
+        ProtocolId::StorageServiceRpc,
+    ];
     let max_network_channel_size = node_config
         .state_sync
         .storage_service
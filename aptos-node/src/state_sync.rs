@@ -2,9 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::network::ApplicationNetworkInterfaces;
+use anyhow::anyhow;
+use aptos_backup_cli::{
+    data_client::{open_backup_storage, BackupDataClient, BackupDataSource},
+    metadata::cache::MetadataCacheOpt,
+    utils::ConcurrentDownloadsOpt,
+};
 use aptos_config::config::{NodeConfig, StateSyncConfig};
-use aptos_consensus_notifications::ConsensusNotifier;
-use aptos_data_client::{client::AptosDataClient, poller};
+use aptos_consensus_notifications::{ConsensusNotificationListener, ConsensusNotifier};
+use aptos_data_client::{client::AptosDataClient, interface::AptosDataClientInterface, poller};
 use aptos_data_streaming_service::{
     streaming_client::{new_streaming_service_client_listener_pair, StreamingServiceClient},
     streaming_service::DataStreamingService,
@@ -14,7 +20,7 @@ use aptos_event_notifications::{
 };
 use aptos_executor::chunk_executor::ChunkExecutor;
 use aptos_infallible::RwLock;
-use aptos_mempool_notifications::MempoolNotificationListener;
+use aptos_mempool_notifications::{MempoolNotificationListener, MempoolNotifier};
 use aptos_network::application::{
     interface::{NetworkClient, NetworkClientInterface, NetworkServiceEvents},
     storage::PeersAndMetadata,
@@ -25,7 +31,9 @@ use aptos_state_sync_driver::{
 };
 use aptos_storage_interface::{DbReader, DbReaderWriter};
 use aptos_storage_service_client::StorageServiceClient;
-use aptos_storage_service_notifications::StorageServiceNotificationListener;
+use aptos_storage_service_notifications::{
+    StorageServiceNotificationListener, StorageServiceNotifier,
+};
 use aptos_storage_service_server::{
     network::StorageServiceNetworkEvents, storage::StorageReader, StorageServiceServer,
 };
@@ -36,6 +44,10 @@ use aptos_vm::AptosVM;
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
+/// The directory (in the storage dir) that caches the backup metadata
+/// when bootstrapping from a backup.
+const BACKUP_METADATA_CACHE_DIR: &str = "backup_metadata_cache";
+
 /// Creates the event subscription service and two reconfiguration
 /// notification listeners (for mempool and consensus, respectively).
 pub fn create_event_subscription_service(
@@ -95,10 +107,6 @@ pub fn start_state_sync_and_get_notification_handles(
     let (aptos_data_client, aptos_data_client_runtime) =
         setup_aptos_data_client(node_config, network_client, db_rw.reader.clone())?;
 
-    // Start the data streaming service
-    let (streaming_service_client, streaming_service_runtime) =
-        setup_data_streaming_service(node_config.state_sync, aptos_data_client.clone())?;
-
     // Create the chunk executor and persistent storage
     let chunk_executor = Arc::new(ChunkExecutor::<AptosVM>::new(db_rw.clone()));
     let metadata_storage = PersistentMetadataStorage::new(&node_config.storage.dir());
@@ -125,6 +133,85 @@ pub fn start_state_sync_and_get_notification_handles(
         storage_service_listener,
     )?;
 
+    // Start the data streaming service and create the state sync driver factory. If
+    // the node bootstraps from a backup, the data required to fast sync is read from
+    // the backup storage (and everything else is fetched from the network).
+    let (streaming_service_runtime, state_sync) = if node_config
+        .state_sync
+        .state_sync_driver
+        .bootstrapping_mode
+        .is_from_backup()
+    {
+        let backup_data_client = setup_backup_data_client(
+            node_config,
+            aptos_data_client.clone(),
+            db_rw.reader.clone(),
+            &aptos_data_client_runtime,
+        )?;
+        create_state_sync_driver(
+            node_config,
+            waypoint,
+            db_rw,
+            chunk_executor,
+            mempool_notifier,
+            storage_service_notifier,
+            metadata_storage,
+            consensus_listener,
+            event_subscription_service,
+            backup_data_client,
+        )?
+    } else {
+        create_state_sync_driver(
+            node_config,
+            waypoint,
+            db_rw,
+            chunk_executor,
+            mempool_notifier,
+            storage_service_notifier,
+            metadata_storage,
+            consensus_listener,
+            event_subscription_service,
+            aptos_data_client.clone(),
+        )?
+    };
+
+    // Create a new state sync runtime handle
+    let state_sync_runtimes = StateSyncRuntimes::new(
+        aptos_data_client_runtime,
+        state_sync,
+        storage_service_runtime,
+        streaming_service_runtime,
+    );
+
+    Ok((
+        aptos_data_client,
+        state_sync_runtimes,
+        mempool_listener,
+        consensus_notifier,
+    ))
+}
+
+/// Starts the data streaming service and creates the state sync driver
+/// factory, using the given data client.
+#[allow(clippy::too_many_arguments)]
+fn create_state_sync_driver<
+    DataClient: AptosDataClientInterface + Send + Sync + Clone + 'static,
+>(
+    node_config: &NodeConfig,
+    waypoint: Waypoint,
+    db_rw: DbReaderWriter,
+    chunk_executor: Arc<ChunkExecutor<AptosVM>>,
+    mempool_notifier: MempoolNotifier,
+    storage_service_notifier: StorageServiceNotifier,
+    metadata_storage: PersistentMetadataStorage,
+    consensus_listener: ConsensusNotificationListener,
+    event_subscription_service: EventSubscriptionService,
+    data_client: DataClient,
+) -> anyhow::Result<(Runtime, DriverFactory)> {
+    // Start the data streaming service
+    let (streaming_service_client, streaming_service_runtime) =
+        setup_data_streaming_service(node_config.state_sync, data_client.clone())?;
+
     // Create the state sync driver factory
     let state_sync = DriverFactory::create_and_spawn_driver(
         true,
@@ -137,31 +224,55 @@ pub fn start_state_sync_and_get_notification_handles(
         metadata_storage,
         consensus_listener,
         event_subscription_service,
-        aptos_data_client.clone(),
+        data_client,
         streaming_service_client,
         TimeService::real(),
     );
 
-    // Create a new state sync runtime handle
-    let state_sync_runtimes = StateSyncRuntimes::new(
-        aptos_data_client_runtime,
-        state_sync,
-        storage_service_runtime,
-        streaming_service_runtime,
-    );
+    Ok((streaming_service_runtime, state_sync))
+}
 
-    Ok((
+/// Sets up the data client that serves the data required to fast
+/// sync from the backup storage, and forwards everything else to
+/// the network data client.
+fn setup_backup_data_client(
+    node_config: &NodeConfig,
+    aptos_data_client: AptosDataClient,
+    storage: Arc<dyn DbReader>,
+    runtime: &Runtime,
+) -> anyhow::Result<BackupDataClient<AptosDataClient>> {
+    let backup_source = node_config
+        .storage
+        .bootstrap_backup_source
+        .as_ref()
+        .ok_or_else(|| anyhow!("No backup source is configured to bootstrap from!"))?;
+
+    // Select the latest state snapshot in the backup storage
+    let metadata_cache_opt = MetadataCacheOpt::new(Some(
+        node_config.storage.dir().join(BACKUP_METADATA_CACHE_DIR),
+    ));
+    let backup_data_source = runtime.block_on(async {
+        let backup_storage = open_backup_storage(backup_source).await?;
+        BackupDataSource::new(
+            backup_storage,
+            &metadata_cache_opt,
+            ConcurrentDownloadsOpt::default().get(),
+        )
+        .await
+    })?;
+
+    BackupDataClient::new(
+        node_config.state_sync.aptos_data_client,
+        backup_data_source,
         aptos_data_client,
-        state_sync_runtimes,
-        mempool_listener,
-        consensus_notifier,
-    ))
+        storage,
+    )
 }
 
 /// Sets up the data streaming service runtime
-fn setup_data_streaming_service(
+fn setup_data_streaming_service<DataClient: AptosDataClientInterface + Send + Clone + 'static>(
     state_sync_config: StateSyncConfig,
-    aptos_data_client: AptosDataClient,
+    aptos_data_client: DataClient,
 ) -> anyhow::Result<(StreamingServiceClient, Runtime)> {
     // Create the data streaming service
     let (streaming_service_client, streaming_service_listener) =
@@ -62,6 +62,10 @@ pub struct ApiConfig {
     ///
     /// This limits the execution length of a view function to the given gas used.
     pub max_gas_view_function: u64,
+    /// Maximum number of ledger versions a view function can be executed at in a single request
+    pub max_view_function_versions: usize,
+    /// Maximum number of resource selectors in a single resource query request
+    pub max_resource_query_selectors: usize,
     /// Optional: Maximum number of worker threads for the API.
     ///
     /// If not set, `runtime_worker_multiplier` will multiply times the number of CPU cores on the machine
@@ -72,6 +76,20 @@ pub struct ApiConfig {
     pub runtime_worker_multiplier: usize,
     /// Configs for computing unit gas price estimation
     pub gas_estimation: GasEstimationConfig,
+    /// Enables the event stream (Server-Sent Events) API
+    #[serde(default = "default_disabled")]
+    pub event_stream_enabled: bool,
+    /// Maximum number of concurrent event stream connections
+    pub max_event_stream_connections: usize,
+    /// Maximum number of subscriptions (i.e., event handles, event types
+    /// and addresses) a single event stream connection can hold
+    pub max_event_stream_subscriptions: usize,
+    /// Interval (in milliseconds) at which the API checks for ledger updates
+    /// to push to event stream connections
+    pub event_stream_poll_interval_ms: u64,
+    /// Maximum number of versions behind the latest committed version that
+    /// an event stream can start at (i.e., the maximum backfill range)
+    pub max_event_stream_backfill_versions: u64,
 }
 
 const DEFAULT_ADDRESS: &str = "127.0.0.1";
@@ -81,6 +99,12 @@ pub const DEFAULT_MAX_SUBMIT_TRANSACTION_BATCH_SIZE: usize = 10;
 pub const DEFAULT_MAX_PAGE_SIZE: u16 = 100;
 const DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE: u16 = 9999;
 const DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE: u16 = 9999;
+const DEFAULT_MAX_EVENT_STREAM_CONNECTIONS: usize = 1000;
+const DEFAULT_MAX_EVENT_STREAM_SUBSCRIPTIONS: usize = 100;
+const DEFAULT_EVENT_STREAM_POLL_INTERVAL_MS: u64 = 100;
+const DEFAULT_MAX_EVENT_STREAM_BACKFILL_VERSIONS: u64 = 100_000;
+const DEFAULT_MAX_VIEW_FUNCTION_VERSIONS: usize = 100;
+const DEFAULT_MAX_RESOURCE_QUERY_SELECTORS: usize = 100;
 const DEFAULT_MAX_VIEW_GAS: u64 = 2_000_000; // We keep this value the same as the max number of gas allowed for one single transaction defined in aptos-gas.
 
 fn default_enabled() -> bool {
@@ -113,9 +137,16 @@ impl Default for ApiConfig {
             max_account_resources_page_size: DEFAULT_MAX_ACCOUNT_RESOURCES_PAGE_SIZE,
             max_account_modules_page_size: DEFAULT_MAX_ACCOUNT_MODULES_PAGE_SIZE,
             max_gas_view_function: DEFAULT_MAX_VIEW_GAS,
+            max_view_function_versions: DEFAULT_MAX_VIEW_FUNCTION_VERSIONS,
+            max_resource_query_selectors: DEFAULT_MAX_RESOURCE_QUERY_SELECTORS,
             max_runtime_workers: None,
             runtime_worker_multiplier: 2,
             gas_estimation: GasEstimationConfig::default(),
+            event_stream_enabled: default_disabled(),
+            max_event_stream_connections: DEFAULT_MAX_EVENT_STREAM_CONNECTIONS,
+            max_event_stream_subscriptions: DEFAULT_MAX_EVENT_STREAM_SUBSCRIPTIONS,
+            event_stream_poll_interval_ms: DEFAULT_EVENT_STREAM_POLL_INTERVAL_MS,
+            max_event_stream_backfill_versions: DEFAULT_MAX_EVENT_STREAM_BACKFILL_VERSIONS,
         }
     }
 }
@@ -165,6 +196,14 @@ impl ConfigSanitizer for ApiConfig {
             ));
         }
 
+        // Validate the event stream properties
+        if api_config.event_stream_enabled && api_config.event_stream_poll_interval_ms == 0 {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "event_stream_poll_interval_ms must be greater than 0!".into(),
+            ));
+        }
+
         // Sanitize the gas estimation config
         GasEstimationConfig::sanitize(node_config, node_type, chain_id)?;
 
@@ -238,4 +277,25 @@ mod tests {
                 .unwrap_err();
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
+
+    #[test]
+    fn test_sanitize_invalid_event_stream_poll_interval() {
+        // Create a node config with an invalid event stream poll interval
+        let node_config = NodeConfig {
+            api: ApiConfig {
+                enabled: true,
+                event_stream_enabled: true,
+                event_stream_poll_interval_ms: 0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Sanitize the config and verify that it fails because
+        // the event stream poll interval is invalid.
+        let error =
+            ApiConfig::sanitize(&node_config, NodeType::Validator, Some(ChainId::mainnet()))
+                .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
 }
@@ -4,12 +4,14 @@
 
 use crate::config::{
     config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer,
-    node_config_loader::NodeType, Error, NodeConfig, MAX_APPLICATION_MESSAGE_SIZE,
+    node_config_loader::NodeType, transaction_filter_type::Filter, Error, NodeConfig,
+    MAX_APPLICATION_MESSAGE_SIZE,
 };
 use aptos_global_constants::DEFAULT_BUCKETS;
 use aptos_types::chain_id::ChainId;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
+use std::path::PathBuf;
 
 #[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
@@ -41,6 +43,16 @@ pub struct MempoolConfig {
     /// Interval to broadcast to upstream nodes.
     pub shared_mempool_tick_interval_ms: u64,
     pub shared_mempool_peer_update_interval_ms: u64,
+    /// Whether to pull the pending transactions of newly connected upstream peers, so that
+    /// restarted or partitioned nodes catch up without waiting for rebroadcasts.
+    pub shared_mempool_pull_enabled: bool,
+    /// Maximum number of pull requests sent to an upstream peer when catching up on connection.
+    pub shared_mempool_max_pulls_per_catch_up: usize,
+    /// Maximum number of pull requests served concurrently for a single peer. Further requests
+    /// are answered with a backoff signal.
+    pub shared_mempool_max_concurrent_pulls_per_peer: usize,
+    /// The maximum amount of time to wait for the response to a pull request.
+    pub shared_mempool_pull_timeout_ms: u64,
     /// Number of seconds until the transaction will be removed from the Mempool ignoring if the transaction has expired.
     ///
     /// This ensures that the Mempool isn't just full of non-expiring transactions that are way off into the future.
@@ -53,6 +65,17 @@ pub struct MempoolConfig {
     pub broadcast_buckets: Vec<u64>,
     pub eager_expire_threshold_ms: Option<u64>,
     pub eager_expire_time_ms: u64,
+    /// Transactions denied by this filter are rejected on admission, whether they are submitted
+    /// by clients or broadcast by peers. Uses the same rules as `execution.transaction_filter`.
+    pub transaction_filter: Filter,
+    /// If set, transactions accepted into the Mempool are journaled to this file and replayed on
+    /// startup, so that pending transactions survive restarts. Relative paths are resolved
+    /// against the node's data directory.
+    pub journal_path: Option<PathBuf>,
+    /// Interval to rewrite the journal with only the transactions still in the Mempool.
+    pub journal_compaction_interval_secs: u64,
+    /// Limits on how much of the batches pulled by consensus a single sender or contract can take.
+    pub fairness: MempoolFairnessConfig,
 }
 
 impl Default for MempoolConfig {
@@ -72,22 +95,59 @@ impl Default for MempoolConfig {
             capacity_per_user: 100,
             default_failovers: 1,
             shared_mempool_peer_update_interval_ms: 1_000,
+            shared_mempool_pull_enabled: false,
+            shared_mempool_max_pulls_per_catch_up: 100,
+            shared_mempool_max_concurrent_pulls_per_peer: 2,
+            shared_mempool_pull_timeout_ms: 5_000,
             system_transaction_timeout_secs: 600,
             system_transaction_gc_interval_ms: 60_000,
             broadcast_buckets: DEFAULT_BUCKETS.to_vec(),
             eager_expire_threshold_ms: Some(10_000),
             eager_expire_time_ms: 3_000,
+            transaction_filter: Filter::empty(),
+            journal_path: None,
+            journal_compaction_interval_secs: 60,
+            fairness: MempoolFairnessConfig::default(),
         }
     }
 }
 
+/// Fairness quotas applied when consensus pulls a batch of transactions, so that a single spammy
+/// sender or hot contract can't crowd everyone else out at the same gas price.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MempoolFairnessConfig {
+    /// Maximum number of transactions from a single sender in a batch
+    pub max_txns_per_sender: Option<u64>,
+    /// Maximum number of transactions in a batch calling entry functions of modules published at
+    /// the same address
+    pub max_txns_per_module_address: Option<u64>,
+    /// If set, senders with transactions at the same gas price take turns, each taking up to this
+    /// many consecutive transactions per turn. Otherwise, transactions at the same gas price are
+    /// ordered by expiration time.
+    pub round_robin_weight: Option<u64>,
+}
+
 impl ConfigSanitizer for MempoolConfig {
     fn sanitize(
-        _node_config: &NodeConfig,
+        node_config: &NodeConfig,
         _node_type: NodeType,
         _chain_id: Option<ChainId>,
     ) -> Result<(), Error> {
-        Ok(()) // TODO: add reasonable verifications
+        let sanitizer_name = Self::get_sanitizer_name();
+        let mempool_config = &node_config.mempool;
+
+        // Verify that the journal compaction interval is not zero
+        if mempool_config.journal_path.is_some()
+            && mempool_config.journal_compaction_interval_secs == 0
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The mempool journal compaction interval must be greater than 0!".into(),
+            ));
+        }
+
+        Ok(())
     }
 }
 
@@ -143,6 +203,22 @@ impl ConfigOptimizer for MempoolConfig {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_sanitize_journal_compaction_interval() {
+        // Create a node config with a journal and a zero compaction interval
+        let mut node_config = NodeConfig::default();
+        node_config.mempool.journal_path = Some(PathBuf::from("mempool.journal"));
+        node_config.mempool.journal_compaction_interval_secs = 0;
+
+        // Verify that the config fails sanitization
+        let error = MempoolConfig::sanitize(&node_config, NodeType::Validator, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that a non-zero interval passes sanitization
+        node_config.mempool.journal_compaction_interval_secs = 1;
+        MempoolConfig::sanitize(&node_config, NodeType::Validator, None).unwrap();
+    }
+
     #[test]
     fn test_optimize_vfn_configs() {
         // Create the default VFN config
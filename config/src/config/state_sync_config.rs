@@ -6,6 +6,7 @@ use crate::config::{
     config_optimizer::ConfigOptimizer, config_sanitizer::ConfigSanitizer,
     node_config_loader::NodeType, Error, NodeConfig,
 };
+use aptos_compression::CompressionCodec;
 use aptos_types::chain_id::ChainId;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
@@ -40,6 +41,9 @@ pub enum BootstrappingMode {
     ApplyTransactionOutputsFromGenesis,
     /// Downloads the state keys and values (at the latest version)
     DownloadLatestStates,
+    /// Downloads the state keys and values (at the latest backed up version)
+    /// from the backup storage in `storage.bootstrap_backup_source`
+    DownloadLatestStatesFromBackup,
     /// Executes transactions (starting at genesis)
     ExecuteTransactionsFromGenesis,
     /// Executes transactions or applies outputs from genesis (whichever is faster)
@@ -53,6 +57,9 @@ impl BootstrappingMode {
                 "apply_transaction_outputs_from_genesis"
             },
             BootstrappingMode::DownloadLatestStates => "download_latest_states",
+            BootstrappingMode::DownloadLatestStatesFromBackup => {
+                "download_latest_states_from_backup"
+            },
             BootstrappingMode::ExecuteTransactionsFromGenesis => {
                 "execute_transactions_from_genesis"
             },
@@ -62,7 +69,16 @@ impl BootstrappingMode {
 
     /// Returns true iff the bootstrapping mode is fast sync
     pub fn is_fast_sync(&self) -> bool {
-        *self == BootstrappingMode::DownloadLatestStates
+        matches!(
+            self,
+            BootstrappingMode::DownloadLatestStates
+                | BootstrappingMode::DownloadLatestStatesFromBackup
+        )
+    }
+
+    /// Returns true iff the bootstrapping mode reads from a backup storage
+    pub fn is_from_backup(&self) -> bool {
+        *self == BootstrappingMode::DownloadLatestStatesFromBackup
     }
 }
 
@@ -147,6 +163,9 @@ impl Default for StateSyncDriverConfig {
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 #[serde(default, deny_unknown_fields)]
 pub struct StorageServiceConfig {
+    /// The codec to use when compressing responses for peers that support
+    /// codec negotiation (older peers always receive lz4 compressed data).
+    pub compression_codec: CompressionCodec,
     /// Maximum number of concurrent storage server tasks
     pub max_concurrent_requests: u64,
     /// Maximum number of epoch ending ledger infos per chunk
@@ -182,6 +201,7 @@ pub struct StorageServiceConfig {
 impl Default for StorageServiceConfig {
     fn default() -> Self {
         Self {
+            compression_codec: CompressionCodec::Lz4,
             max_concurrent_requests: 4000,
             max_epoch_chunk_size: MAX_EPOCH_CHUNK_SIZE,
             max_invalid_requests_per_peer: 500,
@@ -295,6 +315,9 @@ impl Default for AptosDataPollerConfig {
 pub struct AptosDataClientConfig {
     /// The aptos data poller config for the data client
     pub data_poller_config: AptosDataPollerConfig,
+    /// Whether or not to persist peer reputations (i.e., scores, latencies and
+    /// bans) in the data directory, so that they survive node restarts.
+    pub enable_peer_reputation_persistence: bool,
     /// The reduction factor for latency filtering when selecting peers
     pub latency_filtering_reduction_factor: u64,
     /// The interval (milliseconds) at which to refresh the latency monitor
@@ -321,6 +344,11 @@ pub struct AptosDataClientConfig {
     pub min_peers_for_latency_filtering: u64,
     /// Timeout (in ms) when waiting for an optimistic fetch response
     pub optimistic_fetch_timeout_ms: u64,
+    /// The interval (milliseconds) at which to decay and persist peer reputations
+    pub peer_reputation_update_interval_ms: u64,
+    /// The time (in seconds) it takes for a peer's score to decay half way back
+    /// to the starting score. If 0, scores never decay.
+    pub peer_score_decay_half_life_secs: u64,
     /// First timeout (in ms) when waiting for a response
     pub response_timeout_ms: u64,
     /// Timeout (in ms) when waiting for a subscription response
@@ -333,6 +361,7 @@ impl Default for AptosDataClientConfig {
     fn default() -> Self {
         Self {
             data_poller_config: AptosDataPollerConfig::default(),
+            enable_peer_reputation_persistence: true,
             latency_filtering_reduction_factor: 2, // Only consider the best 50% of peers
             latency_monitor_loop_interval_ms: 100,
             max_epoch_chunk_size: MAX_EPOCH_CHUNK_SIZE,
@@ -346,6 +375,8 @@ impl Default for AptosDataClientConfig {
             min_peer_ratio_for_latency_filtering: 5, // Only filter if we have at least 5 potential peers per request
             min_peers_for_latency_filtering: 10, // Only filter if we have at least 10 total peers
             optimistic_fetch_timeout_ms: 5000,   // 5 seconds
+            peer_reputation_update_interval_ms: 60_000, // 1 minute
+            peer_score_decay_half_life_secs: 3600, // 1 hour
             response_timeout_ms: 10_000,         // 10 seconds
             subscription_response_timeout_ms: 20_000, // 20 seconds (must be longer than a regular timeout because of pre-fetching)
             use_compression: true,
@@ -360,7 +391,10 @@ impl ConfigSanitizer for StateSyncConfig {
         chain_id: Option<ChainId>,
     ) -> Result<(), Error> {
         // Sanitize the state sync driver config
-        StateSyncDriverConfig::sanitize(node_config, node_type, chain_id)
+        StateSyncDriverConfig::sanitize(node_config, node_type, chain_id)?;
+
+        // Sanitize the storage service config
+        StorageServiceConfig::sanitize(node_config, node_type, chain_id)
     }
 }
 
@@ -384,6 +418,54 @@ impl ConfigSanitizer for StateSyncDriverConfig {
             ));
         }
 
+        // Verify that a backup source is configured for nodes that bootstrap from backups
+        if state_sync_driver_config.bootstrapping_mode.is_from_backup()
+            && node_config.storage.bootstrap_backup_source.is_none()
+        {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                "The bootstrap backup source must be set for nodes that bootstrap from backups!"
+                    .to_string(),
+            ));
+        }
+
+        // Verify that at most one source is configured for the backup encryption key
+        if let Some(backup_source) = &node_config.storage.bootstrap_backup_source {
+            if backup_source.encryption_key_file.is_some()
+                && backup_source.encryption_key_env.is_some()
+            {
+                return Err(Error::ConfigSanitizerFailed(
+                    sanitizer_name,
+                    "Only one of the backup encryption key file and env var can be set!"
+                        .to_string(),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ConfigSanitizer for StorageServiceConfig {
+    fn sanitize(
+        node_config: &NodeConfig,
+        _node_type: NodeType,
+        _chain_id: Option<ChainId>,
+    ) -> Result<(), Error> {
+        let sanitizer_name = Self::get_sanitizer_name();
+        let storage_service_config = &node_config.state_sync.storage_service;
+
+        // Verify that the compression codec is valid
+        if let Err(error) = storage_service_config.compression_codec.validate() {
+            return Err(Error::ConfigSanitizerFailed(
+                sanitizer_name,
+                format!(
+                    "The storage service compression codec is invalid: {}",
+                    error
+                ),
+            ));
+        }
+
         Ok(())
     }
 }
@@ -471,6 +553,25 @@ impl ConfigOptimizer for DataStreamingServiceConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::{BackupSourceConfig, BackupStorageLocation};
+
+    #[test]
+    fn test_sanitize_compression_codec() {
+        // Create a node config with an invalid zstd compression level
+        let mut node_config = NodeConfig::default();
+        node_config.state_sync.storage_service.compression_codec =
+            CompressionCodec::Zstd { level: 100 };
+
+        // Verify that the config fails sanitization
+        let error =
+            StateSyncConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Verify that a valid level passes sanitization
+        node_config.state_sync.storage_service.compression_codec =
+            CompressionCodec::Zstd { level: 3 };
+        StateSyncConfig::sanitize(&node_config, NodeType::PublicFullnode, None).unwrap();
+    }
 
     #[test]
     fn test_optimize_bootstrapping_mode_devnet_vfn() {
@@ -689,6 +790,58 @@ mod tests {
         assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
     }
 
+    #[test]
+    fn test_sanitize_missing_bootstrap_backup_source() {
+        // Create a node config that bootstraps from backups without a backup source
+        let mut node_config = NodeConfig {
+            state_sync: StateSyncConfig {
+                state_sync_driver: StateSyncDriverConfig {
+                    bootstrapping_mode: BootstrappingMode::DownloadLatestStatesFromBackup,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        // Verify that sanitization fails
+        let error = StateSyncConfig::sanitize(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+
+        // Set the backup source and verify that sanitization succeeds
+        node_config.storage.bootstrap_backup_source =
+            Some(BackupSourceConfig::new(BackupStorageLocation::LocalFs {
+                dir: "/opt/aptos/backups".into(),
+            }));
+        StateSyncConfig::sanitize(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap();
+
+        // Set both encryption key sources and verify that sanitization fails
+        let backup_source = node_config
+            .storage
+            .bootstrap_backup_source
+            .as_mut()
+            .unwrap();
+        backup_source.encryption_key_file = Some("/opt/aptos/backup.key".into());
+        backup_source.encryption_key_env = Some("BACKUP_KEY".into());
+        let error = StateSyncConfig::sanitize(
+            &node_config,
+            NodeType::PublicFullnode,
+            Some(ChainId::testnet()),
+        )
+        .unwrap_err();
+        assert!(matches!(error, Error::ConfigSanitizerFailed(_, _)));
+    }
+
     /// Creates and returns a node config with the syncing modes set to execution
     fn create_execution_mode_config() -> NodeConfig {
         NodeConfig {
@@ -174,6 +174,45 @@ pub struct StorageConfig {
     /// If not specificed, will use `dir` as default.
     /// Only allowed when sharding is enabled.
     pub db_path_overrides: Option<DbPathConfig>,
+    /// The backup storage that state sync bootstraps from when using the
+    /// `DownloadLatestStatesFromBackup` bootstrapping mode.
+    pub bootstrap_backup_source: Option<BackupSourceConfig>,
+}
+
+/// The backup storage that state sync bootstraps from
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct BackupSourceConfig {
+    /// The location of the backups
+    pub storage: BackupStorageLocation,
+    /// File holding the hex encoded key the backups were encrypted with
+    #[serde(default)]
+    pub encryption_key_file: Option<PathBuf>,
+    /// Name of the env var holding the encryption key, alternative to `encryption_key_file`
+    #[serde(default)]
+    pub encryption_key_env: Option<String>,
+}
+
+impl BackupSourceConfig {
+    pub fn new(storage: BackupStorageLocation) -> Self {
+        Self {
+            storage,
+            encryption_key_file: None,
+            encryption_key_env: None,
+        }
+    }
+}
+
+/// The location of the backups written by the backup coordinator
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStorageLocation {
+    /// A local directory holding the backups
+    LocalFs { dir: PathBuf },
+    /// The config file of a command adapter (e.g., to read from a cloud bucket)
+    CommandAdapter { config: PathBuf },
+    /// The config file of an S3 (or S3 compatible) backup store
+    S3 { config: PathBuf },
 }
 
 pub const NO_OP_STORAGE_PRUNER_CONFIG: PrunerConfig = PrunerConfig {
@@ -321,6 +360,7 @@ impl Default for StorageConfig {
             rocksdb_configs: RocksdbConfigs::default(),
             enable_indexer: false,
             db_path_overrides: None,
+            bootstrap_backup_source: None,
             buffered_state_target_items: BUFFERED_STATE_TARGET_ITEMS,
             max_num_nodes_per_lru_cache_shard: DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
         }
@@ -8,8 +8,30 @@ use aptos_types::{
 };
 use serde::{Deserialize, Serialize};
 
+/// The type of a transaction payload, see `TransactionPayload`
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PayloadType {
+    Script,
+    ModuleBundle,
+    EntryFunction,
+    Multisig,
+}
+
+impl PayloadType {
+    fn of(payload: &TransactionPayload) -> Self {
+        match payload {
+            TransactionPayload::Script(_) => PayloadType::Script,
+            TransactionPayload::ModuleBundle(_) => PayloadType::ModuleBundle,
+            TransactionPayload::EntryFunction(_) => PayloadType::EntryFunction,
+            TransactionPayload::Multisig(_) => PayloadType::Multisig,
+        }
+    }
+}
+
+/// Matches transactions, either on their own properties or on the block they are proposed in.
+/// Matchers can be composed with `And`, `Or` and `Not`.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
-enum Matcher {
+pub enum Matcher {
     All,
     BlockId(HashValue),
     BlockTimeStampGreaterThan(u64),
@@ -18,13 +40,38 @@ enum Matcher {
     Sender(AccountAddress),
     ModuleAddress(AccountAddress),
     EntryFunction(AccountAddress, String, String),
+    /// Matches if all the matchers match (and matches if there are none)
+    And(Vec<Matcher>),
+    /// Matches if any of the matchers match (and doesn't match if there are none)
+    Or(Vec<Matcher>),
+    Not(Box<Matcher>),
+    PayloadType(PayloadType),
+    GasUnitPriceGreaterThan(u64),
+    GasUnitPriceLessThan(u64),
+    MaxGasAmountGreaterThan(u64),
+    MaxGasAmountLessThan(u64),
+    /// Matches entry function calls whose argument at the given index equals the given BCS
+    /// encoded value, written as a hex string (e.g., "0x0a00000000000000" for the u64 10)
+    EntryFunctionArgument(usize, #[serde(with = "hex_bytes")] Vec<u8>),
+    /// Matches entry function calls with any argument equal to the given address
+    AddressArgument(AccountAddress),
+    /// Matches multi-agent and fee payer transactions with the given secondary signer
+    SecondarySigner(AccountAddress),
+    FeePayer(AccountAddress),
 }
 
 impl Matcher {
-    fn matches(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> bool {
+    /// Evaluates the matcher. The block id is `None` when the transaction is not (yet) in a
+    /// block, e.g., on mempool admission, in which case `BlockId` never matches.
+    fn matches(
+        &self,
+        block_id: Option<HashValue>,
+        timestamp: u64,
+        txn: &SignedTransaction,
+    ) -> bool {
         match self {
             Matcher::All => true,
-            Matcher::BlockId(id) => block_id == *id,
+            Matcher::BlockId(id) => block_id == Some(*id),
             Matcher::BlockTimeStampGreaterThan(ts) => timestamp > *ts,
             Matcher::BlockTimeStampLessThan(ts) => timestamp < *ts,
             Matcher::TransactionId(id) => txn.clone().committed_hash() == *id,
@@ -43,10 +90,57 @@ impl Matcher {
                 },
                 _ => false,
             },
+            Matcher::And(matchers) => matchers
+                .iter()
+                .all(|matcher| matcher.matches(block_id, timestamp, txn)),
+            Matcher::Or(matchers) => matchers
+                .iter()
+                .any(|matcher| matcher.matches(block_id, timestamp, txn)),
+            Matcher::Not(matcher) => !matcher.matches(block_id, timestamp, txn),
+            Matcher::PayloadType(payload_type) => PayloadType::of(txn.payload()) == *payload_type,
+            Matcher::GasUnitPriceGreaterThan(price) => txn.gas_unit_price() > *price,
+            Matcher::GasUnitPriceLessThan(price) => txn.gas_unit_price() < *price,
+            Matcher::MaxGasAmountGreaterThan(amount) => txn.max_gas_amount() > *amount,
+            Matcher::MaxGasAmountLessThan(amount) => txn.max_gas_amount() < *amount,
+            Matcher::EntryFunctionArgument(index, value) => match txn.payload() {
+                TransactionPayload::EntryFunction(entry_function) => {
+                    entry_function.args().get(*index) == Some(value)
+                },
+                _ => false,
+            },
+            Matcher::AddressArgument(address) => match txn.payload() {
+                TransactionPayload::EntryFunction(entry_function) => entry_function
+                    .args()
+                    .iter()
+                    .any(|arg| arg[..] == address.into_bytes()),
+                _ => false,
+            },
+            Matcher::SecondarySigner(address) => txn
+                .authenticator_ref()
+                .secondary_signer_addresses()
+                .contains(address),
+            Matcher::FeePayer(address) => {
+                txn.authenticator_ref().fee_payer_address() == Some(*address)
+            },
         }
     }
 }
 
+/// (De)serializes bytes as a hex string, with an optional "0x" prefix
+mod hex_bytes {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format!("0x{}", hex::encode(bytes)))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let hex_string = String::deserialize(deserializer)?;
+        let hex_string = hex_string.strip_prefix("0x").unwrap_or(&hex_string);
+        hex::decode(hex_string).map_err(D::Error::custom)
+    }
+}
+
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
 enum Rule {
     Allow(Matcher),
@@ -60,7 +154,12 @@ enum EvalResult {
 }
 
 impl Rule {
-    fn eval(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> EvalResult {
+    fn eval(
+        &self,
+        block_id: Option<HashValue>,
+        timestamp: u64,
+        txn: &SignedTransaction,
+    ) -> EvalResult {
         match self {
             Rule::Allow(matcher) => {
                 if matcher.matches(block_id, timestamp, txn) {
@@ -104,6 +203,27 @@ impl Rule {
 /// This filter allows transactions from the sender with address f8871acf2c827d40e23b71f6ff2b9accef8dbb17709b88bd9eb95e6bb748c25a or
 /// from the module with address 0000000000000000000000000000000000000000000000000000000000000001 or entry functions
 /// test::check and test::new from the module 0000000000000000000000000000000000000000000000000000000000000001. All other transactions are denied.
+///
+/// Matchers can be combined with And, Or and Not, e.g.:
+///             rules:
+///                 - Deny:
+///                     And:
+///                         - Sender: f8871acf2c827d40e23b71f6ff2b9accef8dbb17709b88bd9eb95e6bb748c25a
+///                         - Not:
+///                             PayloadType: EntryFunction
+///                 - Deny:
+///                     And:
+///                         - GasUnitPriceLessThan: 150
+///                         - Or:
+///                             - SecondarySigner: "0000000000000000000000000000000000000000000000000000000000000042"
+///                             - EntryFunctionArgument:
+///                                 - 0
+///                                 - "0x0000000000000000000000000000000000000000000000000000000000000042"
+/// This filter denies all non entry function transactions from the given sender, and cheap
+/// transactions that involve the address 0x42 as a secondary signer or as the first argument.
+///
+/// The same filter can be used in consensus (execution.transaction_filter) and on mempool
+/// admission (mempool.transaction_filter).
 #[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub struct Filter {
     rules: Vec<Rule>,
@@ -188,7 +308,33 @@ impl Filter {
         self
     }
 
+    pub fn add_allow(mut self, matcher: Matcher) -> Self {
+        self.rules.push(Rule::Allow(matcher));
+        self
+    }
+
+    pub fn add_deny(mut self, matcher: Matcher) -> Self {
+        self.rules.push(Rule::Deny(matcher));
+        self
+    }
+
     pub fn allows(&self, block_id: HashValue, timestamp: u64, txn: &SignedTransaction) -> bool {
+        self.allows_internal(Some(block_id), timestamp, txn)
+    }
+
+    /// Evaluates the filter for a transaction that is not in a block, e.g., on mempool
+    /// admission. `BlockId` rules never match, and block timestamp rules are evaluated
+    /// against the given timestamp (e.g., the current time).
+    pub fn allows_transaction(&self, timestamp: u64, txn: &SignedTransaction) -> bool {
+        self.allows_internal(None, timestamp, txn)
+    }
+
+    fn allows_internal(
+        &self,
+        block_id: Option<HashValue>,
+        timestamp: u64,
+        txn: &SignedTransaction,
+    ) -> bool {
         for rule in &self.rules {
             // Rules are evaluated in the order and the first rule that matches is used. If no rule
             // matches, the transaction is allowed.
@@ -12,7 +12,8 @@ use aptos_consensus_types::{block::Block, quorum_cert::QuorumCert};
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_schemadb::{
-    schema::Schema, Options, ReadOptions, SchemaBatch, DB, DEFAULT_COLUMN_FAMILY_NAME,
+    schema::Schema, ColumnFamilyName, Options, ReadOptions, SchemaBatch, DB,
+    DEFAULT_COLUMN_FAMILY_NAME,
 };
 pub use schema::{
     block::BlockSchema,
@@ -50,8 +51,8 @@ pub struct ConsensusDB {
 }
 
 impl ConsensusDB {
-    pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
-        let column_families = vec![
+    fn column_families() -> Vec<ColumnFamilyName> {
+        vec![
             /* UNUSED CF = */ DEFAULT_COLUMN_FAMILY_NAME,
             BLOCK_CF_NAME,
             QC_CF_NAME,
@@ -60,14 +61,16 @@ impl ConsensusDB {
             CERTIFIED_NODE_CF_NAME,
             DAG_VOTE_CF_NAME,
             "ordered_anchor_id", // deprecated CF
-        ];
+        ]
+    }
 
+    pub fn new<P: AsRef<Path> + Clone>(db_root_path: P) -> Self {
         let path = db_root_path.as_ref().join(CONSENSUS_DB_NAME);
         let instant = Instant::now();
         let mut opts = Options::default();
         opts.create_if_missing(true);
         opts.create_missing_column_families(true);
-        let db = DB::open(path.clone(), "consensus", column_families, &opts)
+        let db = DB::open(path.clone(), "consensus", Self::column_families(), &opts)
             .expect("ConsensusDB open failed; unable to continue");
 
         info!(
@@ -79,6 +82,18 @@ impl ConsensusDB {
         Self { db }
     }
 
+    /// Opens an existing DB for offline inspection, nothing can be written through it.
+    pub fn new_readonly<P: AsRef<Path>>(db_root_path: P) -> Result<Self> {
+        let path = db_root_path.as_ref().join(CONSENSUS_DB_NAME);
+        let db = DB::open_cf_readonly(
+            &Options::default(),
+            path,
+            "consensus_readonly",
+            Self::column_families(),
+        )?;
+        Ok(Self { db })
+    }
+
     pub fn get_data(
         &self,
     ) -> Result<(
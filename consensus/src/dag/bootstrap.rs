@@ -37,7 +37,7 @@ use aptos_channels::{
     aptos_channel::{self, Receiver},
     message_queues::QueueStyle,
 };
-use aptos_config::config::DagConsensusConfig;
+use aptos_config::config::{ChainHealthBackoffValues, DagConsensusConfig};
 use aptos_consensus_types::common::{Author, Round};
 use aptos_infallible::RwLock;
 use aptos_logger::{debug, info};
@@ -363,49 +363,11 @@ impl DagBootstrapper {
     }
 
     fn build_leader_reputation_components(&self) -> Arc<LeaderReputationAdapter> {
-        let num_validators = self.epoch_state.verifier.len();
-        // TODO: support multiple epochs
-        let metadata_adapter = Arc::new(MetadataBackendAdapter::new(
-            num_validators * 10,
-            HashMap::from([(
-                self.epoch_state.epoch,
-                self.epoch_state
-                    .verifier
-                    .address_to_validator_index()
-                    .clone(),
-            )]),
-        ));
-        // TODO: use onchain config
-        let heuristic: Box<dyn ReputationHeuristic> = Box::new(ProposerAndVoterHeuristic::new(
+        build_leader_reputation_adapter(
             self.self_peer,
-            1000,
-            10,
-            1,
-            10,
-            num_validators,
-            num_validators * 10,
-            false,
-        ));
-
-        let voting_power: Vec<u64> = self
-            .epoch_state
-            .verifier
-            .get_ordered_account_addresses_iter()
-            .map(|p| self.epoch_state.verifier.get_voting_power(&p).unwrap())
-            .collect();
-
-        Arc::new(LeaderReputationAdapter::new(
-            self.epoch_state.epoch,
-            HashMap::from([(
-                self.epoch_state.epoch,
-                self.epoch_state.verifier.get_ordered_account_addresses(),
-            )]),
-            voting_power,
-            metadata_adapter,
-            heuristic,
-            100,
-            ChainHealthBackoffConfig::new(self.config.chain_backoff_config.clone()),
-        ))
+            &self.epoch_state,
+            self.config.chain_backoff_config.clone(),
+        )
     }
 
     fn bootstrap_dag_store(
@@ -614,6 +576,54 @@ impl DagBootstrapper {
     }
 }
 
+/// The anchor election used by DAG consensus. `self_peer` only affects the reputation metrics,
+/// not the elected anchors.
+pub(super) fn build_leader_reputation_adapter(
+    self_peer: Author,
+    epoch_state: &EpochState,
+    chain_backoff_config: Vec<ChainHealthBackoffValues>,
+) -> Arc<LeaderReputationAdapter> {
+    let num_validators = epoch_state.verifier.len();
+    // TODO: support multiple epochs
+    let metadata_adapter = Arc::new(MetadataBackendAdapter::new(
+        num_validators * 10,
+        HashMap::from([(
+            epoch_state.epoch,
+            epoch_state.verifier.address_to_validator_index().clone(),
+        )]),
+    ));
+    // TODO: use onchain config
+    let heuristic: Box<dyn ReputationHeuristic> = Box::new(ProposerAndVoterHeuristic::new(
+        self_peer,
+        1000,
+        10,
+        1,
+        10,
+        num_validators,
+        num_validators * 10,
+        false,
+    ));
+
+    let voting_power: Vec<u64> = epoch_state
+        .verifier
+        .get_ordered_account_addresses_iter()
+        .map(|p| epoch_state.verifier.get_voting_power(&p).unwrap())
+        .collect();
+
+    Arc::new(LeaderReputationAdapter::new(
+        epoch_state.epoch,
+        HashMap::from([(
+            epoch_state.epoch,
+            epoch_state.verifier.get_ordered_account_addresses(),
+        )]),
+        voting_power,
+        metadata_adapter,
+        heuristic,
+        100,
+        ChainHealthBackoffConfig::new(chain_backoff_config),
+    ))
+}
+
 pub(super) fn bootstrap_dag_for_test(
     self_peer: Author,
     signer: ValidatorSigner,
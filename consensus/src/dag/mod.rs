@@ -16,6 +16,7 @@ mod errors;
 mod observability;
 mod order_rule;
 mod rb_handler;
+mod replay;
 mod round_state;
 mod storage;
 #[cfg(test)]
@@ -26,6 +27,7 @@ pub use adapter::{ProofNotifier, StorageAdapter};
 pub use bootstrap::DagBootstrapper;
 pub use commit_signer::DagCommitSigner;
 pub use dag_network::{RpcHandler, RpcWithFallback, TDAGNetworkSender};
+pub use replay::{DagReplay, NodeSummary, OrderedAnchor, RoundSummary};
 pub use storage::DAGStorage;
 pub use types::{
     CertifiedNode, DAGMessage, DAGNetworkMessage, DAGRpcResult, Extensions, Node, NodeId, Vote,
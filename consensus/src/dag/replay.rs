@@ -0,0 +1,287 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Offline reconstruction of the persisted DAG, for inspecting it and re-running the ordering
+//! exactly as a node would on restart.
+
+use crate::{
+    dag::{
+        adapter::{compute_initial_block_and_ledger_info, OrderedNotifier},
+        bootstrap::build_leader_reputation_adapter,
+        dag_store::{Dag, NodeStatus},
+        order_rule::OrderRule,
+        storage::{CommitEvent, DAGStorage},
+        types::Vote,
+        CertifiedNode, Node, NodeId,
+    },
+    payload_manager::PayloadManager,
+};
+use anyhow::ensure;
+use aptos_config::config::ChainHealthBackoffValues;
+use aptos_consensus_types::common::{Author, Round};
+use aptos_crypto::HashValue;
+use aptos_infallible::{Mutex, RwLock};
+use aptos_types::{
+    account_address::AccountAddress, epoch_state::EpochState, ledger_info::LedgerInfoWithSignatures,
+};
+use std::sync::Arc;
+
+/// Reads through to the wrapped storage but never writes, so that rebuilding the DAG doesn't
+/// prune or rewrite anything. Certified nodes above `until_round` are hidden, to replay the DAG
+/// as it was at that round.
+struct ReadOnlyStorage {
+    inner: Arc<dyn DAGStorage>,
+    until_round: Option<Round>,
+    /// Nodes that the DAG store would have deleted on bootstrap.
+    rejected: Mutex<Vec<HashValue>>,
+}
+
+impl DAGStorage for ReadOnlyStorage {
+    fn save_pending_node(&self, _node: &Node) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn get_pending_node(&self) -> anyhow::Result<Option<Node>> {
+        self.inner.get_pending_node()
+    }
+
+    fn delete_pending_node(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn save_vote(&self, _node_id: &NodeId, _vote: &Vote) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn get_votes(&self) -> anyhow::Result<Vec<(NodeId, Vote)>> {
+        self.inner.get_votes()
+    }
+
+    fn delete_votes(&self, _node_ids: Vec<NodeId>) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn save_certified_node(&self, _node: &CertifiedNode) -> anyhow::Result<()> {
+        Ok(())
+    }
+
+    fn get_certified_nodes(&self) -> anyhow::Result<Vec<(HashValue, CertifiedNode)>> {
+        let mut nodes = self.inner.get_certified_nodes()?;
+        if let Some(until_round) = self.until_round {
+            nodes.retain(|(_, node)| node.round() <= until_round);
+        }
+        Ok(nodes)
+    }
+
+    fn delete_certified_nodes(&self, digests: Vec<HashValue>) -> anyhow::Result<()> {
+        self.rejected.lock().extend(digests);
+        Ok(())
+    }
+
+    fn get_latest_k_committed_events(&self, k: u64) -> anyhow::Result<Vec<CommitEvent>> {
+        self.inner.get_latest_k_committed_events(k)
+    }
+
+    fn get_latest_ledger_info(&self) -> anyhow::Result<LedgerInfoWithSignatures> {
+        self.inner.get_latest_ledger_info()
+    }
+}
+
+/// An anchor ordered by the order rule, along with the nodes ordered with it.
+pub struct OrderedAnchor {
+    pub anchor: NodeId,
+    /// Ordered nodes, from the lowest round up to the anchor itself.
+    pub nodes: Vec<NodeId>,
+    /// The anchors skipped since the previously ordered anchor.
+    pub failed_anchors: Vec<(Round, Author)>,
+}
+
+#[derive(Default)]
+struct RecordingNotifier {
+    ordered: Mutex<Vec<OrderedAnchor>>,
+}
+
+impl OrderedNotifier for RecordingNotifier {
+    fn send_ordered_nodes(
+        &self,
+        ordered_nodes: Vec<Arc<CertifiedNode>>,
+        failed_author: Vec<(Round, Author)>,
+    ) {
+        let anchor = ordered_nodes.last().expect("must have the anchor").id();
+        self.ordered.lock().push(OrderedAnchor {
+            anchor,
+            nodes: ordered_nodes.iter().map(|node| node.id()).collect(),
+            failed_anchors: failed_author,
+        });
+    }
+}
+
+pub struct NodeSummary {
+    pub author: Author,
+    pub digest: HashValue,
+    pub timestamp: u64,
+    pub num_parents: usize,
+    /// Number of nodes in the next round that have this node as a parent.
+    pub num_votes: usize,
+    pub ordered: bool,
+}
+
+pub struct RoundSummary {
+    pub round: Round,
+    pub nodes: Vec<NodeSummary>,
+    pub has_strong_links: bool,
+}
+
+/// The DAG rebuilt from storage, with the ordering re-run the way `DagBootstrapper` does on
+/// startup: anchors of committed blocks are marked ordered and the anchor election is fed the
+/// committed history, then everything that can be ordered is.
+///
+/// Given the same storage, the result is deterministic.
+pub struct DagReplay {
+    epoch_state: Arc<EpochState>,
+    dag: Arc<RwLock<Dag>>,
+    committed_round: Round,
+    ordered_anchors: Vec<OrderedAnchor>,
+    rejected_nodes: Vec<HashValue>,
+    pending_node: Option<Node>,
+    votes: Vec<(NodeId, Vote)>,
+}
+
+impl DagReplay {
+    pub fn new(
+        epoch_state: Arc<EpochState>,
+        storage: Arc<dyn DAGStorage>,
+        dag_window_size_config: Round,
+        chain_backoff_config: Vec<ChainHealthBackoffValues>,
+        until_round: Option<Round>,
+    ) -> anyhow::Result<Self> {
+        let storage = Arc::new(ReadOnlyStorage {
+            inner: storage,
+            until_round,
+            rejected: Mutex::new(vec![]),
+        });
+        let (_, ledger_info) =
+            compute_initial_block_and_ledger_info(storage.get_latest_ledger_info()?);
+        let ledger_info = ledger_info.ledger_info();
+        ensure!(
+            ledger_info.epoch() == epoch_state.epoch,
+            "Latest ledger info is in epoch {}, but replaying epoch {}.",
+            ledger_info.epoch(),
+            epoch_state.epoch,
+        );
+        let committed_round = ledger_info.round();
+        let initial_round =
+            std::cmp::max(1, committed_round.saturating_sub(dag_window_size_config));
+
+        let dag = Arc::new(RwLock::new(Dag::new(
+            epoch_state.clone(),
+            storage.clone(),
+            Arc::new(PayloadManager::DirectMempool),
+            initial_round,
+            dag_window_size_config,
+        )));
+        let notifier = Arc::new(RecordingNotifier::default());
+        let anchor_election = build_leader_reputation_adapter(
+            AccountAddress::ZERO,
+            &epoch_state,
+            chain_backoff_config,
+        );
+        // Ordering everything possible happens in the constructor.
+        OrderRule::new(
+            epoch_state.clone(),
+            committed_round + 1,
+            dag.clone(),
+            anchor_election,
+            notifier.clone(),
+            storage.clone(),
+            dag_window_size_config,
+        );
+
+        let ordered_anchors = std::mem::take(&mut *notifier.ordered.lock());
+        let rejected_nodes = std::mem::take(&mut *storage.rejected.lock());
+        Ok(Self {
+            epoch_state,
+            dag,
+            committed_round,
+            ordered_anchors,
+            rejected_nodes,
+            pending_node: storage.get_pending_node()?,
+            votes: storage.get_votes()?,
+        })
+    }
+
+    pub fn epoch(&self) -> u64 {
+        self.epoch_state.epoch
+    }
+
+    /// Round of the anchor of the latest committed block.
+    pub fn committed_round(&self) -> Round {
+        self.committed_round
+    }
+
+    pub fn lowest_round(&self) -> Round {
+        self.dag.read().lowest_round()
+    }
+
+    pub fn highest_round(&self) -> Round {
+        self.dag.read().highest_round()
+    }
+
+    pub fn rounds(&self) -> Vec<RoundSummary> {
+        let dag = self.dag.read();
+        let verifier = &self.epoch_state.verifier;
+        let validators = verifier.get_ordered_account_addresses();
+        (dag.lowest_round()..=dag.highest_round())
+            .map(|round| {
+                let nodes = validators
+                    .iter()
+                    .filter_map(|author| dag.get_node_ref(round, author))
+                    .map(|node_status| {
+                        let node = node_status.as_node();
+                        let num_votes = validators
+                            .iter()
+                            .filter_map(|author| dag.get_node_by_round_author(round + 1, author))
+                            .filter(|next| {
+                                next.parents()
+                                    .iter()
+                                    .any(|cert| cert.metadata() == node.metadata())
+                            })
+                            .count();
+                        NodeSummary {
+                            author: *node.author(),
+                            digest: node.digest(),
+                            timestamp: node.timestamp(),
+                            num_parents: node.parents().len(),
+                            num_votes,
+                            ordered: matches!(node_status, NodeStatus::Ordered(_)),
+                        }
+                    })
+                    .collect();
+                RoundSummary {
+                    round,
+                    nodes,
+                    has_strong_links: dag.get_strong_links_for_round(round, verifier).is_some(),
+                }
+            })
+            .collect()
+    }
+
+    /// Anchors ordered by the replay, i.e. not yet committed according to storage.
+    pub fn ordered_anchors(&self) -> &[OrderedAnchor] {
+        &self.ordered_anchors
+    }
+
+    /// Persisted certified nodes that didn't make it into the DAG, e.g. because they are too old
+    /// or of another epoch. A node would delete them on startup.
+    pub fn rejected_nodes(&self) -> &[HashValue] {
+        &self.rejected_nodes
+    }
+
+    pub fn pending_node(&self) -> Option<&Node> {
+        self.pending_node.as_ref()
+    }
+
+    pub fn votes(&self) -> &[(NodeId, Vote)] {
+        &self.votes
+    }
+}
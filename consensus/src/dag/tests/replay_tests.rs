@@ -0,0 +1,121 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::dag::{
+    storage::DAGStorage,
+    tests::{
+        dag_test::MockStorage,
+        helpers::{generate_dag_nodes, TEST_DAG_WINDOW},
+    },
+    DagReplay, NodeId,
+};
+use aptos_crypto::HashValue;
+use aptos_types::{
+    aggregate_signature::AggregateSignature,
+    block_info::BlockInfo,
+    epoch_state::EpochState,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
+    validator_verifier::random_validator_verifier,
+};
+use std::sync::Arc;
+
+const NUM_ROUNDS: usize = 8;
+
+fn setup() -> (Arc<EpochState>, Arc<MockStorage>, usize) {
+    let (_, validator_verifier) = random_validator_verifier(4, None, false);
+    let validators = validator_verifier.get_ordered_account_addresses();
+    let mut dag = vec![vec![Some(vec![]); 4]];
+    dag.extend(vec![
+        vec![Some(vec![true, true, true, false]); 4];
+        NUM_ROUNDS - 1
+    ]);
+    let nodes = generate_dag_nodes(&dag, &validators);
+    let epoch_state = Arc::new(EpochState {
+        epoch: 1,
+        verifier: validator_verifier,
+    });
+
+    let ledger_info = LedgerInfo::new(
+        BlockInfo::new(1, 0, HashValue::zero(), HashValue::zero(), 0, 0, None),
+        HashValue::zero(),
+    );
+    let storage = Arc::new(MockStorage::new_with_ledger_info(
+        LedgerInfoWithSignatures::new(ledger_info, AggregateSignature::empty()),
+    ));
+    let mut num_nodes = 0;
+    for node in nodes.into_iter().flatten().flatten() {
+        storage.save_certified_node(&node).unwrap();
+        num_nodes += 1;
+    }
+    (epoch_state, storage, num_nodes)
+}
+
+fn replay(
+    epoch_state: &Arc<EpochState>,
+    storage: &Arc<MockStorage>,
+    until_round: Option<u64>,
+) -> DagReplay {
+    DagReplay::new(
+        epoch_state.clone(),
+        storage.clone(),
+        TEST_DAG_WINDOW,
+        vec![],
+        until_round,
+    )
+    .unwrap()
+}
+
+fn ordered_nodes(replay: &DagReplay) -> Vec<NodeId> {
+    replay
+        .ordered_anchors()
+        .iter()
+        .flat_map(|ordered| ordered.nodes.clone())
+        .collect()
+}
+
+#[test]
+fn test_replay_is_deterministic_and_read_only() {
+    let (epoch_state, storage, num_nodes) = setup();
+
+    let first = replay(&epoch_state, &storage, None);
+    assert_eq!(first.highest_round(), NUM_ROUNDS as u64);
+    assert!(!first.ordered_anchors().is_empty());
+    for ordered in first.ordered_anchors() {
+        assert_eq!(ordered.nodes.last(), Some(&ordered.anchor));
+    }
+    let rounds = first.rounds();
+    assert_eq!(rounds.len(), NUM_ROUNDS);
+    assert!(rounds.iter().all(|round| round.nodes.len() == 4));
+    // Nodes of the last round have no votes yet.
+    assert!(rounds[NUM_ROUNDS - 1]
+        .nodes
+        .iter()
+        .all(|node| node.num_votes == 0));
+    assert_eq!(
+        rounds
+            .iter()
+            .flat_map(|round| &round.nodes)
+            .filter(|node| node.ordered)
+            .count(),
+        ordered_nodes(&first).len()
+    );
+
+    let second = replay(&epoch_state, &storage, None);
+    assert_eq!(ordered_nodes(&first), ordered_nodes(&second));
+
+    assert_eq!(storage.get_certified_nodes().unwrap().len(), num_nodes);
+    assert!(first.rejected_nodes().is_empty());
+}
+
+#[test]
+fn test_replay_until_round() {
+    let (epoch_state, storage, _) = setup();
+
+    let full = ordered_nodes(&replay(&epoch_state, &storage, None));
+    let partial_replay = replay(&epoch_state, &storage, Some(4));
+    assert_eq!(partial_replay.highest_round(), 4);
+    let partial = ordered_nodes(&partial_replay);
+    assert!(partial.iter().all(|node_id| node_id.round() <= 4));
+    assert!(partial.len() < full.len());
+    assert_eq!(partial, full[..partial.len()]);
+}
@@ -33,7 +33,7 @@ impl TransactionFilter {
 #[cfg(test)]
 mod test {
     use crate::transaction_filter::TransactionFilter;
-    use aptos_config::config::transaction_filter_type::Filter;
+    use aptos_config::config::transaction_filter_type::{Filter, Matcher, PayloadType};
     use aptos_crypto::{ed25519::Ed25519PrivateKey, HashValue, PrivateKey, SigningKey, Uniform};
     use aptos_types::{
         chain_id::ChainId,
@@ -43,6 +43,13 @@ mod test {
     use move_core_types::account_address::AccountAddress;
 
     fn create_signed_transaction(function: MemberId) -> SignedTransaction {
+        create_signed_transaction_with_args(function, vec![])
+    }
+
+    fn create_signed_transaction_with_args(
+        function: MemberId,
+        args: Vec<Vec<u8>>,
+    ) -> SignedTransaction {
         let private_key = Ed25519PrivateKey::generate_for_testing();
         let public_key = private_key.public_key();
         let sender = AccountAddress::random();
@@ -56,7 +63,7 @@ mod test {
             module_id,
             function_id,
             vec![],
-            vec![],
+            args,
         ));
         let raw_transaction =
             RawTransaction::new(sender, sequence_number, payload, 0, 0, 0, ChainId::new(10));
@@ -287,4 +294,117 @@ mod test {
         let filtered_txns = allow_list_filter.filter(block_id, 0, txns.clone());
         assert_eq!(filtered_txns, txns[4..].to_vec());
     }
+
+    #[test]
+    fn test_boolean_composition_filter() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+        let filter = serde_yaml::from_str::<Filter>(r#"
+            rules:
+                - Deny:
+                    And:
+                        - ModuleAddress: "0000000000000000000000000000000000000000000000000000000000000001"
+                        - Not:
+                            EntryFunction:
+                                - "0000000000000000000000000000000000000000000000000000000000000001"
+                                - test
+                                - check
+                - Allow:
+                    Or:
+                        - ModuleAddress: "0000000000000000000000000000000000000000000000000000000000000001"
+                        - ModuleAddress: "0000000000000000000000000000000000000000000000000000000000000002"
+                - Deny: All
+              "#).unwrap();
+
+        let filter = TransactionFilter::new(filter);
+        let filtered_txns = filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, vec![txns[1].clone(), txns[4].clone()]);
+    }
+
+    #[test]
+    fn test_payload_type_and_gas_filter() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+
+        // All test transactions are entry functions with a gas unit price and max gas amount of 0
+        let payload_type_filter = TransactionFilter::new(Filter::empty().add_deny(Matcher::Not(
+            Box::new(Matcher::PayloadType(PayloadType::EntryFunction)),
+        )));
+        let filtered_txns = payload_type_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns);
+
+        let gas_filter = TransactionFilter::new(Filter::empty().add_deny(Matcher::And(vec![
+            Matcher::GasUnitPriceLessThan(1),
+            Matcher::MaxGasAmountLessThan(1),
+        ])));
+        let filtered_txns = gas_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, vec![]);
+
+        let gas_filter =
+            TransactionFilter::new(Filter::empty().add_deny(Matcher::GasUnitPriceGreaterThan(0)));
+        let filtered_txns = gas_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns);
+    }
+
+    #[test]
+    fn test_argument_filter() {
+        let address = AccountAddress::random();
+        let txns = vec![
+            create_signed_transaction_with_args(str::parse("0x1::test::transfer").unwrap(), vec![
+                bcs::to_bytes(&address).unwrap(),
+                bcs::to_bytes(&10u64).unwrap(),
+            ]),
+            create_signed_transaction_with_args(str::parse("0x1::test::transfer").unwrap(), vec![
+                bcs::to_bytes(&AccountAddress::random()).unwrap(),
+                bcs::to_bytes(&20u64).unwrap(),
+            ]),
+        ];
+        let block_id = HashValue::random();
+
+        let address_filter =
+            TransactionFilter::new(Filter::empty().add_deny(Matcher::AddressArgument(address)));
+        let filtered_txns = address_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[1..].to_vec());
+
+        let argument_filter = TransactionFilter::new(
+            serde_yaml::from_str::<Filter>(
+                r#"
+            rules:
+                - Deny:
+                    EntryFunctionArgument:
+                        - 1
+                        - "0x1400000000000000"
+              "#,
+            )
+            .unwrap(),
+        );
+        let filtered_txns = argument_filter.filter(block_id, 0, txns.clone());
+        assert_eq!(filtered_txns, txns[..1].to_vec());
+    }
+
+    #[test]
+    fn test_filter_outside_of_block() {
+        let txns = get_transactions();
+        let block_id = HashValue::random();
+        let filter = Filter::empty()
+            .add_deny_block_id(block_id)
+            .add_deny_sender(txns[0].sender());
+
+        assert!(!filter.allows(block_id, 0, &txns[1]));
+        assert!(!filter.allows_transaction(0, &txns[0]));
+        assert!(filter.allows_transaction(0, &txns[1]));
+    }
+
+    #[test]
+    fn test_filter_serialization_roundtrip() {
+        let filter = Filter::empty()
+            .add_deny(Matcher::Or(vec![
+                Matcher::SecondarySigner(AccountAddress::random()),
+                Matcher::FeePayer(AccountAddress::random()),
+                Matcher::EntryFunctionArgument(0, vec![1, 2, 3]),
+            ]))
+            .add_allow(Matcher::PayloadType(PayloadType::Multisig));
+        let yaml = serde_yaml::to_string(&filter).unwrap();
+        assert_eq!(serde_yaml::from_str::<Filter>(&yaml).unwrap(), filter);
+    }
 }
@@ -0,0 +1,64 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+pub use crate::dag::{DagReplay, NodeSummary, OrderedAnchor, RoundSummary};
+use crate::{consensusdb::ConsensusDB, dag::StorageAdapter};
+use anyhow::{anyhow, Result};
+use aptos_config::config::DagConsensusConfig;
+use aptos_consensus_types::common::Round;
+use aptos_storage_interface::{state_view::LatestDbStateCheckpointView, DbReader};
+use aptos_types::on_chain_config::{OnChainConfig, OnChainConsensusConfig};
+use aptos_vm::data_cache::AsMoveResolver;
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+/// Rebuilds the DAG persisted in the consensus db at `consensus_db_dir` and re-runs the ordering
+/// the way a node does on restart, without writing to either DB.
+///
+/// `aptos_db` is the storage of the same node, providing the validator set, the latest ledger
+/// info and the committed history fed to the anchor election. `dag_window` overrides the
+/// `dag_ordering_causal_history_window` from the on-chain consensus config, and `until_round`
+/// ignores the persisted nodes after it.
+pub fn open_dag_replay(
+    consensus_db_dir: &Path,
+    aptos_db: Arc<dyn DbReader>,
+    dag_window: Option<Round>,
+    until_round: Option<Round>,
+) -> Result<DagReplay> {
+    let consensus_db = Arc::new(ConsensusDB::new_readonly(consensus_db_dir)?);
+
+    let epoch_state = Arc::new(aptos_db.get_latest_epoch_state()?);
+    let dag_window = match dag_window {
+        Some(dag_window) => dag_window,
+        None => {
+            let state_view = aptos_db.latest_state_checkpoint_view()?;
+            let config = OnChainConsensusConfig::fetch_config(&state_view.as_move_resolver())
+                .ok_or_else(|| anyhow!("Failed to read the on-chain consensus config."))?;
+            if !config.is_dag_enabled() {
+                return Err(anyhow!(
+                    "DAG is not enabled on chain, specify a DAG window to replay anyway."
+                ));
+            }
+            config
+                .unwrap_dag_config_v1()
+                .dag_ordering_causal_history_window as Round
+        },
+    };
+    // Only the committed history of the current epoch is used, as a node does on the first
+    // epoch with DAG enabled.
+    let storage = Arc::new(StorageAdapter::new(
+        epoch_state.epoch,
+        HashMap::from([(
+            epoch_state.epoch,
+            epoch_state.verifier.get_ordered_account_addresses(),
+        )]),
+        consensus_db,
+        aptos_db,
+    ));
+    DagReplay::new(
+        epoch_state,
+        storage,
+        dag_window,
+        DagConsensusConfig::default().chain_backoff_config,
+        until_round,
+    )
+}
@@ -0,0 +1,102 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::server::utils::{reply_with, reply_with_status};
+use anyhow::{anyhow, Result};
+use aptos_config::network_id::{NetworkId, PeerNetworkId};
+use aptos_data_client::client::AptosDataClient;
+use aptos_logger::info;
+use aptos_types::PeerId;
+use http::header::{HeaderValue, CONTENT_TYPE};
+use hyper::{Body, Request, Response, StatusCode};
+use std::{collections::HashMap, str::FromStr};
+
+/// Lists the reputations (i.e., scores, recent latencies and ban reasons)
+/// of all peers known to the data client.
+pub async fn handle_peer_reputations_request(
+    _req: Request<Body>,
+    aptos_data_client: AptosDataClient,
+) -> hyper::Result<Response<Body>> {
+    let peer_reputations = aptos_data_client.get_peer_states().get_peer_reputations();
+    match serde_json::to_string_pretty(&peer_reputations) {
+        Ok(peer_reputations) => {
+            let headers: Vec<(_, HeaderValue)> = vec![(
+                CONTENT_TYPE,
+                HeaderValue::from_str(mime::APPLICATION_JSON.as_ref()).unwrap(),
+            )];
+            Ok(reply_with(headers, peer_reputations))
+        },
+        Err(e) => Ok(reply_with_status(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            e.to_string(),
+        )),
+    }
+}
+
+/// Bans the peer specified by the `network_id` and `peer_id` query
+/// parameters, with the reason given by the `reason` query parameter.
+pub async fn handle_ban_peer_request(
+    req: Request<Body>,
+    aptos_data_client: AptosDataClient,
+) -> hyper::Result<Response<Body>> {
+    let query_pairs = get_query_pairs(&req);
+    let peer = match parse_peer(&query_pairs) {
+        Ok(peer) => peer,
+        Err(e) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, e.to_string())),
+    };
+    let reason = query_pairs
+        .get("reason")
+        .cloned()
+        .unwrap_or_else(|| "Banned by an operator".into());
+
+    info!("Banning peer {} from the data client: {}", peer, reason);
+    aptos_data_client.get_peer_states().ban_peer(peer, reason);
+    Ok(reply_with_status(
+        StatusCode::OK,
+        format!("Peer {} has been banned.", peer),
+    ))
+}
+
+/// Unbans the peer specified by the `network_id` and `peer_id` query parameters
+pub async fn handle_unban_peer_request(
+    req: Request<Body>,
+    aptos_data_client: AptosDataClient,
+) -> hyper::Result<Response<Body>> {
+    let query_pairs = get_query_pairs(&req);
+    let peer = match parse_peer(&query_pairs) {
+        Ok(peer) => peer,
+        Err(e) => return Ok(reply_with_status(StatusCode::BAD_REQUEST, e.to_string())),
+    };
+
+    info!("Unbanning peer {} from the data client.", peer);
+    if aptos_data_client.get_peer_states().unban_peer(&peer) {
+        Ok(reply_with_status(
+            StatusCode::OK,
+            format!("Peer {} has been unbanned.", peer),
+        ))
+    } else {
+        Ok(reply_with_status(
+            StatusCode::NOT_FOUND,
+            format!("Peer {} is not banned.", peer),
+        ))
+    }
+}
+
+fn get_query_pairs(req: &Request<Body>) -> HashMap<String, String> {
+    let query = req.uri().query().unwrap_or("");
+    url::form_urlencoded::parse(query.as_bytes())
+        .into_owned()
+        .collect()
+}
+
+fn parse_peer(query_pairs: &HashMap<String, String>) -> Result<PeerNetworkId> {
+    let network_id = query_pairs
+        .get("network_id")
+        .ok_or_else(|| anyhow!("Missing the network_id query parameter."))?;
+    let network_id = NetworkId::from_str(network_id).map_err(|e| anyhow!(e))?;
+    let peer_id = query_pairs
+        .get("peer_id")
+        .ok_or_else(|| anyhow!("Missing the peer_id query parameter."))?;
+    let peer_id = PeerId::from_str(peer_id)?;
+    Ok(PeerNetworkId::new(network_id, peer_id))
+}
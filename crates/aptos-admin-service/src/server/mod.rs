@@ -6,6 +6,7 @@ use aptos_config::config::{AuthenticationConfig, NodeConfig};
 use aptos_consensus::{
     persistent_liveness_storage::StorageWriteProxy, quorum_store::quorum_store_db::QuorumStoreDB,
 };
+use aptos_data_client::client::AptosDataClient;
 use aptos_infallible::RwLock;
 use aptos_logger::info;
 use aptos_storage_interface::DbReaderWriter;
@@ -22,6 +23,7 @@ use std::{
 use tokio::runtime::Runtime;
 
 mod consensus;
+mod data_client;
 #[cfg(target_os = "linux")]
 mod profiling;
 #[cfg(target_os = "linux")]
@@ -35,6 +37,7 @@ pub struct Context {
     aptos_db: RwLock<Option<Arc<DbReaderWriter>>>,
     consensus_db: RwLock<Option<Arc<StorageWriteProxy>>>,
     quorum_store_db: RwLock<Option<Arc<QuorumStoreDB>>>,
+    aptos_data_client: RwLock<Option<AptosDataClient>>,
 }
 
 impl Context {
@@ -50,6 +53,10 @@ impl Context {
         *self.consensus_db.write() = Some(consensus_db);
         *self.quorum_store_db.write() = Some(quorum_store_db);
     }
+
+    fn set_aptos_data_client(&self, aptos_data_client: AptosDataClient) {
+        *self.aptos_data_client.write() = Some(aptos_data_client);
+    }
 }
 
 pub struct AdminService {
@@ -108,6 +115,10 @@ impl AdminService {
             .set_consensus_dbs(consensus_db, quorum_store_db)
     }
 
+    pub fn set_aptos_data_client(&self, aptos_data_client: AptosDataClient) {
+        self.context.set_aptos_data_client(aptos_data_client)
+    }
+
     fn start(&self, address: SocketAddr, enabled: bool) {
         let context = self.context.clone();
         self.runtime.spawn(async move {
@@ -211,6 +222,39 @@ impl AdminService {
                     ))
                 }
             },
+            (hyper::Method::GET, "/debug/data_client/peers") => {
+                let aptos_data_client = context.aptos_data_client.read().clone();
+                if let Some(aptos_data_client) = aptos_data_client {
+                    data_client::handle_peer_reputations_request(req, aptos_data_client).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Data client is not available.",
+                    ))
+                }
+            },
+            (hyper::Method::POST, "/debug/data_client/ban_peer") => {
+                let aptos_data_client = context.aptos_data_client.read().clone();
+                if let Some(aptos_data_client) = aptos_data_client {
+                    data_client::handle_ban_peer_request(req, aptos_data_client).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Data client is not available.",
+                    ))
+                }
+            },
+            (hyper::Method::POST, "/debug/data_client/unban_peer") => {
+                let aptos_data_client = context.aptos_data_client.read().clone();
+                if let Some(aptos_data_client) = aptos_data_client {
+                    data_client::handle_unban_peer_request(req, aptos_data_client).await
+                } else {
+                    Ok(reply_with_status(
+                        StatusCode::NOT_FOUND,
+                        "Data client is not available.",
+                    ))
+                }
+            },
             _ => Ok(reply_with_status(StatusCode::NOT_FOUND, "Not found.")),
         }
     }
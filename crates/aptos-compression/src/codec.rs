@@ -0,0 +1,208 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use lz4::block::CompressionMode;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{Display, Formatter},
+    io::{Error, ErrorKind},
+};
+
+/// The acceleration parameter to use for FAST compression mode.
+/// This was determined anecdotally.
+const ACCELERATION_PARAMETER: i32 = 1;
+
+/// The first byte of the header prepended to data compressed with a non-lz4 codec
+const HEADER_MAGIC: u8 = 0xAC;
+/// The version of the header format
+const HEADER_VERSION: u8 = 1;
+/// The last byte of the header. Legacy lz4 data starts with the uncompressed size as
+/// a little endian i32, which is never negative. Setting the highest bit of the fourth
+/// byte makes the header an invalid lz4 size prefix, so old peers reject the data
+/// cleanly instead of misinterpreting it.
+const HEADER_TERMINATOR: u8 = 0xFF;
+/// The length of the header (magic, version, codec id and terminator)
+const HEADER_LENGTH: usize = 4;
+
+/// The codec id of zstd written in the header
+const ZSTD_CODEC_ID: u8 = 1;
+
+/// The codec to use when compressing data. Decompression detects the codec
+/// automatically, so peers only need to agree on which codecs they understand.
+///
+/// Note: data compressed with `Lz4` is identical to the output of older versions
+/// of this crate (i.e., it has no header), so it can be read by all peers.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionCodec {
+    /// LZ4 block compression in fast mode
+    Lz4,
+    /// Zstd compression at the given level (1 to 22)
+    Zstd { level: i32 },
+}
+
+impl CompressionCodec {
+    /// Verifies the codec parameters (e.g., the compression level) are valid
+    pub fn validate(&self) -> std::io::Result<()> {
+        match self {
+            Self::Lz4 => Ok(()),
+            Self::Zstd { level } => check_zstd_level(*level),
+        }
+    }
+}
+
+impl Default for CompressionCodec {
+    fn default() -> Self {
+        Self::Lz4
+    }
+}
+
+impl Display for CompressionCodec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Lz4 => write!(f, "lz4"),
+            Self::Zstd { level } => write!(f, "zstd (level: {})", level),
+        }
+    }
+}
+
+fn check_zstd_level(level: i32) -> std::io::Result<()> {
+    if !zstd::compression_level_range().contains(&level) {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Invalid zstd compression level: {}", level),
+        ));
+    }
+    Ok(())
+}
+
+/// Compresses the raw data with the given codec
+pub(crate) fn compress(raw_data: &[u8], codec: CompressionCodec) -> std::io::Result<Vec<u8>> {
+    match codec {
+        CompressionCodec::Lz4 => {
+            let compression_mode = CompressionMode::FAST(ACCELERATION_PARAMETER);
+            lz4::block::compress(raw_data, Some(compression_mode), true)
+        },
+        CompressionCodec::Zstd { level } => {
+            codec.validate()?;
+            let mut compressed_data = create_header(ZSTD_CODEC_ID, raw_data.len())?;
+            compressed_data.extend(zstd::bulk::compress(raw_data, level)?);
+            Ok(compressed_data)
+        },
+    }
+}
+
+/// Decompresses the data, detecting the codec from the header (if any).
+/// Data without a header is treated as legacy lz4 data.
+pub(crate) fn decompress(compressed_data: &[u8], max_size: usize) -> std::io::Result<Vec<u8>> {
+    if !has_header(compressed_data) {
+        let size = get_lz4_decompressed_size(compressed_data, max_size)?;
+        let mut raw_data = vec![0u8; size];
+        lz4::block::decompress_to_buffer(compressed_data, None, &mut raw_data)?;
+        return Ok(raw_data);
+    }
+
+    let version = compressed_data[1];
+    if version != HEADER_VERSION {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Unsupported compression header version: {}", version),
+        ));
+    }
+    let codec_id = compressed_data[2];
+    let (size, payload) = read_u32(&compressed_data[HEADER_LENGTH..])?;
+    let size = size as usize;
+    if size > max_size {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Given size parameter is too big: {} > {}", size, max_size),
+        ));
+    }
+
+    let raw_data = match codec_id {
+        ZSTD_CODEC_ID => zstd::bulk::decompress(payload, size)?,
+        codec_id => {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("Unknown compression codec id: {}", codec_id),
+            ))
+        },
+    };
+    if raw_data.len() != size {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            format!(
+                "Decompressed size does not match the header: {} != {}",
+                raw_data.len(),
+                size
+            ),
+        ));
+    }
+
+    Ok(raw_data)
+}
+
+/// Creates the header for the given codec, followed by the uncompressed size
+fn create_header(codec_id: u8, raw_size: usize) -> std::io::Result<Vec<u8>> {
+    let raw_size = u32::try_from(raw_size).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("Uncompressed size is too big: {}", raw_size),
+        )
+    })?;
+    let mut header = vec![HEADER_MAGIC, HEADER_VERSION, codec_id, HEADER_TERMINATOR];
+    header.extend(raw_size.to_le_bytes());
+    Ok(header)
+}
+
+/// Returns true iff the data starts with a codec header
+fn has_header(compressed_data: &[u8]) -> bool {
+    compressed_data.len() >= HEADER_LENGTH
+        && compressed_data[0] == HEADER_MAGIC
+        && compressed_data[3] == HEADER_TERMINATOR
+}
+
+/// Reads a little endian u32 and returns it along with the remaining bytes
+fn read_u32(bytes: &[u8]) -> std::io::Result<(u32, &[u8])> {
+    if bytes.len() < 4 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Source buffer is too short for the compression header.",
+        ));
+    }
+    let (value, remaining) = bytes.split_at(4);
+    let value = u32::from_le_bytes([value[0], value[1], value[2], value[3]]);
+    Ok((value, remaining))
+}
+
+/// Derived from lz4-rs crate, which starts the compressed payload with the original data size as i32
+/// see: https://github.com/10XGenomics/lz4-rs/blob/0abc0a52af1f6010f9a57640b1dc8eb8d2d697aa/src/block/mod.rs#L162
+fn get_lz4_decompressed_size(src: &[u8], max_size: usize) -> std::io::Result<usize> {
+    if src.len() < 4 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Source buffer must at least contain size prefix.",
+        ));
+    }
+
+    let size =
+        (src[0] as i32) | (src[1] as i32) << 8 | (src[2] as i32) << 16 | (src[3] as i32) << 24;
+
+    if size < 0 {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            "Parsed size prefix in buffer must not be negative.",
+        ));
+    }
+
+    let size = size as usize;
+
+    if size > max_size {
+        return Err(Error::new(
+            ErrorKind::InvalidInput,
+            format!("Given size parameter is too big: {} > {}", size, max_size),
+        ));
+    }
+
+    Ok(size)
+}
@@ -7,26 +7,25 @@ use crate::metrics::{
     RAW_BYTES,
 };
 use aptos_logger::prelude::*;
-use lz4::block::CompressionMode;
-use std::io::{Error, ErrorKind};
 use thiserror::Error;
 
 /// This crate provides a simple library interface for data compression.
 /// It is useful for compressing large data chunks that are
 /// sent across the network (e.g., by state sync and consensus).
-/// Internally, it uses LZ4 in fast mode to compress the data.
+/// By default, it uses LZ4 in fast mode to compress the data.
 /// See <https://github.com/10xGenomics/lz4-rs> for more information.
+/// Zstd can be selected instead, see [`CompressionCodec`].
 ///
 /// Note: the crate also exposes some basic compression metrics
 /// that can be used to track the cumulative compression ratio
 /// and compression/decompression durations during the runtime.
 pub mod metrics;
+
+mod codec;
 #[cfg(test)]
 mod tests;
 
-/// The acceleration parameter to use for FAST compression mode.
-/// This was determined anecdotally.
-const ACCELERATION_PARAMETER: i32 = 1;
+pub use codec::CompressionCodec;
 
 /// A useful wrapper for representing compressed data
 pub type CompressedData = Vec<u8>;
@@ -36,11 +35,21 @@ pub type CompressedData = Vec<u8>;
 #[error("Encountered a compression error! Error: {0}")]
 pub struct CompressionError(String);
 
-/// Compresses the raw data stream
+/// Compresses the raw data stream (using LZ4)
 pub fn compress(
     raw_data: Vec<u8>,
     client: CompressionClient,
     max_bytes: usize,
+) -> Result<CompressedData, CompressionError> {
+    compress_with_codec(raw_data, CompressionCodec::Lz4, client, max_bytes)
+}
+
+/// Compresses the raw data stream using the specified codec
+pub fn compress_with_codec(
+    raw_data: Vec<u8>,
+    codec: CompressionCodec,
+    client: CompressionClient,
+    max_bytes: usize,
 ) -> Result<CompressedData, CompressionError> {
     if raw_data.len() > max_bytes {
         return Err(CompressionError(format!(
@@ -53,14 +62,13 @@ pub fn compress(
     let timer = start_compression_operation_timer(COMPRESS, client.clone());
 
     // Compress the data
-    let compression_mode = CompressionMode::FAST(ACCELERATION_PARAMETER);
-    let compressed_data = match lz4::block::compress(&raw_data, Some(compression_mode), true) {
+    let compressed_data = match codec::compress(&raw_data, codec) {
         Ok(compressed_data) => compressed_data,
         Err(error) => {
             increment_compression_error(COMPRESS, client);
             return Err(CompressionError(format!(
-                "Failed to compress the data: {}",
-                error
+                "Failed to compress the data with {}: {}",
+                codec, error
             )));
         },
     };
@@ -94,7 +102,8 @@ pub fn compress(
     Ok(compressed_data)
 }
 
-/// Decompresses the compressed data stream
+/// Decompresses the compressed data stream. The codec is detected
+/// automatically (data without a codec header is treated as LZ4).
 pub fn decompress(
     compressed_data: &CompressedData,
     client: CompressionClient,
@@ -103,27 +112,17 @@ pub fn decompress(
     // Start the decompression timer
     let timer = start_compression_operation_timer(DECOMPRESS, client.clone());
 
-    // Check size of the data and initialize raw_data
-    let size = match get_decompressed_size(compressed_data, max_size) {
-        Ok(size) => size,
+    // Decompress the data
+    let raw_data = match codec::decompress(compressed_data, max_size) {
+        Ok(raw_data) => raw_data,
         Err(error) => {
             increment_compression_error(DECOMPRESS, client);
             return Err(CompressionError(format!(
-                "Failed to get decompressed size: {}",
+                "Failed to decompress the data: {}",
                 error
             )));
         },
     };
-    let mut raw_data = vec![0u8; size];
-
-    // Decompress the data
-    if let Err(error) = lz4::block::decompress_to_buffer(compressed_data, None, &mut raw_data) {
-        increment_compression_error(DECOMPRESS, client);
-        return Err(CompressionError(format!(
-            "Failed to decompress the data: {}",
-            error
-        )));
-    };
 
     // Stop the timer and log the relative data compression statistics
     let decompression_duration = timer.stop_and_record();
@@ -139,38 +138,6 @@ pub fn decompress(
     Ok(raw_data)
 }
 
-/// Derived from lz4-rs crate, which starts the compressed payload with the original data size as i32
-/// see: https://github.com/10XGenomics/lz4-rs/blob/0abc0a52af1f6010f9a57640b1dc8eb8d2d697aa/src/block/mod.rs#L162
-fn get_decompressed_size(src: &CompressedData, max_size: usize) -> std::io::Result<usize> {
-    if src.len() < 4 {
-        return Err(Error::new(
-            ErrorKind::InvalidInput,
-            "Source buffer must at least contain size prefix.",
-        ));
-    }
-
-    let size =
-        (src[0] as i32) | (src[1] as i32) << 8 | (src[2] as i32) << 16 | (src[3] as i32) << 24;
-
-    if size < 0 {
-        return Err(Error::new(
-            ErrorKind::InvalidInput,
-            "Parsed size prefix in buffer must not be negative.",
-        ));
-    }
-
-    let size = size as usize;
-
-    if size > max_size {
-        return Err(Error::new(
-            ErrorKind::InvalidInput,
-            format!("Given size parameter is too big: {} > {}", size, max_size),
-        ));
-    }
-
-    Ok(size)
-}
-
 /// Calculates the relative size (%) between the input and output after a
 /// compression/decompression operation, i.e., (output / input) * 100.
 fn calculate_relative_size(input: &[u8], output: &[u8]) -> f64 {
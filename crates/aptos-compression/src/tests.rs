@@ -1,7 +1,7 @@
 // Copyright © Aptos Foundation
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::CompressionClient;
+use crate::{CompressionClient, CompressionCodec};
 use aptos_crypto::{ed25519::Ed25519PrivateKey, hash::HashValue, PrivateKey, SigningKey, Uniform};
 use aptos_types::{
     account_address::AccountAddress,
@@ -66,6 +66,91 @@ fn test_compression_limits() {
     assert!(maybe_decompressed_bytes.is_err());
 }
 
+#[test]
+fn test_zstd_compression() {
+    for level in [1, 3, 19] {
+        let codec = CompressionCodec::Zstd { level };
+
+        // Test epoch ending ledger infos
+        let epoch_ending_ledger_infos = create_epoch_ending_ledger_infos(0, 999);
+        test_compress_and_decompress_with_codec(epoch_ending_ledger_infos, codec);
+
+        // Test transaction outputs with proof
+        let outputs_with_proof = create_output_list_with_proof(13434, 17000, 19000);
+        test_compress_and_decompress_with_codec(outputs_with_proof, codec);
+
+        // Test transactions with proof
+        let transactions_with_proof = create_transaction_list_with_proof(1000, 1999, 1999, true);
+        test_compress_and_decompress_with_codec(transactions_with_proof, codec);
+    }
+
+    // Test an invalid compression level
+    let raw_bytes = bcs::to_bytes(&create_epoch_ending_ledger_infos(0, 10)).unwrap();
+    let maybe_compressed_bytes = crate::compress_with_codec(
+        raw_bytes,
+        CompressionCodec::Zstd { level: 100 },
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    );
+    assert!(maybe_compressed_bytes.is_err());
+}
+
+#[test]
+fn test_lz4_backward_compatibility() {
+    let raw_bytes = bcs::to_bytes(&create_epoch_ending_ledger_infos(0, 100)).unwrap();
+
+    // Verify lz4 data is still written without a header (i.e., in the legacy format)
+    let compressed_bytes = crate::compress_with_codec(
+        raw_bytes.clone(),
+        CompressionCodec::Lz4,
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    )
+    .unwrap();
+    let legacy_compressed_bytes =
+        lz4::block::compress(&raw_bytes, Some(lz4::block::CompressionMode::FAST(1)), true).unwrap();
+    assert_eq!(compressed_bytes, legacy_compressed_bytes);
+
+    // Verify the zstd header is rejected by legacy lz4 decompression
+    let compressed_bytes = crate::compress_with_codec(
+        raw_bytes,
+        CompressionCodec::Zstd { level: 3 },
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    )
+    .unwrap();
+    assert!(lz4::block::decompress(&compressed_bytes, None).is_err());
+}
+
+#[test]
+fn test_zstd_decompression_limits() {
+    let too_small_bytes = 1;
+    let raw_bytes =
+        bcs::to_bytes(&create_transaction_list_with_proof(1000, 1999, 1999, true)).unwrap();
+    let compressed_bytes = crate::compress_with_codec(
+        raw_bytes,
+        CompressionCodec::Zstd { level: 3 },
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    )
+    .unwrap();
+    let maybe_decompressed_bytes = crate::decompress(
+        &compressed_bytes,
+        CompressionClient::StateSync,
+        too_small_bytes,
+    );
+    assert!(maybe_decompressed_bytes.is_err());
+
+    // Truncated data should fail to decompress
+    let truncated_bytes = compressed_bytes[..compressed_bytes.len() / 2].to_vec();
+    let maybe_decompressed_bytes = crate::decompress(
+        &truncated_bytes,
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    );
+    assert!(maybe_decompressed_bytes.is_err());
+}
+
 /// Ensures that the given object can be compressed and decompressed successfully
 /// when BCS encoded.
 fn test_compress_and_decompress<T: Debug + DeserializeOwned + PartialEq + Serialize>(object: T) {
@@ -87,6 +172,31 @@ fn test_compress_and_decompress<T: Debug + DeserializeOwned + PartialEq + Serial
     assert_eq!(object, decoded_object);
 }
 
+/// Ensures that the given object can be compressed with the codec
+/// and decompressed successfully when BCS encoded.
+fn test_compress_and_decompress_with_codec<T: Debug + DeserializeOwned + PartialEq + Serialize>(
+    object: T,
+    codec: CompressionCodec,
+) {
+    let bcs_encoded_bytes = bcs::to_bytes(&object).unwrap();
+    let compressed_bytes = crate::compress_with_codec(
+        bcs_encoded_bytes,
+        codec,
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    )
+    .unwrap();
+    let decompressed_bytes = crate::decompress(
+        &compressed_bytes,
+        CompressionClient::StateSync,
+        MAX_COMPRESSION_SIZE,
+    )
+    .unwrap();
+    let decoded_object = bcs::from_bytes::<T>(&decompressed_bytes).unwrap();
+
+    assert_eq!(object, decoded_object);
+}
+
 /// Creates a test epoch change proof
 fn create_epoch_ending_ledger_infos(
     start_epoch: u64,
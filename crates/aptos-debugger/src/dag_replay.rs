@@ -0,0 +1,137 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::Result;
+use aptos_config::config::{
+    RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_consensus::util::dag_tool::{open_dag_replay, DagReplay};
+use aptos_consensus_types::common::Round;
+use aptos_db::AptosDB;
+use aptos_storage_interface::DbReader;
+use clap::Parser;
+use std::{path::PathBuf, sync::Arc};
+
+#[derive(Parser)]
+#[clap(
+    about = "Rebuild the DAG persisted in the consensus db offline, print its rounds and re-run \
+    the ordering the way a node does on restart. Nothing is written to either DB."
+)]
+pub struct Command {
+    #[clap(long, value_parser)]
+    pub db_dir: PathBuf,
+
+    /// The AptosDB of the same node, providing the validator set, the latest ledger info and the
+    /// committed history fed to the anchor election.
+    #[clap(long, value_parser)]
+    pub aptos_db_dir: PathBuf,
+
+    /// Overrides the `dag_ordering_causal_history_window` from the on-chain consensus config.
+    #[clap(long)]
+    pub dag_window: Option<Round>,
+
+    /// Only use persisted nodes up to this round, to see what was orderable at that point.
+    #[clap(long)]
+    pub until_round: Option<Round>,
+
+    /// Only print rounds from this one on.
+    #[clap(long)]
+    pub from_round: Option<Round>,
+
+    /// Print the ordered nodes of every ordered anchor.
+    #[clap(long)]
+    pub show_ordered_nodes: bool,
+}
+
+impl Command {
+    pub async fn run(self) -> Result<()> {
+        let aptos_db: Arc<dyn DbReader> = Arc::new(AptosDB::open(
+            StorageDirPaths::from_path(&self.aptos_db_dir),
+            true, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs::default(),
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?);
+        let replay = open_dag_replay(&self.db_dir, aptos_db, self.dag_window, self.until_round)?;
+
+        self.print(&replay);
+        Ok(())
+    }
+
+    fn print(&self, replay: &DagReplay) {
+        println!(
+            "Epoch {}, committed anchor round {}, DAG rounds {}..={}.",
+            replay.epoch(),
+            replay.committed_round(),
+            replay.lowest_round(),
+            replay.highest_round(),
+        );
+
+        let from_round = self.from_round.unwrap_or(0);
+        for round in replay
+            .rounds()
+            .into_iter()
+            .filter(|r| r.round >= from_round)
+        {
+            println!(
+                "Round {}: {} nodes{}",
+                round.round,
+                round.nodes.len(),
+                if round.has_strong_links {
+                    ""
+                } else {
+                    ", no strong links"
+                },
+            );
+            for node in round.nodes {
+                println!(
+                    "    {} digest {} timestamp {} parents {} votes {}{}",
+                    node.author.short_str(),
+                    node.digest.short_str(),
+                    node.timestamp,
+                    node.num_parents,
+                    node.num_votes,
+                    if node.ordered { " ordered" } else { "" },
+                );
+            }
+        }
+
+        println!(
+            "{} anchors ordered on top of the committed ones:",
+            replay.ordered_anchors().len()
+        );
+        for ordered in replay.ordered_anchors() {
+            println!(
+                "    round {} anchor {}: {} nodes, skipped anchors {:?}",
+                ordered.anchor.round(),
+                ordered.anchor.author().short_str(),
+                ordered.nodes.len(),
+                ordered
+                    .failed_anchors
+                    .iter()
+                    .map(|(round, author)| format!("{}:{}", round, author.short_str()))
+                    .collect::<Vec<_>>(),
+            );
+            if self.show_ordered_nodes {
+                for node_id in &ordered.nodes {
+                    println!("        {}", node_id);
+                }
+            }
+        }
+
+        if let Some(node) = replay.pending_node() {
+            println!("Pending node: {}", node.id());
+        }
+        println!("{} persisted votes.", replay.votes().len());
+        if !replay.rejected_nodes().is_empty() {
+            println!(
+                "{} persisted certified nodes are not part of the DAG and would be deleted on \
+                startup.",
+                replay.rejected_nodes().len()
+            );
+        }
+    }
+}
@@ -4,11 +4,15 @@
 use anyhow::Result;
 use clap::Parser;
 
+mod dag_replay;
+
 #[derive(Parser)]
 pub enum Cmd {
     #[clap(subcommand)]
     AptosDb(aptos_db_tool::DBTool),
 
+    DagReplay(dag_replay::Command),
+
     Decode(aptos_move_debugger::bcs_txn_decoder::Command),
 
     DumpPendingTxns(aptos_consensus::util::db_tool::Command),
@@ -21,6 +25,7 @@ impl Cmd {
     pub async fn run(self) -> Result<()> {
         match self {
             Cmd::AptosDb(cmd) => cmd.run().await,
+            Cmd::DagReplay(cmd) => cmd.run().await,
             Cmd::Decode(cmd) => cmd.run().await,
             Cmd::DumpPendingTxns(cmd) => cmd.run().await,
             Cmd::Move(cmd) => cmd.run().await,
@@ -4,14 +4,19 @@
 use crate::common::types::{CliCommand, CliError, CliResult, CliTypedResult, MovePackageDir};
 use aptos_framework::extended_checks;
 use async_trait::async_trait;
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
 use move_coverage::{
-    coverage_map::CoverageMap, format_csv_summary, format_human_summary,
-    source_coverage::SourceCoverageBuilder, summary::summarize_inst_cov,
+    coverage_map::CoverageMap,
+    diff_coverage::{changed_lines_since, format_diff_summary},
+    format_csv_summary, format_human_summary,
+    line_coverage::LineCoverage,
+    source_coverage::SourceCoverageBuilder,
+    summary::summarize_inst_cov,
 };
 use move_disassembler::disassembler::Disassembler;
 use move_package::{compilation::compiled_package::CompiledPackage, BuildConfig, CompilerConfig};
+use std::{fs::File, path::PathBuf};
 
 /// Display a coverage summary for all modules in a package
 ///
@@ -142,6 +147,133 @@ impl CliCommand<()> for BytecodeCoverage {
     }
 }
 
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum CoverageFormat {
+    Lcov,
+    Cobertura,
+}
+
+/// Export line and branch coverage for all modules in a package
+///
+/// Writes the coverage as an LCOV tracefile or a Cobertura XML report, which can be
+/// consumed by coverage dashboards and CI tooling
+#[derive(Debug, Parser)]
+pub struct ExportCoverage {
+    /// Format of the exported coverage
+    #[clap(long, value_enum, default_value = "lcov")]
+    pub format: CoverageFormat,
+    /// File to write the coverage to
+    #[clap(long, value_parser)]
+    pub output_file: PathBuf,
+    /// Only export coverage of the lines changed relative to this git revision
+    #[clap(long)]
+    pub changed_since: Option<String>,
+    #[clap(flatten)]
+    pub move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<()> for ExportCoverage {
+    fn command_name(&self) -> &'static str {
+        "ExportCoverage"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let package_path = canonical_package_path(&self.move_options)?;
+        let (coverage_map, package) = compile_coverage(self.move_options)?;
+        let mut line_coverage = compute_line_coverage(&coverage_map, &package)?;
+        if let Some(base) = &self.changed_since {
+            let changed_lines = changed_lines_since(base, &package_path).map_err(|err| {
+                CliError::UnexpectedError(format!("Failed to get changed lines {}", err))
+            })?;
+            line_coverage.restrict_to_lines(&changed_lines);
+        }
+
+        let mut writer = File::create(&self.output_file)
+            .map_err(|err| CliError::IO(self.output_file.display().to_string(), err))?;
+        match self.format {
+            CoverageFormat::Lcov => line_coverage.write_lcov(&mut writer),
+            CoverageFormat::Cobertura => line_coverage.write_cobertura(&mut writer, &package_path),
+        }
+        .map_err(|err| CliError::UnexpectedError(format!("Failed to export coverage {}", err)))
+    }
+}
+
+/// Display coverage of the lines changed relative to a git revision
+///
+/// Lists the changed lines of each source file that are not covered, e.g., to check the
+/// coverage of the changes in a PR
+#[derive(Debug, Parser)]
+pub struct DiffCoverage {
+    /// Git revision to compare the working tree against, e.g., the base branch of a PR
+    #[clap(long)]
+    pub base: String,
+    /// Fail if less than this percentage of the changed lines is covered
+    #[clap(long)]
+    pub min_coverage: Option<f64>,
+    #[clap(flatten)]
+    pub move_options: MovePackageDir,
+}
+
+#[async_trait]
+impl CliCommand<()> for DiffCoverage {
+    fn command_name(&self) -> &'static str {
+        "DiffCoverage"
+    }
+
+    async fn execute(self) -> CliTypedResult<()> {
+        let package_path = canonical_package_path(&self.move_options)?;
+        let (coverage_map, package) = compile_coverage(self.move_options)?;
+        let mut line_coverage = compute_line_coverage(&coverage_map, &package)?;
+        let changed_lines = changed_lines_since(&self.base, &package_path).map_err(|err| {
+            CliError::UnexpectedError(format!("Failed to get changed lines {}", err))
+        })?;
+        line_coverage.restrict_to_lines(&changed_lines);
+
+        let counts = format_diff_summary(&line_coverage, &package_path, &mut std::io::stdout())
+            .map_err(|err| CliError::UnexpectedError(format!("Failed to get coverage {}", err)))?;
+        match self.min_coverage {
+            Some(min_coverage)
+                if counts.lines_valid > 0 && counts.line_rate() * 100f64 < min_coverage =>
+            {
+                Err(CliError::UnexpectedError(format!(
+                    "Coverage of changed lines {:.2}% is below the minimum of {:.2}%",
+                    counts.line_rate() * 100f64,
+                    min_coverage
+                )))
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+fn canonical_package_path(move_options: &MovePackageDir) -> CliTypedResult<PathBuf> {
+    let path = move_options.get_package_path()?;
+    path.canonicalize()
+        .map_err(|err| CliError::IO(path.display().to_string(), err))
+}
+
+fn compute_line_coverage(
+    coverage_map: &CoverageMap,
+    package: &CompiledPackage,
+) -> CliTypedResult<LineCoverage> {
+    let coverage_map = coverage_map.to_unified_exec_map();
+    let mut line_coverage = LineCoverage::new();
+    for unit in package.root_modules() {
+        if let CompiledUnit::Module(NamedCompiledModule {
+            module, source_map, ..
+        }) = &unit.unit
+        {
+            line_coverage
+                .add_module(module, source_map, &unit.source_path, &coverage_map)
+                .map_err(|err| {
+                    CliError::UnexpectedError(format!("Failed to get coverage {}", err))
+                })?;
+        }
+    }
+    Ok(line_coverage)
+}
+
 fn compile_coverage(
     move_options: MovePackageDir,
 ) -> CliTypedResult<(CoverageMap, CompiledPackage)> {
@@ -178,6 +310,8 @@ pub enum CoveragePackage {
     Summary(SummaryCoverage),
     Source(SourceCoverage),
     Bytecode(BytecodeCoverage),
+    Export(ExportCoverage),
+    Diff(DiffCoverage),
 }
 
 impl CoveragePackage {
@@ -186,6 +320,8 @@ impl CoveragePackage {
             Self::Summary(tool) => tool.execute_serialized_success().await,
             Self::Source(tool) => tool.execute_serialized_success().await,
             Self::Bytecode(tool) => tool.execute_serialized_success().await,
+            Self::Export(tool) => tool.execute_serialized_success().await,
+            Self::Diff(tool) => tool.execute_serialized_success().await,
         }
     }
 }
@@ -53,7 +53,7 @@ use move_core_types::{identifier::Identifier, language_storage::ModuleId, u256::
 use move_package::{
     source_package::layout::SourcePackageLayout, BuildConfig, CompilerConfig, CompilerVersion,
 };
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{property_test::DEFAULT_NUM_FUZZ_CASES, TestReportFormat, UnitTestingConfig};
 pub use package_hooks::*;
 use serde::{Deserialize, Serialize};
 use serde_json::json;
@@ -439,6 +439,46 @@ pub struct TestPackage {
     /// Dump storage state on failure.
     #[clap(long = "dump")]
     pub dump_state: bool,
+
+    /// Run the tests under a Debug Adapter Protocol server listening on this port
+    ///
+    /// The tests start once a debugger (e.g., an editor) attaches, and are run one at a time.
+    #[clap(long, conflicts_with = "compute_coverage")]
+    pub debug_adapter_port: Option<u16>,
+
+    /// Format of the test report
+    ///
+    /// The JSON and JUnit reports include the status, duration, instructions executed, gas
+    /// used, abort location and debug output of each test. They are written to `--report-file`,
+    /// so that they aren't mixed up with the build output and the result of the command.
+    #[clap(long, value_enum, default_value = "human")]
+    pub format: TestReportFormat,
+
+    /// Write the JSON or JUnit test report to this file. Required by these formats.
+    #[clap(
+        long,
+        value_parser,
+        required_if_eq_any = [("format", "json"), ("format", "junit")]
+    )]
+    pub report_file: Option<PathBuf>,
+
+    /// Fuzz the property-based tests with randomly seeded arguments
+    ///
+    /// Property-based tests are `#[test]` functions with parameters which are not assigned in
+    /// the attribute, e.g., `#[test] fun test_add(x: u64, v: vector<u8>)`. Their arguments are
+    /// generated, and the arguments of a failing case are shrunk to a minimal failing input.
+    /// Without this flag, they run a few cases from a fixed seed.
+    #[clap(long)]
+    pub fuzz: bool,
+
+    /// Number of cases each property-based test is run with when fuzzing
+    #[clap(long, default_value_t = DEFAULT_NUM_FUZZ_CASES)]
+    pub fuzz_cases: u64,
+
+    /// Seed the arguments of property-based tests are generated from, e.g., to reproduce a
+    /// failure
+    #[clap(long)]
+    pub fuzz_seed: Option<u64>,
 }
 
 #[async_trait]
@@ -466,25 +506,43 @@ impl CliCommand<&'static str> for TestPackage {
         };
 
         let path = self.move_options.get_package_path()?;
-        let result = move_cli::base::test::run_move_unit_tests(
-            path.as_path(),
-            config.clone(),
-            UnitTestingConfig {
-                filter: self.filter.clone(),
-                report_stacktrace_on_abort: true,
-                report_storage_on_error: self.dump_state,
-                ignore_compile_warnings: self.ignore_compile_warnings,
-                ..UnitTestingConfig::default_with_bound(None)
-            },
-            // TODO(Gas): we may want to switch to non-zero costs in the future
-            aptos_debug_natives::aptos_debug_natives(
-                NativeGasParameters::zeros(),
-                MiscGasParameters::zeros(),
+        let unit_test_config = UnitTestingConfig {
+            filter: self.filter.clone(),
+            report_stacktrace_on_abort: true,
+            report_storage_on_error: self.dump_state,
+            ignore_compile_warnings: self.ignore_compile_warnings,
+            report_format: self.format,
+            report_file: self.report_file.clone(),
+            fuzz: self.fuzz,
+            fuzz_cases: self.fuzz_cases,
+            fuzz_seed: self.fuzz_seed,
+            ..UnitTestingConfig::default_with_bound(None)
+        };
+        // TODO(Gas): we may want to switch to non-zero costs in the future
+        let natives = aptos_debug_natives::aptos_debug_natives(
+            NativeGasParameters::zeros(),
+            MiscGasParameters::zeros(),
+        );
+        let result = match self.debug_adapter_port {
+            Some(debug_adapter_port) => move_cli::base::test::debug_move_unit_tests(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                natives,
+                None,
+                debug_adapter_port,
+                &mut std::io::stdout(),
             ),
-            None,
-            self.compute_coverage,
-            &mut std::io::stdout(),
-        )
+            None => move_cli::base::test::run_move_unit_tests(
+                path.as_path(),
+                config.clone(),
+                unit_test_config,
+                natives,
+                None,
+                self.compute_coverage,
+                &mut std::io::stdout(),
+            ),
+        }
         .map_err(|err| CliError::UnexpectedError(format!("Failed to run tests: {:#}", err)))?;
 
         // Print coverage summary if --coverage is set
@@ -61,6 +61,7 @@ use aptos_sdk::move_types::{account_address::AccountAddress, language_storage::M
 use aptos_temppath::TempPath;
 use aptos_types::on_chain_config::ValidatorSet;
 use move_core_types::ident_str;
+use move_unit_test::{property_test::DEFAULT_NUM_FUZZ_CASES, TestReportFormat};
 use reqwest::Url;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
@@ -850,6 +851,12 @@ impl CliTestFramework {
             ignore_compile_warnings: false,
             compute_coverage: false,
             dump_state: false,
+            debug_adapter_port: None,
+            format: TestReportFormat::Human,
+            report_file: None,
+            fuzz: false,
+            fuzz_cases: DEFAULT_NUM_FUZZ_CASES,
+            fuzz_seed: None,
         }
         .execute()
         .await
@@ -15,8 +15,8 @@ use aptos_types::account_address::AccountAddress;
 use rand::seq::SliceRandom;
 use std::{
     cmp::Ordering,
-    collections::{btree_set::Iter, BTreeMap, BTreeSet, HashMap},
-    iter::Rev,
+    collections::{btree_set::Iter, BTreeMap, BTreeSet, HashMap, VecDeque},
+    iter::{Peekable, Rev},
     ops::Bound,
     time::Duration,
 };
@@ -72,6 +72,80 @@ impl PriorityIndex {
     }
 }
 
+/// Iterates over the priority queue like `PriorityQueueIter`, except that senders with
+/// transactions at the same gas price take turns: each sender takes up to `weight` consecutive
+/// transactions per turn, in the order in which the senders first appear in the queue.
+///
+/// The turns are taken among at most `max_txns * weight` transactions of a gas tier at a time, the
+/// rest of the tier following in the same way once these are exhausted. Most transactions usually
+/// pay the same (minimum) gas price, so a batch would otherwise scan most of mempool.
+pub struct RoundRobinQueueIter<'a> {
+    queue: Peekable<PriorityQueueIter<'a>>,
+    weight: usize,
+    // Maximum number of transactions taking turns at a time
+    max_tier_len: usize,
+    // Transactions of the current gas tier, grouped by sender in turn order
+    tier: VecDeque<VecDeque<&'a OrderedQueueKey>>,
+    // Transactions left in the current sender's turn
+    turn_remaining: usize,
+}
+
+impl<'a> RoundRobinQueueIter<'a> {
+    pub(crate) fn new(queue: PriorityQueueIter<'a>, weight: u64, max_txns: u64) -> Self {
+        let weight = weight.max(1) as usize;
+        Self {
+            queue: queue.peekable(),
+            weight,
+            max_tier_len: (max_txns.max(1) as usize).saturating_mul(weight),
+            tier: VecDeque::new(),
+            turn_remaining: weight,
+        }
+    }
+
+    fn load_next_tier(&mut self) {
+        let gas_ranking_score = match self.queue.peek() {
+            Some(key) => key.gas_ranking_score,
+            None => return,
+        };
+        let mut sender_turns = HashMap::new();
+        let max_tier_len = self.max_tier_len;
+        let mut tier_len = 0;
+        while let Some(key) = self
+            .queue
+            .next_if(|key| tier_len < max_tier_len && key.gas_ranking_score == gas_ranking_score)
+        {
+            tier_len += 1;
+            let turn = *sender_turns.entry(key.address).or_insert_with(|| {
+                self.tier.push_back(VecDeque::new());
+                self.tier.len() - 1
+            });
+            self.tier[turn].push_back(key);
+        }
+        self.turn_remaining = self.weight;
+    }
+}
+
+impl<'a> Iterator for RoundRobinQueueIter<'a> {
+    type Item = &'a OrderedQueueKey;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.tier.is_empty() {
+            self.load_next_tier();
+        }
+        let sender_txns = self.tier.front_mut()?;
+        let key = sender_txns.pop_front()?;
+        self.turn_remaining -= 1;
+        if sender_txns.is_empty() {
+            self.tier.pop_front();
+            self.turn_remaining = self.weight;
+        } else if self.turn_remaining == 0 {
+            self.tier.rotate_left(1);
+            self.turn_remaining = self.weight;
+        }
+        Some(key)
+    }
+}
+
 #[derive(Eq, PartialEq, Clone, Debug, Hash)]
 pub struct OrderedQueueKey {
     pub gas_ranking_score: u64,
@@ -6,7 +6,7 @@
 //! agreed upon.
 use crate::{
     core_mempool::{
-        index::TxnPointer,
+        index::{OrderedQueueKey, TxnPointer},
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
         transaction_store::TransactionStore,
     },
@@ -14,7 +14,7 @@ use crate::{
     logging::{LogEntry, LogSchema, TxnsLog},
     shared_mempool::types::MultiBucketTimelineIndexIds,
 };
-use aptos_config::config::NodeConfig;
+use aptos_config::config::{MempoolFairnessConfig, NodeConfig};
 use aptos_consensus_types::common::{TransactionInProgress, TransactionSummary};
 use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
@@ -34,6 +34,9 @@ pub struct Mempool {
     transactions: TransactionStore,
 
     pub system_transaction_timeout: Duration,
+
+    // Fairness quotas applied to batches pulled by consensus
+    fairness: MempoolFairnessConfig,
 }
 
 impl Mempool {
@@ -43,6 +46,7 @@ impl Mempool {
             system_transaction_timeout: Duration::from_secs(
                 config.mempool.system_transaction_timeout_secs,
             ),
+            fairness: config.mempool.fairness.clone(),
         }
     }
 
@@ -272,8 +276,16 @@ impl Mempool {
         let mut skipped = HashSet::new();
         let mut total_bytes = 0;
         let mut txn_walked = 0usize;
+        let mut quotas = BatchQuotas::new(&self.fairness);
+        let queue: Box<dyn Iterator<Item = &OrderedQueueKey> + '_> = match self
+            .fairness
+            .round_robin_weight
+        {
+            Some(weight) => Box::new(self.transactions.iter_queue_round_robin(weight, max_txns)),
+            None => Box::new(self.transactions.iter_queue()),
+        };
         // iterate over the queue of transactions based on gas price
-        'main: for txn in self.transactions.iter_queue() {
+        'main: for txn in queue {
             txn_walked += 1;
             if Self::was_seen(
                 &TxnPointer::from(txn),
@@ -296,6 +308,11 @@ impl Mempool {
             // we've already sent its ancestor to Consensus.
             if previous_txn_was_seen || account_sequence_number == Some(&tx_seq) {
                 let ptr = TxnPointer::from(txn);
+                // Later transactions of the sender can't be included either, as they are never
+                // seen to follow this one.
+                if !quotas.try_add(&self.transactions, &ptr) {
+                    continue;
+                }
                 seen.insert(ptr, txn.gas_ranking_score);
                 result.push(ptr);
                 if (result.len() as u64) == max_txns {
@@ -306,6 +323,9 @@ impl Mempool {
                 // that were skipped before for given account
                 let mut skipped_txn = TxnPointer::new(txn.address, tx_seq + 1);
                 while skipped.contains(&skipped_txn) {
+                    if !quotas.try_add(&self.transactions, &skipped_txn) {
+                        break;
+                    }
                     seen.insert(skipped_txn, txn.gas_ranking_score);
                     result.push(skipped_txn);
                     if (result.len() as u64) == max_txns {
@@ -429,6 +449,11 @@ impl Mempool {
         self.transactions.timeline_range(start_end_pairs)
     }
 
+    /// Returns all transactions currently in mempool, both ready and parked.
+    pub(crate) fn get_all_transactions(&self) -> Vec<SignedTransaction> {
+        self.transactions.get_all_transactions()
+    }
+
     pub fn gen_snapshot(&self) -> TxnsLog {
         self.transactions.gen_snapshot()
     }
@@ -443,3 +468,52 @@ impl Mempool {
         &self.transactions
     }
 }
+
+/// Counts the transactions of a batch pulled by consensus against the fairness quotas.
+struct BatchQuotas<'a> {
+    config: &'a MempoolFairnessConfig,
+    txns_per_sender: HashMap<AccountAddress, u64>,
+    txns_per_module_address: HashMap<AccountAddress, u64>,
+}
+
+impl<'a> BatchQuotas<'a> {
+    fn new(config: &'a MempoolFairnessConfig) -> Self {
+        Self {
+            config,
+            txns_per_sender: HashMap::new(),
+            txns_per_module_address: HashMap::new(),
+        }
+    }
+
+    /// Returns whether the quotas allow adding the transaction to the batch, counting it if so.
+    fn try_add(&mut self, transactions: &TransactionStore, txn: &TxnPointer) -> bool {
+        let sender_txns = self.txns_per_sender.get(&txn.sender).copied().unwrap_or(0);
+        if let Some(max_txns) = self.config.max_txns_per_sender {
+            if sender_txns >= max_txns {
+                counters::core_mempool_fairness_throttled_inc(counters::SENDER_QUOTA_LABEL);
+                return false;
+            }
+        }
+
+        if let Some(max_txns) = self.config.max_txns_per_module_address {
+            if let Some(module_address) =
+                transactions.get_module_address(&txn.sender, txn.sequence_number)
+            {
+                let module_txns = self
+                    .txns_per_module_address
+                    .entry(module_address)
+                    .or_insert(0);
+                if *module_txns >= max_txns {
+                    counters::core_mempool_fairness_throttled_inc(
+                        counters::MODULE_ADDRESS_QUOTA_LABEL,
+                    );
+                    return false;
+                }
+                *module_txns += 1;
+            }
+        }
+
+        self.txns_per_sender.insert(txn.sender, sender_txns + 1);
+        true
+    }
+}
@@ -6,7 +6,7 @@ use crate::{
     core_mempool::{
         index::{
             AccountTransactions, MultiBucketTimelineIndex, ParkingLotIndex, PriorityIndex,
-            PriorityQueueIter, TTLIndex,
+            PriorityQueueIter, RoundRobinQueueIter, TTLIndex,
         },
         mempool::Mempool,
         transaction::{InsertionInfo, MempoolTransaction, TimelineState},
@@ -23,7 +23,7 @@ use aptos_logger::{prelude::*, Level};
 use aptos_types::{
     account_address::AccountAddress,
     mempool_status::{MempoolStatus, MempoolStatusCode},
-    transaction::SignedTransaction,
+    transaction::{SignedTransaction, TransactionPayload},
 };
 use std::{
     cmp::max,
@@ -137,6 +137,24 @@ impl TransactionStore {
         None
     }
 
+    /// Returns the address of the module whose entry function the transaction calls, if any.
+    pub(crate) fn get_module_address(
+        &self,
+        address: &AccountAddress,
+        sequence_number: u64,
+    ) -> Option<AccountAddress> {
+        match self
+            .get_mempool_txn(address, sequence_number)?
+            .txn
+            .payload()
+        {
+            TransactionPayload::EntryFunction(entry_function) => {
+                Some(*entry_function.module().address())
+            },
+            _ => None,
+        }
+    }
+
     /// Fetch transaction by account address + sequence_number, including ranking score
     pub(crate) fn get_with_ranking_score(
         &self,
@@ -763,6 +781,18 @@ impl TransactionStore {
         self.priority_index.iter()
     }
 
+    pub(crate) fn iter_queue_round_robin(&self, weight: u64, max_txns: u64) -> RoundRobinQueueIter {
+        RoundRobinQueueIter::new(self.priority_index.iter(), weight, max_txns)
+    }
+
+    /// Returns all transactions in the store, ordered by sequence number within each account.
+    pub(crate) fn get_all_transactions(&self) -> Vec<SignedTransaction> {
+        self.transactions
+            .values()
+            .flat_map(|txns| txns.values().map(|txn| txn.txn.clone()))
+            .collect()
+    }
+
     pub(crate) fn gen_snapshot(&self) -> TxnsLog {
         let mut txns_log = TxnsLog::new();
         for (account, txns) in self.transactions.iter() {
@@ -5,8 +5,9 @@
 use aptos_config::network_id::{NetworkId, PeerNetworkId};
 use aptos_metrics_core::{
     exponential_buckets, histogram_opts, op_counters::DurationHistogram, register_histogram,
-    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge_vec,
-    Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec, IntGauge, IntGaugeVec,
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Histogram, HistogramTimer, HistogramVec, IntCounter, IntCounterVec,
+    IntGauge, IntGaugeVec,
 };
 use aptos_short_hex_str::AsShortHexStr;
 use once_cell::sync::Lazy;
@@ -49,6 +50,10 @@ pub const GET_BLOCK_GET_BATCH_LABEL: &str = "get_block_get_batch";
 pub const COMMIT_STATE_SYNC_LABEL: &str = "commit_accepted";
 pub const COMMIT_CONSENSUS_LABEL: &str = "commit_rejected";
 
+// Fairness quota labels
+pub const SENDER_QUOTA_LABEL: &str = "sender";
+pub const MODULE_ADDRESS_QUOTA_LABEL: &str = "module_address";
+
 // Mempool service request result labels
 pub const REQUEST_FAIL_LABEL: &str = "fail";
 pub const REQUEST_SUCCESS_LABEL: &str = "success";
@@ -66,6 +71,9 @@ pub const CLIENT_EVENT_LABEL: &str = "client_event";
 pub const CLIENT_EVENT_GET_TXN_LABEL: &str = "client_event_get_txn";
 pub const RECONFIG_EVENT_LABEL: &str = "reconfig";
 pub const PEER_BROADCAST_EVENT_LABEL: &str = "peer_broadcast";
+pub const PEER_PULL_EVENT_LABEL: &str = "peer_pull";
+pub const PULL_CATCH_UP_EVENT_LABEL: &str = "pull_catch_up";
+pub const CLIENT_EVENT_PULL_TXNS_LABEL: &str = "client_event_pull_txns";
 
 // task spawn stage labels
 pub const SPAWN_LABEL: &str = "spawn";
@@ -74,6 +82,27 @@ pub const START_LABEL: &str = "start";
 // Mempool network msg failure type labels:
 pub const BROADCAST_TXNS: &str = "broadcast_txns";
 pub const ACK_TXNS: &str = "ack_txns";
+pub const PULL_TXNS: &str = "pull_txns";
+pub const PULL_RESPONSE: &str = "pull_response";
+
+// Pull request type labels
+pub const PULL_TIMELINE_LABEL: &str = "timeline";
+pub const PULL_BY_HASH_LABEL: &str = "by_hash";
+
+// Pull request result labels
+pub const PULL_SERVED_LABEL: &str = "served";
+pub const PULL_BACKOFF_LABEL: &str = "backoff";
+pub const PULL_FAILED_LABEL: &str = "failed";
+
+// Journal operation labels
+pub const JOURNAL_APPEND_LABEL: &str = "append";
+pub const JOURNAL_COMPACT_LABEL: &str = "compact";
+pub const JOURNAL_REPLAY_LABEL: &str = "replay";
+
+// Journal replay result labels
+pub const JOURNAL_REPLAY_EXPIRED_LABEL: &str = "expired";
+pub const JOURNAL_REPLAY_ACCEPTED_LABEL: &str = "accepted";
+pub const JOURNAL_REPLAY_REJECTED_LABEL: &str = "rejected";
 
 // Broadcast/ACK type labels
 pub const EXPIRED_BROADCAST_LABEL: &str = "expired";
@@ -295,6 +324,22 @@ pub fn mempool_service_transactions(label: &'static str, num: usize) {
         .observe(num as f64)
 }
 
+/// Counter of transactions left out of batches pulled by consensus because of a fairness quota
+static CORE_MEMPOOL_FAIRNESS_THROTTLED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_core_mempool_fairness_throttled_txns",
+        "Number of transactions left out of batches pulled by consensus, by fairness quota",
+        &["quota"]
+    )
+    .unwrap()
+});
+
+pub fn core_mempool_fairness_throttled_inc(quota: &'static str) {
+    CORE_MEMPOOL_FAIRNESS_THROTTLED_TXNS
+        .with_label_values(&[quota])
+        .inc();
+}
+
 /// Histogram for the byte size of transactions processed in get_block
 pub static MEMPOOL_SERVICE_BYTES_GET_BLOCK: Lazy<Histogram> = Lazy::new(|| {
     register_histogram!(
@@ -496,6 +541,85 @@ pub fn shared_mempool_ack_inc(network_id: NetworkId, direction: &str, label: &'s
         .inc();
 }
 
+/// Counter for pull requests sent/received by shared mempool
+static SHARED_MEMPOOL_PULL_REQUEST_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_shared_mempool_pull_request_count",
+        "Number of pull requests sent/received by shared mempool",
+        &["network", "direction", "type", "result"]
+    )
+    .unwrap()
+});
+
+pub fn shared_mempool_pull_request_inc(
+    network_id: NetworkId,
+    direction: &str,
+    request_type: &str,
+    result: &str,
+) {
+    SHARED_MEMPOOL_PULL_REQUEST_COUNT
+        .with_label_values(&[network_id.as_str(), direction, request_type, result])
+        .inc();
+}
+
+/// Counter for failed mempool journal operations
+static MEMPOOL_JOURNAL_ERROR_COUNT: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_mempool_journal_error_count",
+        "Number of failed mempool journal operations",
+        &["operation"]
+    )
+    .unwrap()
+});
+
+pub fn mempool_journal_error_inc(operation: &str) {
+    MEMPOOL_JOURNAL_ERROR_COUNT
+        .with_label_values(&[operation])
+        .inc();
+}
+
+/// Counter for transactions replayed from the mempool journal on startup, by result
+static MEMPOOL_JOURNAL_REPLAYED_TXNS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "aptos_mempool_journal_replayed_txns",
+        "Number of transactions replayed from the mempool journal on startup, by result",
+        &["result"]
+    )
+    .unwrap()
+});
+
+pub fn mempool_journal_replayed_txns_inc(result: &str, num_txns: usize) {
+    MEMPOOL_JOURNAL_REPLAYED_TXNS
+        .with_label_values(&[result])
+        .inc_by(num_txns as u64);
+}
+
+/// Number of transactions in the mempool journal after the last compaction
+pub static MEMPOOL_JOURNAL_SIZE: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_mempool_journal_size",
+        "Number of transactions in the mempool journal after the last compaction"
+    )
+    .unwrap()
+});
+
+/// Counter for number of transactions in each pull response sent/received
+static SHARED_MEMPOOL_PULL_RESPONSE_SIZE: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "aptos_shared_mempool_pull_response_size",
+        "Number of transactions in each pull response sent/received",
+        &["network", "direction"],
+        TRANSACTION_COUNT_BUCKETS.clone()
+    )
+    .unwrap()
+});
+
+pub fn shared_mempool_pull_response_size(network_id: NetworkId, direction: &str, num_txns: usize) {
+    SHARED_MEMPOOL_PULL_RESPONSE_SIZE
+        .with_label_values(&[network_id.as_str(), direction])
+        .observe(num_txns as f64);
+}
+
 static TASK_SPAWN_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
     register_histogram_vec!(
         "aptos_mempool_bounded_executor_spawn_latency",
@@ -22,6 +22,12 @@
 //! local mempool contains transactions with sequence numbers 2,3,4,7,8, then only transactions 2, 3
 //! and 4 will be broadcast.
 //!
+//! Broadcasts only carry transactions as they become ready, so a node that restarts or is cut off
+//! from its upstream peers can also pull their pending transactions over RPC: either those
+//! following a position in the peer's timeline, or specific transactions by hash. Pull requests
+//! are served with the same batch limits as broadcasts, and peers with too many pull requests in
+//! progress are asked to back off.
+//!
 //! Consensus pulls transactions from mempool rather than mempool pushing into consensus. This is
 //! done so that while consensus is not yet ready for transactions, we keep ordering based on gas
 //! and consensus can let transactions build up.  This allows for batching of transactions into a
@@ -162,6 +162,8 @@ pub enum LogEntry {
     BroadcastTransaction,
     BroadcastACK,
     ReceiveACK,
+    PullTransactions,
+    ServePullRequest,
     InvariantViolated,
     AddTxn,
     RemoveTxn,
@@ -170,6 +172,7 @@ pub enum LogEntry {
     CleanCommittedTxn,
     CleanRejectedTxn,
     ProcessReadyTxns,
+    Journal,
     DBError,
     UnexpectedNetworkMsg,
     MempoolSnapshot,
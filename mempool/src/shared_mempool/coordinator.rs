@@ -10,6 +10,7 @@ use crate::{
     logging::{LogEntry, LogEvent, LogSchema},
     network::MempoolSyncMsg,
     shared_mempool::{
+        journal::MempoolJournal,
         tasks,
         tasks::process_committed_transactions,
         types::{notify_subscribers, ScheduledBroadcast, SharedMempool, SharedMempoolNotification},
@@ -41,7 +42,10 @@ use std::{
     sync::Arc,
     time::{Duration, Instant, SystemTime},
 };
-use tokio::{runtime::Handle, time::interval};
+use tokio::{
+    runtime::Handle,
+    time::{self, interval, interval_at},
+};
 use tokio_stream::wrappers::IntervalStream;
 
 /// Coordinator that handles inbound network events and outbound txn broadcasts.
@@ -114,7 +118,7 @@ pub(crate) async fn coordinator<NetworkClient, TransactionValidator, ConfigProvi
                 handle_network_event(&bounded_executor, &mut smp, network_id, event).await;
             },
             _ = update_peers_interval.tick().fuse() => {
-                handle_update_peers(peers_and_metadata.clone(), &mut smp, &mut scheduled_broadcasts, executor.clone()).await;
+                handle_update_peers(peers_and_metadata.clone(), &mut smp, &bounded_executor, &mut scheduled_broadcasts, executor.clone()).await;
             },
             complete => break,
         }
@@ -177,6 +181,27 @@ async fn handle_client_request<NetworkClient, TransactionValidator>(
                 ))
                 .await;
         },
+        MempoolClientRequest::PullTransactionsByHash(hashes, callback) => {
+            // This timer measures how long it took for the bounded executor to *schedule* the
+            // task.
+            let _timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_PULL_TXNS_LABEL,
+                counters::SPAWN_LABEL,
+            );
+            // This timer measures how long it took for the task to go from scheduled to started.
+            let task_start_timer = counters::task_spawn_latency_timer(
+                counters::CLIENT_EVENT_PULL_TXNS_LABEL,
+                counters::START_LABEL,
+            );
+            bounded_executor
+                .spawn(tasks::process_client_pull_transactions(
+                    smp.clone(),
+                    hashes,
+                    callback,
+                    task_start_timer,
+                ))
+                .await;
+        },
     }
 }
 
@@ -257,6 +282,7 @@ async fn handle_mempool_reconfig_event<NetworkClient, TransactionValidator, Conf
 /// - NewPeer events start new automatic broadcasts if the peer is upstream. If the peer is not upstream, we ignore it.
 /// - LostPeer events disable the upstream peer, which will cancel ongoing broadcasts.
 /// - Network messages follow a simple Request/Response framework to accept new transactions
+/// - RPC requests serve pull requests of peers catching up on pending transactions
 /// TODO: Move to RPC off of DirectSend
 async fn handle_network_event<NetworkClient, TransactionValidator>(
     bounded_executor: &BoundedExecutor,
@@ -328,15 +354,60 @@ async fn handle_network_event<NetworkClient, TransactionValidator>(
                         ack_timestamp,
                     );
                 },
+                MempoolSyncMsg::PullTransactionsRequest { .. }
+                | MempoolSyncMsg::PullTransactionsByHashRequest { .. }
+                | MempoolSyncMsg::PullTransactionsResponse { .. } => {
+                    // Pulls are only served over RPC
+                    counters::unexpected_msg_count_inc(&network_id);
+                    sample!(
+                        SampleRate::Duration(Duration::from_secs(60)),
+                        warn!(LogSchema::new(LogEntry::UnexpectedNetworkMsg)
+                            .peer(&PeerNetworkId::new(network_id, peer_id)))
+                    );
+                },
             }
         },
-        Event::RpcRequest(peer_id, _msg, _, _res_tx) => {
-            counters::unexpected_msg_count_inc(&network_id);
-            sample!(
-                SampleRate::Duration(Duration::from_secs(60)),
-                warn!(LogSchema::new(LogEntry::UnexpectedNetworkMsg)
-                    .peer(&PeerNetworkId::new(network_id, peer_id)))
-            );
+        Event::RpcRequest(peer_id, msg, protocol_id, res_tx) => match msg {
+            MempoolSyncMsg::PullTransactionsRequest { .. }
+            | MempoolSyncMsg::PullTransactionsByHashRequest { .. } => {
+                counters::shared_mempool_event_inc("pull_request");
+                let peer = PeerNetworkId::new(network_id, peer_id);
+                // Requests over the per peer limit are not queued, the peer is asked to back off
+                if !smp.network_interface.start_serving_pull(peer) {
+                    tasks::reject_pull_request(&msg, peer, protocol_id, res_tx);
+                    return;
+                }
+                // This timer measures how long it took for the bounded executor to
+                // *schedule* the task.
+                let _timer = counters::task_spawn_latency_timer(
+                    counters::PEER_PULL_EVENT_LABEL,
+                    counters::SPAWN_LABEL,
+                );
+                // This timer measures how long it took for the task to go from scheduled
+                // to started.
+                let task_start_timer = counters::task_spawn_latency_timer(
+                    counters::PEER_PULL_EVENT_LABEL,
+                    counters::START_LABEL,
+                );
+                bounded_executor
+                    .spawn(tasks::process_pull_request(
+                        smp.clone(),
+                        msg,
+                        peer,
+                        protocol_id,
+                        res_tx,
+                        task_start_timer,
+                    ))
+                    .await;
+            },
+            _ => {
+                counters::unexpected_msg_count_inc(&network_id);
+                sample!(
+                    SampleRate::Duration(Duration::from_secs(60)),
+                    warn!(LogSchema::new(LogEntry::UnexpectedNetworkMsg)
+                        .peer(&PeerNetworkId::new(network_id, peer_id)))
+                );
+            },
         },
     }
 }
@@ -344,6 +415,7 @@ async fn handle_network_event<NetworkClient, TransactionValidator>(
 async fn handle_update_peers<NetworkClient, TransactionValidator>(
     peers_and_metadata: Arc<PeersAndMetadata>,
     smp: &mut SharedMempool<NetworkClient, TransactionValidator>,
+    bounded_executor: &BoundedExecutor,
     scheduled_broadcasts: &mut FuturesUnordered<ScheduledBroadcast>,
     executor: Handle,
 ) where
@@ -360,6 +432,21 @@ async fn handle_update_peers<NetworkClient, TransactionValidator>(
             debug!(LogSchema::new(LogEntry::NewPeer).peer(peer));
             tasks::execute_broadcast(*peer, false, smp, scheduled_broadcasts, executor.clone())
                 .await;
+            if smp.config.shared_mempool_pull_enabled
+                && smp.network_interface.should_pull_from_peer(*peer)
+            {
+                let task_start_timer = counters::task_spawn_latency_timer(
+                    counters::PULL_CATCH_UP_EVENT_LABEL,
+                    counters::START_LABEL,
+                );
+                bounded_executor
+                    .spawn(tasks::pull_transactions_from_peer(
+                        smp.clone(),
+                        *peer,
+                        task_start_timer,
+                    ))
+                    .await;
+            }
         }
         for peer in &disabled {
             debug!(LogSchema::new(LogEntry::LostPeer).peer(peer));
@@ -395,3 +482,19 @@ pub(crate) async fn snapshot_job(mempool: Arc<Mutex<CoreMempool>>, snapshot_inte
         trace!(LogSchema::new(LogEntry::MempoolSnapshot).txns(snapshot));
     }
 }
+
+/// Periodically rewrites the mempool journal with only the transactions still in mempool, so
+/// that it doesn't grow with transactions that were committed or expired since. The first
+/// compaction happens one interval after the job starts, as the journal was just replayed and
+/// compacted then.
+pub(crate) async fn journal_compaction_job(
+    mempool: Arc<Mutex<CoreMempool>>,
+    journal: Arc<MempoolJournal>,
+    compaction_interval_secs: u64,
+) {
+    let period = Duration::from_secs(compaction_interval_secs);
+    let mut interval = IntervalStream::new(interval_at(time::Instant::now() + period, period));
+    while let Some(_interval) = interval.next().await {
+        tasks::compact_journal(&mempool, &journal);
+    }
+}
@@ -0,0 +1,113 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! On-disk journal of the transactions accepted into mempool, replayed on startup so that
+//! pending transactions survive node restarts.
+//!
+//! The journal is a file of length-prefixed BCS serialized transactions. Accepted transactions
+//! are appended to it, but removals (commits, expirations, evictions) are not recorded. Instead,
+//! the journal is periodically compacted by rewriting it with only the transactions that are
+//! still in mempool. Replayed transactions go through the same validation as any incoming
+//! transaction, so stale entries are dropped on replay.
+
+use crate::core_mempool::CoreMempool;
+use anyhow::{format_err, Result};
+use aptos_infallible::Mutex;
+use aptos_types::transaction::SignedTransaction;
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+/// Size of the length prefix of each journal record
+const RECORD_LENGTH_BYTES: usize = 4;
+
+pub(crate) struct MempoolJournal {
+    path: PathBuf,
+    writer: Mutex<BufWriter<File>>,
+}
+
+impl MempoolJournal {
+    /// Opens the journal at `path`, creating it if it doesn't exist, and returns it along with
+    /// the transactions it holds. A record torn by a crash while appending is truncated.
+    pub fn open(path: &Path) -> Result<(Self, Vec<SignedTransaction>)> {
+        let (transactions, valid_len) = if path.exists() {
+            Self::read(path)?
+        } else {
+            (vec![], 0)
+        };
+
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        if file.metadata()?.len() != valid_len {
+            file.set_len(valid_len)?;
+        }
+        let journal = Self {
+            path: path.to_path_buf(),
+            writer: Mutex::new(BufWriter::new(file)),
+        };
+        Ok((journal, transactions))
+    }
+
+    /// Reads all complete records of the journal, returning the transactions and the length of
+    /// the journal prefix they occupy.
+    fn read(path: &Path) -> Result<(Vec<SignedTransaction>, u64)> {
+        let bytes = fs::read(path)?;
+        let mut transactions = vec![];
+        let mut offset = 0;
+        while offset + RECORD_LENGTH_BYTES <= bytes.len() {
+            let mut len_bytes = [0u8; RECORD_LENGTH_BYTES];
+            len_bytes.copy_from_slice(&bytes[offset..offset + RECORD_LENGTH_BYTES]);
+            let start = offset + RECORD_LENGTH_BYTES;
+            let end = start + u32::from_le_bytes(len_bytes) as usize;
+            if end > bytes.len() {
+                break;
+            }
+            match bcs::from_bytes(&bytes[start..end]) {
+                Ok(txn) => transactions.push(txn),
+                Err(_) => break,
+            }
+            offset = end;
+        }
+        Ok((transactions, offset as u64))
+    }
+
+    /// Appends the transactions to the journal. They are handed to the OS before returning, so
+    /// they survive a process crash, but not necessarily a machine crash.
+    pub fn append(&self, transactions: &[SignedTransaction]) -> Result<()> {
+        let mut writer = self.writer.lock();
+        write_records(&mut *writer, transactions)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Rewrites the journal with only the transactions currently in mempool, returning the
+    /// number of transactions written.
+    pub fn compact(&self, mempool: &Mutex<CoreMempool>) -> Result<usize> {
+        // The mempool is read while holding the writer lock, so that transactions added to
+        // mempool after the read are appended to the compacted journal rather than lost.
+        let mut writer = self.writer.lock();
+        let transactions = mempool.lock().get_all_transactions();
+
+        let tmp_path = self.path.with_extension("tmp");
+        let mut tmp_writer = BufWriter::new(File::create(&tmp_path)?);
+        write_records(&mut tmp_writer, &transactions)?;
+        let tmp_file = tmp_writer
+            .into_inner()
+            .map_err(|e| format_err!("Failed to flush mempool journal: {}", e))?;
+        tmp_file.sync_all()?;
+        fs::rename(&tmp_path, &self.path)?;
+
+        *writer = BufWriter::new(OpenOptions::new().append(true).open(&self.path)?);
+        Ok(transactions.len())
+    }
+}
+
+fn write_records<W: Write>(writer: &mut W, transactions: &[SignedTransaction]) -> Result<()> {
+    for txn in transactions {
+        let bytes = bcs::to_bytes(txn)?;
+        writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        writer.write_all(&bytes)?;
+    }
+    Ok(())
+}
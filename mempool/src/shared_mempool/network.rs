@@ -10,8 +10,8 @@ use crate::{
     shared_mempool::{
         tasks,
         types::{
-            notify_subscribers, MultiBatchId, PeerSyncState, SharedMempool,
-            SharedMempoolNotification,
+            notify_subscribers, MultiBatchId, MultiBucketTimelineIndexIds, PeerSyncState,
+            SharedMempool, SharedMempoolNotification,
         },
     },
 };
@@ -19,12 +19,14 @@ use aptos_config::{
     config::{MempoolConfig, PeerRole, RoleType},
     network_id::PeerNetworkId,
 };
+use aptos_crypto::HashValue;
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_netcore::transport::ConnectionOrigin;
 use aptos_network::{
     application::{error::Error, interface::NetworkClientInterface, metadata::PeerMetadata},
     transport::ConnectionMetadata,
+    ProtocolId,
 };
 use aptos_types::{transaction::SignedTransaction, PeerId};
 use aptos_vm_validator::vm_validator::TransactionValidation;
@@ -59,6 +61,27 @@ pub enum MempoolSyncMsg {
         /// A backpressure signal from the recipient when it is overwhelmed (e.g., mempool is full).
         backoff: bool,
     },
+    /// Pull request issued by a peer catching up on pending transactions, sent over RPC.
+    /// Asks for the ready transactions following `timeline_id` in the responder's timeline.
+    PullTransactionsRequest {
+        /// Position in the responder's timeline, as returned in its previous response. Pulls
+        /// start from the beginning of the timeline if the number of buckets doesn't match.
+        timeline_id: MultiBucketTimelineIndexIds,
+        max_txns: u64,
+    },
+    /// Pull request for specific transactions missing from the requester's mempool, sent over
+    /// RPC.
+    PullTransactionsByHashRequest { hashes: Vec<HashValue> },
+    /// Response to a pull request.
+    PullTransactionsResponse {
+        transactions: Vec<SignedTransaction>,
+        /// Position in the responder's timeline after `transactions`, to continue pulling from.
+        /// Not set in responses to requests by hash.
+        timeline_id: Option<MultiBucketTimelineIndexIds>,
+        /// A backpressure signal from the responder when it is serving too many pull requests
+        /// of the requester.
+        backoff: bool,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -77,6 +100,22 @@ pub enum BroadcastError {
     TooManyPendingBroadcasts(PeerNetworkId),
 }
 
+#[derive(Debug, Error)]
+pub enum PullError {
+    #[error("Peer {0} NetworkError: '{1}'")]
+    NetworkError(PeerNetworkId, anyhow::Error),
+    #[error("Peer {0} sent an unexpected response to a pull request: {1:?}")]
+    UnexpectedResponse(PeerNetworkId, Box<MempoolSyncMsg>),
+}
+
+/// Transactions returned by a pull request, along with the responder's timeline position and
+/// backoff signal.
+pub(crate) type PullResponse = (
+    Vec<SignedTransaction>,
+    Option<MultiBucketTimelineIndexIds>,
+    bool,
+);
+
 #[derive(Clone, Debug)]
 pub(crate) struct MempoolNetworkInterface<NetworkClient> {
     network_client: NetworkClient,
@@ -85,6 +124,8 @@ pub(crate) struct MempoolNetworkInterface<NetworkClient> {
     role: RoleType,
     mempool_config: MempoolConfig,
     prioritized_peers_comparator: PrioritizedPeersComparator,
+    /// Number of pull requests currently being served for each peer
+    pulls_in_progress: Arc<Mutex<HashMap<PeerNetworkId, usize>>>,
 }
 
 impl<NetworkClient: NetworkClientInterface<MempoolSyncMsg>> MempoolNetworkInterface<NetworkClient> {
@@ -100,6 +141,7 @@ impl<NetworkClient: NetworkClientInterface<MempoolSyncMsg>> MempoolNetworkInterf
             role,
             mempool_config,
             prioritized_peers_comparator: PrioritizedPeersComparator::new(),
+            pulls_in_progress: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
@@ -508,6 +550,89 @@ impl<NetworkClient: NetworkClientInterface<MempoolSyncMsg>> MempoolNetworkInterf
     pub fn sync_states_exists(&self, peer: &PeerNetworkId) -> bool {
         self.sync_states.read().get(peer).is_some()
     }
+
+    /// Returns the upstream peers that serve pull requests, in the order they are broadcast to.
+    pub fn pullable_upstream_peers(&self) -> Vec<PeerNetworkId> {
+        let mut peers = self.prioritized_peers.lock().clone();
+        // Validators don't prioritize peers, so add any that are missing
+        for peer in self.sync_states.read().keys() {
+            if !peers.contains(peer) {
+                peers.push(*peer);
+            }
+        }
+        peers.retain(|peer| self.supports_pull(peer));
+        peers
+    }
+
+    /// Whether to catch up on the pending transactions of a newly added upstream peer. Only
+    /// fullnodes pull, and only from the peers they would broadcast to.
+    pub fn should_pull_from_peer(&self, peer: PeerNetworkId) -> bool {
+        !self.role.is_validator()
+            && self.supports_pull(&peer)
+            && self.check_peer_prioritized(peer).is_ok()
+    }
+
+    /// Whether the upstream peer serves pull requests, i.e. supports `MempoolRpc`
+    fn supports_pull(&self, peer: &PeerNetworkId) -> bool {
+        self.sync_states.read().get(peer).map_or(false, |state| {
+            state
+                .metadata
+                .application_protocols
+                .contains(ProtocolId::MempoolRpc)
+        })
+    }
+
+    /// Sends a pull request to the given peer and waits for the response
+    pub async fn send_pull_request(
+        &self,
+        peer: PeerNetworkId,
+        request: MempoolSyncMsg,
+    ) -> Result<PullResponse, PullError> {
+        let timeout = Duration::from_millis(self.mempool_config.shared_mempool_pull_timeout_ms);
+        let response = self
+            .network_client
+            .send_to_peer_rpc(request, timeout, peer)
+            .await
+            .map_err(|e| {
+                counters::network_send_fail_inc(counters::PULL_TXNS);
+                PullError::NetworkError(peer, e.into())
+            })?;
+        match response {
+            MempoolSyncMsg::PullTransactionsResponse {
+                transactions,
+                timeline_id,
+                backoff,
+            } => Ok((transactions, timeline_id, backoff)),
+            response => Err(PullError::UnexpectedResponse(peer, Box::new(response))),
+        }
+    }
+
+    /// Starts serving a pull request from the given peer. Returns false if the peer already has
+    /// the maximum number of pull requests being served, in which case it should back off.
+    pub fn start_serving_pull(&self, peer: PeerNetworkId) -> bool {
+        let mut pulls_in_progress = self.pulls_in_progress.lock();
+        let num_pulls = pulls_in_progress.get(&peer).copied().unwrap_or(0);
+        if num_pulls
+            >= self
+                .mempool_config
+                .shared_mempool_max_concurrent_pulls_per_peer
+        {
+            return false;
+        }
+        pulls_in_progress.insert(peer, num_pulls + 1);
+        true
+    }
+
+    /// Marks a pull request started by `start_serving_pull` as served.
+    pub fn finish_serving_pull(&self, peer: PeerNetworkId) {
+        let mut pulls_in_progress = self.pulls_in_progress.lock();
+        if let Some(num_pulls) = pulls_in_progress.get_mut(&peer) {
+            *num_pulls = num_pulls.saturating_sub(1);
+            if *num_pulls == 0 {
+                pulls_in_progress.remove(&peer);
+            }
+        }
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -4,9 +4,13 @@
 
 use crate::{
     core_mempool::CoreMempool,
+    counters,
+    logging::{LogEntry, LogSchema},
     network::MempoolSyncMsg,
     shared_mempool::{
-        coordinator::{coordinator, gc_coordinator, snapshot_job},
+        coordinator::{coordinator, gc_coordinator, journal_compaction_job, snapshot_job},
+        journal::MempoolJournal,
+        tasks,
         types::{MempoolEventsReceiver, SharedMempool, SharedMempoolNotification},
     },
     QuorumStoreRequest,
@@ -14,14 +18,14 @@ use crate::{
 use aptos_config::config::NodeConfig;
 use aptos_event_notifications::{DbBackedOnChainConfig, ReconfigNotificationListener};
 use aptos_infallible::{Mutex, RwLock};
-use aptos_logger::Level;
+use aptos_logger::{error, Level};
 use aptos_mempool_notifications::MempoolNotificationListener;
 use aptos_network::application::{
     interface::{NetworkClient, NetworkServiceEvents},
     storage::PeersAndMetadata,
 };
 use aptos_storage_interface::DbReader;
-use aptos_types::on_chain_config::OnChainConfigProvider;
+use aptos_types::{on_chain_config::OnChainConfigProvider, transaction::SignedTransaction};
 use aptos_vm_validator::vm_validator::{TransactionValidation, VMValidator};
 use futures::channel::mpsc::{Receiver, UnboundedSender};
 use std::sync::Arc;
@@ -50,6 +54,10 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
     TransactionValidator: TransactionValidation + 'static,
     ConfigProvider: OnChainConfigProvider,
 {
+    let (journal, journaled_transactions) = match open_journal(config) {
+        Some((journal, transactions)) => (Some(Arc::new(journal)), transactions),
+        None => (None, vec![]),
+    };
     let smp: SharedMempool<NetworkClient<MempoolSyncMsg>, TransactionValidator> =
         SharedMempool::new(
             mempool.clone(),
@@ -59,8 +67,24 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
             validator,
             subscribers,
             config.base.role,
+            journal.clone(),
         );
 
+    if let Some(journal) = journal {
+        let replay = tasks::replay_journal(smp.clone(), journal.clone(), journaled_transactions);
+        let compaction = journal_compaction_job(
+            mempool.clone(),
+            journal,
+            config.mempool.journal_compaction_interval_secs,
+        );
+        // Compacting the journal during replay would rewrite it from a mempool still missing the
+        // journaled transactions, losing them if the node stops before the replay is done.
+        executor.spawn(async move {
+            replay.await;
+            compaction.await;
+        });
+    }
+
     executor.spawn(coordinator(
         smp,
         executor.clone(),
@@ -86,6 +110,26 @@ pub(crate) fn start_shared_mempool<TransactionValidator, ConfigProvider>(
     }
 }
 
+/// Opens the mempool journal if one is configured. Failing to open it is logged and counted,
+/// and the node runs without a journal rather than failing to start.
+fn open_journal(config: &NodeConfig) -> Option<(MempoolJournal, Vec<SignedTransaction>)> {
+    let path = config
+        .base
+        .data_dir
+        .join(config.mempool.journal_path.as_ref()?);
+    match MempoolJournal::open(&path) {
+        Ok(journal) => Some(journal),
+        Err(e) => {
+            counters::mempool_journal_error_inc(counters::JOURNAL_REPLAY_LABEL);
+            error!(
+                LogSchema::new(LogEntry::Journal).error(&e),
+                "Failed to open mempool journal at {:?}", path
+            );
+            None
+        },
+    }
+}
+
 pub fn bootstrap(
     config: &NodeConfig,
     db: Arc<dyn DbReader>,
@@ -8,21 +8,26 @@ use crate::{
     counters,
     logging::{LogEntry, LogEvent, LogSchema},
     network::{BroadcastError, MempoolSyncMsg},
-    shared_mempool::types::{
-        notify_subscribers, MultiBatchId, ScheduledBroadcast, SharedMempool,
-        SharedMempoolNotification, SubmissionStatusBundle,
+    shared_mempool::{
+        journal::MempoolJournal,
+        types::{
+            notify_subscribers, MultiBatchId, MultiBucketTimelineIndexIds, ScheduledBroadcast,
+            SharedMempool, SharedMempoolNotification, SubmissionStatusBundle,
+        },
     },
     thread_pool::IO_POOL,
     QuorumStoreRequest, QuorumStoreResponse, SubmissionStatus,
 };
 use anyhow::Result;
-use aptos_config::network_id::PeerNetworkId;
+use aptos_config::{config::transaction_filter_type::Filter, network_id::PeerNetworkId};
 use aptos_consensus_types::common::{RejectedTransactionSummary, TransactionSummary};
 use aptos_crypto::HashValue;
 use aptos_infallible::{Mutex, RwLock};
 use aptos_logger::prelude::*;
 use aptos_metrics_core::HistogramTimer;
-use aptos_network::application::interface::NetworkClientInterface;
+use aptos_network::{
+    application::interface::NetworkClientInterface, protocols::rpc::error::RpcError, ProtocolId,
+};
 use aptos_storage_interface::state_view::LatestDbStateCheckpointView;
 use aptos_types::{
     mempool_status::{MempoolStatus, MempoolStatusCode},
@@ -31,6 +36,7 @@ use aptos_types::{
     vm_status::{DiscardedVMStatus, StatusCode},
 };
 use aptos_vm_validator::vm_validator::{get_account_sequence_number, TransactionValidation};
+use bytes::Bytes;
 use futures::{channel::oneshot, stream::FuturesUnordered};
 use rayon::prelude::*;
 use std::{
@@ -263,6 +269,12 @@ where
 {
     let mut statuses = vec![];
 
+    let transactions =
+        filter_transactions(&smp.config.transaction_filter, transactions, &mut statuses);
+    if transactions.is_empty() {
+        return statuses;
+    }
+
     let start_storage_read = Instant::now();
     let state_view = smp
         .db
@@ -325,10 +337,62 @@ where
         &mut statuses,
         client_submitted,
     );
+    if let Some(journal) = &smp.journal {
+        journal_accepted_transactions(journal, &statuses);
+    }
     notify_subscribers(SharedMempoolNotification::NewTransactions, &smp.subscribers);
     statuses
 }
 
+/// Appends the transactions accepted into mempool to the journal. Failures are logged and
+/// counted but otherwise ignored, as the journal is only a best effort across restarts.
+fn journal_accepted_transactions(journal: &MempoolJournal, statuses: &[SubmissionStatusBundle]) {
+    let accepted: Vec<_> = statuses
+        .iter()
+        .filter(|(_, (status, _))| status.code == MempoolStatusCode::Accepted)
+        .map(|(txn, _)| txn.clone())
+        .collect();
+    if accepted.is_empty() {
+        return;
+    }
+    if let Err(e) = journal.append(&accepted) {
+        counters::mempool_journal_error_inc(counters::JOURNAL_APPEND_LABEL);
+        error!(LogSchema::new(LogEntry::Journal).error(&e));
+    }
+}
+
+/// Rejects the transactions denied by the mempool transaction filter, adding their
+/// statuses, and returns the rest.
+fn filter_transactions(
+    filter: &Filter,
+    transactions: Vec<SignedTransaction>,
+    statuses: &mut Vec<SubmissionStatusBundle>,
+) -> Vec<SignedTransaction> {
+    // Special case for no filter to avoid unnecessary iteration through all transactions in the default case
+    if filter.is_empty() {
+        return transactions;
+    }
+
+    let timestamp_usecs = aptos_infallible::duration_since_epoch().as_micros() as u64;
+    transactions
+        .into_iter()
+        .filter_map(|txn| {
+            if filter.allows_transaction(timestamp_usecs, &txn) {
+                Some(txn)
+            } else {
+                statuses.push((
+                    txn,
+                    (
+                        MempoolStatus::new(MempoolStatusCode::RejectedByFilter),
+                        None,
+                    ),
+                ));
+                None
+            }
+        })
+        .collect()
+}
+
 /// Perfoms VM validation on the transactions and inserts those that passes
 /// validation into the mempool.
 #[cfg(not(feature = "consensus-only-perf-test"))]
@@ -453,6 +517,387 @@ fn log_txn_process_results(results: &[SubmissionStatusBundle], sender: Option<Pe
     }
 }
 
+// ============================== //
+//  Tasks processing pull sync    //
+// ============================== //
+
+/// Serves a pull request from another node with transactions from the local mempool.
+pub(crate) async fn process_pull_request<NetworkClient, TransactionValidator>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    request: MempoolSyncMsg,
+    peer: PeerNetworkId,
+    protocol_id: ProtocolId,
+    response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+    timer: HistogramTimer,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation,
+{
+    timer.stop_and_record();
+    let (request_label, transactions, timeline_id) = read_pull_request(&smp, request);
+    counters::shared_mempool_pull_request_inc(
+        peer.network_id(),
+        counters::RECEIVED_LABEL,
+        request_label,
+        counters::PULL_SERVED_LABEL,
+    );
+    counters::shared_mempool_pull_response_size(
+        peer.network_id(),
+        counters::SENT_LABEL,
+        transactions.len(),
+    );
+    let response = MempoolSyncMsg::PullTransactionsResponse {
+        transactions,
+        timeline_id,
+        backoff: false,
+    };
+    send_pull_response(peer, protocol_id, response_sender, response);
+    smp.network_interface.finish_serving_pull(peer);
+}
+
+/// Reads the transactions asked for by a pull request from the local mempool. Like broadcasts,
+/// responses hold at most `shared_mempool_batch_size` transactions and
+/// `shared_mempool_max_batch_bytes` bytes.
+fn read_pull_request<NetworkClient, TransactionValidator>(
+    smp: &SharedMempool<NetworkClient, TransactionValidator>,
+    request: MempoolSyncMsg,
+) -> (
+    &'static str,
+    Vec<SignedTransaction>,
+    Option<MultiBucketTimelineIndexIds>,
+) {
+    let batch_size = smp.config.shared_mempool_batch_size;
+    match request {
+        MempoolSyncMsg::PullTransactionsRequest {
+            timeline_id,
+            max_txns,
+        } => {
+            // The timeline id is ours, unless the peer is starting over or we restarted
+            // with different buckets in between.
+            let num_buckets = smp.config.broadcast_buckets.len();
+            let timeline_id = if timeline_id.id_per_bucket.len() == num_buckets {
+                timeline_id
+            } else {
+                MultiBucketTimelineIndexIds::new(num_buckets)
+            };
+            let count = cmp::min(max_txns, batch_size as u64).max(1) as usize;
+            let (transactions, timeline_id) = smp.mempool.lock().read_timeline(&timeline_id, count);
+            (
+                counters::PULL_TIMELINE_LABEL,
+                transactions,
+                Some(timeline_id),
+            )
+        },
+        MempoolSyncMsg::PullTransactionsByHashRequest { hashes } => {
+            let mempool = smp.mempool.lock();
+            let mut transactions = vec![];
+            let mut total_bytes: u64 = 0;
+            for hash in hashes.into_iter().take(batch_size) {
+                if let Some(txn) = mempool.get_by_hash(hash) {
+                    total_bytes = total_bytes.saturating_add(txn.raw_txn_bytes_len() as u64);
+                    if total_bytes > smp.config.shared_mempool_max_batch_bytes {
+                        break; // The response is full
+                    }
+                    transactions.push(txn);
+                }
+            }
+            (counters::PULL_BY_HASH_LABEL, transactions, None)
+        },
+        request => unreachable!("Not a pull request: {:?}", request),
+    }
+}
+
+/// Responds to a pull request that won't be served because the peer has too many pull requests
+/// in progress.
+pub(crate) fn reject_pull_request(
+    request: &MempoolSyncMsg,
+    peer: PeerNetworkId,
+    protocol_id: ProtocolId,
+    response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+) {
+    let request_label = match request {
+        MempoolSyncMsg::PullTransactionsByHashRequest { .. } => counters::PULL_BY_HASH_LABEL,
+        _ => counters::PULL_TIMELINE_LABEL,
+    };
+    counters::shared_mempool_pull_request_inc(
+        peer.network_id(),
+        counters::RECEIVED_LABEL,
+        request_label,
+        counters::PULL_BACKOFF_LABEL,
+    );
+    let response = MempoolSyncMsg::PullTransactionsResponse {
+        transactions: vec![],
+        timeline_id: None,
+        backoff: true,
+    };
+    send_pull_response(peer, protocol_id, response_sender, response);
+}
+
+fn send_pull_response(
+    peer: PeerNetworkId,
+    protocol_id: ProtocolId,
+    response_sender: oneshot::Sender<Result<Bytes, RpcError>>,
+    response: MempoolSyncMsg,
+) {
+    let result = protocol_id.to_bytes(&response).and_then(|bytes| {
+        response_sender
+            .send(Ok(bytes.into()))
+            .map_err(|_| anyhow::anyhow!("Failed to send the pull response"))
+    });
+    if let Err(error) = result {
+        counters::network_send_fail_inc(counters::PULL_RESPONSE);
+        warn!(
+            LogSchema::event_log(LogEntry::ServePullRequest, LogEvent::NetworkSendFail)
+                .peer(&peer)
+                .error(&error)
+        );
+    }
+}
+
+/// Catches up on the pending transactions of a newly added upstream peer, by pulling its
+/// timeline until it's exhausted, either side asks to back off, or
+/// `shared_mempool_max_pulls_per_catch_up` requests were sent.
+pub(crate) async fn pull_transactions_from_peer<NetworkClient, TransactionValidator>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    peer: PeerNetworkId,
+    timer: HistogramTimer,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation,
+{
+    timer.stop_and_record();
+    // Start from the beginning of the peer's timeline
+    let mut timeline_id = MultiBucketTimelineIndexIds::new(0);
+    for _ in 0..smp.config.shared_mempool_max_pulls_per_catch_up {
+        // Stop if the peer was disconnected in the meantime
+        if !smp.network_interface.sync_states_exists(&peer) {
+            return;
+        }
+        let request = MempoolSyncMsg::PullTransactionsRequest {
+            timeline_id: timeline_id.clone(),
+            max_txns: smp.config.shared_mempool_batch_size as u64,
+        };
+        let (transactions, new_timeline_id) =
+            match send_pull_request(&smp, peer, request, counters::PULL_TIMELINE_LABEL).await {
+                Some(response) => response,
+                None => return,
+            };
+        if transactions.is_empty() {
+            break;
+        }
+        if !add_pulled_transactions(&smp, peer, transactions) {
+            break;
+        }
+        match new_timeline_id {
+            Some(new_timeline_id) => timeline_id = new_timeline_id,
+            None => break,
+        }
+    }
+    debug!(LogSchema::event_log(LogEntry::PullTransactions, LogEvent::Success).peer(&peer));
+}
+
+/// Pulls the transactions with the given hashes that are missing from the local mempool from
+/// the upstream peers, asking each in turn, and responds with how many of the missing
+/// transactions made it into mempool.
+pub(crate) async fn process_client_pull_transactions<NetworkClient, TransactionValidator>(
+    smp: SharedMempool<NetworkClient, TransactionValidator>,
+    hashes: Vec<HashValue>,
+    callback: oneshot::Sender<usize>,
+    timer: HistogramTimer,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation,
+{
+    timer.stop_and_record();
+    let mut missing: Vec<_> = {
+        let mempool = smp.mempool.lock();
+        hashes
+            .into_iter()
+            .filter(|hash| mempool.get_by_hash(*hash).is_none())
+            .collect()
+    };
+    let mut num_added = 0;
+    for peer in smp.network_interface.pullable_upstream_peers() {
+        if missing.is_empty() {
+            break;
+        }
+        for chunk in missing.chunks(smp.config.shared_mempool_batch_size.max(1)) {
+            let request = MempoolSyncMsg::PullTransactionsByHashRequest {
+                hashes: chunk.to_vec(),
+            };
+            match send_pull_request(&smp, peer, request, counters::PULL_BY_HASH_LABEL).await {
+                Some((transactions, _)) if !transactions.is_empty() => {
+                    add_pulled_transactions(&smp, peer, transactions);
+                },
+                Some(_) => {},
+                None => break,
+            }
+        }
+
+        let num_missing = missing.len();
+        {
+            let mempool = smp.mempool.lock();
+            missing.retain(|hash| mempool.get_by_hash(*hash).is_none());
+        }
+        num_added += num_missing - missing.len();
+    }
+
+    if callback.send(num_added).is_err() {
+        warn!(LogSchema::event_log(
+            LogEntry::PullTransactions,
+            LogEvent::CallbackFail
+        ));
+        counters::CLIENT_CALLBACK_FAIL.inc();
+    }
+}
+
+/// Sends a pull request to the peer. Returns the pulled transactions and the peer's new timeline
+/// position, or nothing if the request failed or the peer asked to back off.
+async fn send_pull_request<NetworkClient, TransactionValidator>(
+    smp: &SharedMempool<NetworkClient, TransactionValidator>,
+    peer: PeerNetworkId,
+    request: MempoolSyncMsg,
+    request_label: &'static str,
+) -> Option<(Vec<SignedTransaction>, Option<MultiBucketTimelineIndexIds>)>
+where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+{
+    let network_id = peer.network_id();
+    match smp.network_interface.send_pull_request(peer, request).await {
+        Ok((_, _, true)) => {
+            counters::shared_mempool_pull_request_inc(
+                network_id,
+                counters::SENT_LABEL,
+                request_label,
+                counters::PULL_BACKOFF_LABEL,
+            );
+            None
+        },
+        Ok((transactions, timeline_id, false)) => {
+            counters::shared_mempool_pull_request_inc(
+                network_id,
+                counters::SENT_LABEL,
+                request_label,
+                counters::PULL_SERVED_LABEL,
+            );
+            counters::shared_mempool_pull_response_size(
+                network_id,
+                counters::RECEIVED_LABEL,
+                transactions.len(),
+            );
+            Some((transactions, timeline_id))
+        },
+        Err(error) => {
+            counters::shared_mempool_pull_request_inc(
+                network_id,
+                counters::SENT_LABEL,
+                request_label,
+                counters::PULL_FAILED_LABEL,
+            );
+            warn!(
+                LogSchema::event_log(LogEntry::PullTransactions, LogEvent::NetworkSendFail)
+                    .peer(&peer)
+                    .error(&error.into())
+            );
+            None
+        },
+    }
+}
+
+/// Adds transactions pulled from an upstream peer to the local mempool. Returns false if the
+/// local mempool is full, in which case pulling should stop.
+fn add_pulled_transactions<NetworkClient, TransactionValidator>(
+    smp: &SharedMempool<NetworkClient, TransactionValidator>,
+    peer: PeerNetworkId,
+    transactions: Vec<SignedTransaction>,
+) -> bool
+where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation,
+{
+    let _timer = counters::process_txn_submit_latency_timer(peer.network_id());
+    // As with broadcasts from upstream peers, pulled transactions are not broadcast back.
+    let results =
+        process_incoming_transactions(smp, transactions, TimelineState::NonQualified, false);
+    log_txn_process_results(&results, Some(peer));
+    !results
+        .iter()
+        .any(|(_, (status, _))| status.code == MempoolStatusCode::MempoolIsFull)
+}
+
+// ============================== //
+//  Tasks processing the journal  //
+// ============================== //
+
+/// Adds the transactions read from the mempool journal on startup back to mempool. They go
+/// through the same validation as incoming transactions, which drops the ones committed or
+/// invalidated while the node was down. The journal is compacted afterwards.
+pub(crate) async fn replay_journal<NetworkClient, TransactionValidator>(
+    mut smp: SharedMempool<NetworkClient, TransactionValidator>,
+    journal: Arc<MempoolJournal>,
+    transactions: Vec<SignedTransaction>,
+) where
+    NetworkClient: NetworkClientInterface<MempoolSyncMsg>,
+    TransactionValidator: TransactionValidation + 'static,
+{
+    let now_secs = aptos_infallible::duration_since_epoch().as_secs();
+    let num_journaled = transactions.len();
+    let transactions: Vec<_> = transactions
+        .into_iter()
+        .filter(|txn| txn.expiration_timestamp_secs() > now_secs)
+        .collect();
+    counters::mempool_journal_replayed_txns_inc(
+        counters::JOURNAL_REPLAY_EXPIRED_LABEL,
+        num_journaled - transactions.len(),
+    );
+
+    let ineligible_for_broadcast =
+        smp.network_interface.is_validator() && !smp.broadcast_within_validator_network();
+    let timeline_state = if ineligible_for_broadcast {
+        TimelineState::NonQualified
+    } else {
+        TimelineState::NotReady
+    };
+    // The replayed transactions are already in the journal, don't append them again.
+    smp.journal = None;
+    let mut num_accepted = 0;
+    for batch in transactions.chunks(smp.config.shared_mempool_batch_size.max(1)) {
+        let statuses = process_incoming_transactions(&smp, batch.to_vec(), timeline_state, false);
+        num_accepted += statuses
+            .iter()
+            .filter(|(_, (status, _))| status.code == MempoolStatusCode::Accepted)
+            .count();
+    }
+    counters::mempool_journal_replayed_txns_inc(
+        counters::JOURNAL_REPLAY_ACCEPTED_LABEL,
+        num_accepted,
+    );
+    counters::mempool_journal_replayed_txns_inc(
+        counters::JOURNAL_REPLAY_REJECTED_LABEL,
+        transactions.len() - num_accepted,
+    );
+    info!(
+        LogSchema::event_log(LogEntry::Journal, LogEvent::Success),
+        "Replayed mempool journal: {} journaled, {} unexpired, {} accepted",
+        num_journaled,
+        transactions.len(),
+        num_accepted
+    );
+
+    compact_journal(&smp.mempool, &journal);
+}
+
+/// Rewrites the journal with only the transactions currently in mempool.
+pub(crate) fn compact_journal(mempool: &Mutex<CoreMempool>, journal: &MempoolJournal) {
+    match journal.compact(mempool) {
+        Ok(num_txns) => counters::MEMPOOL_JOURNAL_SIZE.set(num_txns as i64),
+        Err(e) => {
+            counters::mempool_journal_error_inc(counters::JOURNAL_COMPACT_LABEL);
+            error!(LogSchema::new(LogEntry::Journal).error(&e));
+        },
+    }
+}
+
 // ================================= //
 // intra-node communication handlers //
 // ================================= //
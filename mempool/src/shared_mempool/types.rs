@@ -6,6 +6,7 @@
 use crate::{
     core_mempool::CoreMempool,
     network::{MempoolNetworkInterface, MempoolSyncMsg},
+    shared_mempool::journal::MempoolJournal,
 };
 use anyhow::Result;
 use aptos_config::{
@@ -52,6 +53,7 @@ pub(crate) struct SharedMempool<NetworkClient, TransactionValidator> {
     pub validator: Arc<RwLock<TransactionValidator>>,
     pub subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
     pub broadcast_within_validator_network: Arc<RwLock<bool>>,
+    pub journal: Option<Arc<MempoolJournal>>,
 }
 
 impl<
@@ -67,6 +69,7 @@ impl<
         validator: Arc<RwLock<TransactionValidator>>,
         subscribers: Vec<UnboundedSender<SharedMempoolNotification>>,
         role: RoleType,
+        journal: Option<Arc<MempoolJournal>>,
     ) -> Self {
         let network_interface = MempoolNetworkInterface::new(network_client, role, config.clone());
         SharedMempool {
@@ -77,6 +80,7 @@ impl<
             validator,
             subscribers,
             broadcast_within_validator_network: Arc::new(RwLock::new(true)),
+            journal,
         }
     }
 
@@ -232,6 +236,9 @@ pub type SubmissionStatusBundle = (SignedTransaction, SubmissionStatus);
 pub enum MempoolClientRequest {
     SubmitTransaction(SignedTransaction, oneshot::Sender<Result<SubmissionStatus>>),
     GetTransactionByHash(HashValue, oneshot::Sender<Option<SignedTransaction>>),
+    /// Pulls the transactions with the given hashes from upstream peers, if they are missing
+    /// from mempool. Responds with the number of missing transactions that were added.
+    PullTransactionsByHash(Vec<HashValue>, oneshot::Sender<usize>),
 }
 
 pub type MempoolClientSender = mpsc::Sender<MempoolClientRequest>;
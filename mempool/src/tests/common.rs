@@ -15,8 +15,9 @@ use aptos_types::{
     account_address::AccountAddress,
     chain_id::ChainId,
     mempool_status::MempoolStatusCode,
-    transaction::{RawTransaction, Script, SignedTransaction},
+    transaction::{EntryFunction, RawTransaction, Script, SignedTransaction},
 };
+use move_core_types::{ident_str, language_storage::ModuleId};
 use once_cell::sync::Lazy;
 use rand::{rngs::StdRng, SeedableRng};
 use serde::{Deserialize, Serialize};
@@ -81,12 +82,34 @@ impl TestTransaction {
         self.make_signed_transaction_impl(100, u64::MAX)
     }
 
+    /// Makes a transaction calling an entry function of a module published at `module_address`
+    pub(crate) fn make_signed_entry_function_transaction(
+        &self,
+        module_address: AccountAddress,
+    ) -> SignedTransaction {
+        let entry_function = EntryFunction::new(
+            ModuleId::new(module_address, ident_str!("module").to_owned()),
+            ident_str!("function").to_owned(),
+            vec![],
+            vec![],
+        );
+        Self::sign(RawTransaction::new_entry_function(
+            TestTransaction::get_address(self.address),
+            self.sequence_number,
+            entry_function,
+            100,
+            self.gas_price,
+            u64::MAX,
+            ChainId::test(),
+        ))
+    }
+
     fn make_signed_transaction_impl(
         &self,
         max_gas_amount: u64,
         exp_timestamp_secs: u64,
     ) -> SignedTransaction {
-        let raw_txn = RawTransaction::new_script(
+        Self::sign(RawTransaction::new_script(
             TestTransaction::get_address(self.address),
             self.sequence_number,
             Script::new(vec![], vec![], vec![]),
@@ -94,7 +117,10 @@ impl TestTransaction {
             self.gas_price,
             exp_timestamp_secs,
             ChainId::test(),
-        );
+        ))
+    }
+
+    fn sign(raw_txn: RawTransaction) -> SignedTransaction {
         let mut seed: [u8; 32] = [0u8; 32];
         seed[..4].copy_from_slice(&[1, 2, 3, 4]);
         let mut rng: StdRng = StdRng::from_seed(seed);
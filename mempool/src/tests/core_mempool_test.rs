@@ -914,3 +914,89 @@ fn test_include_gas_upgraded() {
     });
     assert_eq!(batch.len(), 0);
 }
+
+#[test]
+fn test_fairness_max_txns_per_sender() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.fairness.max_txns_per_sender = Some(2);
+    let mut pool = CoreMempool::new(&config);
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(0, 1, 1),
+        TestTransaction::new(0, 2, 1),
+        TestTransaction::new(1, 0, 1),
+    ]);
+
+    // The sender's later transactions are left out, other senders are unaffected
+    let batch = pool.get_batch(10, 10240, true, false, btreemap![]);
+    assert_eq!(batch.len(), 3);
+    for txn in [&txns[0], &txns[1], &txns[3]] {
+        assert!(batch.contains(txn));
+    }
+}
+
+#[test]
+fn test_fairness_max_txns_per_module_address() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.fairness.max_txns_per_module_address = Some(2);
+    let mut pool = CoreMempool::new(&config);
+    let hot_module_address = TestTransaction::get_address(3);
+    let other_module_address = TestTransaction::get_address(2);
+    let hot_txns: Vec<_> = [(0, 0), (0, 1), (1, 0)]
+        .iter()
+        .map(|(address, seq)| {
+            TestTransaction::new(*address, *seq, 1)
+                .make_signed_entry_function_transaction(hot_module_address)
+        })
+        .collect();
+    let other_txn =
+        TestTransaction::new(2, 0, 1).make_signed_entry_function_transaction(other_module_address);
+    for txn in hot_txns.iter().chain(std::iter::once(&other_txn)) {
+        add_signed_txn(&mut pool, txn.clone()).unwrap();
+    }
+
+    let batch = pool.get_batch(10, 10240, true, false, btreemap![]);
+    assert_eq!(batch.len(), 3);
+    assert!(batch.contains(&other_txn));
+    assert_eq!(
+        batch.iter().filter(|txn| hot_txns.contains(*txn)).count(),
+        2
+    );
+}
+
+#[test]
+fn test_fairness_round_robin_within_gas_tier() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.fairness.round_robin_weight = Some(2);
+    let mut pool = CoreMempool::new(&config);
+    let mut txns = vec![];
+    for address in 0..2 {
+        for seq in 0..4 {
+            txns.push(TestTransaction::new(address, seq, 1));
+        }
+    }
+    // A higher gas price still takes precedence over taking turns
+    txns.push(TestTransaction::new(2, 0, 5));
+    let txns = add_txns_to_mempool(&mut pool, txns);
+
+    let batch = pool.get_batch(10, 10240, true, false, btreemap![]);
+    assert_eq!(batch.len(), 9);
+    assert_eq!(batch[0], txns[8]);
+    // Senders at the same gas price take turns of two transactions each
+    let turns: Vec<_> = batch[1..]
+        .chunks(2)
+        .map(|turn| {
+            assert_eq!(turn[0].sender(), turn[1].sender());
+            turn[0].sender()
+        })
+        .collect();
+    assert_eq!(turns.len(), 4);
+    for (turn, next_turn) in turns.iter().tuple_windows() {
+        assert_ne!(turn, next_turn);
+    }
+
+    // Smaller batches take turns among only part of a gas tier, but still fill up
+    let batch = pool.get_batch(3, 10240, true, false, btreemap![]);
+    assert_eq!(batch.len(), 3);
+    assert_eq!(batch[0], txns[8]);
+}
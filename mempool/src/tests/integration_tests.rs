@@ -2,11 +2,22 @@
 // Parts of the project are originally copyright © Meta Platforms, Inc.
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::tests::{
-    common::TestTransaction,
-    test_framework::{test_transaction, MempoolNode, MempoolTestFrameworkBuilder},
+use crate::{
+    network::MempoolSyncMsg,
+    shared_mempool::{journal::MempoolJournal, types::MultiBucketTimelineIndexIds},
+    tests::{
+        common::TestTransaction,
+        test_framework::{
+            block_only_contains_transactions, sign_transactions, test_transaction, MempoolNode,
+            MempoolTestFramework, MempoolTestFrameworkBuilder,
+        },
+    },
 };
-use aptos_config::network_id::PeerNetworkId;
+use aptos_config::{
+    config::{transaction_filter_type::Filter, NodeConfig},
+    network_id::{NetworkId, PeerNetworkId},
+};
+use aptos_crypto::HashValue;
 use aptos_netcore::transport::ConnectionOrigin;
 use aptos_network::{
     testutils::{
@@ -19,9 +30,12 @@ use aptos_network::{
     transport::ConnectionMetadata,
     ProtocolId,
 };
-use std::time::Duration;
+use aptos_temppath::TempPath;
+use aptos_types::{mempool_status::MempoolStatusCode, transaction::SignedTransaction, PeerId};
+use std::{collections::HashSet, time::Duration};
 
 const ALL_PROTOCOLS: [ProtocolId; 1] = [ProtocolId::MempoolDirectSend];
+const PULL_PROTOCOLS: [ProtocolId; 2] = [ProtocolId::MempoolDirectSend, ProtocolId::MempoolRpc];
 static ALL_TXNS: &[TestTransaction] = &[test_transaction(0), test_transaction(1)];
 static TXN_1: &[TestTransaction] = &[test_transaction(0)];
 static TXN_2: &[TestTransaction] = &[test_transaction(1)];
@@ -380,3 +394,217 @@ async fn fn_to_val_test() {
         val.assert_only_txns_in_mempool(ALL_TXNS);
     }
 }
+
+/// Tests that transactions denied by the mempool transaction filter are rejected on admission
+#[tokio::test]
+async fn test_transaction_filter() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.transaction_filter =
+        Filter::empty().add_deny_sender(TestTransaction::get_address(1));
+    let peer_network_ids = [PeerNetworkId::new(NetworkId::Validator, PeerId::random())];
+    let mut node =
+        MempoolTestFramework::build_node(NodeId::validator(0), config, &peer_network_ids);
+    let (other_peer_network_id, other_metadata) =
+        validator_mock_connection(ConnectionOrigin::Inbound, &ALL_PROTOCOLS);
+    let allowed_txns = &[TestTransaction::new(2, 0, 1)];
+
+    // Denied transactions are rejected, both from clients and peers
+    node.submit_txns_via_client(TXN_1, MempoolStatusCode::RejectedByFilter)
+        .await;
+    node.connect_self(other_peer_network_id.network_id(), other_metadata);
+    node.receive_message(ProtocolId::MempoolDirectSend, other_peer_network_id, TXN_2)
+        .await;
+    node.assert_txns_not_in_mempool(ALL_TXNS);
+
+    // Other transactions are accepted
+    node.add_txns_via_client(allowed_txns).await;
+    node.assert_only_txns_in_mempool(allowed_txns);
+}
+
+/// Tests that pull requests are served from the timeline and by hash
+#[tokio::test]
+async fn test_serve_pull_requests() {
+    let mut node = MempoolTestFrameworkBuilder::single_vfn();
+    let (other_peer_network_id, other_metadata) =
+        pfn_vfn_mock_connection(ConnectionOrigin::Inbound, &PULL_PROTOCOLS);
+    node.add_txns_via_client(ALL_TXNS).await;
+    node.connect_self(other_peer_network_id.network_id(), other_metadata);
+
+    // A pull from the start of the timeline returns the transactions, up to the max
+    let (transactions, timeline_id) = pull_timeline(
+        &mut node,
+        other_peer_network_id,
+        MultiBucketTimelineIndexIds::new(0),
+        1,
+    )
+    .await;
+    assert!(block_only_contains_transactions(&transactions, TXN_1));
+    let (transactions, timeline_id) =
+        pull_timeline(&mut node, other_peer_network_id, timeline_id, 100).await;
+    assert!(block_only_contains_transactions(&transactions, TXN_2));
+
+    // Pulling after the returned timeline id returns nothing, until there is a new transaction
+    let (transactions, timeline_id) =
+        pull_timeline(&mut node, other_peer_network_id, timeline_id.clone(), 100).await;
+    assert!(transactions.is_empty());
+    let txn_3 = &[test_transaction(2)];
+    node.add_txns_via_client(txn_3).await;
+    let (transactions, _) = pull_timeline(&mut node, other_peer_network_id, timeline_id, 100).await;
+    assert!(block_only_contains_transactions(&transactions, txn_3));
+
+    // Pulls by hash only return the transactions in mempool
+    let hashes = vec![
+        transactions[0].clone().committed_hash(),
+        HashValue::random(),
+    ];
+    let response = node
+        .receive_pull_request(
+            other_peer_network_id,
+            MempoolSyncMsg::PullTransactionsByHashRequest { hashes },
+        )
+        .await;
+    match response {
+        MempoolSyncMsg::PullTransactionsResponse {
+            transactions,
+            timeline_id,
+            backoff,
+        } => {
+            assert!(block_only_contains_transactions(&transactions, txn_3));
+            assert!(timeline_id.is_none());
+            assert!(!backoff);
+        },
+        response => panic!("Expected a pull response, got {:?}", response),
+    }
+}
+
+/// Tests that peers over the limit of concurrent pull requests are asked to back off
+#[tokio::test]
+async fn test_pull_request_backoff() {
+    let mut config = NodeConfig::generate_random_config();
+    config.mempool.shared_mempool_max_concurrent_pulls_per_peer = 0;
+    let peer_network_ids = [PeerNetworkId::new(NetworkId::Validator, PeerId::random())];
+    let mut node =
+        MempoolTestFramework::build_node(NodeId::validator(0), config, &peer_network_ids);
+    let (other_peer_network_id, other_metadata) =
+        validator_mock_connection(ConnectionOrigin::Inbound, &PULL_PROTOCOLS);
+    node.add_txns_via_client(ALL_TXNS).await;
+    node.connect_self(other_peer_network_id.network_id(), other_metadata);
+
+    let response = node
+        .receive_pull_request(
+            other_peer_network_id,
+            MempoolSyncMsg::PullTransactionsRequest {
+                timeline_id: MultiBucketTimelineIndexIds::new(0),
+                max_txns: 100,
+            },
+        )
+        .await;
+    match response {
+        MempoolSyncMsg::PullTransactionsResponse {
+            transactions,
+            backoff,
+            ..
+        } => {
+            assert!(transactions.is_empty());
+            assert!(backoff);
+        },
+        response => panic!("Expected a pull response, got {:?}", response),
+    }
+}
+
+/// Tests that a fullnode catches up on the pending transactions of a new upstream peer
+#[tokio::test]
+async fn test_pull_on_connect() {
+    let mut config = NodeConfig::get_default_pfn_config();
+    config.mempool.shared_mempool_pull_enabled = true;
+    let peer_network_ids = [PeerNetworkId::new(NetworkId::Public, PeerId::random())];
+    let mut node = MempoolTestFramework::build_node(NodeId::pfn(0), config, &peer_network_ids);
+    let (other_peer_network_id, other_metadata) =
+        pfn_vfn_mock_connection(ConnectionOrigin::Outbound, &PULL_PROTOCOLS);
+    node.connect_self(other_peer_network_id.network_id(), other_metadata);
+
+    // The first pull starts from the beginning of the peer's timeline, the next ones continue
+    // from the returned timeline id until nothing is returned
+    let timeline_id = node
+        .respond_to_pull_request(other_peer_network_id, TXN_1, vec![1].into())
+        .await;
+    assert!(timeline_id.id_per_bucket.is_empty());
+    let timeline_id = node
+        .respond_to_pull_request(other_peer_network_id, TXN_2, vec![2].into())
+        .await;
+    assert_eq!(timeline_id, MultiBucketTimelineIndexIds::from(vec![1]));
+    let timeline_id = node
+        .respond_to_pull_request(other_peer_network_id, &[], vec![2].into())
+        .await;
+    assert_eq!(timeline_id, MultiBucketTimelineIndexIds::from(vec![2]));
+
+    node.wait_on_txns_in_mempool(ALL_TXNS).await;
+    node.assert_only_txns_in_mempool(ALL_TXNS);
+
+    // No more pulls are sent
+    node.wait_for_no_msg(
+        other_peer_network_id.network_id(),
+        Duration::from_millis(100),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn test_journal_replay_on_restart() {
+    let journal_path = TempPath::new();
+    let mut config = NodeConfig::get_default_pfn_config();
+    config.mempool.journal_path = Some(journal_path.path().to_path_buf());
+    let peer_network_ids = [PeerNetworkId::new(NetworkId::Public, PeerId::random())];
+
+    // Journal a transaction that expires before the node starts
+    let expired_txn = TestTransaction::new(1, 2, 1).make_signed_transaction_with_expiration_time(0);
+    let (journal, _) = MempoolJournal::open(journal_path.path()).unwrap();
+    journal
+        .append(&[sign_transactions(TXN_1).remove(0), expired_txn])
+        .unwrap();
+    drop(journal);
+
+    // The unexpired transaction is replayed on startup
+    let mut node =
+        MempoolTestFramework::build_node(NodeId::pfn(0), config.clone(), &peer_network_ids);
+    node.wait_on_txns_in_mempool(TXN_1).await;
+    node.assert_only_txns_in_mempool(TXN_1);
+
+    // Transactions accepted after startup are journaled as well
+    node.add_txns_via_client(TXN_2).await;
+
+    // Replayed transactions are not journaled again
+    let (_, journaled) = MempoolJournal::open(journal_path.path()).unwrap();
+    let journaled_hashes: HashSet<_> = journaled.iter().map(|txn| txn.committed_hash()).collect();
+    assert_eq!(journaled_hashes.len(), journaled.len());
+    for txn in sign_transactions(ALL_TXNS) {
+        assert!(journaled_hashes.contains(&txn.committed_hash()));
+    }
+    let restarted_node =
+        MempoolTestFramework::build_node(NodeId::pfn(1), config, &peer_network_ids);
+    restarted_node.wait_on_txns_in_mempool(ALL_TXNS).await;
+    restarted_node.assert_only_txns_in_mempool(ALL_TXNS);
+}
+
+/// Sends a pull request for the timeline and returns the transactions and the new timeline id
+async fn pull_timeline(
+    node: &mut MempoolNode,
+    peer_network_id: PeerNetworkId,
+    timeline_id: MultiBucketTimelineIndexIds,
+    max_txns: u64,
+) -> (Vec<SignedTransaction>, MultiBucketTimelineIndexIds) {
+    let response = node
+        .receive_pull_request(peer_network_id, MempoolSyncMsg::PullTransactionsRequest {
+            timeline_id,
+            max_txns,
+        })
+        .await;
+    match response {
+        MempoolSyncMsg::PullTransactionsResponse {
+            transactions,
+            timeline_id: Some(timeline_id),
+            backoff: false,
+        } => (transactions, timeline_id),
+        response => panic!("Expected a pull response, got {:?}", response),
+    }
+}
@@ -0,0 +1,85 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    shared_mempool::journal::MempoolJournal,
+    tests::common::{add_txns_to_mempool, setup_mempool, TestTransaction},
+};
+use aptos_infallible::Mutex;
+use aptos_temppath::TempPath;
+use std::fs::{self, OpenOptions};
+
+#[test]
+fn test_append_and_reopen() {
+    let path = TempPath::new();
+    let txns: Vec<_> = (0..3)
+        .map(|seq| TestTransaction::new(0, seq, 1).make_signed_transaction())
+        .collect();
+
+    let (journal, replayed) = MempoolJournal::open(path.path()).unwrap();
+    assert!(replayed.is_empty());
+    journal.append(&txns[..2]).unwrap();
+    journal.append(&txns[2..]).unwrap();
+    drop(journal);
+
+    let (_journal, replayed) = MempoolJournal::open(path.path()).unwrap();
+    assert_eq!(replayed, txns);
+}
+
+#[test]
+fn test_torn_record_is_truncated() {
+    let path = TempPath::new();
+    let txns: Vec<_> = (0..2)
+        .map(|seq| TestTransaction::new(0, seq, 1).make_signed_transaction())
+        .collect();
+
+    let (journal, _) = MempoolJournal::open(path.path()).unwrap();
+    journal.append(&txns).unwrap();
+    drop(journal);
+
+    // Simulate a crash in the middle of appending the last record.
+    let len = fs::metadata(path.path()).unwrap().len();
+    OpenOptions::new()
+        .write(true)
+        .open(path.path())
+        .unwrap()
+        .set_len(len - 1)
+        .unwrap();
+
+    // The torn record is dropped, and records appended afterwards are still readable.
+    let (journal, replayed) = MempoolJournal::open(path.path()).unwrap();
+    assert_eq!(replayed, txns[..1].to_vec());
+    journal.append(&txns[1..]).unwrap();
+    drop(journal);
+
+    let (_journal, replayed) = MempoolJournal::open(path.path()).unwrap();
+    assert_eq!(replayed, txns);
+}
+
+#[test]
+fn test_compact() {
+    let path = TempPath::new();
+    let (mut pool, _) = setup_mempool();
+    let txns = add_txns_to_mempool(&mut pool, vec![
+        TestTransaction::new(0, 0, 1),
+        TestTransaction::new(1, 0, 1),
+    ]);
+    let stale_txn = TestTransaction::new(2, 0, 1).make_signed_transaction();
+    let mempool = Mutex::new(pool);
+
+    let (journal, _) = MempoolJournal::open(path.path()).unwrap();
+    journal.append(&txns).unwrap();
+    journal.append(&[stale_txn]).unwrap();
+    assert_eq!(journal.compact(&mempool).unwrap(), 2);
+
+    // Appends after compaction go to the compacted journal.
+    let new_txn = TestTransaction::new(0, 1, 1).make_signed_transaction();
+    journal.append(&[new_txn.clone()]).unwrap();
+    drop(journal);
+
+    let (_journal, replayed) = MempoolJournal::open(path.path()).unwrap();
+    assert_eq!(replayed.len(), 3);
+    for txn in txns.iter().chain(std::iter::once(&new_txn)) {
+        assert!(replayed.contains(txn));
+    }
+}
@@ -4,7 +4,10 @@
 
 use crate::{
     core_mempool::CoreMempool,
-    shared_mempool::{start_shared_mempool, types::MultiBatchId},
+    shared_mempool::{
+        start_shared_mempool,
+        types::{MultiBatchId, MultiBucketTimelineIndexIds},
+    },
     tests::{common, common::TestTransaction},
     MempoolClientRequest, MempoolClientSender, MempoolSyncMsg, QuorumStoreRequest,
 };
@@ -30,7 +33,7 @@ use aptos_network::{
         direct_send::Message,
         network::{NetworkEvents, NetworkSender, NewNetworkEvents, NewNetworkSender},
         rpc::InboundRpcRequest,
-        wire::handshake::v1::ProtocolId::MempoolDirectSend,
+        wire::handshake::v1::ProtocolId::{MempoolDirectSend, MempoolRpc},
     },
     testutils::{
         builder::TestFrameworkBuilder,
@@ -145,6 +148,16 @@ impl ApplicationNode for MempoolNode {
 impl MempoolNode {
     /// Queues transactions for sending on a node, uses client
     pub async fn add_txns_via_client(&mut self, txns: &[TestTransaction]) {
+        self.submit_txns_via_client(txns, MempoolStatusCode::Accepted)
+            .await
+    }
+
+    /// Submits transactions via client, and checks they all get the expected status
+    pub async fn submit_txns_via_client(
+        &mut self,
+        txns: &[TestTransaction],
+        expected_status: MempoolStatusCode,
+    ) {
         for txn in sign_transactions(txns) {
             let (sender, receiver) = oneshot::channel();
 
@@ -153,7 +166,7 @@ impl MempoolNode {
                 .await
                 .unwrap();
             let status = receiver.await.unwrap().unwrap();
-            assert_eq!(status.0.code, MempoolStatusCode::Accepted)
+            assert_eq!(status.0.code, expected_status)
         }
     }
 
@@ -311,6 +324,64 @@ impl MempoolNode {
         }
     }
 
+    /// Sends a pull request from the remote peer over RPC and returns the response
+    pub async fn receive_pull_request(
+        &mut self,
+        remote_peer_network_id: PeerNetworkId,
+        request: MempoolSyncMsg,
+    ) -> MempoolSyncMsg {
+        let remote_peer_id = remote_peer_network_id.peer_id();
+        let inbound_handle = self.get_inbound_handle(remote_peer_network_id.network_id());
+        let (res_tx, res_rx) = oneshot::channel();
+        let notif = PeerManagerNotification::RecvRpc(remote_peer_id, InboundRpcRequest {
+            protocol_id: MempoolRpc,
+            data: MempoolRpc.to_bytes(&request).unwrap().into(),
+            res_tx,
+        });
+        inbound_handle
+            .inbound_message_sender
+            .push((remote_peer_id, MempoolRpc), notif)
+            .unwrap();
+
+        let response = res_rx.await.unwrap().unwrap();
+        MempoolRpc.from_bytes(&response).unwrap()
+    }
+
+    /// Waits for a pull request to the expected peer and responds with the given transactions
+    /// and timeline id. Returns the timeline id the request was sent with.
+    pub async fn respond_to_pull_request(
+        &mut self,
+        expected_peer_network_id: PeerNetworkId,
+        txns: &[TestTransaction],
+        timeline_id: MultiBucketTimelineIndexIds,
+    ) -> MultiBucketTimelineIndexIds {
+        let message = self
+            .get_next_network_msg(expected_peer_network_id.network_id())
+            .await;
+        let (peer_id, rpc_request) = match message {
+            PeerManagerRequest::SendRpc(peer_id, rpc_request) => (peer_id, rpc_request),
+            message => panic!("Expected a pull request, got {:?}", message),
+        };
+        assert_eq!(peer_id, expected_peer_network_id.peer_id());
+        let request_timeline_id = match rpc_request
+            .protocol_id
+            .from_bytes::<MempoolSyncMsg>(&rpc_request.data)
+            .unwrap()
+        {
+            MempoolSyncMsg::PullTransactionsRequest { timeline_id, .. } => timeline_id,
+            message => panic!("Expected a pull request, got {:?}", message),
+        };
+
+        let response = MempoolSyncMsg::PullTransactionsResponse {
+            transactions: sign_transactions(txns),
+            timeline_id: Some(timeline_id),
+            backoff: false,
+        };
+        let bytes = rpc_request.protocol_id.to_bytes(&response).unwrap();
+        rpc_request.res_tx.send(Ok(bytes.into())).unwrap();
+        request_timeline_id
+    }
+
     pub async fn send_broadcast_and_receive_ack(
         &mut self,
         expected_peer_network_id: PeerNetworkId,
@@ -389,8 +460,11 @@ impl MempoolNode {
                 }
                 request_id
             },
-            MempoolSyncMsg::BroadcastTransactionsResponse { .. } => {
-                panic!("We aren't supposed to be getting as response here");
+            message => {
+                panic!(
+                    "We aren't supposed to be getting {:?} here, only broadcasts",
+                    message
+                );
             },
         };
         let response = MempoolSyncMsg::BroadcastTransactionsResponse {
@@ -513,7 +587,7 @@ pub fn setup_node_networks(
     // Create a network client and service events
     let network_client = NetworkClient::new(
         vec![MempoolDirectSend],
-        vec![],
+        vec![MempoolRpc],
         network_senders,
         peers_and_metadata.clone(),
     );
@@ -53,12 +53,13 @@ pub enum ProtocolId {
     ConsensusDirectSendJson = 6, // Json provides flexibility for backwards compatible upgrade
     ConsensusRpcJson = 7,
     StorageServiceRpc = 8,
-    MempoolRpc = 9, // Currently unused
+    MempoolRpc = 9,
     PeerMonitoringServiceRpc = 10,
     ConsensusRpcCompressed = 11,
     ConsensusDirectSendCompressed = 12,
     NetbenchDirectSend = 13,
     NetbenchRpc = 14,
+    StorageServiceRpcZstd = 15, // The peer also understands zstd compressed storage responses
 }
 
 /// The encoding types for Protocols
@@ -87,6 +88,7 @@ impl ProtocolId {
             ConsensusDirectSendCompressed => "ConsensusDirectSendCompressed",
             NetbenchDirectSend => "NetbenchDirectSend",
             NetbenchRpc => "NetbenchRpc",
+            StorageServiceRpcZstd => "StorageServiceRpcZstd",
         }
     }
 
@@ -108,6 +110,7 @@ impl ProtocolId {
             ProtocolId::ConsensusDirectSendCompressed,
             ProtocolId::NetbenchDirectSend,
             ProtocolId::NetbenchRpc,
+            ProtocolId::StorageServiceRpcZstd,
         ]
     }
 
@@ -13,6 +13,8 @@ use crate::{
     metrics::{
         increment_request_counter, set_gauge, start_request_timer, PRIORITIZED_PEER, REGULAR_PEER,
     },
+    peer_reputations,
+    peer_reputations::PEER_REPUTATIONS_FILE_NAME,
     peer_states::{ErrorType, PeerStates},
     poller::DataSummaryPoller,
     utils,
@@ -43,7 +45,7 @@ use aptos_storage_service_types::{
     responses::{StorageServerSummary, StorageServiceResponse, TransactionOrOutputListWithProof},
     Epoch, StorageServiceMessage,
 };
-use aptos_time_service::TimeService;
+use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
@@ -53,7 +55,7 @@ use aptos_types::{
 use arc_swap::ArcSwap;
 use async_trait::async_trait;
 use maplit::hashset;
-use std::{collections::HashSet, fmt, ops::Deref, sync::Arc, time::Duration};
+use std::{collections::HashSet, fmt, ops::Deref, path::PathBuf, sync::Arc, time::Duration};
 use tokio::runtime::Handle;
 
 // Useful constants
@@ -122,6 +124,9 @@ impl AptosDataClient {
             time_service: time_service.clone(),
         };
 
+        // Restore the peer reputations persisted before the last shutdown
+        data_client.restore_peer_reputations();
+
         // Create the data summary poller
         let data_summary_poller = DataSummaryPoller::new(
             data_client_config,
@@ -155,6 +160,71 @@ impl AptosDataClient {
         self.peer_states.update_summary(peer, summary)
     }
 
+    /// Returns the path of the file holding the persisted peer reputations
+    /// (if peer reputation persistence is enabled).
+    fn get_peer_reputations_path(&self) -> Option<PathBuf> {
+        if self.data_client_config.enable_peer_reputation_persistence {
+            Some(self.base_config.data_dir.join(PEER_REPUTATIONS_FILE_NAME))
+        } else {
+            None
+        }
+    }
+
+    /// Restores the persisted peer reputations (if any)
+    fn restore_peer_reputations(&self) {
+        let peer_reputations_path = match self.get_peer_reputations_path() {
+            Some(peer_reputations_path) => peer_reputations_path,
+            None => return, // Peer reputation persistence is disabled
+        };
+
+        match peer_reputations::load_peer_reputations(&peer_reputations_path) {
+            Ok((peer_reputations, persisted_at)) => {
+                info!(
+                    (LogSchema::new(LogEntry::PeerStates)
+                        .event(LogEvent::PeerReputationsUpdate)
+                        .message(&format!(
+                            "Restoring {} persisted peer reputations!",
+                            peer_reputations.len()
+                        )))
+                );
+
+                // Decay the scores by the time elapsed since they were persisted
+                let elapsed = self
+                    .time_service
+                    .now_unix_time()
+                    .saturating_sub(persisted_at);
+                self.peer_states
+                    .restore_peer_reputations(peer_reputations, elapsed);
+            },
+            Err(error) => {
+                warn!(
+                    (LogSchema::new(LogEntry::PeerStates)
+                        .event(LogEvent::PeerReputationsUpdate)
+                        .message("Unable to restore the persisted peer reputations!")
+                        .error(&error))
+                );
+            },
+        }
+    }
+
+    /// Decays the peer scores by the elapsed time and persists
+    /// the peer reputations (if persistence is enabled).
+    pub fn update_peer_reputations(&self, elapsed: Duration) -> crate::error::Result<(), Error> {
+        // Decay the peer scores
+        self.peer_states.decay_peer_scores(elapsed);
+
+        // Persist the peer reputations
+        if let Some(peer_reputations_path) = self.get_peer_reputations_path() {
+            peer_reputations::persist_peer_reputations(
+                &peer_reputations_path,
+                self.peer_states.get_peer_reputations(),
+                self.time_service.now_unix_time(),
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Recompute and update the global data summary cache
     pub fn update_global_summary_cache(&self) -> crate::error::Result<(), Error> {
         // Before calculating the summary, we should garbage collect
@@ -365,6 +435,11 @@ impl AptosDataClient {
         let mut priority_peers = hashset![];
         let mut regular_peers = hashset![];
         for peer in all_connected_peers {
+            // Ignore peers that have been banned
+            if self.peer_states.is_banned(&peer) {
+                continue;
+            }
+
             if utils::is_priority_peer(
                 self.base_config.clone(),
                 self.get_peers_and_metadata(),
@@ -474,6 +549,7 @@ impl AptosDataClient {
         self.update_sent_request_metrics(peer, &request);
 
         // Send the request and process the result
+        let request_start_time = self.time_service.now();
         let result = self
             .storage_service_client
             .send_request(
@@ -492,8 +568,12 @@ impl AptosDataClient {
                         .peer(&peer))
                 );
 
-                // Update the received response metrics
+                // Update the received response metrics and the peer's latencies
                 self.update_received_response_metrics(peer, &request);
+                self.peer_states.record_response_latency(
+                    peer,
+                    self.time_service.now().duration_since(request_start_time),
+                );
 
                 // For now, record all responses that at least pass the data
                 // client layer successfully. An alternative might also have the
@@ -54,11 +54,14 @@ pub enum LogEvent {
     AggregateSummary,
     CaughtUpToLatest,
     NoPeersToPoll,
+    PeerBanned,
     PeerIgnored,
     PeerNoLongerIgnored,
     PeerPollingError,
+    PeerReputationsUpdate,
     PeerRequestResponseCounts,
     PeerSelectionError,
+    PeerUnbanned,
     PriorityAndRegularPeers,
     ResponseError,
     ResponseSuccess,
@@ -0,0 +1,102 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::error::Error;
+use aptos_config::network_id::PeerNetworkId;
+use serde::{Deserialize, Serialize};
+use std::{fs, path::Path, time::Duration};
+
+/// The name of the file (in the data directory) holding the peer reputations
+pub const PEER_REPUTATIONS_FILE_NAME: &str = "data_client_peer_reputations.json";
+
+/// The reputation of a peer. Unlike the rest of the peer state, reputations
+/// outlive peer connections and are persisted across node restarts.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PeerReputation {
+    /// The peer the reputation belongs to
+    pub peer: PeerNetworkId,
+    /// The score of the peer (see `peer_states.rs`)
+    pub score: f64,
+    /// The latencies (in milliseconds) of the most recent responses from the peer
+    pub recent_latencies_ms: Vec<u64>,
+    /// The reason the peer was manually banned by an operator (if any)
+    pub ban_reason: Option<String>,
+}
+
+/// The on-disk representation of all peer reputations
+#[derive(Debug, Deserialize, Serialize)]
+struct PersistedPeerReputations {
+    /// The unix time (in seconds) at which the reputations were persisted
+    persisted_at_secs: u64,
+    /// The reputations of all known peers
+    peer_reputations: Vec<PeerReputation>,
+}
+
+/// Returns the reputations persisted at the given path, and the time (since the
+/// unix epoch) at which they were persisted. If no reputations have been persisted
+/// yet, no reputations are returned.
+pub fn load_peer_reputations(path: &Path) -> Result<(Vec<PeerReputation>, Duration), Error> {
+    if !path.exists() {
+        return Ok((vec![], Duration::ZERO));
+    }
+
+    let bytes = fs::read(path).map_err(|error| {
+        Error::UnexpectedErrorEncountered(format!(
+            "Failed to read the peer reputations file {:?}: {:?}",
+            path, error
+        ))
+    })?;
+    let persisted_reputations: PersistedPeerReputations =
+        serde_json::from_slice(&bytes).map_err(|error| {
+            Error::UnexpectedErrorEncountered(format!(
+                "Failed to deserialize the peer reputations file {:?}: {:?}",
+                path, error
+            ))
+        })?;
+
+    Ok((
+        persisted_reputations.peer_reputations,
+        Duration::from_secs(persisted_reputations.persisted_at_secs),
+    ))
+}
+
+/// Persists the given reputations at the given path. The reputations are written
+/// to a temporary file first, so that a crash never leaves a partially written file.
+pub fn persist_peer_reputations(
+    path: &Path,
+    peer_reputations: Vec<PeerReputation>,
+    now_unix_time: Duration,
+) -> Result<(), Error> {
+    let persisted_reputations = PersistedPeerReputations {
+        persisted_at_secs: now_unix_time.as_secs(),
+        peer_reputations,
+    };
+    let bytes = serde_json::to_vec_pretty(&persisted_reputations).map_err(|error| {
+        Error::UnexpectedErrorEncountered(format!(
+            "Failed to serialize the peer reputations: {:?}",
+            error
+        ))
+    })?;
+
+    let tmp_path = path.with_extension("tmp");
+    fs::write(&tmp_path, bytes)
+        .and_then(|_| fs::rename(&tmp_path, path))
+        .map_err(|error| {
+            Error::UnexpectedErrorEncountered(format!(
+                "Failed to write the peer reputations file {:?}: {:?}",
+                path, error
+            ))
+        })
+}
+
+/// Decays the given score towards the target score, halving the distance
+/// between them every `half_life_secs`. If the half-life is 0, the score
+/// is returned as is.
+pub fn decay_score(score: f64, target_score: f64, elapsed: Duration, half_life_secs: u64) -> f64 {
+    if half_life_secs == 0 {
+        return score;
+    }
+
+    let num_half_lives = elapsed.as_secs_f64() / half_life_secs as f64;
+    target_score + (score - target_score) * 0.5_f64.powf(num_half_lives)
+}
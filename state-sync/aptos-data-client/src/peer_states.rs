@@ -6,6 +6,7 @@ use crate::{
     interface::ResponseError,
     logging::{LogEntry, LogEvent, LogSchema},
     metrics,
+    peer_reputations::{decay_score, PeerReputation},
 };
 use aptos_config::{config::AptosDataClientConfig, network_id::PeerNetworkId};
 use aptos_logger::prelude::*;
@@ -16,7 +17,7 @@ use aptos_time_service::TimeService;
 use dashmap::DashMap;
 use std::{
     cmp::min,
-    collections::{BTreeMap, HashSet},
+    collections::{BTreeMap, HashSet, VecDeque},
     sync::Arc,
     time::Duration,
 };
@@ -25,6 +26,7 @@ use std::{
 const LOGS_FREQUENCY_SECS: u64 = 120; // 2 minutes
 const METRICS_FREQUENCY_SECS: u64 = 15; // 15 seconds
 const NUM_PEER_BUCKETS_FOR_METRICS: u8 = 4; // To avoid metric explosion, we bucket peers into groups
+const MAX_NUM_RECENT_LATENCIES: usize = 20; // The number of response latencies tracked per peer
 
 /// Scores for peer rankings based on preferences and behavior.
 const MAX_SCORE: f64 = 100.0;
@@ -38,6 +40,9 @@ const NOT_USEFUL_MULTIPLIER: f64 = 0.95;
 const MALICIOUS_MULTIPLIER: f64 = 0.8;
 /// Ignore a peer when their score dips below this threshold.
 const IGNORE_PEER_THRESHOLD: f64 = 25.0;
+/// Forget the reputation of a disconnected peer once its score has decayed
+/// to within this distance of the starting score.
+const FORGET_REPUTATION_THRESHOLD: f64 = 1.0;
 
 pub enum ErrorType {
     /// A response or error that's not actively malicious but also doesn't help
@@ -70,6 +75,8 @@ pub struct PeerState {
     storage_summary: Option<StorageServerSummary>,
     /// For now, a simplified port of the original state-sync v1 scoring system.
     score: f64,
+    /// The latencies (in milliseconds) of the most recent responses from this peer
+    recent_latencies_ms: VecDeque<u64>,
 }
 
 impl Default for PeerState {
@@ -79,6 +86,7 @@ impl Default for PeerState {
             sent_requests_by_type: Arc::new(DashMap::new()),
             storage_summary: None,
             score: STARTING_SCORE,
+            recent_latencies_ms: VecDeque::new(),
         }
     }
 }
@@ -105,6 +113,11 @@ impl PeerState {
         self.score
     }
 
+    /// Returns the latencies (in milliseconds) of the most recent responses from the peer
+    pub fn get_recent_latencies_ms(&self) -> Vec<u64> {
+        self.recent_latencies_ms.iter().copied().collect()
+    }
+
     /// Returns the storage summary for the peer
     pub fn get_storage_summary(&self) -> Option<StorageServerSummary> {
         self.storage_summary.clone()
@@ -156,6 +169,24 @@ impl PeerState {
     fn update_storage_summary(&mut self, storage_summary: StorageServerSummary) {
         self.storage_summary = Some(storage_summary);
     }
+
+    /// Records the latency of a response from the peer
+    fn record_response_latency(&mut self, latency: Duration) {
+        if self.recent_latencies_ms.len() >= MAX_NUM_RECENT_LATENCIES {
+            self.recent_latencies_ms.pop_front();
+        }
+        self.recent_latencies_ms
+            .push_back(latency.as_millis() as u64);
+    }
+
+    /// Restores the score and latencies of the peer from its reputation
+    fn restore_reputation(&mut self, peer_reputation: PeerReputation) {
+        self.score = peer_reputation.score.clamp(MIN_SCORE, MAX_SCORE);
+        self.recent_latencies_ms = peer_reputation.recent_latencies_ms.into_iter().collect();
+        while self.recent_latencies_ms.len() > MAX_NUM_RECENT_LATENCIES {
+            self.recent_latencies_ms.pop_front();
+        }
+    }
 }
 
 /// Contains all of the unbanned peers' most recent [`StorageServerSummary`] data
@@ -164,6 +195,10 @@ impl PeerState {
 pub struct PeerStates {
     data_client_config: Arc<AptosDataClientConfig>,
     peer_to_state: Arc<DashMap<PeerNetworkId, PeerState>>,
+    /// The reputations of known peers that are not currently connected
+    disconnected_peer_reputations: Arc<DashMap<PeerNetworkId, PeerReputation>>,
+    /// The peers manually banned by an operator (and the reasons for the bans)
+    banned_peers: Arc<DashMap<PeerNetworkId, String>>,
 }
 
 impl PeerStates {
@@ -171,6 +206,8 @@ impl PeerStates {
         Self {
             data_client_config,
             peer_to_state: Arc::new(DashMap::new()),
+            disconnected_peer_reputations: Arc::new(DashMap::new()),
+            banned_peers: Arc::new(DashMap::new()),
         }
     }
 
@@ -182,6 +219,11 @@ impl PeerStates {
         time_service: TimeService,
         request: &StorageServiceRequest,
     ) -> bool {
+        // Banned peers cannot service any requests
+        if self.is_banned(peer) {
+            return false;
+        }
+
         // Storage services can always respond to data advertisement requests.
         // We need this outer check, since we need to be able to send data summary
         // requests to new peers (who don't have a peer state yet).
@@ -294,18 +336,146 @@ impl PeerStates {
         }
     }
 
+    /// Records the latency of a response from the given peer
+    pub fn record_response_latency(&self, peer: PeerNetworkId, latency: Duration) {
+        if let Some(mut entry) = self.peer_to_state.get_mut(&peer) {
+            entry.record_response_latency(latency);
+        }
+    }
+
     /// Updates the storage summary for the given peer
     pub fn update_summary(&self, peer: PeerNetworkId, storage_summary: StorageServerSummary) {
         self.peer_to_state
             .entry(peer)
-            .or_default()
+            .or_insert_with(|| {
+                // Restore the reputation of the peer (if we know it)
+                let mut peer_state = PeerState::default();
+                if let Some((_, peer_reputation)) = self.disconnected_peer_reputations.remove(&peer)
+                {
+                    peer_state.restore_reputation(peer_reputation);
+                }
+                peer_state
+            })
             .update_storage_summary(storage_summary);
     }
 
-    /// Garbage collects the peer states to remove data for disconnected peers
+    /// Garbage collects the peer states to remove data for disconnected peers.
+    /// The reputations of the disconnected peers are kept.
     pub fn garbage_collect_peer_states(&self, connected_peers: HashSet<PeerNetworkId>) {
-        self.peer_to_state
-            .retain(|peer_network_id, _| connected_peers.contains(peer_network_id));
+        self.peer_to_state.retain(|peer_network_id, peer_state| {
+            let connected = connected_peers.contains(peer_network_id);
+            if !connected {
+                self.disconnected_peer_reputations.insert(
+                    *peer_network_id,
+                    create_peer_reputation(*peer_network_id, peer_state),
+                );
+            }
+            connected
+        });
+    }
+
+    /// Bans the given peer for the specified reason. Banned peers are
+    /// never sent requests and their data advertisements are ignored.
+    pub fn ban_peer(&self, peer: PeerNetworkId, ban_reason: String) {
+        warn!(
+            (LogSchema::new(LogEntry::PeerStates)
+                .event(LogEvent::PeerBanned)
+                .message(&format!("Peer has been banned! Reason: {}", ban_reason))
+                .peer(&peer))
+        );
+        self.banned_peers.insert(peer, ban_reason);
+    }
+
+    /// Unbans the given peer. Returns true iff the peer was banned.
+    pub fn unban_peer(&self, peer: &PeerNetworkId) -> bool {
+        let was_banned = self.banned_peers.remove(peer).is_some();
+        if was_banned {
+            info!(
+                (LogSchema::new(LogEntry::PeerStates)
+                    .event(LogEvent::PeerUnbanned)
+                    .message("Peer has been unbanned!")
+                    .peer(peer))
+            );
+        }
+        was_banned
+    }
+
+    /// Returns true iff the given peer is banned
+    pub fn is_banned(&self, peer: &PeerNetworkId) -> bool {
+        self.banned_peers.contains_key(peer)
+    }
+
+    /// Restores the given peer reputations (e.g., those persisted before a
+    /// restart). The scores are first decayed by the elapsed time.
+    pub fn restore_peer_reputations(
+        &self,
+        peer_reputations: Vec<PeerReputation>,
+        elapsed: Duration,
+    ) {
+        for mut peer_reputation in peer_reputations {
+            let peer = peer_reputation.peer;
+            if let Some(ban_reason) = peer_reputation.ban_reason.take() {
+                self.banned_peers.insert(peer, ban_reason);
+            }
+            peer_reputation.score = self.decay_score(peer_reputation.score, elapsed);
+
+            // Restore the reputation directly if the peer is already connected
+            if let Some(mut peer_state) = self.peer_to_state.get_mut(&peer) {
+                peer_state.restore_reputation(peer_reputation);
+            } else {
+                self.disconnected_peer_reputations
+                    .insert(peer, peer_reputation);
+            }
+        }
+    }
+
+    /// Decays the scores of all peers towards the starting score. The
+    /// reputations of disconnected peers are forgotten once they are
+    /// close enough to the starting score.
+    pub fn decay_peer_scores(&self, elapsed: Duration) {
+        for mut peer_state in self.peer_to_state.iter_mut() {
+            peer_state.score = self.decay_score(peer_state.score, elapsed);
+        }
+
+        self.disconnected_peer_reputations
+            .retain(|_, peer_reputation| {
+                peer_reputation.score = self.decay_score(peer_reputation.score, elapsed);
+                (peer_reputation.score - STARTING_SCORE).abs() > FORGET_REPUTATION_THRESHOLD
+            });
+    }
+
+    /// Returns the reputations of all known (and banned) peers, sorted by peer
+    pub fn get_peer_reputations(&self) -> Vec<PeerReputation> {
+        let mut peer_reputations: BTreeMap<PeerNetworkId, PeerReputation> = BTreeMap::new();
+        for entry in self.disconnected_peer_reputations.iter() {
+            peer_reputations.insert(*entry.key(), entry.value().clone());
+        }
+        for entry in self.peer_to_state.iter() {
+            let peer = *entry.key();
+            peer_reputations.insert(peer, create_peer_reputation(peer, entry.value()));
+        }
+
+        // Add the ban reasons (banned peers might not be known otherwise)
+        for entry in self.banned_peers.iter() {
+            let peer = *entry.key();
+            let ban_reason = Some(entry.value().clone());
+            peer_reputations
+                .entry(peer)
+                .or_insert_with(|| create_peer_reputation(peer, &PeerState::default()))
+                .ban_reason = ban_reason;
+        }
+
+        peer_reputations.into_values().collect()
+    }
+
+    /// Decays the given score by the elapsed time
+    fn decay_score(&self, score: f64, elapsed: Duration) -> f64 {
+        decay_score(
+            score,
+            STARTING_SCORE,
+            elapsed,
+            self.data_client_config.peer_score_decay_half_life_secs,
+        )
     }
 
     /// Calculates a global data summary using all known storage summaries
@@ -314,6 +484,7 @@ impl PeerStates {
         let storage_summaries: Vec<StorageServerSummary> = self
             .peer_to_state
             .iter()
+            .filter(|peer_state| !self.is_banned(peer_state.key()))
             .filter_map(|peer_state| {
                 peer_state
                     .value()
@@ -386,6 +557,16 @@ impl PeerStates {
     }
 }
 
+/// Creates the reputation of the given peer from its state
+fn create_peer_reputation(peer: PeerNetworkId, peer_state: &PeerState) -> PeerReputation {
+    PeerReputation {
+        peer,
+        score: peer_state.score,
+        recent_latencies_ms: peer_state.get_recent_latencies_ms(),
+        ban_reason: None,
+    }
+}
+
 /// To calculate the optimal chunk size, we take the median for each
 /// chunk size parameter. This works well when we have an honest
 /// majority that mostly agrees on the same chunk sizes.
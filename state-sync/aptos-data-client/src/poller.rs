@@ -280,6 +280,11 @@ pub async fn start_poller(poller: DataSummaryPoller) {
     let poll_loop_ticker = poller.time_service.interval(data_polling_interval);
     futures::pin_mut!(poll_loop_ticker);
 
+    // Track the last time the peer reputations were updated
+    let peer_reputation_update_interval =
+        Duration::from_millis(poller.data_client_config.peer_reputation_update_interval_ms);
+    let mut last_peer_reputation_update = poller.time_service.now();
+
     // Start the poller
     let mut polling_round: u64 = 0;
     info!((LogSchema::new(LogEntry::DataSummaryPoller).message("Starting the Aptos data poller!")));
@@ -306,6 +311,26 @@ pub async fn start_poller(poller: DataSummaryPoller) {
         // Update the logs and metrics for the peer request distributions
         poller.data_client.update_peer_request_logs_and_metrics();
 
+        // Decay and persist the peer reputations (periodically)
+        let time_since_reputation_update = poller
+            .time_service
+            .now()
+            .duration_since(last_peer_reputation_update);
+        if time_since_reputation_update >= peer_reputation_update_interval {
+            if let Err(error) = poller
+                .data_client
+                .update_peer_reputations(time_since_reputation_update)
+            {
+                warn!(
+                    (LogSchema::new(LogEntry::DataSummaryPoller)
+                        .event(LogEvent::PeerReputationsUpdate)
+                        .message("Unable to update the peer reputations!")
+                        .error(&error))
+                );
+            }
+            last_peer_reputation_update = poller.time_service.now();
+        }
+
         // Determine the peers to poll this round. If the round is even, poll
         // the priority peers. Otherwise, poll the regular peers. This allows
         // us to alternate between peer types and load balance requests.
@@ -5,16 +5,22 @@ use crate::{
     client::AptosDataClient,
     error::Error,
     interface::AptosDataClientInterface,
+    peer_reputations::PeerReputation,
+    peer_states::ErrorType,
     poller,
     poller::{poll_peer, DataSummaryPoller},
     tests::{mock::MockNetwork, utils},
 };
-use aptos_config::{config::AptosDataClientConfig, network_id::PeerNetworkId};
+use aptos_config::{
+    config::{AptosDataClientConfig, BaseConfig},
+    network_id::PeerNetworkId,
+};
 use aptos_storage_service_types::{
     requests::DataRequest,
     responses::{CompleteDataRange, DataResponse, StorageServerSummary, StorageServiceResponse},
     StorageServiceError,
 };
+use aptos_temppath::TempPath;
 use aptos_types::transaction::TransactionListWithProof;
 use claims::{assert_err, assert_matches};
 use maplit::hashset;
@@ -350,6 +356,128 @@ async fn disconnected_peers_garbage_collection() {
     }
 }
 
+#[tokio::test]
+async fn banned_peer_is_ignored() {
+    // Create the mock network and client
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, None, None);
+
+    // Add a good and a bad peer. The good peer advertises txns 0 -> 100
+    // and the bad peer advertises txns 0 -> 200.
+    let good_peer = mock_network.add_peer(true);
+    let bad_peer = mock_network.add_peer(true);
+    client.update_peer_storage_summary(good_peer, utils::create_storage_summary(100));
+    client.update_peer_storage_summary(bad_peer, utils::create_storage_summary(200));
+
+    // Ban the bad peer and update the global summary
+    let peer_states = client.get_peer_states();
+    let ban_reason = "Serving invalid data".to_string();
+    peer_states.ban_peer(bad_peer, ban_reason.clone());
+    client.update_global_summary_cache().unwrap();
+
+    // Verify the bad peer's advertisement is ignored and the peer is never selected
+    let global_summary = client.get_global_data_summary();
+    assert!(!global_summary
+        .advertised_data
+        .transactions
+        .contains(&CompleteDataRange::new(0, 200).unwrap()));
+    let (priority_peers, regular_peers) = client.get_priority_and_regular_peers().unwrap();
+    assert_eq!(priority_peers, hashset![good_peer]);
+    assert!(regular_peers.is_empty());
+
+    // Verify the ban reason is reported in the peer's reputation
+    let peer_reputation = get_peer_reputation(&client, bad_peer).unwrap();
+    assert_eq!(peer_reputation.ban_reason, Some(ban_reason));
+
+    // Unban the bad peer and verify its advertisement is no longer ignored
+    assert!(peer_states.unban_peer(&bad_peer));
+    assert!(!peer_states.unban_peer(&bad_peer));
+    client.update_global_summary_cache().unwrap();
+    let global_summary = client.get_global_data_summary();
+    assert!(global_summary
+        .advertised_data
+        .transactions
+        .contains(&CompleteDataRange::new(0, 200).unwrap()));
+}
+
+#[tokio::test]
+async fn disconnected_peer_reputations_decay() {
+    // Create the mock network and client
+    let data_client_config = AptosDataClientConfig::default();
+    let (mut mock_network, _, client, _) = MockNetwork::new(None, Some(data_client_config), None);
+
+    // Add two peers and lower the score of the first peer
+    let peer_1 = mock_network.add_peer(true);
+    let peer_2 = mock_network.add_peer(true);
+    client.update_peer_storage_summary(peer_1, utils::create_storage_summary(100));
+    client.update_peer_storage_summary(peer_2, utils::create_storage_summary(100));
+    let peer_states = client.get_peer_states();
+    peer_states.update_score_error(peer_1, ErrorType::Malicious);
+    assert_eq!(get_peer_reputation(&client, peer_1).unwrap().score, 40.0);
+
+    // Decay the scores by a single half-life and verify the new score
+    let half_life = Duration::from_secs(data_client_config.peer_score_decay_half_life_secs);
+    peer_states.decay_peer_scores(half_life);
+    assert_eq!(get_peer_reputation(&client, peer_1).unwrap().score, 45.0);
+
+    // Disconnect the first peer and verify its reputation is kept
+    mock_network.disconnect_peer(peer_1);
+    client.update_global_summary_cache().unwrap();
+    assert!(!peer_states.get_peer_to_states().contains_key(&peer_1));
+    assert_eq!(get_peer_reputation(&client, peer_1).unwrap().score, 45.0);
+
+    // Reconnect the first peer and verify its score is restored
+    mock_network.reconnect_peer(peer_1);
+    client.update_peer_storage_summary(peer_1, utils::create_storage_summary(100));
+    let peer_to_states = peer_states.get_peer_to_states();
+    assert_eq!(peer_to_states.get(&peer_1).unwrap().get_score(), 45.0);
+
+    // Disconnect the peer again, decay the scores and verify the reputation is forgotten
+    mock_network.disconnect_peer(peer_1);
+    client.update_global_summary_cache().unwrap();
+    peer_states.decay_peer_scores(half_life * 10);
+    assert!(get_peer_reputation(&client, peer_1).is_none());
+}
+
+#[tokio::test]
+async fn peer_reputations_persist_across_restarts() {
+    // Create a base config with a temporary data directory
+    let data_dir = TempPath::new();
+    data_dir.create_as_dir().unwrap();
+    let base_config = BaseConfig {
+        data_dir: data_dir.path().to_path_buf(),
+        ..Default::default()
+    };
+
+    // Create the mock network and client
+    let (mut mock_network, _, client, _) = MockNetwork::new(Some(base_config.clone()), None, None);
+
+    // Add a peer with a lowered score and response latencies
+    let peer = mock_network.add_peer(true);
+    client.update_peer_storage_summary(peer, utils::create_storage_summary(100));
+    let peer_states = client.get_peer_states();
+    peer_states.update_score_error(peer, ErrorType::Malicious);
+    peer_states.record_response_latency(peer, Duration::from_millis(250));
+
+    // Ban another peer
+    let banned_peer = mock_network.add_peer(false);
+    peer_states.ban_peer(banned_peer, "Manually banned".into());
+
+    // Persist the peer reputations
+    client.update_peer_reputations(Duration::ZERO).unwrap();
+    let peer_reputations = peer_states.get_peer_reputations();
+    assert_eq!(peer_reputations.len(), 2);
+
+    // Restart the client and verify the peer reputations are restored
+    let (_, _, restarted_client, _) = MockNetwork::new(Some(base_config), None, None);
+    let restarted_peer_states = restarted_client.get_peer_states();
+    assert_eq!(
+        restarted_peer_states.get_peer_reputations(),
+        peer_reputations
+    );
+    assert!(restarted_peer_states.is_banned(&banned_peer));
+    assert!(!restarted_peer_states.is_banned(&peer));
+}
+
 /// A simple helper function that polls all the specified peers
 /// and returns storage server summaries for each.
 async fn poll_peers(
@@ -382,3 +510,12 @@ fn verify_peer_states(client: &AptosDataClient, all_peers: HashSet<PeerNetworkId
     }
     assert_eq!(peer_to_states.len(), all_peers.len());
 }
+
+/// Returns the reputation of the given peer (if the peer is known)
+fn get_peer_reputation(client: &AptosDataClient, peer: PeerNetworkId) -> Option<PeerReputation> {
+    client
+        .get_peer_states()
+        .get_peer_reputations()
+        .into_iter()
+        .find(|peer_reputation| peer_reputation.peer == peer)
+}
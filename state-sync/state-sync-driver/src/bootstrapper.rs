@@ -6,7 +6,7 @@ use crate::{
     driver::DriverConfiguration,
     error::Error,
     logging::{LogEntry, LogSchema},
-    metadata_storage::MetadataStorageInterface,
+    metadata_storage::{can_resume_snapshot_sync, MetadataStorageInterface},
     metrics,
     metrics::ExecutingComponent,
     storage_synchronizer::StorageSynchronizerInterface,
@@ -538,7 +538,20 @@ impl<
                         )))
                     }
                 } else {
-                    // Continue snapshot syncing to the target
+                    // Continue snapshot syncing to the target. If a newer target is known
+                    // in the same epoch, it is synced once the node has bootstrapped.
+                    if can_resume_snapshot_sync(&target, &highest_known_ledger_info)
+                        && target.ledger_info().version()
+                            < highest_known_ledger_info.ledger_info().version()
+                    {
+                        info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                            "Resuming the snapshot sync to the previous target at version {:?}! \
+                            The highest known version {:?} (in the same epoch) will be synced \
+                            after bootstrapping.",
+                            target.ledger_info().version(),
+                            highest_known_ledger_info.ledger_info().version()
+                        )));
+                    }
                     self.fetch_missing_state_values(target, true).await
                 }
             } else {
@@ -657,23 +670,27 @@ impl<
         target_ledger_info: LedgerInfoWithSignatures,
         existing_snapshot_progress: bool,
     ) -> Result<(), Error> {
-        // Initialize the target ledger info and verify it never changes
-        if let Some(ledger_info_to_sync) = &self.state_value_syncer.ledger_info_to_sync {
-            if ledger_info_to_sync != &target_ledger_info {
-                return Err(Error::UnexpectedError(format!(
-                    "Mismatch in ledger info to sync! Given target: {:?}, stored target: {:?}",
-                    target_ledger_info, ledger_info_to_sync
+        // Initialize the target ledger info and verify it never changes (a newer
+        // target in the same epoch is reached once the current one is synced).
+        let target_ledger_info =
+            if let Some(ledger_info_to_sync) = &self.state_value_syncer.ledger_info_to_sync {
+                if !can_resume_snapshot_sync(ledger_info_to_sync, &target_ledger_info) {
+                    return Err(Error::UnexpectedError(format!(
+                        "Mismatch in ledger info to sync! Given target: {:?}, stored target: {:?}",
+                        target_ledger_info, ledger_info_to_sync
+                    )));
+                }
+                ledger_info_to_sync.clone()
+            } else {
+                info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                    "Setting the target ledger info for fast sync! Target: {:?}",
+                    target_ledger_info
                 )));
-            }
-        } else {
-            info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
-                "Setting the target ledger info for fast sync! Target: {:?}",
-                target_ledger_info
-            )));
 
-            self.state_value_syncer
-                .set_ledger_info_to_sync(target_ledger_info.clone());
-        }
+                self.state_value_syncer
+                    .set_ledger_info_to_sync(target_ledger_info.clone());
+                target_ledger_info
+            };
 
         // Fetch the data that we're missing
         let target_ledger_info_version = target_ledger_info.ledger_info().version();
@@ -689,19 +706,7 @@ impl<
         } else {
             // Identify the next state index to fetch
             let next_state_index_to_process = if existing_snapshot_progress {
-                // The state snapshot receiver requires that after each reboot we
-                // rewrite the last persisted index (again!). This is a limitation
-                // of how the snapshot is persisted (i.e., in-memory sibling freezing).
-                // Thus, on each stream reset, we overlap every chunk by a single item.
-                self
-                    .metadata_storage
-                    .get_last_persisted_state_value_index(&target_ledger_info)
-                    .map_err(|error| {
-                        Error::StorageError(format!(
-                            "Failed to get the last persisted state value index at version {:?}! Error: {:?}",
-                            target_ledger_info_version, error
-                        ))
-                    })?
+                self.get_next_state_index_to_resume(&target_ledger_info)?
             } else {
                 0 // We need to start the snapshot sync from index 0
             };
@@ -721,6 +726,59 @@ impl<
         Ok(())
     }
 
+    /// Returns the state value index at which to resume the snapshot sync for the target
+    fn get_next_state_index_to_resume(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<u64, Error> {
+        let target_ledger_info_version = target_ledger_info.ledger_info().version();
+        let last_persisted_state_value_index = self
+            .metadata_storage
+            .get_last_persisted_state_value_index(target_ledger_info)
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to get the last persisted state value index at version {:?}! Error: {:?}",
+                    target_ledger_info_version, error
+                ))
+            })?;
+
+        // If the last committed chunk was recorded, the state snapshot receiver re-adds
+        // its last state value, so we can resume from the exact next index.
+        let chunk_progress = self
+            .metadata_storage
+            .get_snapshot_chunk_progress(target_ledger_info)
+            .map_err(|error| {
+                Error::StorageError(format!(
+                    "Failed to get the snapshot chunk progress at version {:?}! Error: {:?}",
+                    target_ledger_info_version, error
+                ))
+            })?;
+        if let Some(chunk_progress) = chunk_progress {
+            if chunk_progress.last_committed_state_index == last_persisted_state_value_index {
+                info!(LogSchema::new(LogEntry::Bootstrapper).message(&format!(
+                    "Resuming the state snapshot sync after the last committed chunk! \
+                    Last committed index: {:?}, key hash: {:?}, committed leaves: {:?}",
+                    chunk_progress.last_committed_state_index,
+                    chunk_progress.last_committed_key_hash,
+                    chunk_progress.num_committed_leaves
+                )));
+                return last_persisted_state_value_index
+                    .checked_add(1)
+                    .ok_or_else(|| {
+                        Error::IntegerOverflow(
+                            "The next state value index to process has overflown!".into(),
+                        )
+                    });
+            }
+        }
+
+        // Otherwise, the state snapshot receiver requires that we rewrite the
+        // last persisted index (again!). This is a limitation of how the snapshot
+        // is persisted (i.e., in-memory sibling freezing). Thus, we overlap the
+        // last chunk by a single item.
+        Ok(last_persisted_state_value_index)
+    }
+
     /// Fetches all missing transaction data in order to bootstrap the node
     async fn fetch_missing_transaction_data(
         &mut self,
@@ -14,7 +14,7 @@ use crate::{
 };
 use aptos_config::config::NodeConfig;
 use aptos_consensus_notifications::ConsensusNotificationListener;
-use aptos_data_client::client::AptosDataClient;
+use aptos_data_client::interface::AptosDataClientInterface;
 use aptos_data_streaming_service::streaming_client::StreamingServiceClient;
 use aptos_event_notifications::{EventNotificationSender, EventSubscriptionService};
 use aptos_executor_types::ChunkExecutorTrait;
@@ -44,6 +44,7 @@ impl DriverFactory {
         MempoolNotifier: MempoolNotificationSender + 'static,
         MetadataStorage: MetadataStorageInterface + Clone + Send + Sync + 'static,
         StorageServiceNotifier: StorageServiceNotificationSender + 'static,
+        DataClient: AptosDataClientInterface + Send + Clone + 'static,
     >(
         create_runtime: bool,
         node_config: &NodeConfig,
@@ -55,7 +56,7 @@ impl DriverFactory {
         metadata_storage: MetadataStorage,
         consensus_listener: ConsensusNotificationListener,
         event_subscription_service: EventSubscriptionService,
-        aptos_data_client: AptosDataClient,
+        aptos_data_client: DataClient,
         streaming_service_client: StreamingServiceClient,
         time_service: TimeService,
     ) -> Self {
@@ -85,6 +86,7 @@ impl DriverFactory {
         MempoolNotifier: MempoolNotificationSender + 'static,
         MetadataStorage: MetadataStorageInterface + Clone + Send + Sync + 'static,
         StorageServiceNotifier: StorageServiceNotificationSender + 'static,
+        DataClient: AptosDataClientInterface + Send + Clone + 'static,
     >(
         create_runtime: bool,
         node_config: &NodeConfig,
@@ -96,7 +98,7 @@ impl DriverFactory {
         metadata_storage: MetadataStorage,
         consensus_listener: ConsensusNotificationListener,
         mut event_subscription_service: EventSubscriptionService,
-        aptos_data_client: AptosDataClient,
+        aptos_data_client: DataClient,
         streaming_service_client: StreamingServiceClient,
         time_service: TimeService,
     ) -> (Self, UnboundedSender<CommitNotification>) {
@@ -6,13 +6,17 @@ use crate::{
     metadata_storage::database_schema::{MetadataKey, MetadataSchema, MetadataValue},
 };
 use anyhow::{anyhow, Result};
+use aptos_crypto::HashValue;
 use aptos_logger::prelude::*;
 use aptos_schemadb::{
     define_schema,
     schema::{KeyCodec, ValueCodec},
     ColumnFamilyName, Options, SchemaBatch, DB,
 };
-use aptos_types::ledger_info::LedgerInfoWithSignatures;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::{state_key::StateKey, state_value::StateValue},
+};
 use serde::{Deserialize, Serialize};
 use std::{path::Path, sync::Arc, time::Instant};
 
@@ -47,6 +51,22 @@ pub trait MetadataStorageInterface {
         last_persisted_state_value_index: u64,
         snapshot_sync_completed: bool,
     ) -> Result<(), Error>;
+
+    /// Gets the progress of the last committed state value chunk for the snapshot
+    /// sync at the specified target. Returns None if no chunk progress was recorded.
+    fn get_snapshot_chunk_progress(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<Option<StateSnapshotChunkProgress>, Error>;
+
+    /// Records the progress of a newly committed state value chunk for the (incomplete)
+    /// snapshot sync at the specified target. This also updates the last persisted
+    /// state value index.
+    fn update_snapshot_chunk_progress(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+        chunk_progress: StateSnapshotChunkProgress,
+    ) -> Result<(), Error>;
 }
 
 /// The name of the state sync db file
@@ -106,10 +126,35 @@ impl PersistentMetadataStorage {
                     ))
                 })?;
         match maybe_metadata_value {
-            Some(metadata_value) => {
-                let MetadataValue::StateSnapshotSync(snapshot_progress) = metadata_value;
+            Some(MetadataValue::StateSnapshotSync(snapshot_progress)) => {
                 Ok(Some(snapshot_progress))
             },
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
+            None => Ok(None),
+        }
+    }
+
+    /// Returns the existing chunk progress. Returns None if no progress is found.
+    fn get_chunk_progress(&self) -> Result<Option<StateSnapshotChunkProgress>, Error> {
+        let metadata_key = MetadataKey::StateSnapshotChunkSync;
+        let maybe_metadata_value =
+            self.database
+                .get::<MetadataSchema>(&metadata_key)
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to read metadata value for key: {:?}. Error: {:?}",
+                        metadata_key, error
+                    ))
+                })?;
+        match maybe_metadata_value {
+            Some(MetadataValue::StateSnapshotChunkSync(chunk_progress)) => Ok(Some(chunk_progress)),
+            Some(metadata_value) => Err(Error::StorageError(format!(
+                "Unexpected metadata value for key: {:?}. Value: {:?}",
+                metadata_key, metadata_value
+            ))),
             None => Ok(None),
         }
     }
@@ -122,7 +167,10 @@ impl PersistentMetadataStorage {
     ) -> Result<StateSnapshotProgress, Error> {
         match self.get_snapshot_progress()? {
             Some(snapshot_progress) => {
-                if &snapshot_progress.target_ledger_info != target_ledger_info {
+                if !is_same_snapshot_target(
+                    &snapshot_progress.target_ledger_info,
+                    target_ledger_info,
+                ) {
                     Err(Error::UnexpectedError(format!(
                         "Expected a snapshot progress for target {:?}, but found {:?}!",
                         target_ledger_info, snapshot_progress.target_ledger_info
@@ -137,21 +185,47 @@ impl PersistentMetadataStorage {
         }
     }
 
+    /// Verifies that any previous snapshot progress has the same target
+    fn verify_snapshot_progress_target(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+    ) -> Result<(), Error> {
+        if let Some(snapshot_progress) = self.get_snapshot_progress()? {
+            if !is_same_snapshot_target(target_ledger_info, &snapshot_progress.target_ledger_info) {
+                return Err(Error::StorageError(format!("Failed to update the last persisted state value index! \
+                The given target does not match the previously stored target. Given target: {:?}, stored target: {:?}",
+                    target_ledger_info, snapshot_progress.target_ledger_info
+                )));
+            }
+        }
+        Ok(())
+    }
+
     /// Write the key value pair to the database
     fn commit_key_value(
         &self,
         metadata_key: MetadataKey,
         metadata_value: MetadataValue,
+    ) -> Result<(), Error> {
+        self.commit_key_values(vec![(metadata_key, metadata_value)])
+    }
+
+    /// Write the key value pairs to the database atomically
+    fn commit_key_values(
+        &self,
+        key_values: Vec<(MetadataKey, MetadataValue)>,
     ) -> Result<(), Error> {
         // Create the schema batch
         let batch = SchemaBatch::new();
-        batch
-            .put::<MetadataSchema>(&metadata_key, &metadata_value)
-            .map_err(|error| {
-                Error::StorageError(format!(
-                    "Failed to batch put the metadata key and value. Key: {:?}, Value: {:?}. Error: {:?}", metadata_key, metadata_value, error
-                ))
-            })?;
+        for (metadata_key, metadata_value) in key_values {
+            batch
+                .put::<MetadataSchema>(&metadata_key, &metadata_value)
+                .map_err(|error| {
+                    Error::StorageError(format!(
+                        "Failed to batch put the metadata key and value. Key: {:?}, Value: {:?}. Error: {:?}", metadata_key, metadata_value, error
+                    ))
+                })?;
+        }
 
         // Write the schema batch to the database
         self.database.write_schemas(batch).map_err(|error| {
@@ -204,14 +278,7 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
         snapshot_sync_completed: bool,
     ) -> Result<(), Error> {
         // Ensure that if any previous snapshot progress exists, it has the same target
-        if let Some(snapshot_progress) = self.get_snapshot_progress()? {
-            if target_ledger_info != &snapshot_progress.target_ledger_info {
-                return Err(Error::StorageError(format!("Failed to update the last persisted state value index! \
-                The given target does not match the previously stored target. Given target: {:?}, stored target: {:?}",
-                    target_ledger_info, snapshot_progress.target_ledger_info
-                )));
-            }
-        }
+        self.verify_snapshot_progress_target(target_ledger_info)?;
 
         // Create the key/value pair
         let metadata_key = MetadataKey::StateSnapshotSync;
@@ -224,6 +291,75 @@ impl MetadataStorageInterface for PersistentMetadataStorage {
         // Insert the new key/value pair
         self.commit_key_value(metadata_key, metadata_value)
     }
+
+    fn get_snapshot_chunk_progress(
+        &self,
+        target: &LedgerInfoWithSignatures,
+    ) -> Result<Option<StateSnapshotChunkProgress>, Error> {
+        // Ensure the snapshot progress is for the target (the chunk progress
+        // is always written together with the snapshot progress).
+        self.get_snapshot_progress_at_target(target)?;
+        self.get_chunk_progress()
+    }
+
+    fn update_snapshot_chunk_progress(
+        &self,
+        target_ledger_info: &LedgerInfoWithSignatures,
+        chunk_progress: StateSnapshotChunkProgress,
+    ) -> Result<(), Error> {
+        // Ensure that if any previous snapshot progress exists, it has the same target
+        self.verify_snapshot_progress_target(target_ledger_info)?;
+
+        // Create the key/value pairs
+        let snapshot_progress = StateSnapshotProgress {
+            last_persisted_state_value_index: chunk_progress.last_committed_state_index,
+            snapshot_sync_completed: false,
+            target_ledger_info: target_ledger_info.clone(),
+        };
+        let key_values = vec![
+            (
+                MetadataKey::StateSnapshotSync,
+                MetadataValue::StateSnapshotSync(snapshot_progress),
+            ),
+            (
+                MetadataKey::StateSnapshotChunkSync,
+                MetadataValue::StateSnapshotChunkSync(chunk_progress),
+            ),
+        ];
+
+        // Insert both key/value pairs atomically
+        self.commit_key_values(key_values)
+    }
+}
+
+/// Returns true iff the given ledger infos are the same snapshot sync target. The
+/// signatures are ignored, so that a sync can resume to the same ledger info even
+/// if it was certified by a different set of validators.
+pub fn is_same_snapshot_target(
+    target_ledger_info: &LedgerInfoWithSignatures,
+    other_ledger_info: &LedgerInfoWithSignatures,
+) -> bool {
+    target_ledger_info.ledger_info() == other_ledger_info.ledger_info()
+}
+
+/// Returns true iff a snapshot sync to the previous target can be resumed when the
+/// node is asked to sync to the new target, i.e., the new target is the same as the
+/// previous one, or a newer ledger info in the same epoch.
+///
+/// For a newer target, the sync continues to the previous target, keeping the state
+/// values already restored (their proofs were verified against the previous target),
+/// and the versions up to the new target are synced once the node has bootstrapped.
+/// The restored state can't be moved to the new version instead, as values deleted
+/// after the previous target would remain visible at the new version.
+pub fn can_resume_snapshot_sync(
+    previous_target: &LedgerInfoWithSignatures,
+    new_target: &LedgerInfoWithSignatures,
+) -> bool {
+    let previous_ledger_info = previous_target.ledger_info();
+    let new_ledger_info = new_target.ledger_info();
+    is_same_snapshot_target(previous_target, new_target)
+        || (previous_ledger_info.epoch() == new_ledger_info.epoch()
+            && previous_ledger_info.version() < new_ledger_info.version())
 }
 
 /// A simple struct for recording the progress of a state snapshot sync
@@ -234,6 +370,22 @@ pub struct StateSnapshotProgress {
     pub snapshot_sync_completed: bool,
 }
 
+/// A simple struct for recording the progress of the last committed state
+/// value chunk of a state snapshot sync.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateSnapshotChunkProgress {
+    /// The index of the last committed state value
+    pub last_committed_state_index: u64,
+    /// The hash of the key of the last committed state value
+    pub last_committed_key_hash: HashValue,
+    /// The last committed state value. The Jellyfish Merkle restore only persists
+    /// a leaf once the next leaf has been added, so this is re-added to the restore
+    /// when resuming the sync after a reboot.
+    pub last_committed_state_value: (StateKey, StateValue),
+    /// The number of leaves committed to the state KV and Jellyfish Merkle restore
+    pub num_committed_leaves: u64,
+}
+
 /// The raw schema format used by the database
 pub mod database_schema {
     use super::*;
@@ -251,7 +403,8 @@ pub mod database_schema {
     #[derive(Debug, Deserialize, Eq, PartialEq, Serialize)]
     #[repr(u8)]
     pub enum MetadataKey {
-        StateSnapshotSync, // A state snapshot sync that was started
+        StateSnapshotSync,      // A state snapshot sync that was started
+        StateSnapshotChunkSync, // The last committed chunk of a state snapshot sync
     }
 
     /// A metadata value that can be inserted into the database
@@ -259,6 +412,7 @@ pub mod database_schema {
     #[repr(u8)]
     pub enum MetadataValue {
         StateSnapshotSync(StateSnapshotProgress), // A state snapshot sync progress marker
+        StateSnapshotChunkSync(StateSnapshotChunkProgress), // A state snapshot chunk progress marker
     }
 
     impl KeyCodec<MetadataSchema> for MetadataKey {
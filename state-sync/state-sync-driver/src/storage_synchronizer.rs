@@ -5,7 +5,7 @@
 use crate::{
     error::Error,
     logging::{LogEntry, LogSchema},
-    metadata_storage::MetadataStorageInterface,
+    metadata_storage::{MetadataStorageInterface, StateSnapshotChunkProgress},
     metrics,
     notification_handlers::{
         CommitNotification, CommittedTransactions, ErrorNotification, MempoolNotificationHandler,
@@ -725,6 +725,11 @@ fn spawn_state_snapshot_receiver<
             .get_state_snapshot_receiver(version, expected_root_hash)
             .expect("Failed to initialize the state snapshot receiver!");
 
+        // Fetch the progress of the last committed chunk (if we're resuming a previous sync)
+        let mut resumed_chunk_progress = metadata_storage
+            .get_snapshot_chunk_progress(&target_ledger_info)
+            .unwrap_or(None);
+
         // Handle state value chunks
         let target_ledger_info = &target_ledger_info;
         while let Some(storage_data_chunk) = state_snapshot_listener.next().await {
@@ -733,13 +738,35 @@ fn spawn_state_snapshot_receiver<
                 StorageDataChunk::States(notification_id, states_with_proof) => {
                     let all_states_synced = states_with_proof.is_last_chunk();
                     let last_committed_state_index = states_with_proof.last_index;
+                    let num_state_values = states_with_proof.raw_values.len();
+
+                    // Create the progress of this chunk
+                    let chunk_progress = states_with_proof.raw_values.last().map(|state_value| {
+                        StateSnapshotChunkProgress {
+                            last_committed_state_index,
+                            last_committed_key_hash: states_with_proof.last_key,
+                            last_committed_state_value: state_value.clone(),
+                            num_committed_leaves: last_committed_state_index.saturating_add(1),
+                        }
+                    });
+
+                    // If the sync is resuming right after the last committed chunk, re-add the
+                    // last committed state value, as the restore only persists a leaf once
+                    // the next leaf is added (overlapping values are skipped by the restore).
+                    let mut raw_values = states_with_proof.raw_values;
+                    if let Some(resumed_chunk_progress) = resumed_chunk_progress.take() {
+                        if resumed_chunk_progress
+                            .last_committed_state_index
+                            .checked_add(1)
+                            == Some(states_with_proof.first_index)
+                        {
+                            raw_values.insert(0, resumed_chunk_progress.last_committed_state_value);
+                        }
+                    }
 
                     // Attempt to commit the chunk
-                    let num_state_values = states_with_proof.raw_values.len();
-                    let commit_result = state_snapshot_receiver.add_chunk(
-                        states_with_proof.raw_values,
-                        states_with_proof.proof.clone(),
-                    );
+                    let commit_result = state_snapshot_receiver
+                        .add_chunk(raw_values, states_with_proof.proof.clone());
                     match commit_result {
                         Ok(()) => {
                             // Update the logs and metrics
@@ -765,15 +792,21 @@ fn spawn_state_snapshot_receiver<
                             );
 
                             if !all_states_synced {
-                                // Update the metadata storage with the last committed state index
-                                if let Err(error) = metadata_storage
-                                    .clone()
-                                    .update_last_persisted_state_value_index(
-                                        target_ledger_info,
-                                        last_committed_state_index,
-                                        all_states_synced,
-                                    )
-                                {
+                                // Update the metadata storage with the last committed chunk
+                                let update_result = match chunk_progress {
+                                    Some(chunk_progress) => metadata_storage
+                                        .update_snapshot_chunk_progress(
+                                            target_ledger_info,
+                                            chunk_progress,
+                                        ),
+                                    None => metadata_storage
+                                        .update_last_persisted_state_value_index(
+                                            target_ledger_info,
+                                            last_committed_state_index,
+                                            all_states_synced,
+                                        ),
+                                };
+                                if let Err(error) = update_result {
                                     let error = format!("Failed to update the last persisted state index at version: {:?}! Error: {:?}", version, error);
                                     send_storage_synchronizer_error(
                                         error_notification_sender.clone(),
@@ -5,6 +5,7 @@ use crate::{
     bootstrapper::{Bootstrapper, GENESIS_TRANSACTION_VERSION},
     driver::DriverConfiguration,
     error::Error,
+    metadata_storage::StateSnapshotChunkProgress,
     tests::{
         mocks::{
             create_mock_db_reader, create_mock_streaming_client, create_ready_storage_synchronizer,
@@ -21,6 +22,7 @@ use crate::{
     utils::OutputFallbackHandler,
 };
 use aptos_config::config::BootstrappingMode;
+use aptos_crypto::HashValue;
 use aptos_data_client::global_summary::GlobalDataSummary;
 use aptos_data_streaming_service::{
     data_notification::{DataNotification, DataPayload, NotificationId},
@@ -28,6 +30,7 @@ use aptos_data_streaming_service::{
 };
 use aptos_time_service::TimeService;
 use aptos_types::{
+    state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{TransactionOutputListWithProof, Version},
     waypoint::Waypoint,
 };
@@ -825,6 +828,9 @@ async fn test_snapshot_sync_epoch_change() {
     metadata_storage
         .expect_get_last_persisted_state_value_index()
         .returning(move |_| Ok(last_persisted_index_clone));
+    metadata_storage
+        .expect_get_snapshot_chunk_progress()
+        .returning(|_| Ok(None));
 
     // Create the bootstrapper
     let mut bootstrapper = create_bootstrapper_with_storage(
@@ -854,6 +860,218 @@ async fn test_snapshot_sync_epoch_change() {
         .unwrap();
 }
 
+#[tokio::test]
+async fn test_snapshot_sync_resume_from_chunk_progress() {
+    // Create test data
+    let synced_version = GENESIS_TRANSACTION_VERSION; // Genesis is the highest synced
+    let target_version = 1000;
+    let highest_version = 5000;
+    let last_persisted_index = 1030405;
+    let target_ledger_info = create_random_epoch_ending_ledger_info(target_version, 1);
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 2);
+
+    // Create a driver configuration with a genesis waypoint and state syncing
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+
+    // Create the mock streaming client (the stream should resume after the last committed index)
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender_1, data_stream_listener_1) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_state_values()
+        .times(1)
+        .with(eq(target_version), eq(Some(last_persisted_index + 1)))
+        .return_once(move |_, _| Ok(data_stream_listener_1));
+
+    // Create the mock metadata storage with chunk progress for the last persisted index
+    let mut metadata_storage = MockMetadataStorage::new();
+    let target_ledger_info_clone = target_ledger_info.clone();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(move || Ok(Some(target_ledger_info_clone.clone())));
+    metadata_storage
+        .expect_is_snapshot_sync_complete()
+        .returning(|_| Ok(false));
+    metadata_storage
+        .expect_get_last_persisted_state_value_index()
+        .returning(move |_| Ok(last_persisted_index));
+    metadata_storage
+        .expect_get_snapshot_chunk_progress()
+        .returning(move |_| {
+            Ok(Some(StateSnapshotChunkProgress {
+                last_committed_state_index: last_persisted_index,
+                last_committed_key_hash: HashValue::random(),
+                last_committed_state_value: (
+                    StateKey::raw(vec![1, 2, 3]),
+                    StateValue::new_legacy(vec![4, 5, 6].into()),
+                ),
+                num_committed_leaves: last_persisted_index + 1,
+            }))
+        });
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        synced_version,
+        true,
+    );
+
+    // Insert an epoch ending ledger info into the verified states of the bootstrapper
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Manually insert a transaction output to sync
+    bootstrapper
+        .get_state_value_syncer()
+        .set_transaction_output_to_sync(create_output_list_with_proof());
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+
+    // Drive progress to start the state value stream
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_snapshot_sync_resume_with_newer_target_in_same_epoch() {
+    // Create test data (the highest known ledger info is in the same epoch as the target)
+    let synced_version = GENESIS_TRANSACTION_VERSION; // Genesis is the highest synced
+    let target_version = 1000;
+    let highest_version = 5000;
+    let last_persisted_index = 1030405;
+    let target_ledger_info = create_random_epoch_ending_ledger_info(target_version, 0);
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 0);
+
+    // Create a driver configuration with a genesis waypoint and state syncing
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+
+    // Create the mock streaming client (the stream should resume the previous
+    // target after the last committed index, and not restart at the newer target).
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender_1, data_stream_listener_1) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_state_values()
+        .times(1)
+        .with(eq(target_version), eq(Some(last_persisted_index + 1)))
+        .return_once(move |_, _| Ok(data_stream_listener_1));
+
+    // Create the mock metadata storage with chunk progress for the previous target
+    let mut metadata_storage = MockMetadataStorage::new();
+    let target_ledger_info_clone = target_ledger_info.clone();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(move || Ok(Some(target_ledger_info_clone.clone())));
+    metadata_storage
+        .expect_is_snapshot_sync_complete()
+        .returning(|_| Ok(false));
+    metadata_storage
+        .expect_get_last_persisted_state_value_index()
+        .returning(move |_| Ok(last_persisted_index));
+    metadata_storage
+        .expect_get_snapshot_chunk_progress()
+        .returning(move |_| {
+            Ok(Some(StateSnapshotChunkProgress {
+                last_committed_state_index: last_persisted_index,
+                last_committed_key_hash: HashValue::random(),
+                last_committed_state_value: (
+                    StateKey::raw(vec![1, 2, 3]),
+                    StateValue::new_legacy(vec![4, 5, 6].into()),
+                ),
+                num_committed_leaves: last_persisted_index + 1,
+            }))
+        });
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        synced_version,
+        true,
+    );
+
+    // Insert the newer ledger info (in the same epoch) into the verified states
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Manually insert a transaction output to sync
+    bootstrapper
+        .get_state_value_syncer()
+        .set_transaction_output_to_sync(create_output_list_with_proof());
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+
+    // Drive progress to resume the state value stream for the previous target
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
+#[tokio::test]
+async fn test_snapshot_sync_newer_target_in_same_epoch_before_progress() {
+    // Create test data (the highest known ledger info is in the same epoch as the target)
+    let synced_version = GENESIS_TRANSACTION_VERSION; // Genesis is the highest synced
+    let target_version = 1000;
+    let highest_version = 5000;
+    let target_ledger_info = create_random_epoch_ending_ledger_info(target_version, 0);
+    let highest_ledger_info = create_random_epoch_ending_ledger_info(highest_version, 0);
+
+    // Create a driver configuration with a genesis waypoint and state syncing
+    let mut driver_configuration = create_full_node_driver_configuration();
+    driver_configuration.config.bootstrapping_mode = BootstrappingMode::DownloadLatestStates;
+
+    // Create the mock streaming client (the stream should start at the target
+    // the transaction output was fetched for, and not at the newer target).
+    let mut mock_streaming_client = create_mock_streaming_client();
+    let (_notification_sender_1, data_stream_listener_1) = create_data_stream_listener();
+    mock_streaming_client
+        .expect_get_all_state_values()
+        .times(1)
+        .with(eq(target_version), eq(Some(0)))
+        .return_once(move |_, _| Ok(data_stream_listener_1));
+
+    // Create the mock metadata storage (no state values have been committed yet)
+    let mut metadata_storage = MockMetadataStorage::new();
+    metadata_storage
+        .expect_previous_snapshot_sync_target()
+        .returning(|| Ok(None));
+
+    // Create the bootstrapper
+    let mut bootstrapper = create_bootstrapper_with_storage(
+        driver_configuration,
+        mock_streaming_client,
+        metadata_storage,
+        None,
+        synced_version,
+        true,
+    );
+
+    // Insert the newer ledger info (in the same epoch) into the verified states
+    manipulate_verified_epoch_states(&mut bootstrapper, true, true, Some(highest_version));
+
+    // Manually insert the target and the transaction output to sync
+    let state_value_syncer = bootstrapper.get_state_value_syncer();
+    state_value_syncer.set_ledger_info_to_sync(target_ledger_info);
+    state_value_syncer.set_transaction_output_to_sync(create_output_list_with_proof());
+
+    // Create a global data summary
+    let mut global_data_summary = create_global_summary(1);
+    global_data_summary.advertised_data.synced_ledger_infos = vec![highest_ledger_info.clone()];
+
+    // Drive progress to start the state value stream for the previous target
+    drive_progress(&mut bootstrapper, &global_data_summary, false)
+        .await
+        .unwrap();
+}
+
 #[tokio::test]
 async fn test_snapshot_sync_epoch_change_genesis() {
     // Create test data
@@ -947,6 +1165,9 @@ async fn test_snapshot_sync_epoch_change_genesis_restart() {
     metadata_storage
         .expect_get_last_persisted_state_value_index()
         .returning(move |_| Ok(last_persisted_index_clone));
+    metadata_storage
+        .expect_get_snapshot_chunk_progress()
+        .returning(|_| Ok(None));
 
     // Create the bootstrapper
     let mut bootstrapper = create_bootstrapper_with_storage(
@@ -1035,6 +1256,9 @@ async fn test_snapshot_sync_existing_state() {
     metadata_storage
         .expect_get_last_persisted_state_value_index()
         .returning(move |_| Ok(last_persisted_index_clone));
+    metadata_storage
+        .expect_get_snapshot_chunk_progress()
+        .returning(|_| Ok(None));
 
     // Create the bootstrapper
     let mut bootstrapper = create_bootstrapper_with_storage(
@@ -3,13 +3,20 @@
 
 use crate::{
     metadata_storage::{
+        can_resume_snapshot_sync,
         database_schema::{MetadataKey, MetadataSchema, MetadataValue},
-        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotProgress,
+        MetadataStorageInterface, PersistentMetadataStorage, StateSnapshotChunkProgress,
+        StateSnapshotProgress,
+    },
+    tests::utils::{
+        create_epoch_ending_ledger_info, create_epoch_ending_ledger_info_for_epoch,
+        create_ledger_info_at_version,
     },
-    tests::utils::{create_epoch_ending_ledger_info, create_ledger_info_at_version},
 };
+use aptos_crypto::HashValue;
 use aptos_schemadb::schema::fuzzing::assert_encode_decode;
 use aptos_temppath::TempPath;
+use aptos_types::state_store::{state_key::StateKey, state_value::StateValue};
 use claims::{assert_err, assert_none};
 
 #[test]
@@ -101,6 +108,66 @@ fn test_metadata_schema_encode_decode() {
     );
 }
 
+#[test]
+fn test_chunk_progress_create_then_open() {
+    // Create a new metadata storage
+    let tmp_dir = TempPath::new();
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+
+    // Verify there is no chunk progress for the target
+    let target_ledger_info = create_ledger_info_at_version(12345);
+    assert_err!(metadata_storage.get_snapshot_chunk_progress(&target_ledger_info));
+
+    // Insert a legacy state value entry and verify there is still no chunk progress
+    metadata_storage
+        .update_last_persisted_state_value_index(&target_ledger_info, 100, false)
+        .unwrap();
+    assert_none!(metadata_storage
+        .get_snapshot_chunk_progress(&target_ledger_info)
+        .unwrap());
+
+    // Insert a chunk progress entry for the target
+    let chunk_progress = create_chunk_progress(200);
+    metadata_storage
+        .update_snapshot_chunk_progress(&target_ledger_info, chunk_progress.clone())
+        .unwrap();
+
+    // Drop the handle to the storage (mimic a reboot)
+    drop(metadata_storage);
+
+    // Create another storage (it should reopen the existing file) and verify the state
+    let metadata_storage = PersistentMetadataStorage::new(tmp_dir.path());
+    assert_eq!(
+        Some(chunk_progress.clone()),
+        metadata_storage
+            .get_snapshot_chunk_progress(&target_ledger_info)
+            .unwrap()
+    );
+    assert_eq!(
+        chunk_progress.last_committed_state_index,
+        metadata_storage
+            .get_last_persisted_state_value_index(&target_ledger_info)
+            .unwrap()
+    );
+    assert!(!metadata_storage
+        .is_snapshot_sync_complete(&target_ledger_info)
+        .unwrap());
+
+    // Verify the chunk progress can't be read or written for a different target
+    let other_ledger_info = create_ledger_info_at_version(54321);
+    assert_err!(metadata_storage.get_snapshot_chunk_progress(&other_ledger_info));
+    assert_err!(metadata_storage
+        .update_snapshot_chunk_progress(&other_ledger_info, create_chunk_progress(300)));
+}
+
+#[test]
+fn test_chunk_progress_encode_decode() {
+    assert_encode_decode::<MetadataSchema>(
+        &MetadataKey::StateSnapshotChunkSync,
+        &MetadataValue::StateSnapshotChunkSync(create_chunk_progress(5678)),
+    );
+}
+
 #[test]
 fn test_multiple_reads_and_writes() {
     // Create a new metadata storage
@@ -167,3 +234,46 @@ fn test_writes_to_different_targets() {
         .update_last_persisted_state_value_index(&target_ledger_info, 10101, false)
         .unwrap_err();
 }
+
+#[test]
+fn test_can_resume_snapshot_sync() {
+    // The same target can be resumed
+    let target_ledger_info = create_ledger_info_at_version(100);
+    assert!(can_resume_snapshot_sync(
+        &target_ledger_info,
+        &target_ledger_info
+    ));
+
+    // A newer target in the same epoch can be resumed
+    let newer_ledger_info = create_ledger_info_at_version(200);
+    assert!(can_resume_snapshot_sync(
+        &target_ledger_info,
+        &newer_ledger_info
+    ));
+
+    // An older target in the same epoch can't be resumed
+    assert!(!can_resume_snapshot_sync(
+        &newer_ledger_info,
+        &target_ledger_info
+    ));
+
+    // A target in a different epoch can't be resumed
+    let next_epoch_ledger_info = create_epoch_ending_ledger_info_for_epoch(1, 200);
+    assert!(!can_resume_snapshot_sync(
+        &target_ledger_info,
+        &next_epoch_ledger_info
+    ));
+}
+
+/// Creates a chunk progress entry for the given last committed index
+fn create_chunk_progress(last_committed_state_index: u64) -> StateSnapshotChunkProgress {
+    StateSnapshotChunkProgress {
+        last_committed_state_index,
+        last_committed_key_hash: HashValue::random(),
+        last_committed_state_value: (
+            StateKey::raw(last_committed_state_index.to_le_bytes().to_vec()),
+            StateValue::new_legacy(vec![1, 2, 3].into()),
+        ),
+        num_committed_leaves: last_committed_state_index + 1,
+    }
+}
@@ -3,7 +3,7 @@
 
 use crate::{
     error::Error,
-    metadata_storage::MetadataStorageInterface,
+    metadata_storage::{MetadataStorageInterface, StateSnapshotChunkProgress},
     storage_synchronizer::StorageSynchronizerInterface,
     tests::utils::{create_empty_epoch_state, create_epoch_ending_ledger_info},
 };
@@ -345,6 +345,11 @@ mock! {
             target_ledger_info: &LedgerInfoWithSignatures,
         ) -> Result<u64, Error>;
 
+        fn get_snapshot_chunk_progress(
+            &self,
+            target_ledger_info: &LedgerInfoWithSignatures,
+        ) -> Result<Option<StateSnapshotChunkProgress>, Error>;
+
         fn previous_snapshot_sync_target(&self) -> Result<Option<LedgerInfoWithSignatures>, Error>;
 
         fn update_last_persisted_state_value_index(
@@ -353,6 +358,12 @@ mock! {
             last_persisted_state_value_index: u64,
             snapshot_sync_completed: bool,
         ) -> Result<(), Error>;
+
+        fn update_snapshot_chunk_progress(
+            &self,
+            target_ledger_info: &LedgerInfoWithSignatures,
+            chunk_progress: StateSnapshotChunkProgress,
+        ) -> Result<(), Error>;
     }
 
     impl Clone for MetadataStorage {
@@ -19,8 +19,8 @@ use aptos_config::{config::StorageServiceConfig, network_id::PeerNetworkId};
 use aptos_logger::{debug, error, sample, sample::SampleRate, trace, warn};
 use aptos_storage_service_types::{
     requests::{
-        DataRequest, EpochEndingLedgerInfoRequest, StateValuesWithProofRequest,
-        StorageServiceRequest, TransactionOutputsWithProofRequest,
+        DataRequest, EpochEndingLedgerInfoRequest, StateValuesByPrefixRequest,
+        StateValuesWithProofRequest, StorageServiceRequest, TransactionOutputsWithProofRequest,
         TransactionsOrOutputsWithProofRequest, TransactionsWithProofRequest,
     },
     responses::{
@@ -185,13 +185,21 @@ impl<T: StorageReaderInterface> Handler<T> {
         match &request.data_request {
             DataRequest::GetServerProtocolVersion => {
                 let data_response = self.get_server_protocol_version();
-                StorageServiceResponse::new(data_response, request.use_compression)
-                    .map_err(|error| error.into())
+                StorageServiceResponse::new_with_codec(
+                    data_response,
+                    request.use_compression,
+                    request.compression_codec,
+                )
+                .map_err(|error| error.into())
             },
             DataRequest::GetStorageServerSummary => {
                 let data_response = self.get_storage_server_summary();
-                StorageServiceResponse::new(data_response, request.use_compression)
-                    .map_err(|error| error.into())
+                StorageServiceResponse::new_with_codec(
+                    data_response,
+                    request.use_compression,
+                    request.compression_codec,
+                )
+                .map_err(|error| error.into())
             },
             _ => self.process_cachable_request(peer_network_id, request),
         }
@@ -383,6 +391,9 @@ impl<T: StorageReaderInterface> Handler<T> {
             DataRequest::GetStateValuesWithProof(request) => {
                 self.get_state_value_chunk_with_proof(request)
             },
+            DataRequest::GetStateValuesByPrefix(request) => {
+                self.get_state_values_by_prefix_with_proof(request)
+            },
             DataRequest::GetEpochEndingLedgerInfos(request) => {
                 self.get_epoch_ending_ledger_infos(request)
             },
@@ -403,7 +414,11 @@ impl<T: StorageReaderInterface> Handler<T> {
                 request
             ))),
         }?;
-        let storage_response = StorageServiceResponse::new(data_response, request.use_compression)?;
+        let storage_response = StorageServiceResponse::new_with_codec(
+            data_response,
+            request.use_compression,
+            request.compression_codec,
+        )?;
 
         // Cache the response before returning
         self.lru_response_cache
@@ -427,6 +442,23 @@ impl<T: StorageReaderInterface> Handler<T> {
         ))
     }
 
+    fn get_state_values_by_prefix_with_proof(
+        &self,
+        request: &StateValuesByPrefixRequest,
+    ) -> aptos_storage_service_types::Result<DataResponse, Error> {
+        let state_values_by_prefix_with_proof =
+            self.storage.get_state_values_by_prefix_with_proof(
+                request.version,
+                &request.key_prefix,
+                request.start_key.as_ref(),
+                request.max_num_state_values,
+            )?;
+
+        Ok(DataResponse::StateValuesByPrefixWithProof(
+            state_values_by_prefix_with_proof,
+        ))
+    }
+
     fn get_epoch_ending_ledger_infos(
         &self,
         request: &EpochEndingLedgerInfoRequest,
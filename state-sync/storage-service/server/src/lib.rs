@@ -16,7 +16,7 @@ use aptos_config::{
     network_id::PeerNetworkId,
 };
 use aptos_logger::prelude::*;
-use aptos_network::application::storage::PeersAndMetadata;
+use aptos_network::{application::storage::PeersAndMetadata, ProtocolId};
 use aptos_storage_service_notifications::StorageServiceNotificationListener;
 use aptos_storage_service_types::{
     requests::StorageServiceRequest,
@@ -400,7 +400,7 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
             // Log the request
             let peer_network_id = network_request.peer_network_id;
             let protocol_id = network_request.protocol_id;
-            let storage_service_request = network_request.storage_service_request;
+            let mut storage_service_request = network_request.storage_service_request;
             trace!(LogSchema::new(LogEntry::ReceivedStorageRequest)
                 .request(&storage_service_request)
                 .message(&format!(
@@ -408,6 +408,13 @@ impl<T: StorageReaderInterface + Send + Sync> StorageServiceServer<T> {
                     peer_network_id, protocol_id,
                 )));
 
+            // Only peers that support zstd may receive responses compressed
+            // with the configured codec. All other peers receive lz4.
+            if protocol_id == ProtocolId::StorageServiceRpcZstd {
+                storage_service_request.compression_codec =
+                    self.storage_service_config.compression_codec;
+            }
+
             // All handler methods are currently CPU-bound and synchronous
             // I/O-bound, so we want to spawn on the blocking thread pool to
             // avoid starving other async tasks on the same runtime.
@@ -119,7 +119,8 @@ impl OptimisticFetchRequest {
             request => unreachable!("Unexpected optimistic fetch request: {:?}", request),
         };
         let storage_request =
-            StorageServiceRequest::new(data_request, self.request.use_compression);
+            StorageServiceRequest::new(data_request, self.request.use_compression)
+                .with_compression_codec(self.request.compression_codec);
         Ok(storage_request)
     }
 
@@ -6,11 +6,15 @@ use aptos_config::config::StorageServiceConfig;
 use aptos_logger::debug;
 use aptos_storage_interface::DbReader;
 use aptos_storage_service_types::responses::{
-    CompleteDataRange, DataResponse, DataSummary, TransactionOrOutputListWithProof,
+    CompleteDataRange, DataResponse, DataSummary, StateValuesByPrefixWithProof,
+    TransactionOrOutputListWithProof,
 };
 use aptos_types::{
     epoch_change::EpochChangeProof,
-    state_store::state_value::StateValueChunkWithProof,
+    state_store::{
+        state_key::StateKey, state_key_prefix::StateKeyPrefix,
+        state_value::StateValueChunkWithProof,
+    },
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use serde::Serialize;
@@ -88,6 +92,19 @@ pub trait StorageReaderInterface: Clone + Send + 'static {
         start_index: u64,
         end_index: u64,
     ) -> aptos_storage_service_types::Result<StateValueChunkWithProof, Error>;
+
+    /// Returns a list of state values (each with a proof) under the given
+    /// `key_prefix` at the specified version, starting at `start_key`
+    /// (inclusive, if specified). At most `max_num_state_values` are
+    /// returned. In some cases, less state values may be returned (e.g.,
+    /// due to network or chunk limits).
+    fn get_state_values_by_prefix_with_proof(
+        &self,
+        version: u64,
+        key_prefix: &StateKeyPrefix,
+        start_key: Option<&StateKey>,
+        max_num_state_values: u64,
+    ) -> aptos_storage_service_types::Result<StateValuesByPrefixWithProof, Error>;
 }
 
 /// The underlying implementation of the StorageReaderInterface, used by the
@@ -494,6 +511,81 @@ impl StorageReaderInterface for StorageReader {
             version, start_index, end_index
         )))
     }
+
+    fn get_state_values_by_prefix_with_proof(
+        &self,
+        version: u64,
+        key_prefix: &StateKeyPrefix,
+        start_key: Option<&StateKey>,
+        max_num_state_values: u64,
+    ) -> aptos_storage_service_types::Result<StateValuesByPrefixWithProof, Error> {
+        if max_num_state_values == 0 {
+            return Err(Error::InvalidRequest(
+                "The max number of state values must be > 0!".into(),
+            ));
+        }
+        let num_state_values_to_fetch =
+            min(max_num_state_values, self.config.max_state_chunk_size) as usize;
+
+        // Fetch the state values under the prefix. We fetch one additional
+        // state value to identify the key to continue from (if any).
+        let state_values = self
+            .storage
+            .get_prefixed_state_value_iterator(key_prefix, start_key, version)
+            .and_then(|state_value_iterator| {
+                state_value_iterator
+                    .take(num_state_values_to_fetch + 1)
+                    .collect::<Result<Vec<_>, _>>()
+            })
+            .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+
+        // Fetch a proof for each state value against the state root at the version
+        let mut state_values_with_proof = vec![];
+        for (state_key, state_value) in state_values.iter().take(num_state_values_to_fetch) {
+            let (_, proof) = self
+                .storage
+                .get_state_value_with_proof_by_version(state_key, version)
+                .map_err(|error| Error::StorageErrorEncountered(error.to_string()))?;
+            state_values_with_proof.push((state_key.clone(), state_value.clone(), proof));
+        }
+
+        // Attempt to serve the request
+        let mut num_state_values_to_return = state_values_with_proof.len();
+        loop {
+            let state_values_by_prefix_with_proof = StateValuesByPrefixWithProof {
+                version,
+                state_values_with_proof: state_values_with_proof
+                    .iter()
+                    .take(num_state_values_to_return)
+                    .cloned()
+                    .collect(),
+                next_key: state_values
+                    .get(num_state_values_to_return)
+                    .map(|(state_key, _)| state_key.clone()),
+            };
+            if num_state_values_to_return <= 1 {
+                return Ok(state_values_by_prefix_with_proof); // We cannot return less than a single item
+            }
+
+            // Attempt to divide up the request if it overflows the message size
+            let (overflow_frame, num_bytes) = check_overflow_network_frame(
+                &state_values_by_prefix_with_proof,
+                self.config.max_network_chunk_bytes,
+            )?;
+            if !overflow_frame {
+                return Ok(state_values_by_prefix_with_proof);
+            } else {
+                increment_network_frame_overflow(
+                    DataResponse::StateValuesByPrefixWithProof(state_values_by_prefix_with_proof)
+                        .get_label(),
+                );
+                let new_num_state_values_to_return = num_state_values_to_return / 2;
+                debug!("The request for {:?} state values by prefix was too large (num bytes: {:?}). Retrying with {:?}.",
+                    num_state_values_to_return, num_bytes, new_num_state_values_to_return);
+                num_state_values_to_return = new_num_state_values_to_return; // Try again with half the amount of data
+            }
+        }
+    }
 }
 
 /// Calculate `(start..=end).len()`. Returns an error if `end < start` or
@@ -125,7 +125,8 @@ impl SubscriptionRequest {
             request => unreachable!("Unexpected subscription request: {:?}", request),
         };
         let storage_request =
-            StorageServiceRequest::new(data_request, self.request.use_compression);
+            StorageServiceRequest::new(data_request, self.request.use_compression)
+                .with_compression_codec(self.request.compression_codec);
         Ok(storage_request)
     }
 
@@ -0,0 +1,66 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::mock::MockClient;
+use aptos_compression::CompressionCodec;
+use aptos_config::config::StorageServiceConfig;
+use aptos_network::protocols::wire::handshake::v1::ProtocolId;
+use aptos_storage_service_types::{
+    requests::{DataRequest, StorageServiceRequest},
+    responses::{DataResponse, ServerProtocolVersion, StorageServiceResponse},
+};
+
+#[tokio::test]
+async fn test_compression_codec_negotiation() {
+    // Create a storage service config that uses zstd
+    let compression_codec = CompressionCodec::Zstd { level: 3 };
+    let storage_config = StorageServiceConfig {
+        compression_codec,
+        ..Default::default()
+    };
+
+    // Create the storage client and server
+    let (mut mock_client, service, _, _, _) = MockClient::new(None, Some(storage_config));
+    tokio::spawn(service.start());
+
+    // Create the expected data response
+    let expected_data_response = DataResponse::ServerProtocolVersion(ServerProtocolVersion {
+        protocol_version: 1,
+    });
+
+    // Verify that peers supporting zstd receive zstd compressed data
+    let response = get_protocol_version(&mut mock_client, ProtocolId::StorageServiceRpcZstd).await;
+    let expected_response = StorageServiceResponse::new_with_codec(
+        expected_data_response.clone(),
+        true,
+        compression_codec,
+    )
+    .unwrap();
+    assert_eq!(response, expected_response);
+    assert_eq!(
+        response.get_data_response().unwrap(),
+        expected_data_response
+    );
+
+    // Verify that older peers still receive lz4 compressed data
+    let response = get_protocol_version(&mut mock_client, ProtocolId::StorageServiceRpc).await;
+    let expected_response =
+        StorageServiceResponse::new(expected_data_response.clone(), true).unwrap();
+    assert_eq!(response, expected_response);
+    assert_eq!(
+        response.get_data_response().unwrap(),
+        expected_data_response
+    );
+}
+
+/// Sends a compressed protocol version request over the given protocol
+async fn get_protocol_version(
+    mock_client: &mut MockClient,
+    protocol_id: ProtocolId,
+) -> StorageServiceResponse {
+    let storage_request = StorageServiceRequest::new(DataRequest::GetServerProtocolVersion, true);
+    let receiver = mock_client
+        .send_request_with_protocol(storage_request, None, None, protocol_id)
+        .await;
+    mock_client.wait_for_response(receiver).await.unwrap()
+}
@@ -38,6 +38,7 @@ use aptos_types::{
     state_proof::StateProof,
     state_store::{
         state_key::StateKey,
+        state_key_prefix::StateKeyPrefix,
         state_value::{StateValue, StateValueChunkWithProof},
     },
     transaction::{
@@ -157,11 +158,23 @@ impl MockClient {
         request: StorageServiceRequest,
         peer_id: Option<AccountAddress>,
         network_id: Option<NetworkId>,
+    ) -> Receiver<Result<bytes::Bytes, aptos_network::protocols::network::RpcError>> {
+        self.send_request_with_protocol(request, peer_id, network_id, ProtocolId::StorageServiceRpc)
+            .await
+    }
+
+    /// Send the specified storage request over the given protocol and return
+    /// the receiver on which to expect a result.
+    pub async fn send_request_with_protocol(
+        &mut self,
+        request: StorageServiceRequest,
+        peer_id: Option<AccountAddress>,
+        network_id: Option<NetworkId>,
+        protocol_id: ProtocolId,
     ) -> Receiver<Result<bytes::Bytes, aptos_network::protocols::network::RpcError>> {
         // Create the inbound rpc request
         let peer_id = peer_id.unwrap_or_else(PeerId::random);
         let network_id = network_id.unwrap_or_else(get_random_network_id);
-        let protocol_id = ProtocolId::StorageServiceRpc;
         let data = protocol_id
             .to_bytes(&StorageServiceMessage::Request(request))
             .unwrap();
@@ -283,6 +296,13 @@ mock! {
             _ledger_version: Version,
         ) -> Result<Version>;
 
+        fn get_prefixed_state_value_iterator(
+            &self,
+            key_prefix: &StateKeyPrefix,
+            cursor: Option<&StateKey>,
+            version: Version,
+        ) -> Result<Box<dyn Iterator<Item = Result<(StateKey, StateValue)>> + '_>>;
+
         fn get_latest_ledger_info_option(&self) -> Result<Option<LedgerInfoWithSignatures>>;
 
         fn get_latest_ledger_info(&self) -> Result<LedgerInfoWithSignatures>;
@@ -2,6 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 mod cache;
+mod compression_codec;
 mod epoch_ending;
 mod mock;
 mod new_transaction_outputs;
@@ -12,6 +13,7 @@ mod optimistic_fetch;
 mod protocol_version;
 mod request_moderator;
 mod state_values;
+mod state_values_by_prefix;
 mod storage_summary;
 mod subscribe_transaction_outputs;
 mod subscribe_transactions;
@@ -0,0 +1,242 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::tests::{
+    mock,
+    mock::{MockClient, MockDatabaseReader},
+    utils,
+};
+use aptos_config::config::StorageServiceConfig;
+use aptos_crypto::HashValue;
+use aptos_storage_service_types::{
+    requests::{DataRequest, StateValuesByPrefixRequest},
+    responses::{DataResponse, StateValuesByPrefixWithProof, StorageServiceResponse},
+    StorageServiceError,
+};
+use aptos_types::{
+    access_path::AccessPath,
+    account_address::AccountAddress,
+    proof::SparseMerkleProof,
+    state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix, state_value::StateValue},
+};
+use claims::assert_matches;
+use mockall::predicate::eq;
+
+#[tokio::test]
+async fn test_get_state_values_by_prefix() {
+    // Test requests that cover all, and only some, of the state values
+    let num_state_values = 10;
+    for max_num_state_values in [1, 5, num_state_values, num_state_values * 2] {
+        // Create test data
+        let version = 101;
+        let address = AccountAddress::random();
+        let state_values = create_state_keys_and_values(address, num_state_values);
+
+        // Create the mock db reader
+        let mut db_reader = mock::create_mock_db_reader();
+        expect_get_prefixed_state_value_iterator(&mut db_reader, state_values.clone());
+        let num_expected_values = max_num_state_values.min(num_state_values) as usize;
+        for (state_key, _) in state_values.iter().take(num_expected_values) {
+            expect_get_state_value_with_proof_by_version(&mut db_reader, state_key, version);
+        }
+
+        // Create the storage client and server
+        let (mut mock_client, mut service, _, _, _) = MockClient::new(Some(db_reader), None);
+        utils::update_storage_server_summary(&mut service, version, 10);
+        tokio::spawn(service.start());
+
+        // Process a request to fetch the state values under the account
+        let response = get_state_values_by_prefix(
+            &mut mock_client,
+            version,
+            StateKeyPrefix::from(address),
+            max_num_state_values,
+        )
+        .await
+        .unwrap();
+
+        // Verify the response is correct
+        let expected_state_values_with_proof = state_values
+            .iter()
+            .take(num_expected_values)
+            .map(|(state_key, state_value)| {
+                (state_key.clone(), state_value.clone(), create_proof())
+            })
+            .collect();
+        let expected_next_key = state_values
+            .get(num_expected_values)
+            .map(|(state_key, _)| state_key.clone());
+        assert_matches!(response, StorageServiceResponse::RawResponse(_));
+        assert_eq!(
+            response.get_data_response().unwrap(),
+            DataResponse::StateValuesByPrefixWithProof(StateValuesByPrefixWithProof {
+                version,
+                state_values_with_proof: expected_state_values_with_proof,
+                next_key: expected_next_key,
+            })
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_get_state_values_by_prefix_chunk_limit() {
+    // Create test data
+    let max_state_chunk_size = 10;
+    let version = 101;
+    let address = AccountAddress::random();
+    let state_values = create_state_keys_and_values(address, max_state_chunk_size * 2);
+
+    // Create the mock db reader
+    let mut db_reader = mock::create_mock_db_reader();
+    expect_get_prefixed_state_value_iterator(&mut db_reader, state_values.clone());
+    for (state_key, _) in state_values.iter().take(max_state_chunk_size as usize) {
+        expect_get_state_value_with_proof_by_version(&mut db_reader, state_key, version);
+    }
+
+    // Create the storage client and server
+    let storage_config = StorageServiceConfig {
+        max_state_chunk_size,
+        ..Default::default()
+    };
+    let (mut mock_client, mut service, _, _, _) =
+        MockClient::new(Some(db_reader), Some(storage_config));
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch more state values than the max chunk size
+    let response = get_state_values_by_prefix(
+        &mut mock_client,
+        version,
+        StateKeyPrefix::from(address),
+        max_state_chunk_size * 10,
+    )
+    .await
+    .unwrap();
+
+    // Verify the response is truncated to the max chunk size
+    match response.get_data_response().unwrap() {
+        DataResponse::StateValuesByPrefixWithProof(state_values_by_prefix_with_proof) => {
+            assert_eq!(
+                state_values_by_prefix_with_proof
+                    .state_values_with_proof
+                    .len() as u64,
+                max_state_chunk_size
+            );
+            assert_eq!(
+                state_values_by_prefix_with_proof.next_key,
+                Some(state_values[max_state_chunk_size as usize].0.clone())
+            );
+        },
+        _ => panic!(
+            "Expected state values by prefix with proof but got: {:?}",
+            response
+        ),
+    }
+}
+
+#[tokio::test]
+async fn test_get_state_values_by_prefix_invalid() {
+    // Create test data
+    let version = 101;
+
+    // Create the storage client and server
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, version, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch zero state values
+    let response = get_state_values_by_prefix(
+        &mut mock_client,
+        version,
+        StateKeyPrefix::from(AccountAddress::random()),
+        0,
+    )
+    .await
+    .unwrap_err();
+
+    // Verify the request is invalid
+    assert_matches!(response, StorageServiceError::InvalidRequest(_));
+}
+
+#[tokio::test]
+async fn test_get_state_values_by_prefix_not_serviceable() {
+    // Create test data
+    let version = 101;
+
+    // Create the storage client and server (that cannot service the request)
+    let (mut mock_client, mut service, _, _, _) = MockClient::new(None, None);
+    utils::update_storage_server_summary(&mut service, version - 1, 10);
+    tokio::spawn(service.start());
+
+    // Process a request to fetch the state values under an account
+    let response = get_state_values_by_prefix(
+        &mut mock_client,
+        version,
+        StateKeyPrefix::from(AccountAddress::random()),
+        10,
+    )
+    .await
+    .unwrap_err();
+
+    // Verify the request is not serviceable
+    assert_matches!(response, StorageServiceError::InvalidRequest(_));
+}
+
+/// Returns the (dummy) sparse merkle proof used by the mock db
+fn create_proof() -> SparseMerkleProof {
+    SparseMerkleProof::new(None, vec![HashValue::zero()])
+}
+
+/// Creates the given number of state keys and values under the specified account
+fn create_state_keys_and_values(
+    address: AccountAddress,
+    num_keys_and_values: u64,
+) -> Vec<(StateKey, StateValue)> {
+    (0..num_keys_and_values)
+        .map(|index| {
+            let access_path = AccessPath::new(address, index.to_be_bytes().to_vec());
+            let state_value = StateValue::new_legacy(index.to_be_bytes().to_vec().into());
+            (StateKey::access_path(access_path), state_value)
+        })
+        .collect()
+}
+
+/// Sets an expectation on the given mock db for a call to iterate over prefixed state values
+fn expect_get_prefixed_state_value_iterator(
+    mock_db: &mut MockDatabaseReader,
+    state_values: Vec<(StateKey, StateValue)>,
+) {
+    mock_db
+        .expect_get_prefixed_state_value_iterator()
+        .times(1)
+        .returning(move |_, _, _| Ok(Box::new(state_values.clone().into_iter().map(Ok))));
+}
+
+/// Sets an expectation on the given mock db for a call to fetch a state value with proof
+fn expect_get_state_value_with_proof_by_version(
+    mock_db: &mut MockDatabaseReader,
+    state_key: &StateKey,
+    version: u64,
+) {
+    mock_db
+        .expect_get_state_value_with_proof_by_version()
+        .times(1)
+        .with(eq(state_key.clone()), eq(version))
+        .returning(move |_, _| Ok((None, create_proof())));
+}
+
+/// Sends a state values by prefix request and processes the response
+async fn get_state_values_by_prefix(
+    mock_client: &mut MockClient,
+    version: u64,
+    key_prefix: StateKeyPrefix,
+    max_num_state_values: u64,
+) -> Result<StorageServiceResponse, StorageServiceError> {
+    let data_request = DataRequest::GetStateValuesByPrefix(StateValuesByPrefixRequest {
+        version,
+        key_prefix,
+        start_key: None,
+        max_num_state_values,
+    });
+    utils::send_storage_request(mock_client, false, data_request).await
+}
@@ -96,6 +96,7 @@ pub fn notify_peer_of_new_data<T: StorageReaderInterface>(
 ) -> aptos_storage_service_types::Result<DataResponse, Error> {
     // Handle the storage service request to fetch the missing data
     let use_compression = missing_data_request.use_compression;
+    let compression_codec = missing_data_request.compression_codec;
     let handler = Handler::new(
         cached_storage_server_summary,
         optimistic_fetches,
@@ -159,16 +160,19 @@ pub fn notify_peer_of_new_data<T: StorageReaderInterface>(
     };
 
     // Create the storage service response
-    let storage_response =
-        match StorageServiceResponse::new(transformed_data_response.clone(), use_compression) {
-            Ok(storage_response) => storage_response,
-            Err(error) => {
-                return Err(Error::UnexpectedErrorEncountered(format!(
-                    "Failed to create transformed response! Error: {:?}",
-                    error
-                )));
-            },
-        };
+    let storage_response = match StorageServiceResponse::new_with_codec(
+        transformed_data_response.clone(),
+        use_compression,
+        compression_codec,
+    ) {
+        Ok(storage_response) => storage_response,
+        Err(error) => {
+            return Err(Error::UnexpectedErrorEncountered(format!(
+                "Failed to create transformed response! Error: {:?}",
+                error
+            )));
+        },
+    };
 
     // Send the response to the peer
     handler.send_response(missing_data_request, Ok(storage_response), response_sender);
@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::COMPRESSION_SUFFIX_LABEL;
-use aptos_types::transaction::Version;
+use aptos_compression::CompressionCodec;
+use aptos_types::{
+    state_store::{state_key::StateKey, state_key_prefix::StateKeyPrefix},
+    transaction::Version,
+};
 use serde::{Deserialize, Serialize};
 
 /// A storage service request.
@@ -10,6 +14,11 @@ use serde::{Deserialize, Serialize};
 pub struct StorageServiceRequest {
     pub data_request: DataRequest, // The data to fetch from the storage service
     pub use_compression: bool,     // Whether or not the client wishes data to be compressed
+    // The codec to compress the response with. This is not sent over the wire: the
+    // server sets it based on the protocol the request was received on, as older
+    // clients only understand lz4.
+    #[serde(skip)]
+    pub compression_codec: CompressionCodec,
 }
 
 impl StorageServiceRequest {
@@ -17,9 +26,16 @@ impl StorageServiceRequest {
         Self {
             data_request,
             use_compression,
+            compression_codec: CompressionCodec::Lz4,
         }
     }
 
+    /// Sets the codec to compress the response with (if compression is used)
+    pub fn with_compression_codec(mut self, compression_codec: CompressionCodec) -> Self {
+        self.compression_codec = compression_codec;
+        self
+    }
+
     /// Returns a summary label for the request
     pub fn get_label(&self) -> String {
         let mut label = self.data_request.get_label().to_string();
@@ -47,6 +63,7 @@ pub enum DataRequest {
     SubscribeTransactionOutputsWithProof(SubscribeTransactionOutputsWithProofRequest), // Subscribes to transaction outputs with a proof
     SubscribeTransactionsOrOutputsWithProof(SubscribeTransactionsOrOutputsWithProofRequest), // Subscribes to transactions or outputs with a proof
     SubscribeTransactionsWithProof(SubscribeTransactionsWithProofRequest), // Subscribes to transactions with a proof
+    GetStateValuesByPrefix(StateValuesByPrefixRequest), // Fetches a list of states under a key prefix with proofs
 }
 
 impl DataRequest {
@@ -73,6 +90,7 @@ impl DataRequest {
                 "subscribe_transactions_or_outputs_with_proof"
             },
             Self::SubscribeTransactionsWithProof(_) => "subscribe_transactions_with_proof",
+            Self::GetStateValuesByPrefix(_) => "get_state_values_by_prefix",
         }
     }
 
@@ -130,6 +148,16 @@ pub struct StateValuesWithProofRequest {
     pub end_index: u64,   // The index to stop fetching state values (inclusive)
 }
 
+/// A storage service request for fetching a list of state values (each with
+/// a proof) under a key prefix (e.g., an account address) at a specified version.
+#[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct StateValuesByPrefixRequest {
+    pub version: u64,                // The version to fetch the state values at
+    pub key_prefix: StateKeyPrefix,  // The prefix of the state keys to fetch
+    pub start_key: Option<StateKey>, // The key to start fetching state values (inclusive)
+    pub max_num_state_values: u64,   // The max number of state values to fetch
+}
+
 /// A storage service request for fetching a transaction output list with a
 /// corresponding proof.
 #[derive(Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
@@ -5,23 +5,32 @@ use crate::{
     requests::DataRequest::{
         GetEpochEndingLedgerInfos, GetNewTransactionOutputsWithProof,
         GetNewTransactionsOrOutputsWithProof, GetNewTransactionsWithProof,
-        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValuesWithProof,
-        GetStorageServerSummary, GetTransactionOutputsWithProof, GetTransactionsOrOutputsWithProof,
-        GetTransactionsWithProof, SubscribeTransactionOutputsWithProof,
-        SubscribeTransactionsOrOutputsWithProof, SubscribeTransactionsWithProof,
+        GetNumberOfStatesAtVersion, GetServerProtocolVersion, GetStateValuesByPrefix,
+        GetStateValuesWithProof, GetStorageServerSummary, GetTransactionOutputsWithProof,
+        GetTransactionsOrOutputsWithProof, GetTransactionsWithProof,
+        SubscribeTransactionOutputsWithProof, SubscribeTransactionsOrOutputsWithProof,
+        SubscribeTransactionsWithProof,
     },
     responses::Error::DegenerateRangeError,
     Epoch, StorageServiceRequest, COMPRESSION_SUFFIX_LABEL,
 };
-use aptos_compression::{metrics::CompressionClient, CompressedData, CompressionError};
+use aptos_compression::{
+    metrics::CompressionClient, CompressedData, CompressionCodec, CompressionError,
+};
 use aptos_config::config::{
     AptosDataClientConfig, StorageServiceConfig, MAX_APPLICATION_MESSAGE_SIZE,
 };
+use aptos_crypto::{hash::CryptoHash, HashValue};
 use aptos_time_service::{TimeService, TimeServiceTrait};
 use aptos_types::{
     epoch_change::EpochChangeProof,
     ledger_info::LedgerInfoWithSignatures,
-    state_store::state_value::StateValueChunkWithProof,
+    proof::SparseMerkleProof,
+    state_store::{
+        state_key::StateKey,
+        state_key_prefix::StateKeyPrefix,
+        state_value::{StateValue, StateValueChunkWithProof},
+    },
     transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
 };
 use num_traits::{PrimInt, Zero};
@@ -62,13 +71,24 @@ pub enum StorageServiceResponse {
 }
 
 impl StorageServiceResponse {
-    /// Creates a new response and performs compression if required
+    /// Creates a new response and performs (lz4) compression if required
     pub fn new(data_response: DataResponse, perform_compression: bool) -> Result<Self, Error> {
+        Self::new_with_codec(data_response, perform_compression, CompressionCodec::Lz4)
+    }
+
+    /// Creates a new response and performs compression (using
+    /// the specified codec) if required.
+    pub fn new_with_codec(
+        data_response: DataResponse,
+        perform_compression: bool,
+        compression_codec: CompressionCodec,
+    ) -> Result<Self, Error> {
         if perform_compression {
             let raw_data = bcs::to_bytes(&data_response)
                 .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
-            let compressed_data = aptos_compression::compress(
+            let compressed_data = aptos_compression::compress_with_codec(
                 raw_data,
+                compression_codec,
                 CompressionClient::StateSync,
                 MAX_APPLICATION_MESSAGE_SIZE,
             )?;
@@ -136,6 +156,7 @@ pub enum DataResponse {
     TransactionsWithProof(TransactionListWithProof),
     NewTransactionsOrOutputsWithProof((TransactionOrOutputListWithProof, LedgerInfoWithSignatures)),
     TransactionsOrOutputsWithProof(TransactionOrOutputListWithProof),
+    StateValuesByPrefixWithProof(StateValuesByPrefixWithProof),
 }
 
 impl DataResponse {
@@ -153,6 +174,7 @@ impl DataResponse {
             Self::TransactionsWithProof(_) => "transactions_with_proof",
             Self::NewTransactionsOrOutputsWithProof(_) => "new_transactions_or_outputs_with_proof",
             Self::TransactionsOrOutputsWithProof(_) => "transactions_or_outputs_with_proof",
+            Self::StateValuesByPrefixWithProof(_) => "state_values_by_prefix_with_proof",
         }
     }
 }
@@ -344,6 +366,62 @@ impl TryFrom<StorageServiceResponse> for TransactionOrOutputListWithProof {
     }
 }
 
+impl TryFrom<StorageServiceResponse> for StateValuesByPrefixWithProof {
+    type Error = crate::responses::Error;
+
+    fn try_from(response: StorageServiceResponse) -> crate::Result<Self, Self::Error> {
+        let data_response = response.get_data_response()?;
+        match data_response {
+            DataResponse::StateValuesByPrefixWithProof(inner) => Ok(inner),
+            _ => Err(Error::UnexpectedResponseError(format!(
+                "expected state_values_by_prefix_with_proof, found {}",
+                data_response.get_label()
+            ))),
+        }
+    }
+}
+
+/// A list of state values under a key prefix at a specific version. Each
+/// state value comes with a sparse merkle proof against the root hash of
+/// the state tree at that version.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StateValuesByPrefixWithProof {
+    pub version: Version, // The version the state values (and proofs) are relative to
+    pub state_values_with_proof: Vec<(StateKey, StateValue, SparseMerkleProof)>, // The state values (in key order)
+    pub next_key: Option<StateKey>, // The key to continue from (if more state values exist)
+}
+
+impl StateValuesByPrefixWithProof {
+    /// Verifies that all state values are under the given key prefix and
+    /// that each proof is valid against the given state root hash.
+    pub fn verify(
+        &self,
+        key_prefix: &StateKeyPrefix,
+        state_root_hash: HashValue,
+    ) -> crate::Result<(), Error> {
+        for (state_key, state_value, proof) in &self.state_values_with_proof {
+            let is_prefix = key_prefix
+                .is_prefix(state_key)
+                .map_err(|error| Error::UnexpectedErrorEncountered(error.to_string()))?;
+            if !is_prefix {
+                return Err(Error::UnexpectedResponseError(format!(
+                    "State key {:?} is not under the key prefix {:?}",
+                    state_key, key_prefix
+                )));
+            }
+            proof
+                .verify(state_root_hash, state_key.hash(), Some(state_value))
+                .map_err(|error| {
+                    Error::UnexpectedResponseError(format!(
+                        "Invalid proof for state key {:?}: {:?}",
+                        state_key, error
+                    ))
+                })?;
+        }
+        Ok(())
+    }
+}
+
 /// The protocol version run by this server. Clients request this first to
 /// identify what API calls and data requests the server supports.
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -485,6 +563,20 @@ impl DataSummary {
 
                 can_serve_states && can_create_proof
             },
+            GetStateValuesByPrefix(request) => {
+                let can_serve_states = self
+                    .states
+                    .map(|range| range.contains(request.version))
+                    .unwrap_or(false);
+
+                let can_create_proof = self
+                    .synced_ledger_info
+                    .as_ref()
+                    .map(|li| li.ledger_info().version() >= request.version)
+                    .unwrap_or(false);
+
+                can_serve_states && can_create_proof
+            },
             GetTransactionOutputsWithProof(request) => {
                 let desired_range =
                     match CompleteDataRange::new(request.start_version, request.end_version) {
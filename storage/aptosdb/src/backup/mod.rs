@@ -4,6 +4,7 @@
 
 pub mod backup_handler;
 pub mod restore_handler;
+pub mod restore_progress;
 pub mod restore_utils;
 
 #[cfg(test)]
@@ -3,8 +3,8 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::{
-    backup::restore_utils,
-    db_metadata::{DbMetadataKey, DbMetadataSchema},
+    backup::{restore_progress::RestoreProgress, restore_utils},
+    db_metadata::{DbMetadataKey, DbMetadataSchema, DbMetadataValue},
     event_store::EventStore,
     ledger_store::LedgerStore,
     state_restore::{StateSnapshotRestore, StateSnapshotRestoreMode},
@@ -12,12 +12,12 @@ use crate::{
     transaction_store::TransactionStore,
     AptosDB,
 };
-use anyhow::Result;
+use anyhow::{ensure, Result};
 use aptos_crypto::HashValue;
 use aptos_storage_interface::DbReader;
 use aptos_types::{
     contract_event::ContractEvent,
-    ledger_info::LedgerInfoWithSignatures,
+    ledger_info::{LedgerInfo, LedgerInfoWithSignatures},
     proof::definition::LeafCount,
     state_store::{state_key::StateKey, state_value::StateValue},
     transaction::{Transaction, TransactionInfo, Version},
@@ -165,4 +165,39 @@ impl RestoreHandler {
         }
         Ok(None)
     }
+
+    pub fn get_restore_progress(&self) -> Result<Option<RestoreProgress>> {
+        Ok(self
+            .aptosdb
+            .ledger_db
+            .metadata_db()
+            .get::<DbMetadataSchema>(&DbMetadataKey::RestoreCoordinatorProgress)?
+            .map(DbMetadataValue::expect_restore_progress))
+    }
+
+    pub fn save_restore_progress(&self, progress: &RestoreProgress) -> Result<()> {
+        self.aptosdb
+            .ledger_db
+            .metadata_db()
+            .put::<DbMetadataSchema>(
+                &DbMetadataKey::RestoreCoordinatorProgress,
+                &DbMetadataValue::RestoreProgress(progress.clone()),
+            )
+    }
+
+    /// Epoch ending LedgerInfos of epochs [0, end_epoch), which must have been restored.
+    pub fn get_epoch_ending_ledger_infos(&self, end_epoch: u64) -> Result<Vec<LedgerInfo>> {
+        let ledger_infos = self
+            .ledger_store
+            .get_epoch_ending_ledger_info_iter(0, end_epoch)?
+            .map(|li| li.map(|li| li.ledger_info().clone()))
+            .collect::<Result<Vec<_>>>()?;
+        ensure!(
+            ledger_infos.len() as u64 == end_epoch,
+            "Expecting {} epoch ending LedgerInfos in DB, found {}.",
+            end_epoch,
+            ledger_infos.len(),
+        );
+        Ok(ledger_infos)
+    }
 }
@@ -0,0 +1,77 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use aptos_types::transaction::Version;
+use serde::{Deserialize, Serialize};
+
+/// Progress of a restore coordinated from backups, saved in the target DB so that an interrupted
+/// restore resumes where it stopped, and so that it can be inspected without restoring.
+///
+/// Transactions are not tracked here, the next version to restore is always the one after the
+/// latest transaction in the DB.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
+pub struct RestoreProgress {
+    /// Target version of the latest restore run.
+    pub target_version: Version,
+    /// Set once the epoch history is fully restored.
+    pub epoch_history: Option<EpochHistoryRestoreStatus>,
+    /// State snapshots started, in the order they were started.
+    pub state_snapshots: Vec<StateSnapshotRestoreStatus>,
+}
+
+impl RestoreProgress {
+    pub fn state_snapshot(&self, version: Version) -> Option<&StateSnapshotRestoreStatus> {
+        self.state_snapshots.iter().find(|s| s.version == version)
+    }
+
+    /// Records `chunks_done` out of `total_chunks` chunks of the snapshot at `version` are
+    /// restored.
+    pub fn update_state_snapshot(
+        &mut self,
+        version: Version,
+        manifest: &str,
+        chunks_done: u64,
+        total_chunks: u64,
+    ) {
+        let status = StateSnapshotRestoreStatus {
+            version,
+            manifest: manifest.to_string(),
+            chunks_done,
+            total_chunks,
+        };
+        match self
+            .state_snapshots
+            .iter_mut()
+            .find(|s| s.version == version)
+        {
+            Some(existing) => *existing = status,
+            None => self.state_snapshots.push(status),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
+pub struct EpochHistoryRestoreStatus {
+    /// The epoch history covers all epochs ending at or before this version.
+    pub target_version: Version,
+    /// Epoch ending LedgerInfos of all epochs before this one have been saved.
+    pub next_epoch: u64,
+}
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[cfg_attr(any(test, feature = "fuzzing"), derive(proptest_derive::Arbitrary))]
+pub struct StateSnapshotRestoreStatus {
+    pub version: Version,
+    /// Handle of the manifest the snapshot is restored from.
+    pub manifest: String,
+    pub chunks_done: u64,
+    pub total_chunks: u64,
+}
+
+impl StateSnapshotRestoreStatus {
+    pub fn is_finished(&self) -> bool {
+        self.chunks_done >= self.total_chunks
+    }
+}
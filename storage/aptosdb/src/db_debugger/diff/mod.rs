@@ -0,0 +1,521 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    db_debugger::ShardingConfig, event_store::EventStore, ledger_store::LedgerStore,
+    schema::state_value::StateValueSchema, state_kv_db::StateKvDb, state_merkle_db::StateMerkleDb,
+    transaction_store::TransactionStore, AptosDB,
+};
+use anyhow::{ensure, Result};
+use aptos_config::config::{RocksdbConfigs, StorageDirPaths};
+use aptos_crypto::HashValue;
+use aptos_jellyfish_merkle::{
+    node_type::{LeafNode, Node, NodeKey},
+    TreeReader,
+};
+use aptos_types::{
+    contract_event::ContractEvent,
+    nibble::Nibble,
+    state_store::{state_key::StateKey, state_value::StateValue},
+    transaction::Version,
+    write_set::WriteSet,
+};
+use clap::Parser;
+use std::{
+    collections::BTreeMap,
+    fmt::Debug,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[derive(Parser)]
+#[clap(
+    about = "Find where two DBs diverge: the first version where transaction infos, write \
+    sets or events differ, and the state keys whose values differ at a state snapshot version."
+)]
+pub struct Cmd {
+    #[clap(long, value_parser)]
+    db_a: PathBuf,
+
+    #[clap(long, value_parser)]
+    db_b: PathBuf,
+
+    #[clap(long, default_value_t = 0)]
+    start_version: Version,
+
+    /// Only compare this many versions, by default all versions both DBs have from
+    /// `start_version` on.
+    #[clap(long)]
+    num_versions: Option<usize>,
+
+    /// The version to compare the state trees at, which must be a state snapshot in both DBs.
+    /// Defaults to the latest state snapshot of DB A at or before the last common version.
+    #[clap(long)]
+    state_version: Option<Version>,
+
+    #[clap(long, default_value_t = 100)]
+    max_state_diffs: usize,
+
+    #[clap(long)]
+    skip_state_tree: bool,
+
+    #[clap(flatten)]
+    sharding_config: ShardingConfig,
+}
+
+struct Db {
+    ledger_store: LedgerStore,
+    transaction_store: TransactionStore,
+    event_store: EventStore,
+    state_merkle_db: StateMerkleDb,
+    state_kv_db: StateKvDb,
+}
+
+impl Db {
+    fn open(db_dir: &Path, sharding_config: &ShardingConfig) -> Result<Self> {
+        let (ledger_db, state_merkle_db, state_kv_db) = AptosDB::open_dbs(
+            &StorageDirPaths::from_path(db_dir),
+            RocksdbConfigs {
+                enable_storage_sharding: sharding_config.enable_storage_sharding,
+                ..Default::default()
+            },
+            /*readonly=*/ true,
+            /*max_num_nodes_per_lru_cache_shard=*/ 0,
+        )?;
+        let ledger_db = Arc::new(ledger_db);
+
+        Ok(Self {
+            ledger_store: LedgerStore::new(Arc::clone(&ledger_db)),
+            transaction_store: TransactionStore::new(Arc::clone(&ledger_db)),
+            event_store: EventStore::new(ledger_db.event_db_arc()),
+            state_merkle_db,
+            state_kv_db,
+        })
+    }
+}
+
+impl Cmd {
+    pub fn run(self) -> Result<()> {
+        let db_a = Db::open(&self.db_a, &self.sharding_config)?;
+        let db_b = Db::open(&self.db_b, &self.sharding_config)?;
+
+        let latest_version_a = db_a.ledger_store.get_latest_version()?;
+        let latest_version_b = db_b.ledger_store.get_latest_version()?;
+        println!(
+            "Latest versions: DB A: {}, DB B: {}",
+            latest_version_a, latest_version_b
+        );
+        let mut end_version = std::cmp::min(latest_version_a, latest_version_b);
+        if let Some(num_versions) = self.num_versions {
+            ensure!(num_versions > 0, "--num-versions must be positive.");
+            end_version = std::cmp::min(end_version, self.start_version + num_versions as u64 - 1);
+        }
+        ensure!(
+            self.start_version <= end_version,
+            "Start version {} is after the last version to compare {}.",
+            self.start_version,
+            end_version,
+        );
+
+        println!(
+            "* Comparing versions [{}, {}].",
+            self.start_version, end_version
+        );
+        let txn_info_divergence = first_divergent_txn_info(
+            &db_a.ledger_store,
+            &db_b.ledger_store,
+            self.start_version,
+            end_version,
+        )?;
+        match txn_info_divergence {
+            Some(version) => {
+                println!("Transaction infos diverge at version {}:", version);
+                println!(
+                    "    A: {:?}",
+                    db_a.ledger_store.get_transaction_info(version)?
+                );
+                println!(
+                    "    B: {:?}",
+                    db_b.ledger_store.get_transaction_info(version)?
+                );
+            },
+            None => println!("Transaction infos match."),
+        }
+
+        // Write sets and events are committed to by the transaction infos, so past the first
+        // divergent transaction info they are expected to differ anyway.
+        let scan_end_version = txn_info_divergence.unwrap_or(end_version);
+        let num_versions = (scan_end_version - self.start_version + 1) as usize;
+        match first_divergent_write_set(
+            &db_a.transaction_store,
+            &db_b.transaction_store,
+            self.start_version,
+            num_versions,
+        )? {
+            Some((version, write_set_a, write_set_b)) => {
+                println!("Write sets diverge at version {}:", version);
+                print_diff(write_set_a.iter(), write_set_b.iter());
+            },
+            None => println!("Write sets match."),
+        }
+        match first_divergent_events(
+            &db_a.event_store,
+            &db_b.event_store,
+            self.start_version,
+            num_versions,
+        )? {
+            Some((version, events_a, events_b)) => {
+                println!("Events diverge at version {}:", version);
+                print_diff(events_a.iter().enumerate(), events_b.iter().enumerate());
+            },
+            None => println!("Events match."),
+        }
+
+        if self.skip_state_tree {
+            return Ok(());
+        }
+        let state_version = match self.state_version {
+            Some(version) => version,
+            None => match db_a
+                .state_merkle_db
+                .get_state_snapshot_version_before(end_version + 1)?
+            {
+                Some(version) => version,
+                None => {
+                    println!("No state snapshot found in DB A, skipping the state tree.");
+                    return Ok(());
+                },
+            },
+        };
+        println!("* Comparing state trees at version {}.", state_version);
+        let diffs = diff_state_trees(
+            (&db_a.state_merkle_db, &db_a.state_kv_db),
+            (&db_b.state_merkle_db, &db_b.state_kv_db),
+            state_version,
+            self.max_state_diffs,
+        )?;
+        if diffs.is_empty() {
+            println!("State trees match.");
+        }
+        for diff in &diffs {
+            println!("State key {:?} (hash {}):", diff.state_key, diff.key_hash);
+            println!("    A: {:?}", diff.value_a);
+            println!("    B: {:?}", diff.value_b);
+        }
+        if diffs.len() == self.max_state_diffs {
+            println!(
+                "Stopped after {} differences, see --max-state-diffs.",
+                diffs.len()
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Prints the entries that differ between two lists of key value pairs.
+fn print_diff<K: Debug + Ord, V: Debug + PartialEq>(
+    a: impl Iterator<Item = (K, V)>,
+    b: impl Iterator<Item = (K, V)>,
+) {
+    let a = a.collect::<BTreeMap<_, _>>();
+    let mut b = b.collect::<BTreeMap<_, _>>();
+    for (key, value_a) in a {
+        match b.remove(&key) {
+            Some(value_b) if value_a == value_b => (),
+            value_b => println!(
+                "    {:?}:\n        A: {:?}\n        B: {:?}",
+                key, value_a, value_b
+            ),
+        }
+    }
+    for (key, value_b) in b {
+        println!("    {:?}:\n        A: None\n        B: {:?}", key, value_b);
+    }
+}
+
+/// Returns the first version in [start_version, end_version] at which the transaction
+/// accumulators differ, i.e. the first divergent transaction info (or the start version, if
+/// they diverged before it).
+fn first_divergent_txn_info(
+    ledger_store_a: &LedgerStore,
+    ledger_store_b: &LedgerStore,
+    start_version: Version,
+    end_version: Version,
+) -> Result<Option<Version>> {
+    let differs = |version: Version| -> Result<bool> {
+        Ok(ledger_store_a.get_root_hash(version)? != ledger_store_b.get_root_hash(version)?)
+    };
+
+    if !differs(end_version)? {
+        return Ok(None);
+    }
+    // Once the accumulators differ, they differ at all later versions, so binary search.
+    let (mut low, mut high) = (start_version, end_version);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if differs(mid)? {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Ok(Some(low))
+}
+
+fn first_mismatch<T: PartialEq>(
+    start_version: Version,
+    iter_a: impl Iterator<Item = Result<T>>,
+    iter_b: impl Iterator<Item = Result<T>>,
+) -> Result<Option<(Version, T, T)>> {
+    for (version, (a, b)) in (start_version..).zip(iter_a.zip(iter_b)) {
+        let (a, b) = (a?, b?);
+        if a != b {
+            return Ok(Some((version, a, b)));
+        }
+        if version % 100_000 == 0 {
+            println!("Good until version {}.", version);
+        }
+    }
+    Ok(None)
+}
+
+fn first_divergent_write_set(
+    transaction_store_a: &TransactionStore,
+    transaction_store_b: &TransactionStore,
+    start_version: Version,
+    num_versions: usize,
+) -> Result<Option<(Version, WriteSet, WriteSet)>> {
+    first_mismatch(
+        start_version,
+        transaction_store_a.get_write_set_iter(start_version, num_versions)?,
+        transaction_store_b.get_write_set_iter(start_version, num_versions)?,
+    )
+}
+
+fn first_divergent_events(
+    event_store_a: &EventStore,
+    event_store_b: &EventStore,
+    start_version: Version,
+    num_versions: usize,
+) -> Result<Option<(Version, Vec<ContractEvent>, Vec<ContractEvent>)>> {
+    first_mismatch(
+        start_version,
+        event_store_a.get_events_by_version_iter(start_version, num_versions)?,
+        event_store_b.get_events_by_version_iter(start_version, num_versions)?,
+    )
+}
+
+#[derive(Debug, Eq, PartialEq)]
+struct StateDiff {
+    key_hash: HashValue,
+    state_key: StateKey,
+    value_a: Option<StateValue>,
+    value_b: Option<StateValue>,
+}
+
+/// Walks both Jellyfish Merkle trees at `version`, descending only into subtrees whose hashes
+/// differ, and returns (up to `max_diffs`) state keys whose values differ, ordered by key hash.
+fn diff_state_trees(
+    db_a: (&StateMerkleDb, &StateKvDb),
+    db_b: (&StateMerkleDb, &StateKvDb),
+    version: Version,
+    max_diffs: usize,
+) -> Result<Vec<StateDiff>> {
+    let root_key = NodeKey::new_empty_path(version);
+    for (name, (state_merkle_db, _)) in [("A", db_a), ("B", db_b)] {
+        ensure!(
+            state_merkle_db
+                .get_node_option(&root_key, "diff")?
+                .is_some(),
+            "DB {} has no state snapshot at version {}.",
+            name,
+            version,
+        );
+    }
+
+    let mut diffs = vec![];
+    let mut to_visit = vec![(Some(root_key.clone()), Some(root_key))];
+    while let Some((node_key_a, node_key_b)) = to_visit.pop() {
+        if diffs.len() >= max_diffs {
+            break;
+        }
+        let node_a = get_node_option(db_a.0, &node_key_a)?;
+        let node_b = get_node_option(db_b.0, &node_key_b)?;
+        if node_a.as_ref().map(Node::hash) == node_b.as_ref().map(Node::hash) {
+            continue;
+        }
+
+        if let (Some(Node::Internal(internal_a)), Some(Node::Internal(internal_b))) =
+            (&node_a, &node_b)
+        {
+            let (node_key_a, node_key_b) = (node_key_a.unwrap(), node_key_b.unwrap());
+            // Pushed in reverse so that children are visited in nibble order.
+            for n in (0..16u8).rev() {
+                let nibble = Nibble::from(n);
+                let child_a = internal_a.child(nibble);
+                let child_b = internal_b.child(nibble);
+                if child_a.map(|c| c.hash) != child_b.map(|c| c.hash) {
+                    to_visit.push((
+                        child_a.map(|c| node_key_a.gen_child_node_key(c.version, nibble)),
+                        child_b.map(|c| node_key_b.gen_child_node_key(c.version, nibble)),
+                    ));
+                }
+            }
+            continue;
+        }
+
+        // A leaf or an empty subtree on at least one side, compare all the leaves below.
+        let mut leaves_b = collect_leaves(db_b.0, node_key_b.zip(node_b))?;
+        for (key_hash, leaf_a) in collect_leaves(db_a.0, node_key_a.zip(node_a))? {
+            let leaf_b = leaves_b.remove(&key_hash);
+            if leaf_b.as_ref().map(LeafNode::value_hash) != Some(leaf_a.value_hash()) {
+                diffs.push(StateDiff {
+                    key_hash,
+                    state_key: leaf_a.value_index().0.clone(),
+                    value_a: get_state_value(db_a.1, &leaf_a)?,
+                    value_b: leaf_b
+                        .map(|leaf_b| get_state_value(db_b.1, &leaf_b))
+                        .transpose()?
+                        .flatten(),
+                });
+            }
+        }
+        for (key_hash, leaf_b) in leaves_b {
+            diffs.push(StateDiff {
+                key_hash,
+                state_key: leaf_b.value_index().0.clone(),
+                value_a: None,
+                value_b: get_state_value(db_b.1, &leaf_b)?,
+            });
+        }
+    }
+    diffs.sort_by_key(|diff| diff.key_hash);
+    diffs.truncate(max_diffs);
+
+    Ok(diffs)
+}
+
+fn get_node_option(
+    state_merkle_db: &StateMerkleDb,
+    node_key: &Option<NodeKey>,
+) -> Result<Option<Node<StateKey>>> {
+    node_key
+        .as_ref()
+        .map(|node_key| state_merkle_db.get_node(node_key))
+        .transpose()
+}
+
+/// Returns all the leaves in the subtree rooted at `root`, keyed by key hash.
+fn collect_leaves(
+    state_merkle_db: &StateMerkleDb,
+    root: Option<(NodeKey, Node<StateKey>)>,
+) -> Result<BTreeMap<HashValue, LeafNode<StateKey>>> {
+    let mut leaves = BTreeMap::new();
+    let mut to_visit = root.into_iter().collect::<Vec<_>>();
+    while let Some((node_key, node)) = to_visit.pop() {
+        match node {
+            Node::Internal(internal) => {
+                for (nibble, child) in internal.children_sorted() {
+                    let child_key = node_key.gen_child_node_key(child.version, *nibble);
+                    let child_node = state_merkle_db.get_node(&child_key)?;
+                    to_visit.push((child_key, child_node));
+                }
+            },
+            Node::Leaf(leaf) => {
+                leaves.insert(leaf.account_key(), leaf);
+            },
+            Node::Null => (),
+        }
+    }
+    Ok(leaves)
+}
+
+fn get_state_value(
+    state_kv_db: &StateKvDb,
+    leaf: &LeafNode<StateKey>,
+) -> Result<Option<StateValue>> {
+    let (state_key, version) = leaf.value_index();
+    Ok(state_kv_db
+        .db_shard(state_key.get_shard_id())
+        .get::<StateValueSchema>(&(state_key.clone(), *version))?
+        .flatten())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_helper::{put_as_state_root, put_transaction_info};
+    use aptos_crypto::hash::CryptoHash;
+    use aptos_temppath::TempPath;
+    use aptos_types::transaction::{ExecutionStatus, TransactionInfo};
+
+    fn txn_info(seed: u8) -> TransactionInfo {
+        TransactionInfo::new(
+            HashValue::new([seed; HashValue::LENGTH]),
+            HashValue::zero(),
+            HashValue::zero(),
+            None,
+            0,
+            ExecutionStatus::Success,
+        )
+    }
+
+    #[test]
+    fn test_first_divergent_txn_info() {
+        let (tmp_dir_a, tmp_dir_b) = (TempPath::new(), TempPath::new());
+        let db_a = AptosDB::new_for_test(&tmp_dir_a);
+        let db_b = AptosDB::new_for_test(&tmp_dir_b);
+
+        // The transaction infos differ at versions 5 and 8 only, but the accumulators differ at
+        // every version from 5 on.
+        for version in 0..10 {
+            let seed = version as u8;
+            put_transaction_info(&db_a, version, &txn_info(seed));
+            let seed_b = if version == 5 || version == 8 {
+                100
+            } else {
+                seed
+            };
+            put_transaction_info(&db_b, version, &txn_info(seed_b));
+        }
+
+        let (a, b) = (&db_a.ledger_store, &db_b.ledger_store);
+        assert_eq!(first_divergent_txn_info(a, b, 0, 9).unwrap(), Some(5));
+        assert_eq!(first_divergent_txn_info(a, b, 3, 7).unwrap(), Some(5));
+        assert_eq!(first_divergent_txn_info(a, b, 0, 4).unwrap(), None);
+        // Diverged before the start version, even though the transaction infos at 6 and 7 are the
+        // same.
+        assert_eq!(first_divergent_txn_info(a, b, 6, 7).unwrap(), Some(6));
+        assert_eq!(first_divergent_txn_info(a, b, 7, 9).unwrap(), Some(7));
+        assert_eq!(first_divergent_txn_info(a, a, 0, 9).unwrap(), None);
+    }
+
+    #[test]
+    fn test_diff_state_trees() {
+        let (tmp_dir_a, tmp_dir_b) = (TempPath::new(), TempPath::new());
+        let db_a = AptosDB::new_for_test(&tmp_dir_a);
+        let db_b = AptosDB::new_for_test(&tmp_dir_b);
+
+        let key = StateKey::raw(b"test_key".to_vec());
+        let value_a = StateValue::from(b"value_a".to_vec());
+        let value_b = StateValue::from(b"value_b".to_vec());
+        put_as_state_root(&db_a, 0, key.clone(), value_a.clone());
+        put_as_state_root(&db_b, 0, key.clone(), value_b.clone());
+
+        let state_merkle_db_a = db_a.state_merkle_db();
+        let state_merkle_db_b = db_b.state_merkle_db();
+        let a = (&*state_merkle_db_a, &*db_a.state_kv_db);
+        let b = (&*state_merkle_db_b, &*db_b.state_kv_db);
+
+        assert_eq!(diff_state_trees(a, b, 0, 10).unwrap(), vec![StateDiff {
+            key_hash: key.hash(),
+            state_key: key,
+            value_a: Some(value_a),
+            value_b: Some(value_b),
+        }]);
+        assert_eq!(diff_state_trees(a, b, 0, 0).unwrap(), vec![]);
+        assert_eq!(diff_state_trees(a, a, 0, 10).unwrap(), vec![]);
+        assert!(diff_state_trees(a, b, 1, 10).is_err());
+    }
+}
@@ -3,6 +3,7 @@
 
 pub mod checkpoint;
 mod common;
+pub mod diff;
 mod examine;
 pub mod ledger;
 pub mod state_tree;
@@ -31,6 +32,8 @@ pub enum Cmd {
 
     #[clap(subcommand)]
     Examine(examine::Cmd),
+
+    Diff(diff::Cmd),
 }
 
 impl Cmd {
@@ -41,6 +44,7 @@ impl Cmd {
             Cmd::Ledger(cmd) => cmd.run(),
             Cmd::Truncate(cmd) => cmd.run(),
             Cmd::Examine(cmd) => cmd.run(),
+            Cmd::Diff(cmd) => cmd.run(),
         }
     }
 }
@@ -9,7 +9,10 @@
 //! ```
 //!
 
-use crate::{schema::DB_METADATA_CF_NAME, state_restore::StateSnapshotProgress};
+use crate::{
+    backup::restore_progress::RestoreProgress, schema::DB_METADATA_CF_NAME,
+    state_restore::StateSnapshotProgress,
+};
 use anyhow::Result;
 use aptos_schemadb::{
     define_schema,
@@ -25,6 +28,7 @@ type ShardId = usize;
 pub(crate) enum DbMetadataValue {
     Version(Version),
     StateSnapshotProgress(StateSnapshotProgress),
+    RestoreProgress(RestoreProgress),
 }
 
 impl DbMetadataValue {
@@ -41,6 +45,13 @@ impl DbMetadataValue {
             _ => unreachable!("expected KeyHashAndUsage, got {:?}", self),
         }
     }
+
+    pub fn expect_restore_progress(self) -> RestoreProgress {
+        match self {
+            Self::RestoreProgress(progress) => progress,
+            _ => unreachable!("expected RestoreProgress, got {:?}", self),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
@@ -66,6 +77,7 @@ pub enum DbMetadataKey {
     EpochEndingStateMerkleShardPrunerProgress(ShardId),
     StateKvShardPrunerProgress(ShardId),
     StateMerkleShardRestoreProgress(ShardId, Version),
+    RestoreCoordinatorProgress,
 }
 
 define_schema!(
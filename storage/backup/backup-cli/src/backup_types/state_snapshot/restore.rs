@@ -180,6 +180,15 @@ impl StateSnapshotRestoreController {
             )
         };
         let chunks_to_add = chunks.len();
+        let chunks_skipped = total_chunks - chunks_to_add;
+        self.run_mode.update_restore_progress(|progress| {
+            progress.update_state_snapshot(
+                self.version,
+                &self.manifest_handle,
+                chunks_skipped as u64,
+                total_chunks as u64,
+            )
+        })?;
 
         let start_idx = chunks.first().map_or(0, |chunk| chunk.first_idx);
 
@@ -216,6 +225,14 @@ impl StateSnapshotRestoreController {
             })
             .await??;
             leaf_idx.set(chunk.last_idx as i64);
+            self.run_mode.update_restore_progress(|progress| {
+                progress.update_state_snapshot(
+                    self.version,
+                    &self.manifest_handle,
+                    (chunks_skipped + chunk_idx + 1) as u64,
+                    total_chunks as u64,
+                )
+            })?;
             info!(
                 chunk = chunk_idx,
                 chunks_to_add = chunks_to_add,
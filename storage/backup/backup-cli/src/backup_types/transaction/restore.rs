@@ -181,6 +181,18 @@ impl LoadedChunk {
     }
 }
 
+/// Loads a chunk and verifies its accumulator range proof against the ledger info in the backup
+/// (and the ledger info against the epoch history, if provided), without keeping anything.
+pub(crate) async fn verify_chunk(
+    manifest: TransactionChunk,
+    storage: &Arc<dyn BackupStorage>,
+    epoch_history: Option<&Arc<EpochHistory>>,
+) -> Result<()> {
+    LoadedChunk::load(manifest, storage, epoch_history)
+        .await
+        .map(|_| ())
+}
+
 impl TransactionRestoreController {
     pub fn new(
         opt: TransactionRestoreOpt,
@@ -0,0 +1,503 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Audits the proofs in a backup storage without a node or a DB: the epoch ending ledger infos
+//! are checked against each other (and the trusted waypoints), and the transaction and state
+//! snapshot backups against the resulting epoch history. Nothing is restored, state snapshot
+//! chunks are verified against the snapshot root hash and dropped.
+//!
+//! Unlike `VerifyCoordinator`, an audit doesn't stop at the first problem; the outcome of every
+//! backup is recorded in a JSON report.
+
+use crate::{
+    backup_types::{
+        epoch_ending::restore::{
+            EpochEndingRestoreController, EpochEndingRestoreOpt, EpochHistory,
+        },
+        state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
+        transaction::{manifest::TransactionBackup, restore::verify_chunk},
+    },
+    metadata,
+    metadata::{
+        cache::MetadataCacheOpt, EpochEndingBackupMeta, StateSnapshotBackupMeta,
+        TransactionBackupMeta,
+    },
+    metrics::verify::{
+        AUDIT_COORDINATOR_FAIL_TS, AUDIT_COORDINATOR_START_TS, AUDIT_COORDINATOR_SUCC_TS,
+        AUDIT_NUM_PROBLEMS,
+    },
+    storage::{BackupStorage, FileHandle},
+    utils::{
+        storage_ext::BackupStorageExt, stream::StreamX, unix_timestamp_sec, GlobalRestoreOptions,
+        RestoreRunMode, TrustedWaypointOpt,
+    },
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_db::state_restore::StateSnapshotRestoreMode;
+use aptos_logger::prelude::*;
+use aptos_types::{ledger_info::LedgerInfo, transaction::Version};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::max,
+    fs::File,
+    io::BufWriter,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Verified,
+    Failed {
+        error: String,
+    },
+    /// Not checked because what it's verified against failed or is missing.
+    Skipped {
+        reason: String,
+    },
+}
+
+impl AuditOutcome {
+    fn from_result(res: Result<()>) -> Self {
+        match res {
+            Ok(()) => Self::Verified,
+            Err(e) => Self::Failed {
+                error: format!("{:#}", e),
+            },
+        }
+    }
+
+    pub fn is_verified(&self) -> bool {
+        matches!(self, Self::Verified)
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct EpochEndingAudit {
+    pub first_epoch: u64,
+    pub last_epoch: u64,
+    pub manifest: FileHandle,
+    #[serde(flatten)]
+    pub outcome: AuditOutcome,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct StateSnapshotAudit {
+    pub epoch: u64,
+    pub version: Version,
+    pub manifest: FileHandle,
+    #[serde(flatten)]
+    pub outcome: AuditOutcome,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct TransactionAudit {
+    pub first_version: Version,
+    pub last_version: Version,
+    pub manifest: FileHandle,
+    #[serde(flatten)]
+    pub outcome: AuditOutcome,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct AuditReport {
+    pub started_at: i64,
+    pub finished_at: i64,
+    pub epoch_endings: Vec<EpochEndingAudit>,
+    pub state_snapshots: Vec<StateSnapshotAudit>,
+    pub transactions: Vec<TransactionAudit>,
+    /// Epoch or version ranges not covered by any backup.
+    pub gaps: Vec<String>,
+}
+
+impl AuditReport {
+    /// Number of backups not verified, plus the number of gaps.
+    pub fn num_problems(&self) -> usize {
+        self.epoch_endings
+            .iter()
+            .map(|a| &a.outcome)
+            .chain(self.state_snapshots.iter().map(|a| &a.outcome))
+            .chain(self.transactions.iter().map(|a| &a.outcome))
+            .filter(|outcome| !outcome.is_verified())
+            .count()
+            + self.gaps.len()
+    }
+
+    fn save(&self, path: &Path) -> Result<()> {
+        serde_json::to_writer_pretty(BufWriter::new(File::create(path)?), self)?;
+        Ok(())
+    }
+}
+
+pub struct AuditCoordinator {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    concurrent_downloads: usize,
+    num_state_snapshots: usize,
+    report_path: PathBuf,
+}
+
+impl AuditCoordinator {
+    pub fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: MetadataCacheOpt,
+        trusted_waypoints_opt: TrustedWaypointOpt,
+        concurrent_downloads: usize,
+        num_state_snapshots: usize,
+        report_path: PathBuf,
+    ) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt,
+            trusted_waypoints_opt,
+            concurrent_downloads,
+            num_state_snapshots,
+            report_path,
+        }
+    }
+
+    /// Writes the report and fails if any problem was found.
+    pub async fn run(self) -> Result<()> {
+        info!("Audit coordinator started.");
+        AUDIT_COORDINATOR_START_TS.set(unix_timestamp_sec());
+
+        let ret = self.run_impl().await;
+
+        if let Err(e) = &ret {
+            error!(
+                error = ?e,
+                "Audit coordinator failed."
+            );
+            AUDIT_COORDINATOR_FAIL_TS.set(unix_timestamp_sec());
+        } else {
+            info!("Audit coordinator exiting with success.");
+            AUDIT_COORDINATOR_SUCC_TS.set(unix_timestamp_sec());
+        }
+        ret
+    }
+
+    async fn run_impl(self) -> Result<()> {
+        let started_at = unix_timestamp_sec();
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let global_opt = GlobalRestoreOptions {
+            target_version: Version::MAX,
+            trusted_waypoints: Arc::new(self.trusted_waypoints_opt.verify()?),
+            run_mode: Arc::new(RestoreRunMode::Verify),
+            concurrent_downloads: self.concurrent_downloads,
+            replay_concurrency_level: 0, // won't replay, doesn't matter
+        };
+
+        let epoch_ending_backups = metadata_view.all_epoch_ending_backups();
+        let transaction_backups = metadata_view.all_transaction_backups();
+        let mut gaps = find_gaps(
+            "epochs",
+            epoch_ending_backups
+                .iter()
+                .map(|b| (b.first_epoch, b.last_epoch)),
+        );
+        gaps.extend(find_gaps(
+            "transactions",
+            transaction_backups
+                .iter()
+                .map(|b| (b.first_version, b.last_version)),
+        ));
+
+        let (epoch_endings, epoch_history) = self
+            .audit_epoch_endings(epoch_ending_backups, &global_opt)
+            .await;
+
+        let mut state_snapshots = Vec::new();
+        for backup in metadata_view
+            .all_state_snapshot_backups()
+            .iter()
+            .rev()
+            .take(self.num_state_snapshots)
+        {
+            state_snapshots.push(
+                self.audit_state_snapshot(backup, &global_opt, &epoch_history)
+                    .await,
+            );
+        }
+
+        let mut transactions = Vec::new();
+        for backup in transaction_backups {
+            let outcome = AuditOutcome::from_result(
+                self.audit_transaction_backup(backup, &epoch_history).await,
+            );
+            transactions.push(TransactionAudit {
+                first_version: backup.first_version,
+                last_version: backup.last_version,
+                manifest: backup.manifest.clone(),
+                outcome,
+            });
+        }
+
+        let report = AuditReport {
+            started_at,
+            finished_at: unix_timestamp_sec(),
+            epoch_endings,
+            state_snapshots,
+            transactions,
+            gaps,
+        };
+        report.save(&self.report_path)?;
+
+        let num_problems = report.num_problems();
+        AUDIT_NUM_PROBLEMS.set(num_problems as i64);
+        info!(
+            num_problems = num_problems,
+            report = ?self.report_path,
+            "Audit report written."
+        );
+        ensure!(
+            num_problems == 0,
+            "Audit found {} problems, see {:?}.",
+            num_problems,
+            self.report_path,
+        );
+        Ok(())
+    }
+
+    /// Verifies the epoch ending backups in order, each against the ledger info ending the epoch
+    /// before it, and returns the epoch history up to the first epoch that can't be verified.
+    async fn audit_epoch_endings(
+        &self,
+        backups: &[EpochEndingBackupMeta],
+        global_opt: &GlobalRestoreOptions,
+    ) -> (Vec<EpochEndingAudit>, Arc<EpochHistory>) {
+        let con = self.concurrent_downloads;
+        let mut preheated_stream = futures::stream::iter(backups.iter().map(|backup| {
+            EpochEndingRestoreController::new(
+                EpochEndingRestoreOpt {
+                    manifest_handle: backup.manifest.clone(),
+                },
+                global_opt.clone(),
+                self.storage.clone(),
+            )
+            .preheat()
+        }))
+        .buffered_x(con * 2, con);
+
+        let mut epoch_endings = Vec::new();
+        let mut audits = Vec::new();
+        for backup in backups {
+            let preheated = preheated_stream
+                .next()
+                .await
+                .expect("One preheated restore per backup.");
+            let outcome = if backup.first_epoch > epoch_endings.len() as u64 {
+                AuditOutcome::Skipped {
+                    reason: format!(
+                        "Epoch {} is not verified, so neither are the following ones.",
+                        epoch_endings.len()
+                    ),
+                }
+            } else {
+                let previous_li = backup
+                    .first_epoch
+                    .checked_sub(1)
+                    .map(|epoch| &epoch_endings[epoch as usize]);
+                let res = match preheated.run(previous_li).await {
+                    Ok(lis) => extend_epoch_endings(&mut epoch_endings, lis),
+                    Err(e) => Err(e),
+                };
+                AuditOutcome::from_result(res)
+            };
+            audits.push(EpochEndingAudit {
+                first_epoch: backup.first_epoch,
+                last_epoch: backup.last_epoch,
+                manifest: backup.manifest.clone(),
+                outcome,
+            });
+        }
+
+        let epoch_history = Arc::new(EpochHistory {
+            epoch_endings,
+            trusted_waypoints: global_opt.trusted_waypoints.clone(),
+        });
+        (audits, epoch_history)
+    }
+
+    async fn audit_state_snapshot(
+        &self,
+        backup: &StateSnapshotBackupMeta,
+        global_opt: &GlobalRestoreOptions,
+        epoch_history: &Arc<EpochHistory>,
+    ) -> StateSnapshotAudit {
+        // The ledger info of a snapshot is verified by the ledger info ending the previous epoch.
+        let outcome = if backup.epoch > epoch_history.epoch_endings.len() as u64 {
+            AuditOutcome::Skipped {
+                reason: format!(
+                    "Epoch {} is not verified.",
+                    epoch_history.epoch_endings.len()
+                ),
+            }
+        } else {
+            AuditOutcome::from_result(
+                StateSnapshotRestoreController::new(
+                    StateSnapshotRestoreOpt {
+                        manifest_handle: backup.manifest.clone(),
+                        version: backup.version,
+                        validate_modules: false,
+                        restore_mode: StateSnapshotRestoreMode::Default,
+                    },
+                    global_opt.clone(),
+                    Arc::clone(&self.storage),
+                    Some(epoch_history.clone()),
+                )
+                .run()
+                .await,
+            )
+        };
+        StateSnapshotAudit {
+            epoch: backup.epoch,
+            version: backup.version,
+            manifest: backup.manifest.clone(),
+            outcome,
+        }
+    }
+
+    async fn audit_transaction_backup(
+        &self,
+        backup: &TransactionBackupMeta,
+        epoch_history: &Arc<EpochHistory>,
+    ) -> Result<()> {
+        let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+        manifest.verify()?;
+        ensure!(
+            manifest.first_version == backup.first_version
+                && manifest.last_version == backup.last_version,
+            "Manifest covers versions {}..={}, metadata says {}..={}.",
+            manifest.first_version,
+            manifest.last_version,
+            backup.first_version,
+            backup.last_version,
+        );
+
+        let con = self.concurrent_downloads;
+        let mut chunk_stream = futures::stream::iter(manifest.chunks.into_iter().map(|chunk| {
+            let storage = self.storage.clone();
+            let epoch_history = epoch_history.clone();
+            async move {
+                let (first_version, last_version) = (chunk.first_version, chunk.last_version);
+                tokio::task::spawn(async move {
+                    verify_chunk(chunk, &storage, Some(&epoch_history)).await
+                })
+                .await?
+                .map_err(|e| anyhow!("Chunk {}..={}: {:#}", first_version, last_version, e))
+            }
+        }))
+        .buffered_x(con * 2, con);
+        while let Some(res) = chunk_stream.next().await {
+            res?;
+        }
+        Ok(())
+    }
+}
+
+/// Adds the newly verified ledger infos to the epoch history. Ones for known epochs (from
+/// overlapping backups) must agree with what's known.
+fn extend_epoch_endings(epoch_endings: &mut Vec<LedgerInfo>, lis: Vec<LedgerInfo>) -> Result<()> {
+    for li in lis {
+        ensure!(
+            li.ends_epoch(),
+            "LedgerInfo is not one at an epoch ending. epoch: {}",
+            li.epoch(),
+        );
+        match epoch_endings.get(li.epoch() as usize) {
+            Some(known) => ensure!(
+                *known == li,
+                "LedgerInfo for epoch {} conflicts with the one in an earlier backup.",
+                li.epoch(),
+            ),
+            None => {
+                ensure!(
+                    li.epoch() == epoch_endings.len() as u64,
+                    "LedgerInfo has epoch {}, expecting {}.",
+                    li.epoch(),
+                    epoch_endings.len(),
+                );
+                epoch_endings.push(li);
+            },
+        }
+    }
+    Ok(())
+}
+
+/// Finds the ranges not covered by the given sorted, inclusive ranges, starting from 0.
+fn find_gaps(what: &str, ranges: impl Iterator<Item = (u64, u64)>) -> Vec<String> {
+    let mut gaps = Vec::new();
+    let mut next = 0;
+    for (first, last) in ranges {
+        if first > next {
+            gaps.push(format!("Missing {} {}..={}.", what, next, first - 1));
+        }
+        next = max(next, last + 1);
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_gaps() {
+        assert!(find_gaps("epochs", vec![(0, 3), (4, 9)].into_iter()).is_empty());
+        assert!(find_gaps("epochs", vec![(0, 5), (3, 9), (10, 10)].into_iter()).is_empty());
+        assert_eq!(
+            find_gaps("epochs", vec![(0, 3), (6, 9), (12, 15)].into_iter()),
+            vec![
+                "Missing epochs 4..=5.".to_string(),
+                "Missing epochs 10..=11.".to_string(),
+            ]
+        );
+        assert_eq!(find_gaps("transactions", vec![(5, 9)].into_iter()), vec![
+            "Missing transactions 0..=4.".to_string()
+        ]);
+    }
+
+    #[test]
+    fn test_report_serialization() {
+        let report = AuditReport {
+            started_at: 1,
+            finished_at: 2,
+            epoch_endings: vec![EpochEndingAudit {
+                first_epoch: 0,
+                last_epoch: 1,
+                manifest: "epoch_ending_0-.ab12/epoch_ending.manifest".to_string(),
+                outcome: AuditOutcome::Verified,
+            }],
+            state_snapshots: vec![],
+            transactions: vec![TransactionAudit {
+                first_version: 0,
+                last_version: 9,
+                manifest: "transaction_0-.cd34/transaction.manifest".to_string(),
+                outcome: AuditOutcome::Failed {
+                    error: "bad proof".to_string(),
+                },
+            }],
+            gaps: vec![],
+        };
+        assert_eq!(report.num_problems(), 1);
+
+        let json = serde_json::to_value(&report).unwrap();
+        assert_eq!(json["epoch_endings"][0]["status"], "verified");
+        assert_eq!(json["transactions"][0]["status"], "failed");
+        assert_eq!(json["transactions"][0]["error"], "bad proof");
+
+        let parsed: AuditReport = serde_json::from_value(json).unwrap();
+        assert_eq!(
+            parsed.transactions[0].outcome,
+            report.transactions[0].outcome
+        );
+    }
+}
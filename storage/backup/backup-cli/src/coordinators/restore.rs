@@ -4,12 +4,15 @@
 
 use crate::{
     backup_types::{
-        epoch_ending::restore::EpochHistoryRestoreController,
+        epoch_ending::restore::{EpochHistory, EpochHistoryRestoreController},
         state_snapshot::restore::{StateSnapshotRestoreController, StateSnapshotRestoreOpt},
         transaction::restore::TransactionRestoreBatchController,
     },
     metadata,
-    metadata::{cache::MetadataCacheOpt, TransactionBackupMeta},
+    metadata::{
+        cache::MetadataCacheOpt, EpochEndingBackupMeta, StateSnapshotBackupMeta,
+        TransactionBackupMeta,
+    },
     metrics::restore::{
         COORDINATOR_FAIL_TS, COORDINATOR_START_TS, COORDINATOR_SUCC_TS, COORDINATOR_TARGET_VERSION,
     },
@@ -17,12 +20,118 @@ use crate::{
     utils::{unix_timestamp_sec, GlobalRestoreOptions},
 };
 use anyhow::{anyhow, bail, ensure, Result};
-use aptos_db::state_restore::StateSnapshotRestoreMode;
+use aptos_db::{
+    backup::restore_progress::{EpochHistoryRestoreStatus, RestoreProgress},
+    state_restore::StateSnapshotRestoreMode,
+};
 use aptos_executor_types::VerifyExecutionMode;
 use aptos_logger::prelude::*;
 use aptos_types::transaction::Version;
 use clap::Parser;
-use std::sync::Arc;
+use std::{fmt, sync::Arc};
+
+/// What previous runs have restored into the target DB and what is left to restore given the
+/// backups currently in the storage, as reported by `--status`.
+#[derive(Debug)]
+pub struct RestoreStatus {
+    /// Target version of this run, capped by the latest version in the backups.
+    pub target_version: Version,
+    /// Progress saved in the target DB by previous runs.
+    pub progress: Option<RestoreProgress>,
+    /// Epoch ending backups left to restore, 0 once the epoch history covers the target version.
+    pub epoch_ending_backups_to_restore: usize,
+    /// State snapshot to restore, if no previous run has started restoring one.
+    pub state_snapshot_to_start: Option<StateSnapshotBackupMeta>,
+    /// Next transaction version expected by the target DB.
+    pub next_transaction_version: Version,
+    /// Transaction backups left to restore.
+    pub transaction_backups_to_restore: usize,
+}
+
+impl RestoreStatus {
+    pub fn epoch_history_done(&self) -> bool {
+        self.epoch_ending_backups_to_restore == 0
+    }
+
+    pub fn transactions_done(&self) -> bool {
+        self.next_transaction_version > self.target_version
+    }
+}
+
+impl fmt::Display for RestoreStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Target version: {}", self.target_version)?;
+        match &self.progress {
+            Some(progress) => writeln!(
+                f,
+                "Target version of the previous run: {}",
+                progress.target_version
+            )?,
+            None => writeln!(f, "No restore progress found in the target DB.")?,
+        }
+
+        // Epoch endings.
+        let epoch_history = self
+            .progress
+            .as_ref()
+            .and_then(|p| p.epoch_history.as_ref());
+        if self.epoch_history_done() {
+            writeln!(
+                f,
+                "Epoch endings: done, {} epochs restored.",
+                epoch_history.map_or(0, |h| h.next_epoch)
+            )?;
+        } else {
+            writeln!(
+                f,
+                "Epoch endings: {} backups to restore{}.",
+                self.epoch_ending_backups_to_restore,
+                epoch_history.map_or_else(String::new, |h| format!(
+                    ", {} epochs restored for target version {}",
+                    h.next_epoch, h.target_version
+                )),
+            )?;
+        }
+
+        // State snapshots.
+        for snapshot in self.progress.iter().flat_map(|p| p.state_snapshots.iter()) {
+            writeln!(
+                f,
+                "State snapshot at version {}: {}/{} chunks restored{}. Manifest: {}",
+                snapshot.version,
+                snapshot.chunks_done,
+                snapshot.total_chunks,
+                if snapshot.is_finished() { ", done" } else { "" },
+                snapshot.manifest,
+            )?;
+        }
+        if let Some(snapshot) = &self.state_snapshot_to_start {
+            writeln!(
+                f,
+                "State snapshot at version {}: not started. Manifest: {}",
+                snapshot.version, snapshot.manifest
+            )?;
+        }
+
+        // Transactions.
+        if self.transactions_done() {
+            writeln!(
+                f,
+                "Transactions: done, DB is at version {}.",
+                self.next_transaction_version - 1
+            )
+        } else {
+            writeln!(
+                f,
+                "Transactions: {} versions in {} backups to restore, from version {} to {}.",
+                self.target_version - self.next_transaction_version + 1,
+                self.transaction_backups_to_restore,
+                self.next_transaction_version,
+                self.target_version,
+            )
+        }
+    }
+}
 
 #[derive(Parser)]
 pub struct RestoreCoordinatorOpt {
@@ -42,6 +151,12 @@ pub struct RestoreCoordinatorOpt {
     pub ledger_history_start_version: Option<Version>,
     #[clap(long, help = "Skip restoring epoch ending info, used for debugging.")]
     pub skip_epoch_endings: bool,
+    #[clap(
+        long,
+        help = "Report the restore progress saved in the target DB and what remains to be \
+        restored, without restoring anything."
+    )]
+    pub status: bool,
 }
 
 pub struct RestoreCoordinator {
@@ -51,6 +166,7 @@ pub struct RestoreCoordinator {
     replay_all: bool,
     ledger_history_start_version: Option<Version>,
     skip_epoch_endings: bool,
+    status: bool,
 }
 
 impl RestoreCoordinator {
@@ -66,10 +182,16 @@ impl RestoreCoordinator {
             replay_all: opt.replay_all,
             ledger_history_start_version: opt.ledger_history_start_version,
             skip_epoch_endings: opt.skip_epoch_endings,
+            status: opt.status,
         }
     }
 
     pub async fn run(self) -> Result<()> {
+        if self.status {
+            print!("{}", self.status().await?);
+            return Ok(());
+        }
+
         info!("Restore coordinator started.");
         COORDINATOR_START_TS.set(unix_timestamp_sec());
 
@@ -130,6 +252,9 @@ impl RestoreCoordinator {
         );
 
         COORDINATOR_TARGET_VERSION.set(target_version as i64);
+        self.global_opt
+            .run_mode
+            .update_restore_progress(|progress| progress.target_version = target_version)?;
         let lhs = self.ledger_history_start_version();
 
         let latest_tree_version = self
@@ -209,20 +334,10 @@ impl RestoreCoordinator {
         let transaction_backups =
             metadata_view.select_transaction_backups(txn_start_version, target_version)?;
         let epoch_ending_backups = metadata_view.select_epoch_ending_backups(target_version)?;
-        let epoch_handles = epoch_ending_backups
-            .iter()
-            .filter(|e| e.first_version <= target_version)
-            .map(|backup| backup.manifest.clone())
-            .collect();
         let epoch_history = if !self.skip_epoch_endings {
             Some(Arc::new(
-                EpochHistoryRestoreController::new(
-                    epoch_handles,
-                    self.global_opt.clone(),
-                    self.storage.clone(),
-                )
-                .run()
-                .await?,
+                self.restore_epoch_history(&epoch_ending_backups, target_version)
+                    .await?,
             ))
         } else {
             None
@@ -365,6 +480,114 @@ impl RestoreCoordinator {
 }
 
 impl RestoreCoordinator {
+    /// Restores the epoch history, or loads it from the DB if a previous run has already
+    /// restored it for a target version not older than this one.
+    async fn restore_epoch_history(
+        &self,
+        epoch_ending_backups: &[EpochEndingBackupMeta],
+        target_version: Version,
+    ) -> Result<EpochHistory> {
+        let run_mode = &self.global_opt.run_mode;
+        if let Some(restored) = run_mode
+            .get_restore_progress()?
+            .and_then(|progress| progress.epoch_history)
+        {
+            if restored.target_version >= target_version {
+                info!(
+                    next_epoch = restored.next_epoch,
+                    "Epoch history already restored, loading from DB."
+                );
+                return Ok(EpochHistory {
+                    epoch_endings: run_mode.get_epoch_ending_ledger_infos(restored.next_epoch)?,
+                    trusted_waypoints: self.global_opt.trusted_waypoints.clone(),
+                });
+            }
+        }
+
+        let epoch_handles = epoch_ending_backups
+            .iter()
+            .filter(|e| e.first_version <= target_version)
+            .map(|backup| backup.manifest.clone())
+            .collect();
+        let epoch_history = EpochHistoryRestoreController::new(
+            epoch_handles,
+            self.global_opt.clone(),
+            self.storage.clone(),
+        )
+        .run()
+        .await?;
+        run_mode.update_restore_progress(|progress| {
+            progress.epoch_history = Some(EpochHistoryRestoreStatus {
+                target_version,
+                next_epoch: epoch_history.epoch_endings.len() as u64,
+            })
+        })?;
+
+        Ok(epoch_history)
+    }
+
+    /// Reports what previous runs have restored into the target DB, and what is left to restore
+    /// given the backups currently in the storage.
+    pub async fn status(&self) -> Result<RestoreStatus> {
+        let run_mode = &self.global_opt.run_mode;
+        ensure!(
+            !run_mode.is_verify(),
+            "--status requires --target-db-dir to read progress from."
+        );
+        let metadata_view = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.global_opt.concurrent_downloads,
+        )
+        .await?;
+        let max_txn_ver = metadata_view
+            .max_transaction_version()?
+            .ok_or_else(|| anyhow!("No transaction backup found."))?;
+        let target_version = std::cmp::min(self.global_opt.target_version, max_txn_ver);
+        let progress = run_mode.get_restore_progress()?;
+        let next_transaction_version = run_mode.get_next_expected_transaction_version()?;
+
+        let epoch_ending_backups_to_restore =
+            match progress.as_ref().and_then(|p| p.epoch_history.as_ref()) {
+                Some(h) if h.target_version >= target_version => 0,
+                _ => metadata_view
+                    .select_epoch_ending_backups(target_version)?
+                    .iter()
+                    .filter(|e| e.first_version <= target_version)
+                    .count(),
+            };
+
+        let state_snapshot_to_start = match metadata_view.select_state_snapshot(target_version)? {
+            Some(snapshot)
+                if progress
+                    .as_ref()
+                    .and_then(|p| p.state_snapshot(snapshot.version))
+                    .is_none()
+                    && run_mode.get_state_snapshot_before(Version::MAX).is_none() =>
+            {
+                Some(snapshot)
+            },
+            _ => None,
+        };
+
+        let transaction_backups_to_restore = if next_transaction_version > target_version {
+            0
+        } else {
+            metadata_view
+                .select_transaction_backups(next_transaction_version, target_version)?
+                .len()
+        };
+
+        Ok(RestoreStatus {
+            target_version,
+            progress,
+            epoch_ending_backups_to_restore,
+            state_snapshot_to_start,
+            next_transaction_version,
+            transaction_backups_to_restore,
+        })
+    }
+
     fn target_version(&self) -> Version {
         self.global_opt.target_version
     }
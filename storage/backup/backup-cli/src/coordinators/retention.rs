@@ -0,0 +1,406 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        state_snapshot::manifest::StateSnapshotBackup, transaction::manifest::TransactionBackup,
+    },
+    metadata,
+    metadata::{
+        cache::{LoadMetadataLines, MetadataCacheOpt},
+        view::MetadataView,
+        Metadata, StateSnapshotBackupMeta, TransactionBackupMeta,
+    },
+    storage::{BackupStorage, FileHandle, FileHandleRef},
+    utils::{error_notes::ErrorNotes, storage_ext::BackupStorageExt, stream::StreamX},
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_infallible::duration_since_epoch;
+use aptos_logger::prelude::*;
+use aptos_types::transaction::Version;
+use futures::StreamExt;
+use std::{collections::HashSet, ffi::OsStr, path::Path, sync::Arc};
+
+/// Decides which backups can be deleted while every kept version stays restorable:
+///   * State snapshots at epochs that are multiples of `state_snapshot_epoch_interval` are kept,
+///     as is the latest one.
+///   * Transactions from `keep_transactions_since_version` on are kept, along with the latest
+///     state snapshot at or before that version and all transactions after it, which a restore
+///     to any kept version starts from.
+///   * The transaction backup containing the version of each kept state snapshot is kept, so that
+///     the snapshot can be restored on its own.
+///   * Epoch ending backups are small and needed to verify everything else, they are always kept.
+///
+/// `None` keeps all backups of the kind.
+#[derive(Clone, Debug, Default)]
+pub struct RetentionPolicy {
+    pub state_snapshot_epoch_interval: Option<u64>,
+    pub keep_transactions_since_version: Option<Version>,
+}
+
+#[derive(Debug, Default)]
+pub struct RetentionPlan {
+    pub state_snapshots_to_delete: Vec<StateSnapshotBackupMeta>,
+    pub transactions_to_delete: Vec<TransactionBackupMeta>,
+}
+
+impl RetentionPlan {
+    pub fn is_empty(&self) -> bool {
+        self.state_snapshots_to_delete.is_empty() && self.transactions_to_delete.is_empty()
+    }
+
+    fn manifests(&self) -> HashSet<&FileHandleRef> {
+        self.state_snapshots_to_delete
+            .iter()
+            .map(|s| s.manifest.as_str())
+            .chain(
+                self.transactions_to_delete
+                    .iter()
+                    .map(|t| t.manifest.as_str()),
+            )
+            .collect()
+    }
+}
+
+impl RetentionPolicy {
+    pub fn plan(&self, metadata_view: &MetadataView) -> Result<RetentionPlan> {
+        ensure!(
+            self.state_snapshot_epoch_interval != Some(0),
+            "State snapshot epoch interval must be positive."
+        );
+        let snapshots = metadata_view.all_state_snapshot_backups();
+        let transactions = metadata_view.all_transaction_backups();
+
+        // With all transactions kept, the earliest snapshot is where restoring the earliest
+        // versions starts from.
+        let base_snapshot = match self.keep_transactions_since_version {
+            Some(version) => snapshots.iter().filter(|s| s.version <= version).last(),
+            None => snapshots.first(),
+        };
+        let latest_snapshot = snapshots.last();
+        let (kept_snapshots, state_snapshots_to_delete): (Vec<_>, Vec<_>) =
+            snapshots.iter().cloned().partition(|s| {
+                Some(s) == base_snapshot
+                    || Some(s) == latest_snapshot
+                    || self
+                        .state_snapshot_epoch_interval
+                        .map_or(true, |interval| s.epoch % interval == 0)
+            });
+
+        // Without a snapshot to start from, every transaction is needed.
+        let txn_start_version = match (self.keep_transactions_since_version, base_snapshot) {
+            (Some(_), Some(base_snapshot)) => base_snapshot.version,
+            _ => 0,
+        };
+        let transactions_to_delete = transactions
+            .iter()
+            .filter(|t| {
+                t.last_version < txn_start_version
+                    && !kept_snapshots
+                        .iter()
+                        .any(|s| t.first_version <= s.version && s.version <= t.last_version)
+            })
+            .cloned()
+            .collect();
+
+        Ok(RetentionPlan {
+            state_snapshots_to_delete,
+            transactions_to_delete,
+        })
+    }
+}
+
+pub struct BackupGarbageCollector {
+    storage: Arc<dyn BackupStorage>,
+    metadata_cache_opt: MetadataCacheOpt,
+    concurrent_downloads: usize,
+    policy: RetentionPolicy,
+    dry_run: bool,
+}
+
+impl BackupGarbageCollector {
+    pub fn new(
+        policy: RetentionPolicy,
+        dry_run: bool,
+        metadata_cache_opt: MetadataCacheOpt,
+        storage: Arc<dyn BackupStorage>,
+        concurrent_downloads: usize,
+    ) -> Self {
+        Self {
+            storage,
+            metadata_cache_opt,
+            concurrent_downloads,
+            policy,
+            dry_run,
+        }
+    }
+
+    pub async fn run(self) -> Result<RetentionPlan> {
+        info!(dry_run = self.dry_run, "Backup garbage collection started.");
+        let metaview = metadata::cache::sync_and_load(
+            &self.metadata_cache_opt,
+            Arc::clone(&self.storage),
+            self.concurrent_downloads,
+        )
+        .await?;
+        let plan = self.policy.plan(&metaview)?;
+        for backup in &plan.state_snapshots_to_delete {
+            info!(
+                epoch = backup.epoch,
+                version = backup.version,
+                manifest = backup.manifest,
+                "State snapshot backup to delete."
+            );
+        }
+        for backup in &plan.transactions_to_delete {
+            info!(
+                first_version = backup.first_version,
+                last_version = backup.last_version,
+                manifest = backup.manifest,
+                "Transaction backup to delete."
+            );
+        }
+        info!(
+            num_state_snapshots = plan.state_snapshots_to_delete.len(),
+            num_transactions = plan.transactions_to_delete.len(),
+            "Backups to delete."
+        );
+        if self.dry_run || plan.is_empty() {
+            return Ok(plan);
+        }
+
+        // Read all manifests before touching anything.
+        let files = self.list_files(&plan).await?;
+        // Once the metadata is gone, restores no longer use the backups. If interrupted after
+        // this, some backup files are left behind but nothing refers to them.
+        self.remove_metadata(&plan).await?;
+        info!(num_files = files.len(), "Deleting backup files.");
+        let con = self.concurrent_downloads;
+        let mut deletions = futures::stream::iter(files.into_iter().map(|file| {
+            let storage = self.storage.clone();
+            async move { storage.delete_file(&file).await.err_notes(&file) }
+        }))
+        .buffered_x(con * 2, con);
+        while let Some(res) = deletions.next().await {
+            res?;
+        }
+
+        info!("Backup garbage collection finished.");
+        Ok(plan)
+    }
+
+    async fn list_files(&self, plan: &RetentionPlan) -> Result<Vec<FileHandle>> {
+        let mut files = Vec::new();
+        for backup in &plan.state_snapshots_to_delete {
+            let manifest: StateSnapshotBackup =
+                self.storage.load_json_file(&backup.manifest).await?;
+            for chunk in manifest.chunks {
+                files.push(chunk.blobs);
+                files.push(chunk.proof);
+            }
+            files.push(manifest.proof);
+            files.push(backup.manifest.clone());
+        }
+        for backup in &plan.transactions_to_delete {
+            let manifest: TransactionBackup = self.storage.load_json_file(&backup.manifest).await?;
+            for chunk in manifest.chunks {
+                files.push(chunk.transactions);
+                files.push(chunk.proof);
+            }
+            files.push(backup.manifest.clone());
+        }
+        Ok(files)
+    }
+
+    /// Rewrites the metadata files that refer to deleted backups without them, and moves the
+    /// original files to the metadata backup folder.
+    async fn remove_metadata(&self, plan: &RetentionPlan) -> Result<()> {
+        let manifests = plan.manifests();
+        let now = duration_since_epoch().as_secs();
+        for file in self.storage.list_metadata_files().await? {
+            let lines = self
+                .storage
+                .open_for_read(&file)
+                .await
+                .err_notes(&file)?
+                .load_metadata_lines()
+                .await
+                .err_notes(&file)?;
+            let num_lines = lines.len();
+            let retained = lines
+                .into_iter()
+                .filter(|meta| match meta {
+                    Metadata::StateSnapshotBackup(s) => !manifests.contains(s.manifest.as_str()),
+                    Metadata::TransactionBackup(t) => !manifests.contains(t.manifest.as_str()),
+                    _ => true,
+                })
+                .map(|meta| meta.to_text_line())
+                .collect::<Result<Vec<_>>>()?;
+            if retained.len() == num_lines {
+                continue;
+            }
+
+            if !retained.is_empty() {
+                let name = retained_file_name(&file, now)?;
+                self.storage
+                    .save_metadata_lines(&name.parse()?, &retained)
+                    .await?;
+            }
+            info!(file = file, "Backup metadata file.");
+            self.storage.backup_metadata_file(&file).await?;
+        }
+        Ok(())
+    }
+}
+
+/// Name of the file holding what's left of a metadata file after retention, e.g.
+/// "retained_1700000000_transaction_compacted_0-99.meta".
+fn retained_file_name(file_handle: &FileHandleRef, timestamp: u64) -> Result<String> {
+    let name = Path::new(file_handle)
+        .file_name()
+        .and_then(OsStr::to_str)
+        .ok_or_else(|| anyhow!("cannot extract filename from {}", file_handle))?;
+    // Don't stack prefixes when a file is rewritten again.
+    let name = name
+        .strip_prefix("retained_")
+        .and_then(|rest| rest.split_once('_'))
+        .map_or(name, |(_, original)| original);
+    Ok(format!("retained_{}_{}", timestamp, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata_view(
+        snapshots: &[(u64, Version)],
+        transactions: &[(Version, Version)],
+    ) -> MetadataView {
+        let metadata = snapshots
+            .iter()
+            .map(|(epoch, version)| {
+                Metadata::new_state_snapshot_backup(
+                    *epoch,
+                    *version,
+                    format!("state_ver_{}/state.manifest", version),
+                )
+            })
+            .chain(transactions.iter().map(|(first, last)| {
+                Metadata::new_transaction_backup(
+                    *first,
+                    *last,
+                    format!("transaction_{}-{}/transaction.manifest", first, last),
+                )
+            }))
+            .collect();
+        MetadataView::new(metadata, vec![])
+    }
+
+    fn deleted_snapshot_epochs(plan: &RetentionPlan) -> Vec<u64> {
+        plan.state_snapshots_to_delete
+            .iter()
+            .map(|s| s.epoch)
+            .collect()
+    }
+
+    fn deleted_transactions(plan: &RetentionPlan) -> Vec<(Version, Version)> {
+        plan.transactions_to_delete
+            .iter()
+            .map(|t| (t.first_version, t.last_version))
+            .collect()
+    }
+
+    #[test]
+    fn test_keep_everything_by_default() {
+        let view = metadata_view(&[(0, 0), (1, 15), (2, 25)], &[(0, 9), (10, 19), (20, 29)]);
+        assert!(RetentionPolicy::default().plan(&view).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_state_snapshot_interval() {
+        let view = metadata_view(&[(0, 0), (1, 15), (2, 25), (3, 35), (4, 45), (5, 55)], &[(
+            0, 99,
+        )]);
+        let plan = RetentionPolicy {
+            state_snapshot_epoch_interval: Some(2),
+            keep_transactions_since_version: None,
+        }
+        .plan(&view)
+        .unwrap();
+        // The latest snapshot is always kept.
+        assert_eq!(deleted_snapshot_epochs(&plan), vec![1, 3]);
+        assert!(plan.transactions_to_delete.is_empty());
+
+        assert!(RetentionPolicy {
+            state_snapshot_epoch_interval: Some(0),
+            keep_transactions_since_version: None,
+        }
+        .plan(&view)
+        .is_err());
+    }
+
+    #[test]
+    fn test_transactions_since_version() {
+        let view = metadata_view(&[(0, 0), (1, 15), (2, 25), (3, 35), (4, 45)], &[
+            (0, 9),
+            (10, 19),
+            (20, 29),
+            (30, 39),
+            (40, 49),
+        ]);
+        let plan = RetentionPolicy {
+            state_snapshot_epoch_interval: Some(2),
+            keep_transactions_since_version: Some(38),
+        }
+        .plan(&view)
+        .unwrap();
+        // Snapshot at version 35 is where restoring version 38 starts from.
+        assert_eq!(deleted_snapshot_epochs(&plan), vec![1]);
+        // Kept snapshots at versions 0 and 25 keep the transaction backups they fall into.
+        assert_eq!(deleted_transactions(&plan), vec![(10, 19)]);
+
+        // Restoring from the base snapshot still sees continuous transaction backups.
+        let kept = view
+            .all_transaction_backups()
+            .iter()
+            .filter(|t| !plan.transactions_to_delete.contains(t))
+            .map(|t| (t.first_version, t.last_version))
+            .collect::<Vec<_>>();
+        let kept_view = metadata_view(&[], &kept);
+        assert_eq!(
+            kept_view
+                .select_transaction_backups(35, Version::MAX)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            kept_view.select_transaction_backups(25, 25).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_transactions_since_version_without_snapshot() {
+        let view = metadata_view(&[(2, 25)], &[(0, 9), (10, 19), (20, 29)]);
+        let plan = RetentionPolicy {
+            state_snapshot_epoch_interval: None,
+            keep_transactions_since_version: Some(15),
+        }
+        .plan(&view)
+        .unwrap();
+        assert!(plan.is_empty());
+    }
+
+    #[test]
+    fn test_retained_file_name() {
+        assert_eq!(
+            retained_file_name("metadata/transaction_compacted_0-99.meta", 100).unwrap(),
+            "retained_100_transaction_compacted_0-99.meta"
+        );
+        assert_eq!(
+            retained_file_name("metadata/retained_100_transaction_0-99.meta", 200).unwrap(),
+            "retained_200_transaction_0-99.meta"
+        );
+    }
+}
@@ -0,0 +1,903 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! A data client for state sync that reads the data required to fast sync a node from a backup
+//! storage, i.e., the epoch ending ledger infos, the latest state snapshot and the transaction
+//! output at the snapshot version. This allows new nodes to bootstrap from cold storage when no
+//! peers serve old data. Everything else (including all requests made once the node has synced
+//! to the snapshot) is forwarded to the network data client. The data read from the backup is
+//! not trusted: state sync verifies it exactly as it verifies peer data.
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    backup_types::{
+        epoch_ending::manifest::EpochEndingBackup,
+        state_snapshot::manifest::{StateSnapshotBackup, StateSnapshotChunk},
+        transaction::manifest::TransactionBackup,
+    },
+    metadata::{
+        cache::{sync_and_load, MetadataCacheOpt},
+        EpochEndingBackupMeta, TransactionBackupMeta,
+    },
+    storage::{
+        command_adapter::{config::CommandAdapterConfig, CommandAdapter},
+        encryption::{EncryptedStorage, EncryptionOpt},
+        local_fs::LocalFs,
+        s3::{config::S3Config, S3Storage},
+        BackupStorage, FileHandle,
+    },
+    utils::{read_record_bytes::ReadRecordBytes, storage_ext::BackupStorageExt},
+};
+use anyhow::{anyhow, ensure, Result};
+use aptos_config::config::{AptosDataClientConfig, BackupSourceConfig, BackupStorageLocation};
+use aptos_crypto::{
+    hash::{CryptoHash, SPARSE_MERKLE_PLACEHOLDER_HASH},
+    HashValue,
+};
+use aptos_data_client::{
+    error,
+    error::Error,
+    global_summary::{AdvertisedData, GlobalDataSummary, OptimalChunkSizes},
+    interface::{
+        AptosDataClientInterface, Response, ResponseCallback, ResponseContext, ResponseError,
+        ResponseId, SubscriptionRequestMetadata,
+    },
+};
+use aptos_infallible::Mutex;
+use aptos_logger::prelude::*;
+use aptos_storage_interface::DbReader;
+use aptos_storage_service_types::{
+    responses::{CompleteDataRange, TransactionOrOutputListWithProof},
+    Epoch,
+};
+use aptos_types::{
+    contract_event::ContractEvent,
+    ledger_info::LedgerInfoWithSignatures,
+    proof::{
+        SparseMerkleInternalNode, SparseMerkleLeafNode, SparseMerkleRangeProof,
+        TransactionAccumulatorProof, TransactionAccumulatorRangeProof,
+        TransactionInfoListWithProof, TransactionInfoWithProof,
+    },
+    state_store::{
+        state_key::StateKey,
+        state_value::{StateValue, StateValueChunkWithProof},
+    },
+    transaction::{
+        Transaction, TransactionInfo, TransactionListWithProof, TransactionOutput,
+        TransactionOutputListWithProof, Version,
+    },
+    write_set::WriteSet,
+};
+use async_trait::async_trait;
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        Arc,
+    },
+};
+
+/// The number of state snapshot chunks kept in memory, so that requests
+/// starting in the middle of a chunk don't read the chunk again.
+const NUM_CACHED_STATE_CHUNKS: usize = 4;
+
+/// Opens the backup storage described by the given config. The storage is always wrapped for
+/// decryption, so reading encrypted backups without a key fails with a clear error.
+pub async fn open_backup_storage(config: &BackupSourceConfig) -> Result<Arc<dyn BackupStorage>> {
+    let inner: Arc<dyn BackupStorage> = match &config.storage {
+        BackupStorageLocation::LocalFs { dir } => Arc::new(LocalFs::new(dir.clone())),
+        BackupStorageLocation::CommandAdapter { config } => Arc::new(CommandAdapter::new(
+            CommandAdapterConfig::load_from_file(config).await?,
+        )),
+        BackupStorageLocation::S3 { config } => {
+            Arc::new(S3Storage::new(S3Config::load_from_file(config).await?)?)
+        },
+    };
+    let encryption_opt = EncryptionOpt {
+        encryption_key_file: config.encryption_key_file.clone(),
+        encryption_key_env: config.encryption_key_env.clone(),
+    };
+    Ok(Arc::new(
+        EncryptedStorage::new_with_opt(inner, &encryption_opt).await?,
+    ))
+}
+
+/// The data required to fast sync a node to the latest state snapshot in a backup storage
+pub struct BackupDataSource {
+    storage: Arc<dyn BackupStorage>,
+    epoch_ending_backups: Vec<EpochEndingBackupMeta>,
+    transaction_backup: TransactionBackupMeta,
+    state_snapshot: StateSnapshotBackup,
+    ledger_info: LedgerInfoWithSignatures,
+    transaction_info_with_proof: TransactionInfoWithProof,
+    state_chunk_cache: Mutex<VecDeque<(usize, Arc<Vec<(StateKey, StateValue)>>)>>,
+}
+
+impl BackupDataSource {
+    /// Selects the latest state snapshot in the backup storage, along with the epoch
+    /// ending and transaction backups needed to fast sync to it.
+    pub async fn new(
+        storage: Arc<dyn BackupStorage>,
+        metadata_cache_opt: &MetadataCacheOpt,
+        concurrent_downloads: usize,
+    ) -> Result<Self> {
+        let metadata_view = sync_and_load(
+            metadata_cache_opt,
+            Arc::clone(&storage),
+            concurrent_downloads,
+        )
+        .await?;
+
+        let state_snapshot_meta = metadata_view
+            .select_state_snapshot(Version::MAX)?
+            .ok_or_else(|| anyhow!("No state snapshot found in the backup storage."))?;
+        let state_snapshot: StateSnapshotBackup = storage
+            .load_json_file(&state_snapshot_meta.manifest)
+            .await?;
+        let version = state_snapshot.version;
+        ensure!(
+            !state_snapshot.chunks.is_empty(),
+            "The state snapshot at version {} has no chunks.",
+            version,
+        );
+
+        // State sync fast syncs to an epoch ending ledger info, so the snapshot must be at the
+        // end of an epoch (which is always the case for snapshots taken by the coordinator).
+        let (transaction_info_with_proof, ledger_info): (
+            TransactionInfoWithProof,
+            LedgerInfoWithSignatures,
+        ) = storage.load_bcs_file(&state_snapshot.proof).await?;
+        ensure!(
+            ledger_info.ledger_info().version() == version
+                && ledger_info.ledger_info().ends_epoch(),
+            "The state snapshot at version {} is not at the end of an epoch. Ledger info: {:?}",
+            version,
+            ledger_info,
+        );
+
+        let epoch = ledger_info.ledger_info().epoch();
+        let epoch_ending_backups = metadata_view.select_epoch_ending_backups(version)?;
+        let last_backed_up_epoch = epoch_ending_backups.last().map(|backup| backup.last_epoch);
+        ensure!(
+            last_backed_up_epoch.map_or(false, |last_epoch| last_epoch >= epoch),
+            "The epoch ending backups end at epoch {:?}, but the state snapshot is in epoch {}.",
+            last_backed_up_epoch,
+            epoch,
+        );
+
+        let transaction_backup = metadata_view
+            .select_transaction_backups(version, version)?
+            .into_iter()
+            .find(|backup| backup.first_version <= version && version <= backup.last_version)
+            .ok_or_else(|| {
+                anyhow!(
+                    "No transaction backup found for the state snapshot version {}.",
+                    version
+                )
+            })?;
+
+        info!(
+            version = version,
+            epoch = epoch,
+            "Selected the state snapshot to bootstrap from."
+        );
+        Ok(Self {
+            storage,
+            epoch_ending_backups,
+            transaction_backup,
+            state_snapshot,
+            ledger_info,
+            transaction_info_with_proof,
+            state_chunk_cache: Mutex::new(VecDeque::new()),
+        })
+    }
+
+    /// The version of the state snapshot
+    pub fn version(&self) -> Version {
+        self.state_snapshot.version
+    }
+
+    /// The epoch ending ledger info at the version of the state snapshot
+    pub fn ledger_info(&self) -> &LedgerInfoWithSignatures {
+        &self.ledger_info
+    }
+
+    /// The number of states in the state snapshot
+    pub fn number_of_states(&self) -> u64 {
+        self.state_snapshot
+            .chunks
+            .last()
+            .map_or(0, |chunk| chunk.last_idx as u64 + 1)
+    }
+
+    /// The number of states in the largest chunk of the state snapshot
+    pub fn max_state_chunk_size(&self) -> u64 {
+        self.state_snapshot
+            .chunks
+            .iter()
+            .map(|chunk| (chunk.last_idx - chunk.first_idx + 1) as u64)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Returns the epoch ending ledger infos from `start_epoch` to `end_epoch` (inclusive).
+    /// Only the ledger infos in the backup chunk holding `start_epoch` are returned.
+    pub async fn get_epoch_ending_ledger_infos(
+        &self,
+        start_epoch: Epoch,
+        end_epoch: Epoch,
+    ) -> Result<Vec<LedgerInfoWithSignatures>> {
+        ensure!(
+            start_epoch <= end_epoch,
+            "Invalid epoch range: [{}, {}]",
+            start_epoch,
+            end_epoch,
+        );
+        let backup = self
+            .epoch_ending_backups
+            .iter()
+            .find(|backup| backup.first_epoch <= start_epoch && start_epoch <= backup.last_epoch)
+            .ok_or_else(|| anyhow!("Epoch {} is not in the backup storage.", start_epoch))?;
+        let manifest: EpochEndingBackup = self.storage.load_json_file(&backup.manifest).await?;
+        manifest.verify()?;
+        let chunk = manifest
+            .chunks
+            .iter()
+            .find(|chunk| chunk.first_epoch <= start_epoch && start_epoch <= chunk.last_epoch)
+            .ok_or_else(|| anyhow!("Epoch {} is not in the backup manifest.", start_epoch))?;
+
+        let ledger_infos: Vec<LedgerInfoWithSignatures> =
+            self.read_records(&chunk.ledger_infos).await?;
+        Ok(ledger_infos
+            .into_iter()
+            .skip((start_epoch - chunk.first_epoch) as usize)
+            .take((end_epoch - start_epoch + 1) as usize)
+            .collect())
+    }
+
+    /// Returns the state values from `start_index` to `end_index`, or to the end of the backup
+    /// chunk holding `start_index` if that comes first. The proof in the backup is only valid for
+    /// the last state value of the chunk, so the proof for a range ending in the middle of the
+    /// chunk is built from the rest of the chunk.
+    pub async fn get_state_values_with_proof(
+        &self,
+        start_index: u64,
+        end_index: u64,
+    ) -> Result<StateValueChunkWithProof> {
+        ensure!(
+            start_index <= end_index,
+            "Invalid state index range: [{}, {}]",
+            start_index,
+            end_index,
+        );
+        let chunk_index = self
+            .state_snapshot
+            .chunks
+            .partition_point(|chunk| (chunk.last_idx as u64) < start_index);
+        let chunk =
+            self.state_snapshot.chunks.get(chunk_index).ok_or_else(|| {
+                anyhow!("State index {} is not in the state snapshot.", start_index)
+            })?;
+        let end_index = end_index.min(chunk.last_idx as u64);
+
+        let state_values = self.get_state_chunk(chunk_index, chunk).await?;
+        let first_offset = (start_index - chunk.first_idx as u64) as usize;
+        let last_offset = (end_index - chunk.first_idx as u64) as usize;
+        let raw_values = state_values[first_offset..=last_offset].to_vec();
+        let first_key = raw_values
+            .first()
+            .map(|(state_key, _)| state_key.hash())
+            .ok_or_else(|| anyhow!("The backup chunk holding index {} is empty.", start_index))?;
+        let chunk_proof: SparseMerkleRangeProof = self.storage.load_bcs_file(&chunk.proof).await?;
+
+        let (last_key, proof) = if end_index == chunk.last_idx as u64 {
+            (chunk.last_key, chunk_proof)
+        } else {
+            let previous_key = match last_offset.checked_sub(1) {
+                Some(offset) => Some(state_values[offset].0.hash()),
+                None => chunk_index
+                    .checked_sub(1)
+                    .map(|index| self.state_snapshot.chunks[index].last_key),
+            };
+            let leaves: Vec<_> = state_values[last_offset..]
+                .iter()
+                .map(|(state_key, state_value)| {
+                    SparseMerkleLeafNode::new(state_key.hash(), state_value.hash())
+                })
+                .collect();
+            let proof = mid_chunk_range_proof(previous_key, &leaves, &chunk_proof)?;
+            (leaves[0].key(), proof)
+        };
+
+        Ok(StateValueChunkWithProof {
+            first_index: start_index,
+            last_index: end_index,
+            first_key,
+            last_key,
+            raw_values,
+            proof,
+            root_hash: self.state_snapshot.root_hash,
+        })
+    }
+
+    /// Returns the transaction output at the version of the state snapshot, with a proof
+    /// relative to the epoch ending ledger info at that version.
+    pub async fn get_transaction_output_with_proof(
+        &self,
+    ) -> Result<TransactionOutputListWithProof> {
+        let version = self.version();
+        let manifest: TransactionBackup = self
+            .storage
+            .load_json_file(&self.transaction_backup.manifest)
+            .await?;
+        manifest.verify()?;
+        let chunk = manifest
+            .chunks
+            .iter()
+            .find(|chunk| chunk.first_version <= version && version <= chunk.last_version)
+            .ok_or_else(|| anyhow!("Version {} is not in the transaction backup.", version))?;
+
+        let records: Vec<(Transaction, TransactionInfo, Vec<ContractEvent>, WriteSet)> =
+            self.read_records(&chunk.transactions).await?;
+        let (transaction, _, events, write_set) = records
+            .into_iter()
+            .nth((version - chunk.first_version) as usize)
+            .ok_or_else(|| anyhow!("Version {} is missing in the backup chunk.", version))?;
+
+        // The proof of the state snapshot links the transaction info to the ledger info
+        let transaction_info = self.transaction_info_with_proof.transaction_info().clone();
+        let output = TransactionOutput::new(
+            write_set,
+            events,
+            transaction_info.gas_used(),
+            transaction_info.status().clone().into(),
+        );
+        let range_proof = to_range_proof(
+            self.transaction_info_with_proof
+                .ledger_info_to_transaction_info_proof(),
+            version,
+        );
+
+        Ok(TransactionOutputListWithProof::new(
+            vec![(transaction, output)],
+            Some(version),
+            TransactionInfoListWithProof::new(range_proof, vec![transaction_info]),
+        ))
+    }
+
+    /// Returns the state values in the given chunk, reading them from the cache if possible
+    async fn get_state_chunk(
+        &self,
+        chunk_index: usize,
+        chunk: &StateSnapshotChunk,
+    ) -> Result<Arc<Vec<(StateKey, StateValue)>>> {
+        if let Some((_, state_values)) = self
+            .state_chunk_cache
+            .lock()
+            .iter()
+            .find(|(index, _)| *index == chunk_index)
+        {
+            return Ok(Arc::clone(state_values));
+        }
+
+        let state_values: Vec<(StateKey, StateValue)> = self.read_records(&chunk.blobs).await?;
+        ensure!(
+            state_values.len() == chunk.last_idx - chunk.first_idx + 1,
+            "Number of state values in the chunk doesn't match the manifest. Expected: {}, found: {}",
+            chunk.last_idx - chunk.first_idx + 1,
+            state_values.len(),
+        );
+        let state_values = Arc::new(state_values);
+
+        let mut cache = self.state_chunk_cache.lock();
+        if cache.len() >= NUM_CACHED_STATE_CHUNKS {
+            cache.pop_front();
+        }
+        cache.push_back((chunk_index, Arc::clone(&state_values)));
+        Ok(state_values)
+    }
+
+    /// Reads and deserializes all records in the given file
+    async fn read_records<T: serde::de::DeserializeOwned>(
+        &self,
+        file_handle: &FileHandle,
+    ) -> Result<Vec<T>> {
+        let mut file = self.storage.open_for_read(file_handle).await?;
+        let mut records = vec![];
+        while let Some(record_bytes) = file.read_record_bytes().await? {
+            records.push(bcs::from_bytes(&record_bytes)?);
+        }
+        Ok(records)
+    }
+}
+
+/// Converts the accumulator proof of a single leaf into a range proof of the same leaf
+fn to_range_proof(
+    proof: &TransactionAccumulatorProof,
+    leaf_index: u64,
+) -> TransactionAccumulatorRangeProof {
+    // Both proofs hold the siblings from the bottom level to the root level. The siblings
+    // of right children are on the left of the path, and vice versa.
+    let mut left_siblings = vec![];
+    let mut right_siblings = vec![];
+    let mut index = leaf_index;
+    for sibling in proof.siblings() {
+        if index % 2 == 1 {
+            left_siblings.push(*sibling);
+        } else {
+            right_siblings.push(*sibling);
+        }
+        index /= 2;
+    }
+    TransactionAccumulatorRangeProof::new(left_siblings, right_siblings)
+}
+
+/// Builds the range proof for the first of the given leaves, which run from the middle to the end
+/// of a backup chunk, out of the range proof for the last leaf of the chunk.
+fn mid_chunk_range_proof(
+    previous_key: Option<HashValue>,
+    leaves: &[SparseMerkleLeafNode],
+    chunk_proof: &SparseMerkleRangeProof,
+) -> Result<SparseMerkleRangeProof> {
+    let (leaf, later_leaves) = leaves
+        .split_first()
+        .ok_or_else(|| anyhow!("No leaf to prove."))?;
+    let (next_leaf, chunk_end) = match (later_leaves.first(), later_leaves.last()) {
+        (Some(next_leaf), Some(chunk_end)) => (next_leaf, chunk_end),
+        _ => return Ok(chunk_proof.clone()),
+    };
+    let chunk_end = ChunkEnd::new(*chunk_end, chunk_proof);
+
+    // A leaf sits right below the level at which its key diverges from both neighboring keys
+    let key = leaf.key();
+    let depth = previous_key
+        .map_or(0, |previous_key| key.common_prefix_bits_len(previous_key))
+        .max(key.common_prefix_bits_len(next_leaf.key()))
+        + 1;
+    let mut right_siblings = vec![];
+    for level in (0..depth).rev().filter(|level| !key.bit(*level)) {
+        let sibling_prefix = with_bit(key, level, true);
+        let sibling_leaves: Vec<_> = later_leaves
+            .iter()
+            .filter(|leaf| leaf.key().common_prefix_bits_len(sibling_prefix) > level)
+            .copied()
+            .collect();
+        right_siblings.push(chunk_end.subtree_hash(sibling_prefix, level + 1, &sibling_leaves)?);
+    }
+    Ok(SparseMerkleRangeProof::new(right_siblings))
+}
+
+/// The last leaf of a backup chunk, along with the siblings on the right of its path by level.
+/// Subtrees on the left of the leaf are fully covered by the chunk, and the ones on its right are
+/// known from these siblings.
+struct ChunkEnd {
+    leaf: SparseMerkleLeafNode,
+    right_siblings: HashMap<usize, HashValue>,
+}
+
+impl ChunkEnd {
+    fn new(leaf: SparseMerkleLeafNode, proof: &SparseMerkleRangeProof) -> Self {
+        // The proof holds the siblings from the bottom up
+        let right_siblings = leaf
+            .key()
+            .iter_bits()
+            .enumerate()
+            .filter(|(_, bit)| !bit)
+            .map(|(level, _)| level)
+            .zip(proof.right_siblings().iter().rev().copied())
+            .collect();
+        Self {
+            leaf,
+            right_siblings,
+        }
+    }
+
+    /// Computes the root hash of the subtree at `depth` whose keys start with the first `depth`
+    /// bits of `prefix`, given all its leaves in the chunk.
+    fn subtree_hash(
+        &self,
+        prefix: HashValue,
+        depth: usize,
+        leaves: &[SparseMerkleLeafNode],
+    ) -> Result<HashValue> {
+        let end_key = self.leaf.key();
+        let common_prefix_len = end_key.common_prefix_bits_len(prefix);
+        if common_prefix_len < depth {
+            if end_key.bit(common_prefix_len) {
+                return Ok(known_subtree_hash(depth, leaves));
+            }
+            return self
+                .right_siblings
+                .get(&common_prefix_len)
+                .filter(|_| common_prefix_len + 1 == depth)
+                .copied()
+                .ok_or_else(|| anyhow!("Missing a right sibling at level {}.", depth - 1));
+        }
+
+        // The subtree holds the end of the chunk, and consists of it alone unless there are other
+        // leaves or nonempty right siblings below
+        if leaves.len() == 1
+            && self.right_siblings.iter().all(|(level, sibling)| {
+                *level < depth || *sibling == *SPARSE_MERKLE_PLACEHOLDER_HASH
+            })
+        {
+            return Ok(self.leaf.hash());
+        }
+        let (left, right) = leaves.split_at(leaves.partition_point(|leaf| !leaf.key().bit(depth)));
+        Ok(SparseMerkleInternalNode::new(
+            self.subtree_hash(with_bit(prefix, depth, false), depth + 1, left)?,
+            self.subtree_hash(with_bit(prefix, depth, true), depth + 1, right)?,
+        )
+        .hash())
+    }
+}
+
+/// Computes the root hash of the subtree at `depth` holding exactly the given leaves
+fn known_subtree_hash(depth: usize, leaves: &[SparseMerkleLeafNode]) -> HashValue {
+    match leaves {
+        [] => *SPARSE_MERKLE_PLACEHOLDER_HASH,
+        [leaf] => leaf.hash(),
+        _ => {
+            let (left, right) =
+                leaves.split_at(leaves.partition_point(|leaf| !leaf.key().bit(depth)));
+            SparseMerkleInternalNode::new(
+                known_subtree_hash(depth + 1, left),
+                known_subtree_hash(depth + 1, right),
+            )
+            .hash()
+        },
+    }
+}
+
+/// Returns the given key with the bit at `index` set to `bit`
+fn with_bit(key: HashValue, index: usize, bit: bool) -> HashValue {
+    let bits = key
+        .iter_bits()
+        .enumerate()
+        .map(|(i, key_bit)| if i == index { bit } else { key_bit });
+    HashValue::from_bit_iter(bits).expect("The number of bits is unchanged.")
+}
+
+/// A data client that serves the data required to fast sync from a backup storage until the
+/// node has synced to the state snapshot, and forwards everything else to the network client.
+#[derive(Clone)]
+pub struct BackupDataClient<T> {
+    backup_data_source: Arc<BackupDataSource>,
+    backup_data_summary: GlobalDataSummary,
+    network_data_client: T,
+    storage: Arc<dyn DbReader>,
+    synced_to_snapshot: Arc<AtomicBool>,
+    next_response_id: Arc<AtomicU64>,
+}
+
+impl<T: AptosDataClientInterface + Send + Sync> BackupDataClient<T> {
+    pub fn new(
+        data_client_config: AptosDataClientConfig,
+        backup_data_source: BackupDataSource,
+        network_data_client: T,
+        storage: Arc<dyn DbReader>,
+    ) -> Result<Self> {
+        let version = backup_data_source.version();
+        let epoch = backup_data_source.ledger_info().ledger_info().epoch();
+        let advertised_data = AdvertisedData {
+            epoch_ending_ledger_infos: vec![CompleteDataRange::from_genesis(epoch)],
+            states: vec![CompleteDataRange::new(version, version)?],
+            synced_ledger_infos: vec![backup_data_source.ledger_info().clone()],
+            transactions: vec![],
+            transaction_outputs: vec![CompleteDataRange::new(version, version)?],
+        };
+        let optimal_chunk_sizes = OptimalChunkSizes {
+            epoch_chunk_size: data_client_config.max_epoch_chunk_size,
+            state_chunk_size: backup_data_source.max_state_chunk_size(),
+            transaction_chunk_size: data_client_config.max_transaction_chunk_size,
+            transaction_output_chunk_size: data_client_config.max_transaction_output_chunk_size,
+        };
+
+        Ok(Self {
+            backup_data_source: Arc::new(backup_data_source),
+            backup_data_summary: GlobalDataSummary {
+                advertised_data,
+                optimal_chunk_sizes,
+            },
+            network_data_client,
+            storage,
+            synced_to_snapshot: Arc::new(AtomicBool::new(false)),
+            next_response_id: Arc::new(AtomicU64::new(0)),
+        })
+    }
+
+    /// Returns true iff the node has synced to (or beyond) the state snapshot. From then
+    /// on, all requests are served by the network.
+    fn is_synced_to_snapshot(&self) -> bool {
+        if self.synced_to_snapshot.load(Ordering::Relaxed) {
+            return true;
+        }
+
+        let synced_version = self
+            .storage
+            .get_latest_ledger_info_option()
+            .ok()
+            .flatten()
+            .map(|ledger_info| ledger_info.ledger_info().version());
+        if synced_version.map_or(false, |version| {
+            version >= self.backup_data_source.version()
+        }) {
+            info!("Synced to the backup state snapshot. Serving all data from the network.");
+            self.synced_to_snapshot.store(true, Ordering::Relaxed);
+            return true;
+        }
+        false
+    }
+
+    /// Returns true iff the output at `start_version` for the `proof_version` is served
+    /// by the backup storage.
+    fn serves_transaction_output(&self, proof_version: Version, start_version: Version) -> bool {
+        let version = self.backup_data_source.version();
+        !self.is_synced_to_snapshot() && proof_version == version && start_version == version
+    }
+
+    /// Wraps the data read from the backup storage in a response
+    fn create_response<P>(&self, result: Result<P>) -> error::Result<Response<P>> {
+        let payload = result.map_err(|error| {
+            Error::UnexpectedErrorEncountered(format!(
+                "Failed to read from the backup storage: {:?}",
+                error
+            ))
+        })?;
+        let id = self.next_response_id.fetch_add(1, Ordering::Relaxed);
+        let context = ResponseContext {
+            id,
+            response_callback: Box::new(BackupResponseCallback { id }),
+        };
+        Ok(Response::new(context, payload))
+    }
+}
+
+#[async_trait]
+impl<T: AptosDataClientInterface + Send + Sync> AptosDataClientInterface for BackupDataClient<T> {
+    fn get_global_data_summary(&self) -> GlobalDataSummary {
+        if self.is_synced_to_snapshot() {
+            self.network_data_client.get_global_data_summary()
+        } else {
+            self.backup_data_summary.clone()
+        }
+    }
+
+    async fn get_epoch_ending_ledger_infos(
+        &self,
+        start_epoch: Epoch,
+        expected_end_epoch: Epoch,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<Vec<LedgerInfoWithSignatures>>> {
+        let last_epoch = self.backup_data_source.ledger_info().ledger_info().epoch();
+        if self.is_synced_to_snapshot() || start_epoch > last_epoch {
+            return self
+                .network_data_client
+                .get_epoch_ending_ledger_infos(start_epoch, expected_end_epoch, request_timeout_ms)
+                .await;
+        }
+
+        let result = self
+            .backup_data_source
+            .get_epoch_ending_ledger_infos(start_epoch, expected_end_epoch.min(last_epoch))
+            .await;
+        self.create_response(result)
+    }
+
+    async fn get_new_transaction_outputs_with_proof(
+        &self,
+        known_version: Version,
+        known_epoch: Epoch,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOutputListWithProof, LedgerInfoWithSignatures)>> {
+        self.network_data_client
+            .get_new_transaction_outputs_with_proof(known_version, known_epoch, request_timeout_ms)
+            .await
+    }
+
+    async fn get_new_transactions_with_proof(
+        &self,
+        known_version: Version,
+        known_epoch: Epoch,
+        include_events: bool,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionListWithProof, LedgerInfoWithSignatures)>> {
+        self.network_data_client
+            .get_new_transactions_with_proof(
+                known_version,
+                known_epoch,
+                include_events,
+                request_timeout_ms,
+            )
+            .await
+    }
+
+    async fn get_new_transactions_or_outputs_with_proof(
+        &self,
+        known_version: Version,
+        known_epoch: Epoch,
+        include_events: bool,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOrOutputListWithProof, LedgerInfoWithSignatures)>> {
+        self.network_data_client
+            .get_new_transactions_or_outputs_with_proof(
+                known_version,
+                known_epoch,
+                include_events,
+                request_timeout_ms,
+            )
+            .await
+    }
+
+    async fn get_number_of_states(
+        &self,
+        version: Version,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<u64>> {
+        if self.is_synced_to_snapshot() || version != self.backup_data_source.version() {
+            return self
+                .network_data_client
+                .get_number_of_states(version, request_timeout_ms)
+                .await;
+        }
+
+        self.create_response(Ok(self.backup_data_source.number_of_states()))
+    }
+
+    async fn get_state_values_with_proof(
+        &self,
+        version: u64,
+        start_index: u64,
+        end_index: u64,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<StateValueChunkWithProof>> {
+        if self.is_synced_to_snapshot() || version != self.backup_data_source.version() {
+            return self
+                .network_data_client
+                .get_state_values_with_proof(version, start_index, end_index, request_timeout_ms)
+                .await;
+        }
+
+        let result = self
+            .backup_data_source
+            .get_state_values_with_proof(start_index, end_index)
+            .await;
+        self.create_response(result)
+    }
+
+    async fn get_transaction_outputs_with_proof(
+        &self,
+        proof_version: Version,
+        start_version: Version,
+        end_version: Version,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionOutputListWithProof>> {
+        if !self.serves_transaction_output(proof_version, start_version) {
+            return self
+                .network_data_client
+                .get_transaction_outputs_with_proof(
+                    proof_version,
+                    start_version,
+                    end_version,
+                    request_timeout_ms,
+                )
+                .await;
+        }
+
+        let result = self
+            .backup_data_source
+            .get_transaction_output_with_proof()
+            .await;
+        self.create_response(result)
+    }
+
+    async fn get_transactions_with_proof(
+        &self,
+        proof_version: Version,
+        start_version: Version,
+        end_version: Version,
+        include_events: bool,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionListWithProof>> {
+        self.network_data_client
+            .get_transactions_with_proof(
+                proof_version,
+                start_version,
+                end_version,
+                include_events,
+                request_timeout_ms,
+            )
+            .await
+    }
+
+    async fn get_transactions_or_outputs_with_proof(
+        &self,
+        proof_version: Version,
+        start_version: Version,
+        end_version: Version,
+        include_events: bool,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionOrOutputListWithProof>> {
+        if !self.serves_transaction_output(proof_version, start_version) {
+            return self
+                .network_data_client
+                .get_transactions_or_outputs_with_proof(
+                    proof_version,
+                    start_version,
+                    end_version,
+                    include_events,
+                    request_timeout_ms,
+                )
+                .await;
+        }
+
+        let result = self
+            .backup_data_source
+            .get_transaction_output_with_proof()
+            .await
+            .map(|output_list_with_proof| (None, Some(output_list_with_proof)));
+        self.create_response(result)
+    }
+
+    async fn subscribe_to_transaction_outputs_with_proof(
+        &self,
+        subscription_request_metadata: SubscriptionRequestMetadata,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOutputListWithProof, LedgerInfoWithSignatures)>> {
+        self.network_data_client
+            .subscribe_to_transaction_outputs_with_proof(
+                subscription_request_metadata,
+                request_timeout_ms,
+            )
+            .await
+    }
+
+    async fn subscribe_to_transactions_with_proof(
+        &self,
+        subscription_request_metadata: SubscriptionRequestMetadata,
+        include_events: bool,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionListWithProof, LedgerInfoWithSignatures)>> {
+        self.network_data_client
+            .subscribe_to_transactions_with_proof(
+                subscription_request_metadata,
+                include_events,
+                request_timeout_ms,
+            )
+            .await
+    }
+
+    async fn subscribe_to_transactions_or_outputs_with_proof(
+        &self,
+        subscription_request_metadata: SubscriptionRequestMetadata,
+        include_events: bool,
+        request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOrOutputListWithProof, LedgerInfoWithSignatures)>> {
+        self.network_data_client
+            .subscribe_to_transactions_or_outputs_with_proof(
+                subscription_request_metadata,
+                include_events,
+                request_timeout_ms,
+            )
+            .await
+    }
+}
+
+/// The response callback for data read from the backup storage. There are no
+/// peers to penalize, so bad responses are only logged.
+#[derive(Debug)]
+struct BackupResponseCallback {
+    id: ResponseId,
+}
+
+impl ResponseCallback for BackupResponseCallback {
+    fn notify_bad_response(&self, error: ResponseError) {
+        warn!(
+            response_id = self.id,
+            error = ?error,
+            "Data read from the backup storage was reported as bad!"
+        );
+    }
+}
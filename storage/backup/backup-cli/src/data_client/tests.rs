@@ -0,0 +1,414 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    backup_types::{
+        epoch_ending::backup::{EpochEndingBackupController, EpochEndingBackupOpt},
+        state_snapshot::backup::{StateSnapshotBackupController, StateSnapshotBackupOpt},
+        transaction::backup::{TransactionBackupController, TransactionBackupOpt},
+    },
+    data_client::{open_backup_storage, BackupDataClient, BackupDataSource},
+    metadata::cache::MetadataCacheOpt,
+    storage::{
+        encryption::{EncryptedStorage, EncryptionKey},
+        local_fs::LocalFs,
+        BackupStorage,
+    },
+    utils::{
+        backup_service_client::BackupServiceClient,
+        test_utils::{start_local_backup_service, tmp_db_empty, tmp_db_with_random_content},
+        GlobalBackupOpt,
+    },
+};
+use aptos_config::config::{
+    AptosDataClientConfig, BackupSourceConfig, BackupStorageLocation, DataStreamingServiceConfig,
+};
+use aptos_crypto::{hash::CryptoHash, HashValue};
+use aptos_data_client::{
+    error,
+    global_summary::GlobalDataSummary,
+    interface::{AptosDataClientInterface, Response, SubscriptionRequestMetadata},
+};
+use aptos_data_streaming_service::{
+    data_notification::DataPayload,
+    streaming_client::{new_streaming_service_client_listener_pair, DataStreamingClient},
+    streaming_service::DataStreamingService,
+};
+use aptos_storage_interface::{DbReader, DbWriter};
+use aptos_storage_service_types::{responses::TransactionOrOutputListWithProof, Epoch};
+use aptos_temppath::TempPath;
+use aptos_time_service::TimeService;
+use aptos_types::{
+    ledger_info::LedgerInfoWithSignatures,
+    state_store::state_value::StateValueChunkWithProof,
+    transaction::{TransactionListWithProof, TransactionOutputListWithProof, Version},
+};
+use async_trait::async_trait;
+use futures::StreamExt;
+use std::{collections::HashSet, sync::Arc};
+use tokio::{runtime::Runtime, time::Duration};
+
+#[test]
+fn serve_fast_sync_data_from_backup() {
+    serve_fast_sync_data_from_backup_impl(None)
+}
+
+#[test]
+fn serve_fast_sync_data_from_encrypted_backup() {
+    serve_fast_sync_data_from_backup_impl(Some("ab".repeat(32)))
+}
+
+fn serve_fast_sync_data_from_backup_impl(hex_encryption_key: Option<String>) {
+    let (_src_db_dir, src_db, _blocks) = tmp_db_with_random_content();
+    let backup_dir = TempPath::new();
+    backup_dir.create_as_dir().unwrap();
+    let metadata_cache_dir = TempPath::new();
+    let encryption_key = hex_encryption_key
+        .as_ref()
+        .map(|hex_key| EncryptionKey::from_hex(hex_key).unwrap());
+    let store: Arc<dyn BackupStorage> = Arc::new(EncryptedStorage::new(
+        Arc::new(LocalFs::new(backup_dir.path().to_path_buf())),
+        encryption_key,
+    ));
+
+    let epoch = src_db
+        .get_latest_ledger_info()
+        .unwrap()
+        .ledger_info()
+        .next_block_epoch()
+        - 1;
+    let epoch_ending_lis = src_db
+        .get_epoch_ending_ledger_infos(0, epoch + 1)
+        .unwrap()
+        .ledger_info_with_sigs;
+    let latest_epoch_ending_li = epoch_ending_lis.last().unwrap().clone();
+    let version = latest_epoch_ending_li.ledger_info().version();
+
+    let (rt, port) = start_local_backup_service(Arc::clone(&src_db));
+    let client = Arc::new(BackupServiceClient::new(format!(
+        "http://localhost:{}",
+        port
+    )));
+    let global_opt = GlobalBackupOpt {
+        max_chunk_size: 500,
+    };
+    rt.block_on(
+        EpochEndingBackupController::new(
+            EpochEndingBackupOpt {
+                start_epoch: 0,
+                end_epoch: epoch + 1,
+            },
+            global_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        StateSnapshotBackupController::new(
+            StateSnapshotBackupOpt { epoch },
+            global_opt.clone(),
+            Arc::clone(&client),
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+    rt.block_on(
+        TransactionBackupController::new(
+            TransactionBackupOpt {
+                start_version: 0,
+                num_transactions: version as usize + 1,
+            },
+            global_opt,
+            client,
+            Arc::clone(&store),
+        )
+        .run(),
+    )
+    .unwrap();
+
+    // Read the backups through the storage opened from the node config
+    let mut backup_source = BackupSourceConfig::new(BackupStorageLocation::LocalFs {
+        dir: backup_dir.path().to_path_buf(),
+    });
+    let key_file = TempPath::new();
+    if let Some(hex_key) = &hex_encryption_key {
+        // Encrypted backups can't be read without the key
+        let storage = rt.block_on(open_backup_storage(&backup_source)).unwrap();
+        let no_key_cache_dir = TempPath::new();
+        assert!(rt
+            .block_on(BackupDataSource::new(
+                storage,
+                &MetadataCacheOpt::new(Some(no_key_cache_dir.path())),
+                4,
+            ))
+            .is_err());
+
+        std::fs::write(key_file.path(), hex_key).unwrap();
+        backup_source.encryption_key_file = Some(key_file.path().to_path_buf());
+    }
+    let storage = rt.block_on(open_backup_storage(&backup_source)).unwrap();
+    let data_source = rt
+        .block_on(BackupDataSource::new(
+            storage,
+            &MetadataCacheOpt::new(Some(metadata_cache_dir.path())),
+            4,
+        ))
+        .unwrap();
+    assert_eq!(data_source.version(), version);
+    assert_eq!(data_source.ledger_info(), &latest_epoch_ending_li);
+
+    // The epoch ending ledger infos match the ones in the DB
+    let mut next_epoch = 0;
+    while next_epoch <= epoch {
+        let lis = rt
+            .block_on(data_source.get_epoch_ending_ledger_infos(next_epoch, epoch))
+            .unwrap();
+        assert!(!lis.is_empty());
+        for li in lis {
+            assert_eq!(li, epoch_ending_lis[next_epoch as usize]);
+            next_epoch += 1;
+        }
+    }
+
+    // The state values cover the whole snapshot, with the root hash of the snapshot
+    let state_root_hash = src_db
+        .get_transactions(version, 1, version, false)
+        .unwrap()
+        .proof
+        .transaction_infos
+        .pop()
+        .unwrap()
+        .state_checkpoint_hash()
+        .unwrap();
+    let number_of_states = data_source.number_of_states();
+    let mut next_index = 0;
+    while next_index < number_of_states {
+        let chunk = rt
+            .block_on(data_source.get_state_values_with_proof(next_index, number_of_states - 1))
+            .unwrap();
+        assert_eq!(chunk.first_index, next_index);
+        assert_eq!(
+            chunk.raw_values.len() as u64,
+            chunk.last_index - chunk.first_index + 1
+        );
+        assert_eq!(chunk.root_hash, state_root_hash);
+        next_index = chunk.last_index + 1;
+    }
+
+    // Chunks can start and end in the middle of a backup chunk
+    let chunk = rt
+        .block_on(data_source.get_state_values_with_proof(1, 2))
+        .unwrap();
+    assert_eq!(chunk.first_index, 1);
+    assert_eq!(chunk.last_index, 2);
+    assert_eq!(chunk.first_key, chunk.raw_values[0].0.hash());
+    assert_eq!(chunk.last_key, chunk.raw_values[1].0.hash());
+
+    // The transaction output at the snapshot version verifies against the ledger info
+    let output_list_with_proof = rt
+        .block_on(data_source.get_transaction_output_with_proof())
+        .unwrap();
+    output_list_with_proof
+        .verify(latest_epoch_ending_li.ledger_info(), Some(version))
+        .unwrap();
+
+    stream_state_values_from_backup(&rt, data_source, state_root_hash);
+    rt.shutdown_timeout(Duration::from_secs(1));
+}
+
+/// Streams the state snapshot through the data streaming service, which requests state values
+/// in ranges of the largest backup chunk size, and restores it into an empty DB.
+fn stream_state_values_from_backup(
+    rt: &Runtime,
+    data_source: BackupDataSource,
+    state_root_hash: HashValue,
+) {
+    let version = data_source.version();
+    let backup_chunk_ends: HashSet<u64> = data_source
+        .state_snapshot
+        .chunks
+        .iter()
+        .map(|chunk| chunk.last_idx as u64)
+        .collect();
+    let backup_chunk_sizes: HashSet<usize> = data_source
+        .state_snapshot
+        .chunks
+        .iter()
+        .map(|chunk| chunk.last_idx - chunk.first_idx + 1)
+        .collect();
+    assert!(backup_chunk_sizes.len() > 1);
+
+    let (_db_dir, db) = tmp_db_empty();
+    let data_client = BackupDataClient::new(
+        AptosDataClientConfig::default(),
+        data_source,
+        UnreachableNetworkClient,
+        Arc::clone(&db) as Arc<dyn DbReader>,
+    )
+    .unwrap();
+    let (streaming_client, streaming_service_listener) =
+        new_streaming_service_client_listener_pair();
+    let streaming_service = DataStreamingService::new(
+        AptosDataClientConfig::default(),
+        DataStreamingServiceConfig::default(),
+        data_client,
+        streaming_service_listener,
+        TimeService::real(),
+    );
+    rt.spawn(streaming_service.start_service());
+
+    let mut receiver = db
+        .get_state_snapshot_receiver(version, state_root_hash)
+        .unwrap();
+    let mut stream = rt
+        .block_on(streaming_client.get_all_state_values(version, None))
+        .unwrap();
+    let mut next_index = 0;
+    let mut ended_inside_backup_chunk = false;
+    loop {
+        let notification = rt.block_on(stream.next()).unwrap();
+        let chunk = match notification.data_payload {
+            DataPayload::StateValuesWithProof(chunk) => chunk,
+            DataPayload::EndOfStream => break,
+            payload => panic!("Unexpected payload: {:?}", payload),
+        };
+        assert_eq!(chunk.first_index, next_index);
+        ended_inside_backup_chunk |= !backup_chunk_ends.contains(&chunk.last_index);
+        next_index = chunk.last_index + 1;
+        // The proofs are verified as the chunks are added
+        receiver.add_chunk(chunk.raw_values, chunk.proof).unwrap();
+    }
+    receiver.finish_box().unwrap();
+    assert!(ended_inside_backup_chunk);
+    assert_eq!(
+        db.get_state_leaf_count(version).unwrap(),
+        next_index as usize
+    );
+}
+
+/// A network client for tests in which all data is served by the backup
+struct UnreachableNetworkClient;
+
+#[async_trait]
+impl AptosDataClientInterface for UnreachableNetworkClient {
+    fn get_global_data_summary(&self) -> GlobalDataSummary {
+        GlobalDataSummary::empty()
+    }
+
+    async fn get_epoch_ending_ledger_infos(
+        &self,
+        _start_epoch: Epoch,
+        _expected_end_epoch: Epoch,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<Vec<LedgerInfoWithSignatures>>> {
+        unreachable!()
+    }
+
+    async fn get_new_transaction_outputs_with_proof(
+        &self,
+        _known_version: Version,
+        _known_epoch: Epoch,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOutputListWithProof, LedgerInfoWithSignatures)>> {
+        unreachable!()
+    }
+
+    async fn get_new_transactions_with_proof(
+        &self,
+        _known_version: Version,
+        _known_epoch: Epoch,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionListWithProof, LedgerInfoWithSignatures)>> {
+        unreachable!()
+    }
+
+    async fn get_new_transactions_or_outputs_with_proof(
+        &self,
+        _known_version: Version,
+        _known_epoch: Epoch,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOrOutputListWithProof, LedgerInfoWithSignatures)>> {
+        unreachable!()
+    }
+
+    async fn get_number_of_states(
+        &self,
+        _version: Version,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<u64>> {
+        unreachable!()
+    }
+
+    async fn get_state_values_with_proof(
+        &self,
+        _version: u64,
+        _start_index: u64,
+        _end_index: u64,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<StateValueChunkWithProof>> {
+        unreachable!()
+    }
+
+    async fn get_transaction_outputs_with_proof(
+        &self,
+        _proof_version: Version,
+        _start_version: Version,
+        _end_version: Version,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionOutputListWithProof>> {
+        unreachable!()
+    }
+
+    async fn get_transactions_with_proof(
+        &self,
+        _proof_version: Version,
+        _start_version: Version,
+        _end_version: Version,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionListWithProof>> {
+        unreachable!()
+    }
+
+    async fn get_transactions_or_outputs_with_proof(
+        &self,
+        _proof_version: Version,
+        _start_version: Version,
+        _end_version: Version,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<TransactionOrOutputListWithProof>> {
+        unreachable!()
+    }
+
+    async fn subscribe_to_transaction_outputs_with_proof(
+        &self,
+        _subscription_request_metadata: SubscriptionRequestMetadata,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOutputListWithProof, LedgerInfoWithSignatures)>> {
+        unreachable!()
+    }
+
+    async fn subscribe_to_transactions_with_proof(
+        &self,
+        _subscription_request_metadata: SubscriptionRequestMetadata,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionListWithProof, LedgerInfoWithSignatures)>> {
+        unreachable!()
+    }
+
+    async fn subscribe_to_transactions_or_outputs_with_proof(
+        &self,
+        _subscription_request_metadata: SubscriptionRequestMetadata,
+        _include_events: bool,
+        _request_timeout_ms: u64,
+    ) -> error::Result<Response<(TransactionOrOutputListWithProof, LedgerInfoWithSignatures)>> {
+        unreachable!()
+    }
+}
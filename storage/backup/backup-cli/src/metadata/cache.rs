@@ -218,7 +218,7 @@ impl FileHandleHash for FileHandle {
 }
 
 #[async_trait]
-trait LoadMetadataLines {
+pub(crate) trait LoadMetadataLines {
     async fn load_metadata_lines(&mut self) -> Result<Vec<Metadata>>;
 }
 
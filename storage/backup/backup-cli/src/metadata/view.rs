@@ -101,6 +101,21 @@ impl MetadataView {
         })
     }
 
+    /// All epoch ending backups, sorted, including overlapping ones and regardless of gaps.
+    pub fn all_epoch_ending_backups(&self) -> &[EpochEndingBackupMeta] {
+        &self.epoch_ending_backups
+    }
+
+    /// All state snapshot backups, sorted by epoch and version.
+    pub fn all_state_snapshot_backups(&self) -> &[StateSnapshotBackupMeta] {
+        &self.state_snapshot_backups
+    }
+
+    /// All transaction backups, sorted, including overlapping ones and regardless of gaps.
+    pub fn all_transaction_backups(&self) -> &[TransactionBackupMeta] {
+        &self.transaction_backups
+    }
+
     pub fn select_latest_compaction_timestamps(&self) -> Option<CompactionTimestampsMeta> {
         self.compaction_timestamps.clone()
     }
@@ -131,26 +146,30 @@ impl MetadataView {
         start_version: Version,
         target_version: Version,
     ) -> Result<Vec<TransactionBackupMeta>> {
-        // This can be more flexible, but for now we assume and check backups are continuous in
-        // range (which is always true when we backup from a single backup coordinator)
-        let mut next_ver = 0;
+        // Backups ending before `start_version` are not needed and may have been removed by
+        // retention. From there on, this can be more flexible, but for now we assume and check
+        // backups are continuous in range (which is always true when we backup from a single
+        // backup coordinator)
+        let mut next_ver = None;
         let mut res = Vec::new();
         for backup in self.transaction_backups.iter().sorted() {
             if backup.first_version > target_version {
                 break;
             }
+            if backup.last_version < start_version {
+                continue;
+            }
+            let expected_ver =
+                next_ver.unwrap_or_else(|| std::cmp::min(start_version, backup.first_version));
             ensure!(
-                backup.first_version == next_ver,
+                backup.first_version == expected_ver,
                 "Transaction backup ranges not continuous, expecting version {}, got {}.",
-                next_ver,
+                expected_ver,
                 backup.first_version,
             );
 
-            if backup.last_version >= start_version {
-                res.push(backup.clone());
-            }
-
-            next_ver = backup.last_version + 1;
+            res.push(backup.clone());
+            next_ver = Some(backup.last_version + 1);
         }
 
         Ok(res)
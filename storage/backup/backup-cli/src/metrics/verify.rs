@@ -76,3 +76,35 @@ pub static VERIFY_COORDINATOR_FAIL_TS: Lazy<IntGauge> = Lazy::new(|| {
     )
     .unwrap()
 });
+
+pub static AUDIT_COORDINATOR_START_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_audit_coordinator_start_timestamp_s",
+        "Timestamp when the audit coordinator starts."
+    )
+    .unwrap()
+});
+
+pub static AUDIT_COORDINATOR_SUCC_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_audit_coordinator_succeed_timestamp_s",
+        "Timestamp when the audit coordinator finishes without finding problems."
+    )
+    .unwrap()
+});
+
+pub static AUDIT_COORDINATOR_FAIL_TS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_audit_coordinator_fail_timestamp_s",
+        "Timestamp when the audit coordinator fails or finds problems."
+    )
+    .unwrap()
+});
+
+pub static AUDIT_NUM_PROBLEMS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!(
+        "aptos_db_backup_audit_num_problems",
+        "Number of problems found by the last audit."
+    )
+    .unwrap()
+});
@@ -70,6 +70,10 @@ pub struct Commands {
     pub list_metadata_files: String,
     /// Command line to backup one metadata file to a metadata backup folder
     pub backup_metadata_file: Option<String>,
+    /// Command line to delete a file, needed only for retention.
+    /// input env vars:
+    ///     $FILE_HANDLE
+    pub delete_file: Option<String>,
 }
 
 #[derive(Clone, Default, Deserialize)]
@@ -160,6 +160,19 @@ impl BackupStorage for CommandAdapter {
         Ok(())
     }
 
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let cmd = self
+            .config
+            .commands
+            .delete_file
+            .as_ref()
+            .ok_or_else(|| format_err!("delete_file command not defined."))?;
+        let child = self
+            .cmd(cmd, vec![EnvVar::file_handle(file_handle.to_string())])
+            .spawn()?;
+        child.join().await
+    }
+
     async fn save_metadata_lines(
         &self,
         name: &ShellSafeName,
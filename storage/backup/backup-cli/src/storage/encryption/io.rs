@@ -0,0 +1,314 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! Format of an encrypted file:
+//!
+//!   magic | version | key id length (u8) | key id | wrapped data key | frame ...
+//!
+//! The wrapped data key is the random per-file data key, sealed with the master key. Each frame
+//! is `is_last (u8) | ciphertext length (u32 BE) | ciphertext`, sealed with the data key, using
+//! the frame index as the nonce and `is_last` as the associated data, so reordered, dropped or
+//! truncated frames fail to decrypt.
+
+use crate::storage::encryption::key::{new_aead_key, random_bytes, EncryptionKey, KEY_LEN};
+use anyhow::{anyhow, bail, ensure, Result};
+use bytes::Bytes;
+use futures::{
+    stream::{StreamExt, TryStreamExt},
+    task::{Context, Poll},
+};
+use ring::aead::{Aad, LessSafeKey, Nonce, AES_256_GCM, NONCE_LEN};
+use std::{io::Cursor, pin::Pin};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+pub(super) const FILE_MAGIC: &[u8; 8] = b"APTOSENC";
+/// Encrypted metadata lines start with this, followed by "<key id>:<hex of the sealed line>".
+pub(super) const LINE_PREFIX: &str = "aptosenc:v1:";
+const VERSION: u8 = 1;
+const FRAME_SIZE: usize = 1 << 20;
+
+fn frame_nonce(frame_index: u64) -> Nonce {
+    let mut nonce = [0u8; NONCE_LEN];
+    nonce[NONCE_LEN - 8..].copy_from_slice(&frame_index.to_be_bytes());
+    Nonce::assume_unique_for_key(nonce)
+}
+
+fn to_io_error(e: anyhow::Error) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, e)
+}
+
+/// Encrypts everything written to it into the inner writer. The last frame is only written on
+/// `shutdown()`, without which the file won't decrypt.
+pub(super) struct EncryptingWriter {
+    inner: Box<dyn AsyncWrite + Send + Unpin>,
+    data_key: LessSafeKey,
+    plaintext: Vec<u8>,
+    pending: Vec<u8>,
+    pending_pos: usize,
+    frame_index: u64,
+    finished: bool,
+}
+
+impl EncryptingWriter {
+    pub fn new(key: &EncryptionKey, inner: Box<dyn AsyncWrite + Send + Unpin>) -> Result<Self> {
+        let data_key = random_bytes::<KEY_LEN>()?;
+        let key_id = key.id().as_bytes();
+
+        let mut header = FILE_MAGIC.to_vec();
+        header.push(VERSION);
+        header.push(key_id.len() as u8);
+        header.extend(key_id);
+        header.extend(key.seal(&data_key, key_id)?);
+
+        Ok(Self {
+            inner,
+            data_key: new_aead_key(&data_key)?,
+            plaintext: Vec::with_capacity(FRAME_SIZE),
+            pending: header,
+            pending_pos: 0,
+            frame_index: 0,
+            finished: false,
+        })
+    }
+
+    fn seal_frame(&mut self, is_last: bool) -> std::io::Result<()> {
+        let mut in_out = std::mem::take(&mut self.plaintext);
+        self.data_key
+            .seal_in_place_append_tag(
+                frame_nonce(self.frame_index),
+                Aad::from([is_last as u8]),
+                &mut in_out,
+            )
+            .map_err(|_| to_io_error(anyhow!("Failed to encrypt frame.")))?;
+        self.frame_index += 1;
+
+        self.pending.push(is_last as u8);
+        self.pending.extend((in_out.len() as u32).to_be_bytes());
+        self.pending.extend(in_out);
+        self.plaintext = Vec::with_capacity(FRAME_SIZE);
+        Ok(())
+    }
+
+    fn poll_write_pending(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        while self.pending_pos < self.pending.len() {
+            let n = futures::ready!(
+                Pin::new(&mut self.inner).poll_write(cx, &self.pending[self.pending_pos..])
+            )?;
+            if n == 0 {
+                return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+            }
+            self.pending_pos += n;
+        }
+        self.pending.clear();
+        self.pending_pos = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for EncryptingWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        if self.finished {
+            return Poll::Ready(Err(std::io::ErrorKind::BrokenPipe.into()));
+        }
+        futures::ready!(self.poll_write_pending(cx))?;
+
+        let n = std::cmp::min(buf.len(), FRAME_SIZE - self.plaintext.len());
+        self.plaintext.extend_from_slice(&buf[..n]);
+        if self.plaintext.len() == FRAME_SIZE {
+            self.seal_frame(false)?;
+        }
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        futures::ready!(self.poll_write_pending(cx))?;
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        if !self.finished {
+            futures::ready!(self.poll_write_pending(cx))?;
+            self.seal_frame(true)?;
+            self.finished = true;
+        }
+        futures::ready!(self.poll_write_pending(cx))?;
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
+
+/// Reads up to `N` bytes, fewer only if the reader hits EOF.
+async fn read_prefix<const N: usize>(
+    reader: &mut (dyn AsyncRead + Send + Unpin),
+) -> Result<Vec<u8>> {
+    let mut prefix = vec![0u8; N];
+    let mut len = 0;
+    while len < N {
+        let n = reader.read(&mut prefix[len..]).await?;
+        if n == 0 {
+            break;
+        }
+        len += n;
+    }
+    prefix.truncate(len);
+    Ok(prefix)
+}
+
+/// Returns a reader of the plaintext of `file_handle`, decrypting it if it's encrypted. Files
+/// written without encryption are returned as is.
+pub(super) async fn open_for_read(
+    key: Option<&EncryptionKey>,
+    mut reader: Box<dyn AsyncRead + Send + Unpin>,
+    file_handle: &str,
+) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+    let prefix = read_prefix::<8>(reader.as_mut()).await?;
+
+    if prefix == FILE_MAGIC {
+        let (key, data_key) = read_header(key, reader.as_mut(), file_handle).await?;
+        Ok(decrypting_reader(
+            reader,
+            data_key,
+            format!("file {} (key {})", file_handle, key.id()),
+        ))
+    } else if prefix == LINE_PREFIX.as_bytes()[..8] {
+        let mut content = String::from_utf8(prefix)?;
+        reader.read_to_string(&mut content).await?;
+        let mut plaintext = String::new();
+        for line in content.lines() {
+            plaintext.push_str(&open_line(key, line, file_handle)?);
+            plaintext.push('\n');
+        }
+        Ok(Box::new(Cursor::new(plaintext.into_bytes())))
+    } else {
+        Ok(Box::new(Cursor::new(prefix).chain(reader)))
+    }
+}
+
+async fn read_header<'a>(
+    key: Option<&'a EncryptionKey>,
+    reader: &mut (dyn AsyncRead + Send + Unpin),
+    file_handle: &str,
+) -> Result<(&'a EncryptionKey, LessSafeKey)> {
+    let version = reader.read_u8().await?;
+    ensure!(
+        version == VERSION,
+        "File {} is encrypted with unknown format version {}.",
+        file_handle,
+        version,
+    );
+    let mut key_id = vec![0u8; reader.read_u8().await? as usize];
+    reader.read_exact(&mut key_id).await?;
+    let key_id = String::from_utf8(key_id)?;
+
+    let key = match key {
+        Some(key) => key,
+        None => bail!(
+            "File {} is encrypted with key {}, but no encryption key is configured.",
+            file_handle,
+            key_id,
+        ),
+    };
+    let what = format!("file {}", file_handle);
+    key.ensure_id(&key_id, &what)?;
+
+    let mut wrapped_data_key = vec![0u8; NONCE_LEN + KEY_LEN + AES_256_GCM.tag_len()];
+    reader.read_exact(&mut wrapped_data_key).await?;
+    let data_key = key.open(&wrapped_data_key, key_id.as_bytes(), &what)?;
+
+    Ok((key, new_aead_key(&data_key)?))
+}
+
+fn decrypting_reader(
+    reader: Box<dyn AsyncRead + Send + Unpin>,
+    data_key: LessSafeKey,
+    what: String,
+) -> Box<dyn AsyncRead + Send + Unpin> {
+    let frames = futures::stream::try_unfold(
+        (reader, data_key, what, 0u64, false),
+        |(mut reader, data_key, what, frame_index, done)| async move {
+            if done {
+                ensure!(
+                    read_prefix::<1>(reader.as_mut()).await?.is_empty(),
+                    "Unexpected data after the last frame of {}.",
+                    what,
+                );
+                return Ok(None);
+            }
+
+            let is_last = reader
+                .read_u8()
+                .await
+                .map_err(|e| anyhow!("Failed to read {}, truncated? {}", what, e))?;
+            ensure!(is_last <= 1, "Bad frame header in {}.", what);
+            let len = reader.read_u32().await? as usize;
+            ensure!(
+                len <= FRAME_SIZE + AES_256_GCM.tag_len(),
+                "Frame too large in {}.",
+                what,
+            );
+            let mut in_out = vec![0u8; len];
+            reader.read_exact(&mut in_out).await?;
+            let plaintext_len = data_key
+                .open_in_place(frame_nonce(frame_index), Aad::from([is_last]), &mut in_out)
+                .map_err(|_| anyhow!("Failed to decrypt frame {} of {}.", frame_index, what))?
+                .len();
+            in_out.truncate(plaintext_len);
+
+            Ok(Some((
+                Bytes::from(in_out),
+                (reader, data_key, what, frame_index + 1, is_last == 1),
+            )))
+        },
+    );
+
+    Box::new(
+        frames
+            .map_err(to_io_error)
+            .boxed()
+            .into_async_read()
+            .compat(),
+    )
+}
+
+/// Encrypts a metadata line into another line.
+pub(super) fn seal_line(key: &EncryptionKey, line: &str) -> Result<String> {
+    let sealed = key.seal(line.as_bytes(), key.id().as_bytes())?;
+    Ok(format!(
+        "{}{}:{}",
+        LINE_PREFIX,
+        key.id(),
+        hex::encode(sealed)
+    ))
+}
+
+/// Decrypts a metadata line, lines that are not encrypted are returned as is.
+pub(super) fn open_line(
+    key: Option<&EncryptionKey>,
+    line: &str,
+    file_handle: &str,
+) -> Result<String> {
+    let sealed_line = match line.strip_prefix(LINE_PREFIX) {
+        Some(sealed_line) => sealed_line,
+        None => return Ok(line.to_string()),
+    };
+    let (key_id, sealed) = sealed_line
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Malformed encrypted line in metadata file {}.", file_handle))?;
+
+    let what = format!("metadata file {}", file_handle);
+    let key = match key {
+        Some(key) => key,
+        None => bail!(
+            "Metadata file {} is encrypted with key {}, but no encryption key is configured.",
+            file_handle,
+            key_id,
+        ),
+    };
+    key.ensure_id(key_id, &what)?;
+    let plaintext = key.open(&hex::decode(sealed)?, key_id.as_bytes(), &what)?;
+    Ok(String::from_utf8(plaintext)?)
+}
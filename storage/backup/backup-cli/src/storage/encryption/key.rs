@@ -0,0 +1,128 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{anyhow, bail, ensure, Result};
+use ring::{
+    aead::{Aad, LessSafeKey, Nonce, UnboundKey, AES_256_GCM, NONCE_LEN},
+    digest::{digest, SHA256},
+    rand::{SecureRandom, SystemRandom},
+};
+use std::fmt;
+
+pub(super) const KEY_LEN: usize = 32;
+
+/// The master key backups are encrypted with. Each file is encrypted with its own random data
+/// key, which is in turn encrypted (wrapped) with the master key and stored in the file header.
+#[derive(Clone)]
+pub struct EncryptionKey {
+    id: String,
+    bytes: [u8; KEY_LEN],
+}
+
+impl EncryptionKey {
+    /// Parses a hex encoded 256 bit key, as generated by `openssl rand -hex 32` for example.
+    pub fn from_hex(hex_key: &str) -> Result<Self> {
+        let bytes = hex::decode(hex_key.trim())
+            .map_err(|e| anyhow!("Encryption key is not valid hex: {}", e))?;
+        Self::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        ensure!(
+            bytes.len() == KEY_LEN,
+            "Encryption key must be {} bytes, got {}.",
+            KEY_LEN,
+            bytes.len()
+        );
+        let mut key = [0u8; KEY_LEN];
+        key.copy_from_slice(bytes);
+
+        // The key id is a fingerprint of the key, recorded in every encrypted file and metadata
+        // line so a wrong key is detected before attempting to decrypt.
+        let id = hex::encode(&digest(&SHA256, &key).as_ref()[..8]);
+
+        Ok(Self { id, bytes: key })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub(super) fn ensure_id(&self, key_id: &str, what: &str) -> Result<()> {
+        ensure!(
+            key_id == self.id,
+            "{} is encrypted with key {}, but the configured encryption key is {}.",
+            what,
+            key_id,
+            self.id,
+        );
+        Ok(())
+    }
+
+    /// Encrypts with a random nonce, returning the nonce followed by the ciphertext and tag.
+    pub(super) fn seal(&self, plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        seal_with_random_nonce(&self.bytes, plaintext, aad)
+    }
+
+    /// Reverse of `seal()`.
+    pub(super) fn open(&self, sealed: &[u8], aad: &[u8], what: &str) -> Result<Vec<u8>> {
+        open_with_prepended_nonce(&self.bytes, sealed, aad).map_err(|_| {
+            anyhow!(
+                "Failed to decrypt {} with key {}, the key is wrong or the data is corrupted.",
+                what,
+                self.id,
+            )
+        })
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Never print the key itself.
+        write!(f, "EncryptionKey {{ id: {} }}", self.id)
+    }
+}
+
+pub(super) fn new_aead_key(bytes: &[u8]) -> Result<LessSafeKey> {
+    let key = UnboundKey::new(&AES_256_GCM, bytes).map_err(|_| anyhow!("Invalid AES key."))?;
+    Ok(LessSafeKey::new(key))
+}
+
+pub(super) fn random_bytes<const N: usize>() -> Result<[u8; N]> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| anyhow!("Failed to generate random bytes."))?;
+    Ok(bytes)
+}
+
+fn seal_with_random_nonce(key: &[u8], plaintext: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    let nonce = random_bytes::<NONCE_LEN>()?;
+    let mut in_out = plaintext.to_vec();
+    new_aead_key(key)?
+        .seal_in_place_append_tag(
+            Nonce::assume_unique_for_key(nonce),
+            Aad::from(aad),
+            &mut in_out,
+        )
+        .map_err(|_| anyhow!("Failed to encrypt."))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend(in_out);
+    Ok(sealed)
+}
+
+fn open_with_prepended_nonce(key: &[u8], sealed: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+    if sealed.len() < NONCE_LEN {
+        bail!("Sealed data too short.");
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).map_err(|_| anyhow!("Bad nonce."))?;
+    let mut in_out = ciphertext.to_vec();
+    let plaintext_len = new_aead_key(key)?
+        .open_in_place(nonce, Aad::from(aad), &mut in_out)
+        .map_err(|_| anyhow!("Failed to decrypt."))?
+        .len();
+    in_out.truncate(plaintext_len);
+    Ok(in_out)
+}
@@ -0,0 +1,137 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod io;
+mod key;
+
+#[cfg(test)]
+mod tests;
+
+pub use crate::storage::encryption::key::EncryptionKey;
+use crate::{
+    storage::{
+        encryption::io::{open_for_read, seal_line, EncryptingWriter},
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::error_notes::ErrorNotes,
+};
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use clap::Parser;
+use std::{path::PathBuf, sync::Arc};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[derive(Clone, Debug, Default, Parser)]
+pub struct EncryptionOpt {
+    #[clap(
+        long,
+        value_parser,
+        conflicts_with = "encryption_key_env",
+        help = "File holding a hex encoded 256 bit key (e.g. from `openssl rand -hex 32`). When \
+        specified, backup files and metadata lines are encrypted with it, and encrypted backups \
+        can be read. Backups written without encryption stay readable."
+    )]
+    pub encryption_key_file: Option<PathBuf>,
+    #[clap(
+        long,
+        help = "Name of the env var holding the encryption key, alternative to \
+        --encryption-key-file."
+    )]
+    pub encryption_key_env: Option<String>,
+}
+
+impl EncryptionOpt {
+    pub async fn load_key(&self) -> Result<Option<EncryptionKey>> {
+        if let Some(path) = &self.encryption_key_file {
+            let hex_key = tokio::fs::read_to_string(path).await.err_notes(path)?;
+            Ok(Some(EncryptionKey::from_hex(&hex_key).err_notes(path)?))
+        } else if let Some(var) = &self.encryption_key_env {
+            let hex_key =
+                std::env::var(var).map_err(|e| anyhow!("Failed to read env var {}: {}", var, e))?;
+            Ok(Some(EncryptionKey::from_hex(&hex_key).err_notes(var)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Envelope encryption on top of another `BackupStorage`. With a key, all files and metadata
+/// lines written are encrypted, and the key id is recorded along with them.
+///
+/// Reads are transparent: encrypted content is decrypted (failing with a clear error if it was
+/// encrypted with another key or no key is configured) and plaintext content is returned as is,
+/// so a backup can switch to encryption without rewriting what's already there.
+pub struct EncryptedStorage {
+    inner: Arc<dyn BackupStorage>,
+    key: Option<EncryptionKey>,
+}
+
+impl EncryptedStorage {
+    pub fn new(inner: Arc<dyn BackupStorage>, key: Option<EncryptionKey>) -> Self {
+        Self { inner, key }
+    }
+
+    pub async fn new_with_opt(inner: Arc<dyn BackupStorage>, opt: &EncryptionOpt) -> Result<Self> {
+        Ok(Self::new(inner, opt.load_key().await?))
+    }
+}
+
+#[async_trait]
+impl BackupStorage for EncryptedStorage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        self.inner.create_backup(name).await
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let (file_handle, writer) = self.inner.create_for_write(backup_handle, name).await?;
+        let writer: Box<dyn AsyncWrite + Send + Unpin> = match &self.key {
+            Some(key) => Box::new(EncryptingWriter::new(key, writer)?),
+            None => writer,
+        };
+        Ok((file_handle, writer))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        let reader = self.inner.open_for_read(file_handle).await?;
+        open_for_read(self.key.as_ref(), reader, file_handle).await
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        self.inner.list_metadata_files().await
+    }
+
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.backup_metadata_file(file_handle).await
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.inner.delete_file(file_handle).await
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        match &self.key {
+            Some(key) => {
+                let lines = lines
+                    .iter()
+                    .map(|line| {
+                        TextLine::new(&seal_line(key, line.as_ref().trim_end_matches('\n'))?)
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                self.inner.save_metadata_lines(name, &lines).await
+            },
+            None => self.inner.save_metadata_lines(name, lines).await,
+        }
+    }
+}
@@ -0,0 +1,192 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use crate::storage::{
+    encryption::io::{FILE_MAGIC, LINE_PREFIX},
+    local_fs::LocalFs,
+    test_util::{
+        arb_backups, arb_metadata_files, test_save_and_list_metadata_files_impl,
+        test_write_and_read_impl,
+    },
+};
+use aptos_temppath::TempPath;
+use proptest::prelude::*;
+use std::str::FromStr;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    runtime::Runtime,
+};
+
+fn test_key(seed: u8) -> EncryptionKey {
+    EncryptionKey::from_bytes(&[seed; 32]).unwrap()
+}
+
+fn local_fs(tmpdir: &TempPath) -> Arc<dyn BackupStorage> {
+    tmpdir.create_as_dir().unwrap();
+    Arc::new(LocalFs::new(tmpdir.path().to_path_buf()))
+}
+
+async fn write_file(store: &dyn BackupStorage, content: &[u8]) -> FileHandle {
+    let name = ShellSafeName::from_str("backup_1").unwrap();
+    let backup_handle = store.create_backup(&name).await.unwrap();
+    let (file_handle, mut file) = store
+        .create_for_write(
+            &backup_handle,
+            &ShellSafeName::from_str("file.chunk").unwrap(),
+        )
+        .await
+        .unwrap();
+    file.write_all(content).await.unwrap();
+    file.shutdown().await.unwrap();
+    file_handle
+}
+
+async fn read_file(store: &dyn BackupStorage, file_handle: &FileHandleRef) -> Result<Vec<u8>> {
+    let mut buf = Vec::new();
+    store
+        .open_for_read(file_handle)
+        .await?
+        .read_to_end(&mut buf)
+        .await?;
+    Ok(buf)
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(10))]
+
+    #[test]
+    fn test_write_and_read(
+        backups in arb_backups()
+    ) {
+        let tmpdir = TempPath::new();
+        let store = EncryptedStorage::new(local_fs(&tmpdir), Some(test_key(1)));
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_write_and_read_impl(Box::new(store), backups));
+    }
+
+    #[test]
+    fn test_save_list_metadata_files(
+        input in arb_metadata_files(),
+    ) {
+        let tmpdir = TempPath::new();
+        let store = EncryptedStorage::new(local_fs(&tmpdir), Some(test_key(1)));
+
+        let rt = Runtime::new().unwrap();
+        rt.block_on(test_save_and_list_metadata_files_impl(Box::new(store), input));
+    }
+}
+
+#[tokio::test]
+async fn test_encrypted_at_rest() {
+    let tmpdir = TempPath::new();
+    let inner = local_fs(&tmpdir);
+    let key = test_key(1);
+    let store = EncryptedStorage::new(inner.clone(), Some(key.clone()));
+
+    // Spans a few frames, the last one partial.
+    let content = (0..(5 << 19) + 123)
+        .map(|i| (i % 251) as u8)
+        .collect::<Vec<_>>();
+    let file_handle = write_file(&store, &content).await;
+    let raw = read_file(inner.as_ref(), &file_handle).await.unwrap();
+    assert!(raw.starts_with(FILE_MAGIC));
+    assert!(!raw.windows(64).any(|w| w == &content[..64]));
+    assert_eq!(read_file(&store, &file_handle).await.unwrap(), content);
+
+    let line = TextLine::new("{\"Identity\":{\"id\":\"secret\"}}").unwrap();
+    let name = ShellSafeName::from_str("identity.meta").unwrap();
+    let metadata_file = store.save_metadata_line(&name, &line).await.unwrap();
+    let raw = String::from_utf8(read_file(inner.as_ref(), &metadata_file).await.unwrap()).unwrap();
+    assert!(raw.starts_with(&format!("{}{}:", LINE_PREFIX, key.id())));
+    assert!(!raw.contains("secret"));
+    assert_eq!(
+        read_file(&store, &metadata_file).await.unwrap(),
+        line.as_ref().as_bytes()
+    );
+}
+
+#[tokio::test]
+async fn test_wrong_or_missing_key() {
+    let tmpdir = TempPath::new();
+    let inner = local_fs(&tmpdir);
+    let store = EncryptedStorage::new(inner.clone(), Some(test_key(1)));
+    let file_handle = write_file(&store, b"some content").await;
+    let line = TextLine::new("some metadata").unwrap();
+    let name = ShellSafeName::from_str("some.meta").unwrap();
+    let metadata_file = store.save_metadata_line(&name, &line).await.unwrap();
+
+    let wrong_key_store = EncryptedStorage::new(inner.clone(), Some(test_key(2)));
+    let no_key_store = EncryptedStorage::new(inner, None);
+    for handle in [&file_handle, &metadata_file] {
+        let err = read_file(&wrong_key_store, handle).await.unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("but the configured encryption key is"));
+        let err = read_file(&no_key_store, handle).await.unwrap_err();
+        assert!(err.to_string().contains("no encryption key is configured"));
+    }
+}
+
+#[tokio::test]
+async fn test_plaintext_stays_readable() {
+    let tmpdir = TempPath::new();
+    let inner = local_fs(&tmpdir);
+    let file_handle = write_file(inner.as_ref(), b"plaintext content").await;
+    let short_file_handle = {
+        let (handle, mut file) = inner
+            .create_for_write("backup_1", &ShellSafeName::from_str("short.chunk").unwrap())
+            .await
+            .unwrap();
+        file.write_all(b"abc").await.unwrap();
+        file.shutdown().await.unwrap();
+        handle
+    };
+
+    let store = EncryptedStorage::new(inner, Some(test_key(1)));
+    assert_eq!(
+        read_file(&store, &file_handle).await.unwrap(),
+        b"plaintext content"
+    );
+    assert_eq!(read_file(&store, &short_file_handle).await.unwrap(), b"abc");
+}
+
+#[tokio::test]
+async fn test_tampered_file() {
+    let tmpdir = TempPath::new();
+    let inner = local_fs(&tmpdir);
+    let store = EncryptedStorage::new(inner.clone(), Some(test_key(1)));
+    let content = vec![7u8; 1000];
+    let file_handle = write_file(&store, &content).await;
+    let raw = read_file(inner.as_ref(), &file_handle).await.unwrap();
+    let path = tmpdir.path().join(&file_handle);
+
+    // Truncated, the last frame is lost.
+    tokio::fs::write(&path, &raw[..raw.len() - 10])
+        .await
+        .unwrap();
+    assert!(read_file(&store, &file_handle).await.is_err());
+
+    // Flipped a bit in the ciphertext.
+    let mut tampered = raw.clone();
+    *tampered.last_mut().unwrap() ^= 1;
+    tokio::fs::write(&path, &tampered).await.unwrap();
+    assert!(read_file(&store, &file_handle).await.is_err());
+
+    // Trailing garbage.
+    let mut extended = raw;
+    extended.push(0);
+    tokio::fs::write(&path, &extended).await.unwrap();
+    assert!(read_file(&store, &file_handle).await.is_err());
+}
+
+#[test]
+fn test_key_parsing() {
+    let key = EncryptionKey::from_hex(&format!("{}\n", "ab".repeat(32))).unwrap();
+    assert_eq!(key.id().len(), 16);
+    assert!(!format!("{:?}", key).contains(&"ab".repeat(32)));
+
+    assert!(EncryptionKey::from_hex("not hex").is_err());
+    assert!(EncryptionKey::from_hex(&"ab".repeat(16)).is_err());
+}
@@ -0,0 +1,454 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::s3::{
+    config::S3Config,
+    signer::{
+        authorization, canonical_query_string, sha256_hex, uri_encode_path, Credentials,
+        EMPTY_PAYLOAD_SHA256,
+    },
+};
+use anyhow::{bail, ensure, format_err, Result};
+use aptos_logger::prelude::*;
+use bytes::Bytes;
+use reqwest::{header::HeaderMap, Method, StatusCode, Url};
+use serde::Deserialize;
+use std::{collections::BTreeMap, time::Duration};
+use tokio::sync::Semaphore;
+
+pub(super) struct S3Response {
+    pub headers: HeaderMap,
+    pub body: Bytes,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct InitiateMultipartUploadResult {
+    upload_id: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListBucketResult {
+    #[serde(default)]
+    contents: Vec<ListedObject>,
+    #[serde(default)]
+    is_truncated: bool,
+    next_continuation_token: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct ListedObject {
+    key: String,
+}
+
+/// A minimal S3 REST client, signing requests itself and retrying failed ones.
+/// The number of requests in flight is bounded by `S3Config::max_concurrent_requests`.
+pub(super) struct S3Client {
+    config: S3Config,
+    credentials: Credentials,
+    http: reqwest::Client,
+    /// "scheme://host[:port]" all requests are sent to.
+    base_url: String,
+    /// Value of the "host" header, which is signed.
+    host: String,
+    request_permits: Semaphore,
+}
+
+impl S3Client {
+    const MAX_BACKOFF_MS: u64 = 10_000;
+
+    pub fn new(config: S3Config, credentials: Credentials) -> Result<Self> {
+        let endpoint = config
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| format!("https://s3.{}.amazonaws.com", config.region));
+        let endpoint = Url::parse(&endpoint)?;
+        let endpoint_host = endpoint
+            .host_str()
+            .ok_or_else(|| format_err!("No host in endpoint {}", endpoint))?;
+        let host = if config.path_style {
+            endpoint_host.to_string()
+        } else {
+            format!("{}.{}", config.bucket, endpoint_host)
+        };
+        let host = match endpoint.port() {
+            Some(port) => format!("{}:{}", host, port),
+            None => host,
+        };
+        let base_url = format!("{}://{}", endpoint.scheme(), host);
+
+        let http = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.request_timeout_secs))
+            .build()?;
+        let request_permits = Semaphore::new(config.max_concurrent_requests);
+
+        Ok(Self {
+            config,
+            credentials,
+            http,
+            base_url,
+            host,
+            request_permits,
+        })
+    }
+
+    pub fn config(&self) -> &S3Config {
+        &self.config
+    }
+
+    /// Full object key of a path relative to the configured prefix.
+    pub fn key(&self, path: &str) -> String {
+        if self.config.prefix.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}/{}", self.config.prefix, path)
+        }
+    }
+
+    /// Inverse of `key()`.
+    pub fn path<'a>(&self, key: &'a str) -> Result<&'a str> {
+        if self.config.prefix.is_empty() {
+            Ok(key)
+        } else {
+            key.strip_prefix(&self.config.prefix)
+                .and_then(|k| k.strip_prefix('/'))
+                .ok_or_else(|| {
+                    format_err!("Key {} is not under prefix {}.", key, self.config.prefix)
+                })
+        }
+    }
+
+    fn canonical_uri(&self, key: Option<&str>) -> String {
+        let mut uri = String::new();
+        if self.config.path_style {
+            uri.push('/');
+            uri.push_str(&uri_encode_path(&self.config.bucket));
+        }
+        uri.push('/');
+        if let Some(key) = key {
+            uri.push_str(&uri_encode_path(key));
+        }
+        uri
+    }
+
+    fn is_retryable(status: StatusCode) -> bool {
+        status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS
+    }
+
+    async fn send_once(
+        &self,
+        method: &Method,
+        canonical_uri: &str,
+        canonical_query: &str,
+        extra_headers: &[(&str, String)],
+        body: &Bytes,
+        payload_sha256: &str,
+    ) -> Result<std::result::Result<S3Response, (StatusCode, String)>> {
+        let _permit = self.request_permits.acquire().await?;
+
+        let amz_date = chrono::Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+        let mut headers = BTreeMap::new();
+        headers.insert("host".to_string(), self.host.clone());
+        headers.insert("x-amz-date".to_string(), amz_date.clone());
+        headers.insert(
+            "x-amz-content-sha256".to_string(),
+            payload_sha256.to_string(),
+        );
+        if let Some(token) = &self.credentials.session_token {
+            headers.insert("x-amz-security-token".to_string(), token.clone());
+        }
+        for (k, v) in extra_headers {
+            headers.insert(k.to_string(), v.clone());
+        }
+        let auth = authorization(
+            &self.credentials,
+            &self.config.region,
+            &amz_date,
+            method.as_str(),
+            canonical_uri,
+            canonical_query,
+            &headers,
+            payload_sha256,
+        );
+
+        let mut url = format!("{}{}", self.base_url, canonical_uri);
+        if !canonical_query.is_empty() {
+            url.push('?');
+            url.push_str(canonical_query);
+        }
+        let mut request = self
+            .http
+            .request(method.clone(), &url)
+            .header("authorization", auth)
+            .body(body.clone());
+        for (k, v) in headers.iter().filter(|(k, _)| k.as_str() != "host") {
+            request = request.header(k.as_str(), v.as_str());
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let headers = response.headers().clone();
+        let body = response.bytes().await?;
+        if status.is_success() {
+            Ok(Ok(S3Response { headers, body }))
+        } else {
+            Ok(Err((status, String::from_utf8_lossy(&body).to_string())))
+        }
+    }
+
+    /// Sends a request, retrying with exponential back-off on transport errors, throttling and
+    /// server side errors. The response body is read in full before returning, so that failures
+    /// while streaming the body are retried as well.
+    async fn send(
+        &self,
+        method: Method,
+        key: Option<&str>,
+        query: &[(&str, String)],
+        extra_headers: &[(&str, String)],
+        body: Bytes,
+    ) -> Result<S3Response> {
+        let canonical_uri = self.canonical_uri(key);
+        let canonical_query = canonical_query_string(query);
+        let payload_sha256 = if body.is_empty() {
+            EMPTY_PAYLOAD_SHA256.to_string()
+        } else {
+            sha256_hex(&body)
+        };
+
+        let mut attempt = 0;
+        loop {
+            let res = self
+                .send_once(
+                    &method,
+                    &canonical_uri,
+                    &canonical_query,
+                    extra_headers,
+                    &body,
+                    &payload_sha256,
+                )
+                .await;
+            let err = match res {
+                Ok(Ok(response)) => return Ok(response),
+                Ok(Err((status, message))) => {
+                    let err = format_err!(
+                        "S3 request {} {} failed with status {}: {}",
+                        method,
+                        canonical_uri,
+                        status,
+                        message,
+                    );
+                    if !Self::is_retryable(status) {
+                        return Err(err);
+                    }
+                    err
+                },
+                Err(err) => err,
+            };
+            if attempt >= self.config.max_retries {
+                bail!("Giving up after {} retries: {}", attempt, err);
+            }
+            let backoff_ms = std::cmp::min(
+                100 * 2u64.pow(std::cmp::min(attempt, 16) as u32),
+                Self::MAX_BACKOFF_MS,
+            );
+            warn!(
+                error = %err,
+                attempt = attempt,
+                backoff_ms = backoff_ms,
+                "S3 request failed, retrying."
+            );
+            tokio::time::sleep(Duration::from_millis(backoff_ms)).await;
+            attempt += 1;
+        }
+    }
+
+    pub async fn put_object(&self, key: &str, body: Bytes) -> Result<()> {
+        self.send(Method::PUT, Some(key), &[], &[], body).await?;
+        Ok(())
+    }
+
+    pub async fn head_object(&self, key: &str) -> Result<u64> {
+        let response = self
+            .send(Method::HEAD, Some(key), &[], &[], Bytes::new())
+            .await?;
+        let len = response
+            .headers
+            .get(reqwest::header::CONTENT_LENGTH)
+            .ok_or_else(|| format_err!("No content-length in HEAD response of {}.", key))?
+            .to_str()?
+            .parse()?;
+        Ok(len)
+    }
+
+    /// Reads bytes [start, end] (both inclusive) of an object.
+    pub async fn get_object_range(&self, key: &str, start: u64, end: u64) -> Result<Bytes> {
+        let response = self
+            .send(
+                Method::GET,
+                Some(key),
+                &[],
+                &[("range", format!("bytes={}-{}", start, end))],
+                Bytes::new(),
+            )
+            .await?;
+        ensure!(
+            response.body.len() as u64 == end - start + 1,
+            "Expecting {} bytes from range {}-{} of {}, got {}.",
+            end - start + 1,
+            start,
+            end,
+            key,
+            response.body.len(),
+        );
+        Ok(response.body)
+    }
+
+    pub async fn copy_object(&self, src_key: &str, dst_key: &str) -> Result<()> {
+        let copy_source = format!("/{}/{}", self.config.bucket, uri_encode_path(src_key));
+        let response = self
+            .send(
+                Method::PUT,
+                Some(dst_key),
+                &[],
+                &[("x-amz-copy-source", copy_source)],
+                Bytes::new(),
+            )
+            .await?;
+        // CopyObject can fail after responding with 200, in which case the body is an error.
+        ensure!(
+            !String::from_utf8_lossy(&response.body).contains("<Error>"),
+            "Failed to copy {} to {}: {}",
+            src_key,
+            dst_key,
+            String::from_utf8_lossy(&response.body),
+        );
+        Ok(())
+    }
+
+    pub async fn delete_object(&self, key: &str) -> Result<()> {
+        self.send(Method::DELETE, Some(key), &[], &[], Bytes::new())
+            .await?;
+        Ok(())
+    }
+
+    /// Lists all keys under `prefix`, following continuation tokens.
+    pub async fn list_objects(&self, prefix: &str) -> Result<Vec<String>> {
+        let mut keys = Vec::new();
+        let mut continuation_token = None;
+        loop {
+            let mut query = vec![
+                ("list-type", "2".to_string()),
+                ("prefix", prefix.to_string()),
+            ];
+            if let Some(token) = continuation_token.take() {
+                query.push(("continuation-token", token));
+            }
+            let response = self
+                .send(Method::GET, None, &query, &[], Bytes::new())
+                .await?;
+            let result: ListBucketResult = quick_xml::de::from_reader(response.body.as_ref())?;
+            keys.extend(result.contents.into_iter().map(|o| o.key));
+            if !result.is_truncated {
+                break;
+            }
+            continuation_token =
+                Some(result.next_continuation_token.ok_or_else(|| {
+                    format_err!("Truncated listing without a continuation token.")
+                })?);
+        }
+        Ok(keys)
+    }
+
+    pub async fn create_multipart_upload(&self, key: &str) -> Result<String> {
+        let response = self
+            .send(
+                Method::POST,
+                Some(key),
+                &[("uploads", String::new())],
+                &[],
+                Bytes::new(),
+            )
+            .await?;
+        let result: InitiateMultipartUploadResult =
+            quick_xml::de::from_reader(response.body.as_ref())?;
+        Ok(result.upload_id)
+    }
+
+    /// Uploads one part, returning its ETag.
+    pub async fn upload_part(
+        &self,
+        key: &str,
+        upload_id: &str,
+        part_number: usize,
+        body: Bytes,
+    ) -> Result<String> {
+        let response = self
+            .send(
+                Method::PUT,
+                Some(key),
+                &[
+                    ("partNumber", part_number.to_string()),
+                    ("uploadId", upload_id.to_string()),
+                ],
+                &[],
+                body,
+            )
+            .await?;
+        Ok(response
+            .headers
+            .get(reqwest::header::ETAG)
+            .ok_or_else(|| format_err!("No ETag for part {} of {}.", part_number, key))?
+            .to_str()?
+            .to_string())
+    }
+
+    /// `parts` are (part number, ETag) pairs, ordered by part number.
+    pub async fn complete_multipart_upload(
+        &self,
+        key: &str,
+        upload_id: &str,
+        parts: &[(usize, String)],
+    ) -> Result<()> {
+        let body = format!(
+            "<CompleteMultipartUpload>{}</CompleteMultipartUpload>",
+            parts
+                .iter()
+                .map(|(part_number, etag)| format!(
+                    "<Part><PartNumber>{}</PartNumber><ETag>{}</ETag></Part>",
+                    part_number, etag
+                ))
+                .collect::<String>()
+        );
+        let response = self
+            .send(
+                Method::POST,
+                Some(key),
+                &[("uploadId", upload_id.to_string())],
+                &[],
+                Bytes::from(body),
+            )
+            .await?;
+        // Like CopyObject, this can fail after responding with 200.
+        ensure!(
+            !String::from_utf8_lossy(&response.body).contains("<Error>"),
+            "Failed to complete multipart upload of {}: {}",
+            key,
+            String::from_utf8_lossy(&response.body),
+        );
+        Ok(())
+    }
+
+    pub async fn abort_multipart_upload(&self, key: &str, upload_id: &str) -> Result<()> {
+        self.send(
+            Method::DELETE,
+            Some(key),
+            &[("uploadId", upload_id.to_string())],
+            &[],
+            Bytes::new(),
+        )
+        .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::utils::error_notes::ErrorNotes;
+use anyhow::{ensure, Result};
+use serde::Deserialize;
+use std::path::Path;
+use tokio::io::AsyncReadExt;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    /// Name of the bucket holding the backups.
+    pub bucket: String,
+    /// Key prefix under which everything is stored, without leading or trailing slashes.
+    /// Empty means the root of the bucket.
+    #[serde(default)]
+    pub prefix: String,
+    /// Region of the bucket, used in request signing.
+    #[serde(default = "S3Config::default_region")]
+    pub region: String,
+    /// Alternative endpoint, like "http://127.0.0.1:9000" for a local S3 compatible server.
+    /// Defaults to "https://s3.{region}.amazonaws.com".
+    #[serde(default)]
+    pub endpoint: Option<String>,
+    /// Address objects as "{endpoint}/{bucket}/{key}" instead of "{bucket}.{endpoint}/{key}".
+    /// Most S3 compatible servers require this.
+    #[serde(default)]
+    pub path_style: bool,
+    /// Size of each part in a multipart upload, and of each ranged GET when reading.
+    #[serde(default = "S3Config::default_part_size")]
+    pub part_size: usize,
+    /// Maximum number of requests in flight to the S3 endpoint, across all files.
+    #[serde(default = "S3Config::default_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+    /// Number of times a failed request is retried before giving up.
+    #[serde(default = "S3Config::default_max_retries")]
+    pub max_retries: usize,
+    /// Timeout of a single request, including reading the response body.
+    #[serde(default = "S3Config::default_request_timeout_secs")]
+    pub request_timeout_secs: u64,
+}
+
+impl S3Config {
+    /// S3 rejects multipart uploads with non-trailing parts smaller than this.
+    pub const MIN_PART_SIZE: usize = 5 * 1024 * 1024;
+
+    fn default_region() -> String {
+        "us-east-1".to_string()
+    }
+
+    fn default_part_size() -> usize {
+        16 * 1024 * 1024
+    }
+
+    fn default_max_concurrent_requests() -> usize {
+        16
+    }
+
+    fn default_max_retries() -> usize {
+        5
+    }
+
+    fn default_request_timeout_secs() -> u64 {
+        120
+    }
+
+    pub async fn load_from_file(path: &Path) -> Result<Self> {
+        let path_str = path.to_str().unwrap_or_default();
+        let mut file = tokio::fs::File::open(path).await.err_notes(path_str)?;
+        let mut content = Vec::new();
+        file.read_to_end(&mut content).await.err_notes(path_str)?;
+
+        let config: Self = serde_yaml::from_slice(&content)?;
+        config.sanitize()?;
+        Ok(config)
+    }
+
+    pub fn load_from_str(content: &str) -> Result<Self> {
+        let config: Self = serde_yaml::from_str(content)?;
+        config.sanitize()?;
+        Ok(config)
+    }
+
+    fn sanitize(&self) -> Result<()> {
+        ensure!(!self.bucket.is_empty(), "bucket can't be empty.");
+        ensure!(
+            !self.prefix.starts_with('/') && !self.prefix.ends_with('/'),
+            "prefix should not start or end with '/': {}",
+            self.prefix,
+        );
+        ensure!(
+            self.part_size >= Self::MIN_PART_SIZE,
+            "part_size should be at least {} bytes, got {}.",
+            Self::MIN_PART_SIZE,
+            self.part_size,
+        );
+        ensure!(
+            self.max_concurrent_requests > 0,
+            "max_concurrent_requests can't be 0."
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,226 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::storage::s3::client::S3Client;
+use anyhow::{ensure, Result};
+use aptos_logger::prelude::*;
+use bytes::Bytes;
+use futures::{
+    stream::{StreamExt, TryStreamExt},
+    task::{Context, Poll},
+    Future,
+};
+use std::{collections::VecDeque, sync::Arc};
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWrite, DuplexStream},
+    macros::support::Pin,
+    sync::oneshot,
+    task::JoinHandle,
+};
+use tokio_util::compat::FuturesAsyncReadCompatExt;
+
+/// Opens an object for reading by issuing ranged GETs of `part_size` bytes, a few of them ahead
+/// of the reader. Each range is retried on its own, so a glitch doesn't restart the whole file.
+pub(super) async fn open_object_for_read(
+    client: Arc<S3Client>,
+    key: String,
+) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+    let size = client.head_object(&key).await?;
+    let part_size = client.config().part_size as u64;
+    let read_ahead = client.config().max_concurrent_requests;
+
+    let ranges = (0..size)
+        .step_by(part_size as usize)
+        .map(move |start| (start, std::cmp::min(start + part_size, size) - 1));
+    let reader = futures::stream::iter(ranges)
+        .map(move |(start, end)| {
+            let client = client.clone();
+            let key = key.clone();
+            async move { client.get_object_range(&key, start, end).await }
+        })
+        .buffered(read_ahead)
+        .map_err(|e| futures::io::Error::new(futures::io::ErrorKind::Other, e))
+        .boxed()
+        .into_async_read()
+        .compat();
+
+    Ok(Box::new(reader))
+}
+
+/// A writer that streams into an object, with a multipart upload if the content doesn't fit in
+/// a single part. The upload is completed on `shutdown()`, which returns an error if any part
+/// failed. If the writer is dropped without a `shutdown()`, the upload is aborted.
+pub(super) struct S3ObjectWriter {
+    writer: Option<DuplexStream>,
+    shutdown_tx: Option<oneshot::Sender<()>>,
+    upload: JoinHandle<Result<()>>,
+}
+
+impl S3ObjectWriter {
+    pub fn new(client: Arc<S3Client>, key: String) -> Self {
+        let (writer, reader) = tokio::io::duplex(client.config().part_size);
+        let (shutdown_tx, shutdown_rx) = oneshot::channel();
+        let upload = tokio::spawn(upload(client, key, reader, shutdown_rx));
+
+        Self {
+            writer: Some(writer),
+            shutdown_tx: Some(shutdown_tx),
+            upload,
+        }
+    }
+
+    fn writer(&mut self) -> std::io::Result<&mut DuplexStream> {
+        self.writer
+            .as_mut()
+            .ok_or_else(|| std::io::ErrorKind::BrokenPipe.into())
+    }
+}
+
+impl AsyncWrite for S3ObjectWriter {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        match self.writer() {
+            Ok(writer) => Pin::new(writer).poll_write(cx, buf),
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        match self.writer() {
+            Ok(writer) => Pin::new(writer).poll_flush(cx),
+            Err(e) => Poll::Ready(Err(e)),
+        }
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        if let Some(shutdown_tx) = self.shutdown_tx.take() {
+            // Signal before closing the pipe, so the upload task sees it upon EOF.
+            shutdown_tx.send(()).ok();
+            self.writer = None;
+        }
+
+        Pin::new(&mut self.upload).poll(cx).map(|res| {
+            res.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))
+        })
+    }
+}
+
+async fn read_part(reader: &mut DuplexStream, part_size: usize) -> Result<Bytes> {
+    let mut buf = Vec::with_capacity(part_size);
+    reader.take(part_size as u64).read_to_end(&mut buf).await?;
+    Ok(buf.into())
+}
+
+async fn upload(
+    client: Arc<S3Client>,
+    key: String,
+    mut reader: DuplexStream,
+    mut shutdown_rx: oneshot::Receiver<()>,
+) -> Result<()> {
+    let part_size = client.config().part_size;
+
+    let first_part = read_part(&mut reader, part_size).await?;
+    if first_part.len() < part_size {
+        ensure!(
+            shutdown_rx.try_recv().is_ok(),
+            "Writer of {} dropped without shutdown.",
+            key
+        );
+        return client.put_object(&key, first_part).await;
+    }
+
+    let upload_id = client.create_multipart_upload(&key).await?;
+    let res = upload_parts(
+        &client,
+        &key,
+        &upload_id,
+        first_part,
+        &mut reader,
+        &mut shutdown_rx,
+    )
+    .await;
+    match res {
+        Ok(parts) => {
+            client
+                .complete_multipart_upload(&key, &upload_id, &parts)
+                .await
+        },
+        Err(err) => {
+            if let Err(abort_err) = client.abort_multipart_upload(&key, &upload_id).await {
+                warn!(
+                    key = %key,
+                    error = %abort_err,
+                    "Failed to abort multipart upload."
+                );
+            }
+            Err(err)
+        },
+    }
+}
+
+async fn upload_part(
+    client: Arc<S3Client>,
+    key: String,
+    upload_id: String,
+    part_number: usize,
+    body: Bytes,
+) -> Result<(usize, String)> {
+    let etag = client
+        .upload_part(&key, &upload_id, part_number, body)
+        .await?;
+    Ok((part_number, etag))
+}
+
+async fn upload_parts(
+    client: &Arc<S3Client>,
+    key: &str,
+    upload_id: &str,
+    first_part: Bytes,
+    reader: &mut DuplexStream,
+    shutdown_rx: &mut oneshot::Receiver<()>,
+) -> Result<Vec<(usize, String)>> {
+    let part_size = client.config().part_size;
+    let max_in_flight = client.config().max_concurrent_requests;
+
+    let mut parts = Vec::new();
+    let mut in_flight = InFlightParts(VecDeque::new());
+    let mut part = first_part;
+    let mut part_number = 1;
+    while !part.is_empty() {
+        in_flight.0.push_back(tokio::spawn(upload_part(
+            client.clone(),
+            key.to_string(),
+            upload_id.to_string(),
+            part_number,
+            part,
+        )));
+        if in_flight.0.len() >= max_in_flight {
+            parts.push(in_flight.0.pop_front().expect("Not empty.").await??);
+        }
+        part_number += 1;
+        part = read_part(reader, part_size).await?;
+    }
+    ensure!(
+        shutdown_rx.try_recv().is_ok(),
+        "Writer of {} dropped without shutdown.",
+        key
+    );
+    while let Some(handle) = in_flight.0.pop_front() {
+        parts.push(handle.await??);
+    }
+
+    Ok(parts)
+}
+
+/// Aborts parts still being uploaded when bailing out on an error.
+struct InFlightParts(VecDeque<JoinHandle<Result<(usize, String)>>>);
+
+impl Drop for InFlightParts {
+    fn drop(&mut self) {
+        self.0.iter().for_each(JoinHandle::abort);
+    }
+}
@@ -0,0 +1,166 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+mod client;
+pub mod config;
+mod io;
+mod signer;
+
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    storage::{
+        s3::{
+            client::S3Client,
+            config::S3Config,
+            io::{open_object_for_read, S3ObjectWriter},
+            signer::Credentials,
+        },
+        BackupHandle, BackupHandleRef, BackupStorage, FileHandle, FileHandleRef, ShellSafeName,
+        TextLine,
+    },
+    utils::{error_notes::ErrorNotes, PathToString},
+};
+use anyhow::{format_err, Result};
+use async_trait::async_trait;
+use bytes::Bytes;
+use clap::Parser;
+use serde::{Deserialize, Serialize};
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::Arc,
+};
+use tokio::io::{AsyncRead, AsyncWrite};
+
+#[derive(Parser, Clone, Debug, Serialize, Deserialize)]
+pub struct S3Opt {
+    #[clap(long = "config", help = "Config file for the S3 backup store.")]
+    config: PathBuf,
+}
+
+impl FromStr for S3Opt {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(S3Opt {
+            config: PathBuf::from(s),
+        })
+    }
+}
+
+/// A BackupStorage that talks to S3, or any S3 compatible object store, directly.
+/// Files are laid out the same way as in `LocalFs`, under the configured key prefix.
+/// Credentials are read from `AWS_ACCESS_KEY_ID`, `AWS_SECRET_ACCESS_KEY` and optionally
+/// `AWS_SESSION_TOKEN`. See `S3Config` for the other knobs.
+#[derive(Clone)]
+pub struct S3Storage {
+    client: Arc<S3Client>,
+}
+
+impl S3Storage {
+    const METADATA_BACKUP_DIR: &'static str = "metadata_backup";
+    const METADATA_DIR: &'static str = "metadata";
+
+    pub fn new(config: S3Config) -> Result<Self> {
+        let client = S3Client::new(config, Credentials::from_env()?)?;
+        Ok(Self {
+            client: Arc::new(client),
+        })
+    }
+
+    pub async fn new_with_opt(opt: S3Opt) -> Result<Self> {
+        let config = S3Config::load_from_file(&opt.config).await?;
+
+        Self::new(config)
+    }
+}
+
+#[async_trait]
+impl BackupStorage for S3Storage {
+    async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+        // There are no folders in S3, the backup name is simply part of the object keys.
+        Ok(name.to_string())
+    }
+
+    async fn create_for_write(
+        &self,
+        backup_handle: &BackupHandleRef,
+        name: &ShellSafeName,
+    ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+        let file_handle = Path::new(backup_handle)
+            .join(name.as_ref())
+            .path_to_string()?;
+        let writer = S3ObjectWriter::new(self.client.clone(), self.client.key(&file_handle));
+        Ok((file_handle, Box::new(writer)))
+    }
+
+    async fn open_for_read(
+        &self,
+        file_handle: &FileHandleRef,
+    ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+        open_object_for_read(self.client.clone(), self.client.key(file_handle))
+            .await
+            .err_notes(file_handle)
+    }
+
+    async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+        let prefix = self.client.key(&format!("{}/", Self::METADATA_DIR));
+        self.client
+            .list_objects(&prefix)
+            .await?
+            .iter()
+            .map(|key| self.client.path(key).map(str::to_string))
+            .collect()
+    }
+
+    /// file_handle are expected to be the return results from list_metadata_files
+    /// file_handle is a path with `metadata` in the path, Ex: metadata/epoch_ending_1.meta
+    async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        let name = Path::new(file_handle)
+            .file_name()
+            .and_then(OsStr::to_str)
+            .ok_or_else(|| format_err!("cannot extract filename from {}", file_handle))?;
+        let src_key = self.client.key(file_handle);
+        let dst_key = self
+            .client
+            .key(&format!("{}/{}", Self::METADATA_BACKUP_DIR, name));
+
+        // S3 has no rename, copy then delete.
+        self.client
+            .copy_object(&src_key, &dst_key)
+            .await
+            .err_notes(file_handle)?;
+        self.client
+            .delete_object(&src_key)
+            .await
+            .err_notes(file_handle)
+    }
+
+    async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+        self.client
+            .delete_object(&self.client.key(file_handle))
+            .await
+            .err_notes(file_handle)
+    }
+
+    async fn save_metadata_lines(
+        &self,
+        name: &ShellSafeName,
+        lines: &[TextLine],
+    ) -> Result<FileHandle> {
+        let content = lines
+            .iter()
+            .map(|e| e.as_ref())
+            .collect::<Vec<&str>>()
+            .join("");
+        let file_handle = format!("{}/{}", Self::METADATA_DIR, name.as_ref());
+        self.client
+            .put_object(&self.client.key(&file_handle), Bytes::from(content))
+            .await
+            .err_notes(name)?;
+        Ok(file_handle)
+    }
+}
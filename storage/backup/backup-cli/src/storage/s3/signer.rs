@@ -0,0 +1,131 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! AWS Signature Version 4 request signing, limited to what the S3 backend needs.
+//! See https://docs.aws.amazon.com/AmazonS3/latest/API/sig-v4-header-based-auth.html
+
+use anyhow::{format_err, Result};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use ring::{
+    digest::{digest, SHA256},
+    hmac,
+};
+use std::collections::BTreeMap;
+
+/// Everything except the unreserved characters "A-Za-z0-9-_.~" is encoded.
+const URI_ENCODE_SET: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+const SERVICE: &str = "s3";
+
+pub(super) const EMPTY_PAYLOAD_SHA256: &str =
+    "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+#[derive(Clone)]
+pub(super) struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+impl Credentials {
+    /// Reads credentials from the same env vars the `aws` CLI uses.
+    pub fn from_env() -> Result<Self> {
+        let var = |key: &str| {
+            std::env::var(key).map_err(|e| format_err!("Failed to read env var {}: {}", key, e))
+        };
+        Ok(Self {
+            access_key_id: var("AWS_ACCESS_KEY_ID")?,
+            secret_access_key: var("AWS_SECRET_ACCESS_KEY")?,
+            session_token: std::env::var("AWS_SESSION_TOKEN").ok(),
+        })
+    }
+}
+
+pub(super) fn uri_encode(input: &str) -> String {
+    utf8_percent_encode(input, URI_ENCODE_SET).to_string()
+}
+
+/// Encodes an object key as a URI path, keeping the '/' separators.
+pub(super) fn uri_encode_path(path: &str) -> String {
+    path.split('/')
+        .map(uri_encode)
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Sorted, encoded query string, used both in the canonical request and the actual URL.
+pub(super) fn canonical_query_string(query: &[(&str, String)]) -> String {
+    let mut pairs = query
+        .iter()
+        .map(|(k, v)| (uri_encode(k), uri_encode(v)))
+        .collect::<Vec<_>>();
+    pairs.sort();
+    pairs
+        .into_iter()
+        .map(|(k, v)| format!("{}={}", k, v))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+pub(super) fn sha256_hex(data: &[u8]) -> String {
+    hex::encode(digest(&SHA256, data))
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> hmac::Tag {
+    hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, key), data.as_bytes())
+}
+
+/// Returns the value of the "Authorization" header.
+///
+/// `canonical_uri` and `canonical_query` are expected to be already encoded, and `headers` to
+/// hold every header to be signed with lower case names, including "host", "x-amz-date" and
+/// "x-amz-content-sha256". `amz_date` is in the "%Y%m%dT%H%M%SZ" format.
+pub(super) fn authorization(
+    credentials: &Credentials,
+    region: &str,
+    amz_date: &str,
+    method: &str,
+    canonical_uri: &str,
+    canonical_query: &str,
+    headers: &BTreeMap<String, String>,
+    payload_sha256: &str,
+) -> String {
+    let canonical_headers = headers
+        .iter()
+        .map(|(k, v)| format!("{}:{}\n", k, v.trim()))
+        .collect::<String>();
+    let signed_headers = headers.keys().cloned().collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n{}\n{}\n{}\n{}",
+        method, canonical_uri, canonical_query, canonical_headers, signed_headers, payload_sha256,
+    );
+
+    let date = &amz_date[..8];
+    let scope = format!("{}/{}/{}/aws4_request", date, region, SERVICE);
+    let string_to_sign = format!(
+        "{}\n{}\n{}\n{}",
+        ALGORITHM,
+        amz_date,
+        scope,
+        sha256_hex(canonical_request.as_bytes()),
+    );
+
+    let k_date = hmac_sha256(
+        format!("AWS4{}", credentials.secret_access_key).as_bytes(),
+        date,
+    );
+    let k_region = hmac_sha256(k_date.as_ref(), region);
+    let k_service = hmac_sha256(k_region.as_ref(), SERVICE);
+    let k_signing = hmac_sha256(k_service.as_ref(), "aws4_request");
+    let signature = hex::encode(hmac_sha256(k_signing.as_ref(), &string_to_sign));
+
+    format!(
+        "{} Credential={}/{}, SignedHeaders={}, Signature={}",
+        ALGORITHM, credentials.access_key_id, scope, signed_headers, signature,
+    )
+}
@@ -48,6 +48,45 @@ pub async fn test_write_and_read_impl(
     }
 }
 
+async fn read_file(store: &dyn BackupStorage, handle: &str) -> Result<Vec<u8>> {
+    let mut file = store.open_for_read(handle).await?;
+    let mut buf = Vec::new();
+    file.read_to_end(&mut buf).await?;
+    Ok(buf)
+}
+
+/// Deletes all but one file of each backup and checks only the remaining ones can be read.
+pub async fn test_delete_impl(
+    store: Box<dyn BackupStorage>,
+    backups: HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>,
+) {
+    let mut remaining = Vec::new();
+    let mut deleted = Vec::new();
+    for (backup_name, files) in &backups {
+        let backup_handle = store.create_backup(backup_name).await.unwrap();
+        for (i, (name, content)) in files.iter().enumerate() {
+            let (handle, mut file) = store.create_for_write(&backup_handle, name).await.unwrap();
+            file.write_all(content).await.unwrap();
+            file.shutdown().await.unwrap();
+            if i == 0 {
+                remaining.push((handle, content));
+            } else {
+                deleted.push(handle);
+            }
+        }
+    }
+
+    for handle in &deleted {
+        store.delete_file(handle).await.unwrap();
+    }
+    for handle in &deleted {
+        assert!(read_file(store.as_ref(), handle).await.is_err());
+    }
+    for (handle, content) in remaining {
+        assert_eq!(&read_file(store.as_ref(), &handle).await.unwrap(), content);
+    }
+}
+
 pub fn arb_backups(
 ) -> impl Strategy<Value = HashMap<ShellSafeName, HashMap<ShellSafeName, Vec<u8>>>> {
     hash_map(
@@ -18,7 +18,7 @@ use aptos_config::config::{
 };
 use aptos_crypto::HashValue;
 use aptos_db::{
-    backup::restore_handler::RestoreHandler,
+    backup::{restore_handler::RestoreHandler, restore_progress::RestoreProgress},
     state_restore::{
         StateSnapshotProgress, StateSnapshotRestore, StateSnapshotRestoreMode, StateValueBatch,
         StateValueWriter,
@@ -29,6 +29,7 @@ use aptos_infallible::duration_since_epoch;
 use aptos_jellyfish_merkle::{NodeBatch, TreeWriter};
 use aptos_logger::info;
 use aptos_types::{
+    ledger_info::LedgerInfo,
     state_store::{
         state_key::StateKey, state_storage_usage::StateStorageUsage, state_value::StateValue,
     },
@@ -261,6 +262,35 @@ impl RestoreRunMode {
             RestoreRunMode::Verify => Ok(None),
         }
     }
+
+    pub fn get_restore_progress(&self) -> Result<Option<RestoreProgress>> {
+        match self {
+            RestoreRunMode::Restore { restore_handler } => restore_handler.get_restore_progress(),
+            RestoreRunMode::Verify => Ok(None),
+        }
+    }
+
+    /// Applies `update` to the saved restore progress, starting from an empty one if nothing is
+    /// saved yet. No-op in verify mode.
+    pub fn update_restore_progress(&self, update: impl FnOnce(&mut RestoreProgress)) -> Result<()> {
+        match self {
+            RestoreRunMode::Restore { restore_handler } => {
+                let mut progress = restore_handler.get_restore_progress()?.unwrap_or_default();
+                update(&mut progress);
+                restore_handler.save_restore_progress(&progress)
+            },
+            RestoreRunMode::Verify => Ok(()),
+        }
+    }
+
+    pub fn get_epoch_ending_ledger_infos(&self, end_epoch: u64) -> Result<Vec<LedgerInfo>> {
+        match self {
+            RestoreRunMode::Restore { restore_handler } => {
+                restore_handler.get_epoch_ending_ledger_infos(end_epoch)
+            },
+            RestoreRunMode::Verify => Ok(Vec::new()),
+        }
+    }
 }
 
 #[derive(Clone)]
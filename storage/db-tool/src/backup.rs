@@ -10,6 +10,7 @@ use aptos_backup_cli::{
         transaction::backup::{TransactionBackupController, TransactionBackupOpt},
     },
     coordinators::{
+        audit::AuditCoordinator,
         backup::{BackupCoordinator, BackupCoordinatorOpt},
         verify::VerifyCoordinator,
     },
@@ -41,6 +42,11 @@ pub enum Command {
     Query(OneShotQueryType),
     #[clap(about = "verify the backup through restoring with the backup files")]
     Verify(VerifyOpt),
+    #[clap(
+        about = "Audit the proofs of all backups in the storage without restoring anything, and \
+        write a JSON report. Exits with an error if any problem is found."
+    )]
+    Audit(AuditOpt),
 }
 
 #[derive(Parser)]
@@ -165,6 +171,27 @@ pub struct VerifyOpt {
     output_transaction_analysis: Option<PathBuf>,
 }
 
+#[derive(Parser)]
+pub struct AuditOpt {
+    #[clap(flatten)]
+    metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    trusted_waypoints_opt: TrustedWaypointOpt,
+    #[clap(flatten)]
+    storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    concurrent_downloads: ConcurrentDownloadsOpt,
+    #[clap(
+        long,
+        default_value_t = 1,
+        help = "Number of the latest state snapshots to audit. Each one is read in full, pass 0 \
+        to skip state snapshots."
+    )]
+    num_state_snapshots: usize,
+    #[clap(long, value_parser, help = "Where to write the JSON report.")]
+    report_path: PathBuf,
+}
+
 impl Command {
     pub async fn run(self) -> Result<()> {
         match self {
@@ -250,6 +277,18 @@ impl Command {
                 .run()
                 .await?
             },
+            Command::Audit(opt) => {
+                AuditCoordinator::new(
+                    opt.storage.init_storage().await?,
+                    opt.metadata_cache_opt,
+                    opt.trusted_waypoints_opt,
+                    opt.concurrent_downloads.get(),
+                    opt.num_state_snapshots,
+                    opt.report_path,
+                )
+                .run()
+                .await?
+            },
         }
         Ok(())
     }
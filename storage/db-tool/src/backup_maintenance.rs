@@ -2,9 +2,15 @@
 // SPDX-License-Identifier: Apache-2.0
 use anyhow::Result;
 use aptos_backup_cli::{
-    coordinators::backup::BackupCompactor, metadata::cache::MetadataCacheOpt,
-    storage::DBToolStorageOpt, utils::ConcurrentDownloadsOpt,
+    coordinators::{
+        backup::BackupCompactor,
+        retention::{BackupGarbageCollector, RetentionPolicy},
+    },
+    metadata::cache::MetadataCacheOpt,
+    storage::DBToolStorageOpt,
+    utils::ConcurrentDownloadsOpt,
 };
+use aptos_types::transaction::Version;
 use clap::{Parser, Subcommand};
 
 /// Support compacting and cleaning obsolete metadata files
@@ -14,6 +20,8 @@ pub enum Command {
     Compact(CompactionOpt),
     #[clap(about = "Cleanup the backup metadata files")]
     Cleanup(CleanupOpt),
+    #[clap(about = "Delete backups no longer needed under a retention policy")]
+    Retention(RetentionOpt),
 }
 
 #[derive(Parser)]
@@ -48,6 +56,28 @@ pub struct CleanupOpt {
     pub storage: DBToolStorageOpt,
 }
 
+#[derive(Parser)]
+pub struct RetentionOpt {
+    /// Keep state snapshots at epochs that are multiples of this, plus the latest one. All state
+    /// snapshots are kept if not specified.
+    #[clap(long)]
+    pub state_snapshot_epoch_interval: Option<u64>,
+    /// Keep all transactions from this version on, along with the latest state snapshot at or
+    /// before it and everything after, so that any version from here on can be restored. All
+    /// transactions are kept if not specified.
+    #[clap(long)]
+    pub keep_transactions_since_version: Option<Version>,
+    /// Only print what would be deleted.
+    #[clap(long)]
+    pub dry_run: bool,
+    #[clap(flatten)]
+    pub metadata_cache_opt: MetadataCacheOpt,
+    #[clap(flatten)]
+    pub storage: DBToolStorageOpt,
+    #[clap(flatten)]
+    pub concurrent_downloads: ConcurrentDownloadsOpt,
+}
+
 impl Command {
     pub async fn run(self) -> Result<()> {
         match self {
@@ -63,6 +93,37 @@ impl Command {
                 );
                 compactor.run().await?
             },
+            Command::Retention(opt) => {
+                let plan = BackupGarbageCollector::new(
+                    RetentionPolicy {
+                        state_snapshot_epoch_interval: opt.state_snapshot_epoch_interval,
+                        keep_transactions_since_version: opt.keep_transactions_since_version,
+                    },
+                    opt.dry_run,
+                    opt.metadata_cache_opt,
+                    opt.storage.init_storage().await?,
+                    opt.concurrent_downloads.get(),
+                )
+                .run()
+                .await?;
+                let action = if opt.dry_run {
+                    "Would delete"
+                } else {
+                    "Deleted"
+                };
+                for backup in &plan.state_snapshots_to_delete {
+                    println!(
+                        "{} state snapshot at epoch {} version {}: {}",
+                        action, backup.epoch, backup.version, backup.manifest
+                    );
+                }
+                for backup in &plan.transactions_to_delete {
+                    println!(
+                        "{} transactions {}..={}: {}",
+                        action, backup.first_version, backup.last_version, backup.manifest
+                    );
+                }
+            },
             Command::Cleanup(_) => {
                 // TODO: add cleanup logic for removing obsolete metadata files
             },
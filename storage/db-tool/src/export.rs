@@ -0,0 +1,366 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+use anyhow::{ensure, Result};
+use aptos_config::config::{
+    RocksdbConfigs, StorageDirPaths, BUFFERED_STATE_TARGET_ITEMS,
+    DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD, NO_OP_STORAGE_PRUNER_CONFIG,
+};
+use aptos_db::AptosDB;
+use aptos_resource_viewer::AptosValueAnnotator;
+use aptos_storage_interface::{
+    state_view::{DbStateView, DbStateViewAtVersion},
+    DbReader,
+};
+use aptos_types::{
+    access_path::Path,
+    account_address::AccountAddress,
+    state_store::{
+        state_key::{StateKeyInner, StateKeyTag},
+        state_key_prefix::StateKeyPrefix,
+        state_value::StateValue,
+    },
+    transaction::Version,
+};
+use aptos_vm::data_cache::{AsMoveResolver, StorageAdapter};
+use clap::{Parser, ValueEnum};
+use move_core_types::language_storage::StructTag;
+use parquet::{
+    basic::Compression,
+    data_type::{ByteArray, ByteArrayType, Int64Type},
+    file::{properties::WriterProperties, writer::SerializedFileWriter},
+    schema::parser::parse_message_type,
+};
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufWriter, Write},
+    path::{Path as FsPath, PathBuf},
+    sync::Arc,
+};
+
+#[derive(Parser)]
+#[clap(
+    about = "Export the resources of a set of accounts, or all resources of some types, at a \
+    version, decoded as Move values, to JSON lines or Parquet."
+)]
+pub struct Command {
+    #[clap(long, value_parser)]
+    db_dir: PathBuf,
+
+    /// Version to export the state at, by default the latest state checkpoint.
+    #[clap(long)]
+    version: Option<Version>,
+
+    /// Only export resources under these accounts. Can be repeated.
+    #[clap(long = "address")]
+    addresses: Vec<AccountAddress>,
+
+    /// Only export resources under accounts whose address starts with these bytes, given in hex
+    /// with an even number of digits (e.g. "0x0000a1"). Can be repeated.
+    #[clap(long = "address-prefix", value_parser = parse_address_prefix)]
+    address_prefixes: Vec<Vec<u8>>,
+
+    /// Only export resources of these types, e.g. "0x1::coin::CoinStore". A type given without
+    /// type arguments matches all instantiations. Can be repeated.
+    #[clap(long = "resource-type")]
+    resource_types: Vec<StructTag>,
+
+    #[clap(long, value_enum, default_value_t = OutputFormat::JsonLines)]
+    format: OutputFormat,
+
+    #[clap(long, value_parser)]
+    output: PathBuf,
+
+    /// Stop after exporting this many resources.
+    #[clap(long)]
+    limit: Option<usize>,
+
+    /// Number of rows in a Parquet row group.
+    #[clap(long, default_value_t = 100_000)]
+    parquet_row_group_size: usize,
+
+    #[clap(long)]
+    enable_storage_sharding: bool,
+}
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum OutputFormat {
+    JsonLines,
+    Parquet,
+}
+
+#[derive(Serialize)]
+struct ExportedResource {
+    version: Version,
+    address: AccountAddress,
+    resource_type: String,
+    /// The decoded Move value, or the hex encoded raw bytes if it can't be decoded.
+    value: serde_json::Value,
+}
+
+impl Command {
+    pub fn run(self) -> Result<()> {
+        ensure!(
+            self.addresses.is_empty() || self.address_prefixes.is_empty(),
+            "--address and --address-prefix can't be used together."
+        );
+        ensure!(
+            self.parquet_row_group_size > 0,
+            "--parquet-row-group-size must be positive."
+        );
+
+        let db: Arc<dyn DbReader> = Arc::new(AptosDB::open(
+            StorageDirPaths::from_path(&self.db_dir),
+            true, /* readonly */
+            NO_OP_STORAGE_PRUNER_CONFIG,
+            RocksdbConfigs {
+                enable_storage_sharding: self.enable_storage_sharding,
+                ..Default::default()
+            },
+            false, /* indexer */
+            BUFFERED_STATE_TARGET_ITEMS,
+            DEFAULT_MAX_NUM_NODES_PER_LRU_CACHE_SHARD,
+        )?);
+        let version = match self.version {
+            Some(version) => version,
+            None => db
+                .get_latest_state_checkpoint_version()?
+                .ok_or_else(|| anyhow::anyhow!("DB has no state checkpoint."))?,
+        };
+        println!("Exporting state at version {}.", version);
+
+        let state_view = db.state_view_at_version(Some(version))?;
+        let resolver = state_view.as_move_resolver();
+        let mut exporter = Exporter {
+            version,
+            annotator: AptosValueAnnotator::new(&resolver),
+            resource_types: &self.resource_types,
+            writer: RecordWriter::new(self.format, &self.output, self.parquet_row_group_size)?,
+            limit: self.limit.unwrap_or(usize::MAX),
+            num_exported: 0,
+        };
+
+        // Each prefix is an (address) prefix of the BCS encoded access path, with an empty one
+        // covering all accounts.
+        let prefixes = if !self.addresses.is_empty() {
+            self.addresses.iter().map(|a| a.to_vec()).collect()
+        } else if !self.address_prefixes.is_empty() {
+            self.address_prefixes.clone()
+        } else {
+            vec![vec![]]
+        };
+        for prefix in prefixes {
+            let key_prefix = StateKeyPrefix::new(StateKeyTag::AccessPath, prefix);
+            for res in db.get_prefixed_state_value_iterator(&key_prefix, None, version)? {
+                let (state_key, state_value) = res?;
+                if let StateKeyInner::AccessPath(access_path) = state_key.inner() {
+                    if !exporter.export(access_path.address, access_path.get_path(), state_value)? {
+                        break;
+                    }
+                }
+            }
+            if exporter.is_done() {
+                break;
+            }
+        }
+
+        let num_exported = exporter.num_exported;
+        exporter.writer.finish()?;
+        println!(
+            "Exported {} resources to {}.",
+            num_exported,
+            self.output.display()
+        );
+        Ok(())
+    }
+}
+
+struct Exporter<'a> {
+    version: Version,
+    annotator: AptosValueAnnotator<'a, StorageAdapter<'a, DbStateView>>,
+    resource_types: &'a [StructTag],
+    writer: RecordWriter,
+    limit: usize,
+    num_exported: usize,
+}
+
+impl<'a> Exporter<'a> {
+    fn is_done(&self) -> bool {
+        self.num_exported >= self.limit
+    }
+
+    /// Exports the resource(s) stored under an access path, returns false once the limit is hit.
+    fn export(&mut self, address: AccountAddress, path: Path, value: StateValue) -> Result<bool> {
+        match path {
+            Path::Resource(tag) => self.export_resource(address, tag, value.bytes())?,
+            Path::ResourceGroup(_) => {
+                let members: BTreeMap<StructTag, Vec<u8>> = bcs::from_bytes(value.bytes())?;
+                for (tag, bytes) in members {
+                    if self.is_done() {
+                        break;
+                    }
+                    self.export_resource(address, tag, &bytes)?;
+                }
+            },
+            Path::Code(_) => (),
+        }
+        Ok(!self.is_done())
+    }
+
+    fn export_resource(
+        &mut self,
+        address: AccountAddress,
+        tag: StructTag,
+        bytes: &[u8],
+    ) -> Result<()> {
+        if self.is_done() || !type_matches(self.resource_types, &tag) {
+            return Ok(());
+        }
+
+        let value = match self.annotator.view_resource(&tag, bytes) {
+            Ok(annotated) => serde_json::to_value(annotated)?,
+            Err(_) => serde_json::Value::String(format!("0x{}", hex::encode(bytes))),
+        };
+        self.writer.write(ExportedResource {
+            version: self.version,
+            address,
+            resource_type: tag.to_canonical_string(),
+            value,
+        })?;
+        self.num_exported += 1;
+        Ok(())
+    }
+}
+
+fn type_matches(filters: &[StructTag], tag: &StructTag) -> bool {
+    filters.is_empty()
+        || filters.iter().any(|filter| {
+            filter.address == tag.address
+                && filter.module == tag.module
+                && filter.name == tag.name
+                && (filter.type_params.is_empty() || filter.type_params == tag.type_params)
+        })
+}
+
+fn parse_address_prefix(s: &str) -> Result<Vec<u8>> {
+    let prefix = hex::decode(s.strip_prefix("0x").unwrap_or(s))?;
+    ensure!(
+        prefix.len() <= AccountAddress::LENGTH,
+        "Address prefix longer than an address: {}",
+        s,
+    );
+    Ok(prefix)
+}
+
+const PARQUET_SCHEMA: &str = "
+    message resource {
+        REQUIRED INT64 version;
+        REQUIRED BYTE_ARRAY address (UTF8);
+        REQUIRED BYTE_ARRAY resource_type (UTF8);
+        REQUIRED BYTE_ARRAY value (UTF8);
+    }
+";
+
+enum RecordWriter {
+    JsonLines(BufWriter<File>),
+    Parquet {
+        writer: SerializedFileWriter<File>,
+        row_group_size: usize,
+        rows: Vec<ExportedResource>,
+    },
+}
+
+impl RecordWriter {
+    fn new(format: OutputFormat, output: &FsPath, row_group_size: usize) -> Result<Self> {
+        let file = File::create(output)?;
+        Ok(match format {
+            OutputFormat::JsonLines => Self::JsonLines(BufWriter::new(file)),
+            OutputFormat::Parquet => {
+                let props = WriterProperties::builder()
+                    .set_compression(Compression::SNAPPY)
+                    .build();
+                Self::Parquet {
+                    writer: SerializedFileWriter::new(
+                        file,
+                        Arc::new(parse_message_type(PARQUET_SCHEMA)?),
+                        Arc::new(props),
+                    )?,
+                    row_group_size,
+                    rows: Vec::with_capacity(row_group_size),
+                }
+            },
+        })
+    }
+
+    fn write(&mut self, record: ExportedResource) -> Result<()> {
+        match self {
+            Self::JsonLines(writer) => {
+                serde_json::to_writer(&mut *writer, &record)?;
+                writer.write_all(b"\n")?;
+            },
+            Self::Parquet {
+                writer,
+                row_group_size,
+                rows,
+            } => {
+                rows.push(record);
+                if rows.len() >= *row_group_size {
+                    write_row_group(writer, rows)?;
+                }
+            },
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            Self::JsonLines(mut writer) => writer.flush()?,
+            Self::Parquet {
+                mut writer,
+                mut rows,
+                ..
+            } => {
+                if !rows.is_empty() {
+                    write_row_group(&mut writer, &mut rows)?;
+                }
+                writer.close()?;
+            },
+        }
+        Ok(())
+    }
+}
+
+fn write_row_group(
+    writer: &mut SerializedFileWriter<File>,
+    rows: &mut Vec<ExportedResource>,
+) -> Result<()> {
+    let versions = rows.iter().map(|r| r.version as i64).collect::<Vec<_>>();
+    let strings = |f: fn(&ExportedResource) -> String| {
+        rows.iter()
+            .map(|r| ByteArray::from(f(r).into_bytes()))
+            .collect::<Vec<_>>()
+    };
+    let addresses = strings(|r| r.address.to_hex_literal());
+    let resource_types = strings(|r| r.resource_type.clone());
+    let values = strings(|r| r.value.to_string());
+
+    let mut row_group = writer.next_row_group()?;
+    let mut column = row_group.next_column()?.expect("version column");
+    column
+        .typed::<Int64Type>()
+        .write_batch(&versions, None, None)?;
+    column.close()?;
+    for data in [addresses, resource_types, values] {
+        let mut column = row_group.next_column()?.expect("byte array column");
+        column
+            .typed::<ByteArrayType>()
+            .write_batch(&data, None, None)?;
+        column.close()?;
+    }
+    row_group.close()?;
+
+    rows.clear();
+    Ok(())
+}
@@ -64,6 +64,43 @@ fn test_various_cmd_parsing() {
         "--start-version",
         "Max",
     ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "backup",
+        "audit",
+        "--local-fs-dir",
+        ".",
+        "--num-state-snapshots",
+        "0",
+        "--report-path",
+        "report.json",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "backup-maintenance",
+        "retention",
+        "--state-snapshot-epoch-interval",
+        "100",
+        "--keep-transactions-since-version",
+        "1000000",
+        "--dry-run",
+        "--local-fs-dir",
+        ".",
+    ]);
+    run_cmd(&[
+        "aptos-db-tool",
+        "export",
+        "--db-dir",
+        ".",
+        "--address-prefix",
+        "0x00a1",
+        "--resource-type",
+        "0x1::coin::CoinStore",
+        "--format",
+        "parquet",
+        "--output",
+        "out.parquet",
+    ]);
 }
 
 fn run_cmd(args: &[&str]) {
@@ -73,12 +110,20 @@ fn run_cmd(args: &[&str]) {
 #[cfg(test)]
 mod dbtool_tests {
     use crate::DBTool;
+    use anyhow::{bail, Result};
     use aptos_backup_cli::{
-        coordinators::backup::BackupCompactor,
+        coordinators::{
+            audit::{AuditOutcome, AuditReport},
+            backup::BackupCompactor,
+            restore::{RestoreCoordinator, RestoreCoordinatorOpt, RestoreStatus},
+        },
         metadata,
         metadata::{cache::MetadataCacheOpt, view::MetadataView},
-        storage::{local_fs::LocalFs, BackupStorage},
-        utils::test_utils::start_local_backup_service,
+        storage::{
+            local_fs::LocalFs, BackupHandle, BackupHandleRef, BackupStorage, FileHandle,
+            FileHandleRef, ShellSafeName, TextLine,
+        },
+        utils::{test_utils::start_local_backup_service, GlobalRestoreOpt},
     };
     use aptos_config::config::{RocksdbConfigs, StorageDirPaths};
     use aptos_db::AptosDB;
@@ -88,19 +133,31 @@ mod dbtool_tests {
     use aptos_storage_interface::DbReader;
     use aptos_temppath::TempPath;
     use aptos_types::{
+        account_address::AccountAddress,
         state_store::{state_key::StateKeyTag::AccessPath, state_key_prefix::StateKeyPrefix},
         transaction::Version,
     };
+    use async_trait::async_trait;
     use clap::Parser;
+    use move_core_types::language_storage::StructTag;
+    use parquet::{
+        basic::Type as PhysicalType,
+        file::reader::{FileReader, SerializedFileReader},
+    };
     use std::{
+        collections::HashSet,
         default::Default,
         fs,
         ops::Deref,
         path::{Path, PathBuf},
-        sync::Arc,
+        str::FromStr,
+        sync::{Arc, Mutex},
         time::Duration,
     };
-    use tokio::runtime::Runtime;
+    use tokio::{
+        io::{AsyncRead, AsyncWrite},
+        runtime::Runtime,
+    };
 
     fn assert_metadata_view_eq(view1: &MetadataView, view2: &MetadataView) {
         assert!(
@@ -532,6 +589,115 @@ mod dbtool_tests {
 
         rt.shutdown_timeout(Duration::from_secs(1));
     }
+
+    fn run_audit(rt: &Runtime, backup_dir: &Path, report_path: &Path) -> Result<()> {
+        rt.block_on(
+            DBTool::try_parse_from([
+                "aptos-db-tool",
+                "backup",
+                "audit",
+                "--num-state-snapshots",
+                "3",
+                "--report-path",
+                report_path.to_str().unwrap(),
+                "--local-fs-dir",
+                backup_dir.to_str().unwrap(),
+            ])
+            .unwrap()
+            .run(),
+        )
+    }
+
+    #[test]
+    fn test_backup_audit() {
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let new_db_dir = TempPath::new();
+        let old_db_dir = TempPath::new();
+        let (rt, _) = db_restore_test_setup(
+            16,
+            16,
+            PathBuf::from(backup_dir.path()),
+            PathBuf::from(old_db_dir.path()),
+            PathBuf::from(new_db_dir.path()),
+            false,
+        );
+
+        let report_path = TempPath::new();
+        run_audit(&rt, backup_dir.path(), report_path.path()).unwrap();
+
+        let report: AuditReport =
+            serde_json::from_slice(&fs::read(report_path.path()).unwrap()).unwrap();
+        assert_eq!(report.num_problems(), 0);
+        assert_eq!(report.epoch_endings.len(), 2);
+        assert_eq!(report.state_snapshots.len(), 3);
+        assert_eq!(report.transactions.len(), 2);
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backup_audit_tampered_chunk() {
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let new_db_dir = TempPath::new();
+        let old_db_dir = TempPath::new();
+        let (rt, _) = db_restore_test_setup(
+            16,
+            16,
+            PathBuf::from(backup_dir.path()),
+            PathBuf::from(old_db_dir.path()),
+            PathBuf::from(new_db_dir.path()),
+            false,
+        );
+
+        // flip the last byte of the chunk of the second transaction backup
+        let txn_backup_dir = fs::read_dir(backup_dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .find(|path| {
+                path.file_name()
+                    .unwrap()
+                    .to_str()
+                    .unwrap()
+                    .starts_with("transaction_15-")
+            })
+            .unwrap();
+        let chunk_path = txn_backup_dir.join("15-.chunk");
+        let mut chunk = fs::read(&chunk_path).unwrap();
+        *chunk.last_mut().unwrap() ^= 0xFF;
+        fs::write(&chunk_path, chunk).unwrap();
+
+        let report_path = TempPath::new();
+        assert!(run_audit(&rt, backup_dir.path(), report_path.path()).is_err());
+
+        // the report is written anyway, with only the tampered backup failing
+        let report: AuditReport =
+            serde_json::from_slice(&fs::read(report_path.path()).unwrap()).unwrap();
+        assert_eq!(report.num_problems(), 1);
+        assert!(report.gaps.is_empty());
+        assert!(report
+            .epoch_endings
+            .iter()
+            .all(|audit| audit.outcome.is_verified()));
+        assert!(report
+            .state_snapshots
+            .iter()
+            .all(|audit| audit.outcome.is_verified()));
+        assert_eq!(report.transactions.len(), 2);
+        for audit in &report.transactions {
+            if audit.first_version == 15 {
+                assert!(
+                    matches!(audit.outcome, AuditOutcome::Failed { .. }),
+                    "{:?}",
+                    audit.outcome
+                );
+            } else {
+                assert!(audit.outcome.is_verified());
+            }
+        }
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
     #[test]
     fn test_restore_archive_db() {
         let backup_dir = TempPath::new();
@@ -586,6 +752,268 @@ mod dbtool_tests {
             .run(),
         )
         .unwrap();
+        // the progress saved by the runs above can be reported
+        rt.block_on(
+            DBTool::try_parse_from([
+                "aptos-db-tool",
+                "restore",
+                "bootstrap-db",
+                "--status",
+                "--target-version",
+                "18",
+                "--target-db-dir",
+                new_db_dir.path().to_str().unwrap(),
+                "--local-fs-dir",
+                backup_dir.path().to_str().unwrap(),
+            ])
+            .unwrap()
+            .run(),
+        )
+        .unwrap();
+
+        let status = restore_status(&rt, backup_dir.path(), new_db_dir.path(), 18);
+        let progress = status.progress.as_ref().unwrap();
+        assert_eq!(progress.target_version, 18);
+        assert!(status.epoch_history_done());
+        assert!(progress.epoch_history.is_some());
+        assert!(status.state_snapshot_to_start.is_none());
+        assert!(!progress.state_snapshots.is_empty());
+        assert!(progress.state_snapshots.iter().all(|s| s.is_finished()));
+        assert!(status.transactions_done());
+        assert_eq!(status.next_transaction_version, 19);
+        assert_eq!(status.transaction_backups_to_restore, 0);
+
+        // a later target only leaves the transactions after the DB version to restore
+        let status = restore_status(&rt, backup_dir.path(), new_db_dir.path(), 25);
+        assert_eq!(status.target_version, 25);
+        assert!(status.epoch_history_done());
+        assert!(!status.transactions_done());
+        assert_eq!(status.next_transaction_version, 19);
+        assert_eq!(status.transaction_backups_to_restore, 1);
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
+
+    fn restore_status(
+        rt: &Runtime,
+        backup_dir: &Path,
+        db_dir: &Path,
+        target_version: Version,
+    ) -> RestoreStatus {
+        let coordinator = RestoreCoordinator::new(
+            RestoreCoordinatorOpt::try_parse_from(["restore", "--status"]).unwrap(),
+            GlobalRestoreOpt::try_parse_from([
+                "restore",
+                "--target-db-dir",
+                db_dir.to_str().unwrap(),
+                "--target-version",
+                &target_version.to_string(),
+            ])
+            .unwrap()
+            .try_into()
+            .unwrap(),
+            Arc::new(LocalFs::new(backup_dir.to_path_buf())),
+        );
+        rt.block_on(coordinator.status()).unwrap()
+    }
+
+    /// `LocalFs` that records the files read, and fails reading transaction chunks starting at or
+    /// after `fail_from_version`, to interrupt a restore partway.
+    struct InterruptingStorage {
+        inner: LocalFs,
+        fail_from_version: Option<Version>,
+        opened: Mutex<Vec<FileHandle>>,
+    }
+
+    impl InterruptingStorage {
+        fn new(dir: &Path, fail_from_version: Option<Version>) -> Self {
+            Self {
+                inner: LocalFs::new(dir.to_path_buf()),
+                fail_from_version,
+                opened: Mutex::new(Vec::new()),
+            }
+        }
+
+        fn opened(&self) -> Vec<FileHandle> {
+            self.opened.lock().unwrap().clone()
+        }
+    }
+
+    /// First version of a transaction chunk, `None` for any other file.
+    fn transaction_chunk_first_version(file_handle: &FileHandleRef) -> Option<Version> {
+        if !file_handle.starts_with("transaction_") {
+            return None;
+        }
+        Path::new(file_handle)
+            .file_name()?
+            .to_str()?
+            .strip_suffix("-.chunk")?
+            .parse()
+            .ok()
+    }
+
+    #[async_trait]
+    impl BackupStorage for InterruptingStorage {
+        async fn create_backup(&self, name: &ShellSafeName) -> Result<BackupHandle> {
+            self.inner.create_backup(name).await
+        }
+
+        async fn create_for_write(
+            &self,
+            backup_handle: &BackupHandleRef,
+            name: &ShellSafeName,
+        ) -> Result<(FileHandle, Box<dyn AsyncWrite + Send + Unpin>)> {
+            self.inner.create_for_write(backup_handle, name).await
+        }
+
+        async fn open_for_read(
+            &self,
+            file_handle: &FileHandleRef,
+        ) -> Result<Box<dyn AsyncRead + Send + Unpin>> {
+            if let (Some(fail_from), Some(first_version)) = (
+                self.fail_from_version,
+                transaction_chunk_first_version(file_handle),
+            ) {
+                if first_version >= fail_from {
+                    bail!("Injected failure reading {}.", file_handle);
+                }
+            }
+            self.opened.lock().unwrap().push(file_handle.to_string());
+            self.inner.open_for_read(file_handle).await
+        }
+
+        async fn list_metadata_files(&self) -> Result<Vec<FileHandle>> {
+            self.inner.list_metadata_files().await
+        }
+
+        async fn backup_metadata_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+            self.inner.backup_metadata_file(file_handle).await
+        }
+
+        async fn delete_file(&self, file_handle: &FileHandleRef) -> Result<()> {
+            self.inner.delete_file(file_handle).await
+        }
+
+        async fn save_metadata_lines(
+            &self,
+            name: &ShellSafeName,
+            lines: &[TextLine],
+        ) -> Result<FileHandle> {
+            self.inner.save_metadata_lines(name, lines).await
+        }
+    }
+
+    #[test]
+    fn test_resume_interrupted_restore() {
+        let backup_dir = TempPath::new();
+        backup_dir.create_as_dir().unwrap();
+        let new_db_dir = TempPath::new();
+        new_db_dir.create_as_dir().unwrap();
+        let old_db_dir = TempPath::new();
+
+        let db = test_execution_with_storage_impl_inner(false, old_db_dir.path());
+        let (rt, port) = start_local_backup_service(Arc::clone(&db));
+        let server_addr = format!(" http://localhost:{}", port);
+        let backup = |args: &[&str]| {
+            let mut cmd = vec![
+                "aptos-db-tool",
+                "backup",
+                "oneoff",
+                // one transaction per chunk, so that the restore can be interrupted in between
+                "--max-chunk-size",
+                "1",
+                "--backup-service-address",
+                server_addr.as_str(),
+            ];
+            cmd.extend_from_slice(args);
+            cmd.extend_from_slice(&["--local-fs-dir", backup_dir.path().to_str().unwrap()]);
+            rt.block_on(DBTool::try_parse_from(cmd).unwrap().run())
+                .unwrap();
+        };
+        for (start, end) in [("0", "1"), ("1", "2")] {
+            backup(&["epoch-ending", "--start-epoch", start, "--end-epoch", end]);
+        }
+        for epoch in ["0", "1", "2"] {
+            backup(&["state-snapshot", "--state-snapshot-epoch", epoch]);
+        }
+        for start in ["0", "15"] {
+            backup(&[
+                "transaction",
+                "--start-version",
+                start,
+                "--num_transactions",
+                "15",
+            ]);
+        }
+
+        let restore = |storage: Arc<InterruptingStorage>| {
+            let coordinator = RestoreCoordinator::new(
+                RestoreCoordinatorOpt::try_parse_from([
+                    "restore",
+                    "--ledger-history-start-version",
+                    "16",
+                ])
+                .unwrap(),
+                GlobalRestoreOpt::try_parse_from([
+                    "restore",
+                    "--target-db-dir",
+                    new_db_dir.path().to_str().unwrap(),
+                    "--target-version",
+                    "16",
+                ])
+                .unwrap()
+                .try_into()
+                .unwrap(),
+                storage,
+            );
+            rt.block_on(coordinator.run())
+        };
+
+        // the first run stops before replaying the transactions from version 15
+        let storage = Arc::new(InterruptingStorage::new(backup_dir.path(), Some(15)));
+        assert!(restore(Arc::clone(&storage)).is_err());
+
+        let status = restore_status(&rt, backup_dir.path(), new_db_dir.path(), 16);
+        let progress = status.progress.as_ref().unwrap();
+        assert!(status.epoch_history_done());
+        assert_eq!(progress.state_snapshots.len(), 1);
+        let snapshot = &progress.state_snapshots[0];
+        assert!(snapshot.is_finished());
+        assert!(status.state_snapshot_to_start.is_none());
+        assert!(!status.transactions_done());
+        let interrupted_at = status.next_transaction_version;
+        assert!(interrupted_at > snapshot.version && interrupted_at <= 15);
+
+        // the second run picks up from there
+        let storage = Arc::new(InterruptingStorage::new(backup_dir.path(), None));
+        restore(Arc::clone(&storage)).unwrap();
+        let opened = storage.opened();
+        assert!(
+            !opened.iter().any(|f| f.starts_with("epoch_ending_")),
+            "epoch ending backups restored again: {:?}",
+            opened
+        );
+        assert!(
+            !opened
+                .iter()
+                .any(|f| f.starts_with("state_epoch_") && f.ends_with(".chunk")),
+            "state snapshot chunks restored again: {:?}",
+            opened
+        );
+        let txn_chunks: Vec<Version> = opened
+            .iter()
+            .filter_map(|f| transaction_chunk_first_version(f))
+            .collect();
+        assert!(!txn_chunks.is_empty());
+        assert!(
+            txn_chunks.iter().all(|v| *v >= interrupted_at),
+            "transaction chunks before version {} restored again: {:?}",
+            interrupted_at,
+            txn_chunks
+        );
+
+        let status = restore_status(&rt, backup_dir.path(), new_db_dir.path(), 16);
+        assert!(status.transactions_done());
+        assert_eq!(status.next_transaction_version, 17);
         rt.shutdown_timeout(Duration::from_secs(1));
     }
 
@@ -639,4 +1067,165 @@ mod dbtool_tests {
 
         size
     }
+
+    fn export(rt: &Runtime, db_dir: &Path, output: &Path, args: &[&str]) {
+        let mut cmd = vec![
+            "aptos-db-tool",
+            "export",
+            "--db-dir",
+            db_dir.to_str().unwrap(),
+            "--output",
+            output.to_str().unwrap(),
+        ];
+        cmd.extend_from_slice(args);
+        rt.block_on(DBTool::try_parse_from(cmd).unwrap().run())
+            .unwrap();
+    }
+
+    fn export_json_lines(rt: &Runtime, db_dir: &Path, args: &[&str]) -> Vec<serde_json::Value> {
+        let output = TempPath::new();
+        export(rt, db_dir, output.path(), args);
+        fs::read_to_string(output.path())
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn exported_address(record: &serde_json::Value) -> AccountAddress {
+        AccountAddress::from_str(record["address"].as_str().unwrap()).unwrap()
+    }
+
+    fn exported_type(record: &serde_json::Value) -> &str {
+        record["resource_type"].as_str().unwrap()
+    }
+
+    #[test]
+    fn test_export() {
+        let db_dir = TempPath::new();
+        let db = test_execution_with_storage_impl_inner(false, db_dir.path());
+        let latest_version = db.get_latest_state_checkpoint_version().unwrap().unwrap();
+        drop(db);
+        let rt = Runtime::new().unwrap();
+
+        // by default, everything at the latest state checkpoint
+        let all = export_json_lines(&rt, db_dir.path(), &[]);
+        assert!(!all.is_empty());
+        for record in &all {
+            assert_eq!(record["version"], latest_version);
+            assert!(!record["value"].is_null());
+        }
+
+        // accounts created after genesis are not in the genesis state
+        let genesis = export_json_lines(&rt, db_dir.path(), &["--version", "0"]);
+        assert!(!genesis.is_empty());
+        assert!(genesis.iter().all(|record| record["version"] == 0));
+        let genesis_addresses: HashSet<_> = genesis.iter().map(exported_address).collect();
+        let new_address = all
+            .iter()
+            .map(exported_address)
+            .find(|address| !genesis_addresses.contains(address))
+            .expect("no account created after genesis");
+
+        // --address
+        let account = export_json_lines(&rt, db_dir.path(), &[
+            "--address",
+            &new_address.to_hex_literal(),
+        ]);
+        assert!(!account.is_empty());
+        assert!(account
+            .iter()
+            .all(|record| exported_address(record) == new_address));
+        assert_eq!(
+            account.len(),
+            all.iter()
+                .filter(|record| exported_address(record) == new_address)
+                .count()
+        );
+        assert!(export_json_lines(&rt, db_dir.path(), &[
+            "--address",
+            &new_address.to_hex_literal(),
+            "--version",
+            "0",
+        ])
+        .is_empty());
+
+        // --address-prefix
+        let first_byte = new_address.to_vec()[0];
+        let by_prefix = export_json_lines(&rt, db_dir.path(), &[
+            "--address-prefix",
+            &format!("0x{:02x}", first_byte),
+        ]);
+        assert_eq!(
+            by_prefix.len(),
+            all.iter()
+                .filter(|record| exported_address(record).to_vec()[0] == first_byte)
+                .count()
+        );
+        assert!(by_prefix.len() >= account.len());
+
+        // --resource-type, exact and matching all instantiations of a generic type
+        let account_type = StructTag::from_str("0x1::account::Account")
+            .unwrap()
+            .to_canonical_string();
+        let accounts = export_json_lines(&rt, db_dir.path(), &[
+            "--resource-type",
+            "0x1::account::Account",
+        ]);
+        assert!(!accounts.is_empty());
+        assert!(accounts
+            .iter()
+            .all(|record| exported_type(record) == account_type));
+        assert_eq!(
+            accounts.len(),
+            all.iter()
+                .filter(|record| exported_type(record) == account_type)
+                .count()
+        );
+        let coin_store_prefix = format!(
+            "{}<",
+            StructTag::from_str("0x1::coin::CoinStore")
+                .unwrap()
+                .to_canonical_string()
+        );
+        let coin_stores = export_json_lines(&rt, db_dir.path(), &[
+            "--resource-type",
+            "0x1::coin::CoinStore",
+        ]);
+        assert!(!coin_stores.is_empty());
+        assert!(coin_stores
+            .iter()
+            .all(|record| exported_type(record).starts_with(&coin_store_prefix)));
+
+        // --limit
+        let limited = export_json_lines(&rt, db_dir.path(), &["--limit", "3"]);
+        assert_eq!(limited, all[..3].to_vec());
+
+        // parquet, in row groups of 2
+        let output = TempPath::new();
+        export(&rt, db_dir.path(), output.path(), &[
+            "--format",
+            "parquet",
+            "--parquet-row-group-size",
+            "2",
+        ]);
+        let reader = SerializedFileReader::new(fs::File::open(output.path()).unwrap()).unwrap();
+        let metadata = reader.metadata();
+        assert_eq!(metadata.file_metadata().num_rows() as usize, all.len());
+        assert_eq!(metadata.num_row_groups(), (all.len() + 1) / 2);
+        let columns = metadata
+            .file_metadata()
+            .schema_descr()
+            .columns()
+            .iter()
+            .map(|column| (column.name().to_string(), column.physical_type()))
+            .collect::<Vec<_>>();
+        assert_eq!(columns, vec![
+            ("version".to_string(), PhysicalType::INT64),
+            ("address".to_string(), PhysicalType::BYTE_ARRAY),
+            ("resource_type".to_string(), PhysicalType::BYTE_ARRAY),
+            ("value".to_string(), PhysicalType::BYTE_ARRAY),
+        ]);
+        rt.shutdown_timeout(Duration::from_secs(1));
+    }
 }
@@ -0,0 +1,459 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Quick fixes for common compiler diagnostics. The compiler does not attach any structured data
+//! to the diagnostics it reports, so the diagnostics sent back by the client with a code action
+//! request are recognized by their messages.
+
+use crate::{
+    context::Context,
+    symbols::{ModuleDefs, Symbols},
+};
+use lsp_server::Request;
+use lsp_types::{
+    CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams, Diagnostic, Position, Range,
+    TextEdit, WorkspaceEdit,
+};
+use move_compiler::shared::Identifier;
+use move_symbol_pool::Symbol;
+use std::collections::{BTreeSet, HashMap};
+use url::Url;
+
+/// A quick fix title along with the edits applying it to the document
+type QuickFix = (String, Vec<TextEdit>);
+
+/// Returns the name quoted in a diagnostic message starting with a given prefix (that includes the
+/// opening quote).
+fn quoted_name<'a>(msg: &'a str, prefix: &str) -> Option<&'a str> {
+    msg.strip_prefix(prefix)?.split('\'').next()
+}
+
+/// Computes quick fixes for a single diagnostic.
+fn quick_fixes(lines: &[&str], symbols: &Symbols, diag: &Diagnostic) -> Vec<QuickFix> {
+    let msg = diag.message.as_str();
+    if let Some(name) = quoted_name(msg, "Unused local variable '")
+        .or_else(|| quoted_name(msg, "Unused parameter '"))
+    {
+        vec![prefix_with_underscore(lines, name, &diag.range)]
+    } else if let Some(name) = quoted_name(msg, "Unused 'use' of alias '") {
+        remove_unused_alias(lines, name, &diag.range)
+            .into_iter()
+            .collect()
+    } else if let Some(name) = quoted_name(msg, "Unbound module alias '") {
+        let paths = import_paths(symbols, None, |m| m.name().module.value().as_str() == name);
+        add_use(lines, &diag.range, paths)
+    } else if let Some(name) = quoted_name(msg, "Unbound type '") {
+        let paths = import_paths(symbols, Some(name), |m| m.has_struct(&Symbol::from(name)));
+        add_use(lines, &diag.range, paths)
+    } else if let Some(name) = quoted_name(msg, "Unbound function: '") {
+        let paths = import_paths(symbols, Some(name), |m| {
+            m.functions().contains_key(&Symbol::from(name))
+        });
+        add_use(lines, &diag.range, paths)
+    } else {
+        vec![]
+    }
+}
+
+/// Prefixes an unused variable with an underscore. Variables bound using the field shorthand of a
+/// struct unpack also get the field name spelled out, as it would otherwise change as well.
+fn prefix_with_underscore(lines: &[&str], name: &str, range: &Range) -> QuickFix {
+    let start = range.start;
+    let title = format!("Prefix `{}` with an underscore", name);
+    if is_field_shorthand(lines, &start) {
+        let end = Position::new(start.line, start.character + name.len() as u32);
+        let edit = TextEdit::new(Range::new(start, end), format!("{0}: _{0}", name));
+        (title, vec![edit])
+    } else {
+        (title, vec![TextEdit::new(
+            Range::new(start, start),
+            "_".to_string(),
+        )])
+    }
+}
+
+/// Checks if the variable at a given position is bound using the field shorthand of a struct
+/// unpack (e.g., `let S { f } = s;`).
+fn is_field_shorthand(lines: &[&str], pos: &Position) -> bool {
+    let line = pos.line as usize;
+    if line >= lines.len() {
+        return false;
+    }
+    let mut text_before = lines[..line].join("\n");
+    text_before.push('\n');
+    text_before.extend(lines[line].chars().take(pos.character as usize));
+    let chars: Vec<char> = text_before.chars().collect();
+
+    let mut depth = 0;
+    let mut first = true;
+    for (i, c) in chars.iter().enumerate().rev() {
+        if c.is_whitespace() {
+            continue;
+        }
+        match c {
+            // the variable is bound to a field explicitly (`S { f: v }`)
+            ':' if first => return false,
+            ')' | ']' | '}' => depth += 1,
+            '(' | '[' | '{' if depth > 0 => depth -= 1,
+            '{' => return is_struct_braces(&chars[..i]),
+            '(' | '[' => return false,
+            ';' if depth == 0 => return false,
+            _ => (),
+        }
+        first = false;
+    }
+    false
+}
+
+/// Checks if the braces following a given text enclose the fields of a struct being packed or
+/// unpacked rather than, e.g., a function body (`fun f(): u64 {`) or a block (`else {`).
+fn is_struct_braces(text: &[char]) -> bool {
+    let is_ident_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let skip_whitespace = |mut end: usize| {
+        while end > 0 && text[end - 1].is_whitespace() {
+            end -= 1;
+        }
+        end
+    };
+    let ident_start = |mut end: usize| {
+        while end > 0 && is_ident_char(text[end - 1]) {
+            end -= 1;
+        }
+        end
+    };
+    let word = |start: usize, end: usize| text[start..end].iter().collect::<String>();
+
+    // type arguments of the struct
+    let mut end = skip_whitespace(text.len());
+    if end > 0 && text[end - 1] == '>' {
+        let mut depth = 0;
+        loop {
+            if end == 0 {
+                return false;
+            }
+            end -= 1;
+            match text[end] {
+                '>' => depth += 1,
+                '<' if depth == 1 => break,
+                '<' => depth -= 1,
+                _ => (),
+            }
+        }
+        end = skip_whitespace(end);
+    }
+    // the (possibly qualified) struct name
+    let mut start = ident_start(end);
+    if start == end || ["else", "loop", "script", "spec"].contains(&word(start, end).as_str()) {
+        return false;
+    }
+    loop {
+        let before = skip_whitespace(start);
+        if before < 2 || text[before - 2..before] != [':', ':'] {
+            break;
+        }
+        let end = skip_whitespace(before - 2);
+        start = ident_start(end);
+        if start == end {
+            return false;
+        }
+    }
+
+    // what precedes the struct name tells a pattern or an expression from a type
+    let end = skip_whitespace(start);
+    let prev_start = ident_start(end);
+    if prev_start < end {
+        return word(prev_start, end) == "let";
+    }
+    match text.get(end.wrapping_sub(1)) {
+        // the field of an enclosing pattern (`S { f: T { g } }`) rather than a return type
+        Some(':') => text.get(skip_whitespace(end - 1).wrapping_sub(1)) != Some(&')'),
+        // a pattern in a list rather than an `acquires` list
+        Some(',') => {
+            let list_start = text[..end - 1]
+                .iter()
+                .rposition(|c| "{}();=".contains(*c))
+                .map_or(0, |i| i + 1);
+            !word(list_start, end - 1)
+                .split(|c: char| !is_ident_char(c))
+                .any(|w| w == "acquires")
+        },
+        Some('(' | '=' | ';' | '{' | '}') => true,
+        _ => false,
+    }
+}
+
+/// Removes an unused alias - either the whole `use` declaration if the alias is the only one it
+/// introduces, or the alias along with its separator from the list of members being used.
+fn remove_unused_alias(lines: &[&str], name: &str, range: &Range) -> Option<QuickFix> {
+    let line = range.start.line;
+    let text = lines.get(line as usize)?;
+    let title = format!("Remove unused alias `{}`", name);
+
+    let trimmed = text.trim();
+    if trimmed.starts_with("use ") && trimmed.ends_with(';') && !trimmed.contains(',') {
+        let range = Range::new(Position::new(line, 0), Position::new(line + 1, 0));
+        return Some((title, vec![TextEdit::new(range, String::new())]));
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    let start = range.start.character as usize;
+    let end = range.end.character as usize;
+    if range.end.line != line
+        || end > chars.len()
+        || start > end
+        || chars[start..end].iter().collect::<String>() != name
+        || chars[..start]
+            .iter()
+            .collect::<String>()
+            .trim_end()
+            .ends_with(" as")
+    {
+        // the alias is not a plain member (e.g., it renames a member using `as`)
+        return None;
+    }
+    let edit = |from: usize, to: usize| {
+        let range = Range::new(
+            Position::new(line, from as u32),
+            Position::new(line, to as u32),
+        );
+        TextEdit::new(range, String::new())
+    };
+
+    let mut after = end;
+    while after < chars.len() && chars[after].is_whitespace() {
+        after += 1;
+    }
+    if after < chars.len() && chars[after] == ',' {
+        after += 1;
+        while after < chars.len() && chars[after].is_whitespace() {
+            after += 1;
+        }
+        return Some((title, vec![edit(start, after)]));
+    }
+    let mut before = start;
+    while before > 0 && chars[before - 1].is_whitespace() {
+        before -= 1;
+    }
+    if before > 0 && chars[before - 1] == ',' {
+        return Some((title, vec![edit(before - 1, end)]));
+    }
+    None
+}
+
+/// Returns paths of modules satisfying a predicate (or paths of their members with a given name)
+/// that can be imported with a `use` declaration.
+fn import_paths(
+    symbols: &Symbols,
+    member: Option<&str>,
+    pred: impl Fn(&ModuleDefs) -> bool,
+) -> BTreeSet<String> {
+    symbols
+        .file_mods()
+        .values()
+        .flatten()
+        .filter(|m| pred(m))
+        .map(|m| match member {
+            Some(n) => format!("{}::{}", m.module_path(), n),
+            None => m.module_path(),
+        })
+        .collect()
+}
+
+/// Adds a `use` declaration for each of the given paths to the module (or script) enclosing a
+/// given range.
+fn add_use(lines: &[&str], range: &Range, paths: BTreeSet<String>) -> Vec<QuickFix> {
+    let (line, indent) = match use_insertion_point(lines, range.start.line as usize) {
+        Some(p) => p,
+        None => return vec![],
+    };
+    let pos = Position::new(line, 0);
+    paths
+        .into_iter()
+        .map(|path| {
+            let edit = TextEdit::new(Range::new(pos, pos), format!("{}use {};\n", indent, path));
+            (format!("Add `use {};`", path), vec![edit])
+        })
+        .collect()
+}
+
+/// Finds the line where a new `use` declaration should be inserted (after the last `use`
+/// declaration preceding a given line or at the beginning of the enclosing module or script) along
+/// with its indentation.
+fn use_insertion_point(lines: &[&str], line: usize) -> Option<(u32, String)> {
+    let indentation = |l: &str| l[..l.len() - l.trim_start().len()].to_string();
+
+    let header = (0..=line.min(lines.len().checked_sub(1)?))
+        .rev()
+        .find(|i| {
+            let l = lines[*i].trim_start();
+            l.starts_with("module ") || l.starts_with("script")
+        })?;
+    let body_start = (header..lines.len()).find(|i| lines[*i].contains('{'))? + 1;
+
+    let mut insert_line = body_start;
+    let mut indent = None;
+    let mut i = body_start;
+    while i < line.min(lines.len()) {
+        if lines[i].trim_start().starts_with("use ") {
+            indent = Some(indentation(lines[i]));
+            // a `use` declaration may span multiple lines
+            while i + 1 < lines.len() && !lines[i].trim_end().ends_with(';') {
+                i += 1;
+            }
+            insert_line = i + 1;
+        }
+        i += 1;
+    }
+    let indent = indent
+        .or_else(|| {
+            lines[body_start..]
+                .iter()
+                .find(|l| !l.trim().is_empty())
+                .map(|l| indentation(l))
+        })
+        .unwrap_or_else(|| "    ".to_string());
+    Some((insert_line as u32, indent))
+}
+
+/// Builds a quick fix code action resolving a given diagnostic.
+fn quick_fix_action(uri: &Url, diag: &Diagnostic, (title, edits): QuickFix) -> CodeAction {
+    let mut changes = HashMap::new();
+    changes.insert(uri.clone(), edits);
+    CodeAction {
+        title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diag.clone()]),
+        edit: Some(WorkspaceEdit::new(changes)),
+        ..Default::default()
+    }
+}
+
+/// Sends the given connection a response to a code action request.
+///
+/// Quick fixes are offered for the compiler diagnostics the client sends along with the request.
+pub fn on_code_action_request(context: &Context, request: &Request, symbols: &Symbols) {
+    eprintln!("handling code action request");
+    let parameters = serde_json::from_value::<CodeActionParams>(request.params.clone())
+        .expect("could not deserialize code action request");
+
+    let uri = parameters.text_document.uri;
+    let path = uri.to_file_path().unwrap();
+    let buffer = context.files.get(&path);
+    if buffer.is_none() {
+        eprintln!(
+            "Could not read '{:?}' when handling code action request",
+            path
+        );
+    }
+
+    let mut actions = vec![];
+    if let Some(buffer) = buffer {
+        let lines: Vec<&str> = buffer.lines().collect();
+        for diag in &parameters.context.diagnostics {
+            for fix in quick_fixes(&lines, symbols, diag) {
+                actions.push(CodeActionOrCommand::CodeAction(quick_fix_action(
+                    &uri, diag, fix,
+                )));
+            }
+        }
+    }
+
+    let result = serde_json::to_value(actions).expect("could not serialize code action response");
+    eprintln!("about to send code action response");
+    let response = lsp_server::Response::new_ok(request.id.clone(), result);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send code action response: {:?}", err);
+    }
+}
+
+#[test]
+/// Tests if unused variables are prefixed correctly, including struct unpack field shorthands.
+fn prefix_with_underscore_test() {
+    let lines = vec![
+        "fun f(s: S, p: u64) {",
+        "    let (a, b) = (1, 2);",
+        "    let S { f, g: v } = s;",
+        "    let S {",
+        "        f,",
+        "    } = s;",
+        "}",
+        "fun g(): u64 {",
+        "    let x = 1;",
+        "    if (true) { 0 } else {",
+        "        let y = 1;",
+        "        S { f } = s;",
+        "        let (S { f }, T { g: U { h } }) = (s, t);",
+        "    }",
+        "}",
+        "fun h() acquires R, Q {",
+        "    let z = 1;",
+        "}",
+    ];
+    let fix = |line, character, name| {
+        let start = Position::new(line, character);
+        prefix_with_underscore(&lines, name, &Range::new(start, start)).1[0].clone()
+    };
+    assert_eq!(fix(0, 12, "p").new_text, "_");
+    assert_eq!(fix(1, 12, "b").new_text, "_");
+    assert_eq!(fix(2, 12, "f").new_text, "f: _f");
+    assert_eq!(fix(2, 18, "v").new_text, "_");
+    assert_eq!(fix(4, 8, "f").new_text, "f: _f");
+    assert_eq!(fix(8, 8, "x").new_text, "_");
+    assert_eq!(fix(10, 12, "y").new_text, "_");
+    assert_eq!(fix(11, 12, "f").new_text, "f: _f");
+    assert_eq!(fix(12, 17, "f").new_text, "f: _f");
+    assert_eq!(fix(12, 33, "h").new_text, "h: _h");
+    assert_eq!(fix(16, 8, "z").new_text, "_");
+}
+
+#[test]
+/// Tests if unused aliases are removed along with the right separators.
+fn remove_unused_alias_test() {
+    let lines = vec![
+        "    use std::vector;",
+        "    use std::option::{Self, Option, some};",
+        "    use std::string::{String as S, utf8};",
+    ];
+    let range = |line, start, end| Range::new(Position::new(line, start), Position::new(line, end));
+    let removed = |line, start, end, name| {
+        remove_unused_alias(&lines, name, &range(line, start, end))
+            .map(|(_, edits)| (edits[0].range.start.character, edits[0].range.end.character))
+    };
+    assert_eq!(
+        remove_unused_alias(&lines, "vector", &range(0, 13, 19))
+            .unwrap()
+            .1[0]
+            .range,
+        Range::new(Position::new(0, 0), Position::new(1, 0))
+    );
+    assert_eq!(removed(1, 28, 34, "Option"), Some((28, 36)));
+    assert_eq!(removed(1, 36, 40, "some"), Some((34, 40)));
+    assert_eq!(removed(2, 32, 33, "S"), None);
+}
+
+#[test]
+/// Tests if `use` declarations are inserted after existing ones or at the start of a module.
+fn use_insertion_point_test() {
+    let lines = vec![
+        "module 0x1::m {",
+        "    use std::vector;",
+        "    use std::option::{",
+        "        Self, Option};",
+        "",
+        "    fun f() {}",
+        "}",
+        "module 0x1::n {",
+        "  fun g() {}",
+        "}",
+    ];
+    assert_eq!(
+        use_insertion_point(&lines, 5),
+        Some((4, "    ".to_string()))
+    );
+    assert_eq!(use_insertion_point(&lines, 8), Some((8, "  ".to_string())));
+    assert_eq!(use_insertion_point(&lines[8..], 0), None);
+}
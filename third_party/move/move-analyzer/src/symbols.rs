@@ -59,14 +59,16 @@ use im::ordmap::OrdMap;
 use lsp_server::{Request, RequestId};
 use lsp_types::{
     request::GotoTypeDefinitionParams, Diagnostic, DocumentSymbol, DocumentSymbolParams,
-    GotoDefinitionParams, Hover, HoverContents, HoverParams, LanguageString, Location,
-    MarkedString, Position, Range, ReferenceParams, SymbolKind,
+    Documentation, GotoDefinitionParams, Hover, HoverContents, HoverParams, LanguageString,
+    Location, MarkedString, ParameterInformation, ParameterLabel, Position, PrepareRenameResponse,
+    Range, ReferenceParams, RenameParams, SignatureHelp, SignatureHelpParams, SignatureInformation,
+    SymbolKind, TextDocumentIdentifier, TextDocumentPositionParams, TextEdit, WorkspaceEdit,
 };
 use move_command_line_common::files::FileHash;
 use move_compiler::{
     expansion::ast::{Address, Fields, ModuleIdent, ModuleIdent_},
     naming::ast::{StructDefinition, StructFields, TParam, Type, TypeName_, Type_},
-    parser::ast::StructName,
+    parser::{ast::StructName, keywords::KEYWORDS},
     shared::Identifier,
     typing::ast::{
         BuiltinFunction_, Exp, ExpListItem, Function, FunctionBody_, LValue, LValueList, LValue_,
@@ -80,6 +82,7 @@ use move_package::{
     CompilerConfig,
 };
 use move_symbol_pool::Symbol;
+use serde::{Deserialize, Serialize};
 use std::{
     cmp,
     collections::{BTreeMap, BTreeSet, HashMap},
@@ -152,6 +155,9 @@ pub struct UseDef {
     type_def_loc: Option<DefLoc>,
     /// Doc string for the relevant identifier/function
     doc_string: String,
+    /// Whether this is a local definition whose type was inferred rather than annotated (such
+    /// definitions get type inlay hints)
+    inferred_type: bool,
 }
 
 /// Definition of a struct field
@@ -221,6 +227,52 @@ struct UseDefMap(BTreeMap<u32, BTreeSet<UseDef>>);
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct FunctionIdentTypeMap(BTreeMap<String, IdentType>);
 
+/// Call of a function whose argument list contains the cursor (used for signature help)
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct CallSite {
+    /// Module qualifier of the called function (if any)
+    module: Option<String>,
+    /// Name of the called function
+    name: String,
+    /// Location where the name of the called function starts
+    name_start: Position,
+    /// Index of the argument the cursor is in
+    active_parameter: u32,
+}
+
+/// Inlay hint request - it is only part of version 3.17 of the protocol which is not yet supported
+/// by the `lsp_types` crate so we define it here
+pub enum InlayHintRequest {}
+
+impl lsp_types::request::Request for InlayHintRequest {
+    type Params = InlayHintParams;
+    type Result = Option<Vec<InlayHint>>;
+
+    const METHOD: &'static str = "textDocument/inlayHint";
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InlayHintParams {
+    /// The document for which inlay hints are requested
+    pub text_document: TextDocumentIdentifier,
+    /// The visible document range for which inlay hints should be computed
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct InlayHint {
+    /// Location where the hint is displayed
+    pub position: Position,
+    /// Text of the hint
+    pub label: String,
+    /// Kind of the hint (type or parameter)
+    pub kind: u32,
+}
+
+/// Inlay hint kind for type annotations
+pub const INLAY_HINT_KIND_TYPE: u32 = 1;
+
 /// Result of the symbolication process
 pub struct Symbols {
     /// A map from def locations to all the references (uses)
@@ -249,6 +301,27 @@ impl ModuleDefs {
     pub fn functions(&self) -> &BTreeMap<Symbol, FunctionDef> {
         &self.functions
     }
+
+    pub fn name(&self) -> &ModuleIdent_ {
+        &self.name
+    }
+
+    pub fn start(&self) -> Position {
+        self.start
+    }
+
+    pub fn has_struct(&self, name: &Symbol) -> bool {
+        self.structs.contains_key(name)
+    }
+
+    /// Returns the module path as it would appear in a `use` declaration
+    pub fn module_path(&self) -> String {
+        format!(
+            "{}::{}",
+            addr_to_ide_string(&self.name.address),
+            self.name.module.value()
+        )
+    }
 }
 
 impl fmt::Display for IdentType {
@@ -365,6 +438,179 @@ fn type_list_to_ide_string(types: &[Type]) -> String {
         .join(", ")
 }
 
+/// Checks if a type has been fully inferred (i.e., it does not contain unresolved components)
+fn is_fully_inferred(sp!(_, t): &Type) -> bool {
+    match t {
+        Type_::Unit | Type_::Param(_) => true,
+        Type_::Ref(_, r) => is_fully_inferred(r),
+        Type_::Apply(_, _, ss) => ss.iter().all(is_fully_inferred),
+        Type_::Anything | Type_::Var(_) | Type_::UnresolvedError => false,
+    }
+}
+
+/// Builds signature information for a function, with parameters labeled by their offsets in the
+/// signature label
+fn signature_information(
+    ident_type: &IdentType,
+    doc_string: String,
+) -> Option<SignatureInformation> {
+    match ident_type {
+        IdentType::FunctionType(_, name, type_args, arg_names, arg_types, ret, _) => {
+            let mut label = format!("fun {}", name);
+            if !type_args.is_empty() {
+                label.push('<');
+                label.push_str(&type_list_to_ide_string(type_args));
+                label.push('>');
+            }
+            label.push('(');
+            let mut parameters = vec![];
+            for (i, (n, t)) in arg_names.iter().zip(arg_types.iter()).enumerate() {
+                if i > 0 {
+                    label.push_str(", ");
+                }
+                let start = label.len() as u32;
+                label.push_str(&format!("{}: {}", n, type_to_ide_string(t)));
+                parameters.push(ParameterInformation {
+                    label: ParameterLabel::LabelOffsets([start, label.len() as u32]),
+                    documentation: None,
+                });
+            }
+            label.push(')');
+            if !matches!(ret, sp!(_, Type_::Unit)) {
+                label.push_str(&format!(": {}", type_to_ide_string(ret)));
+            }
+            Some(SignatureInformation {
+                label,
+                documentation: if doc_string.is_empty() {
+                    None
+                } else {
+                    Some(Documentation::String(doc_string))
+                },
+                parameters: Some(parameters),
+                active_parameter: None,
+            })
+        },
+        IdentType::RegularType(_) => None,
+    }
+}
+
+/// Finds the call site of the innermost function whose argument list contains the cursor by
+/// scanning the buffer backwards from the cursor. The scan is purely textual and can be misled by
+/// parentheses in comments and string literals.
+fn call_site(buffer: &str, position: &Position) -> Option<CallSite> {
+    let lines: Vec<Vec<char>> = buffer
+        .lines()
+        .take(position.line as usize + 1)
+        .map(|l| l.chars().collect())
+        .collect();
+    let mut line = position.line as usize;
+    if line >= lines.len() {
+        return None;
+    }
+    let mut col = cmp::min(position.character as usize, lines[line].len());
+    let mut depth = 0;
+    let mut active_parameter = 0;
+    // find the opening parenthesis of the argument list
+    loop {
+        if col == 0 {
+            if line == 0 {
+                return None;
+            }
+            line -= 1;
+            col = lines[line].len();
+            continue;
+        }
+        col -= 1;
+        match lines[line][col] {
+            ')' | ']' | '}' => depth += 1,
+            '(' | '[' | '{' if depth > 0 => depth -= 1,
+            '(' => break,
+            '[' | '{' => return None,
+            ';' if depth == 0 => return None,
+            ',' if depth == 0 => active_parameter += 1,
+            _ => (),
+        }
+    }
+
+    let chars = &lines[line];
+    let mut end = skip_whitespace_back(chars, col);
+    // skip explicit type arguments
+    if end > 0 && chars[end - 1] == '>' {
+        let mut angle_depth = 0;
+        loop {
+            if end == 0 {
+                return None;
+            }
+            end -= 1;
+            match chars[end] {
+                '>' => angle_depth += 1,
+                '<' => {
+                    angle_depth -= 1;
+                    if angle_depth == 0 {
+                        break;
+                    }
+                },
+                _ => (),
+            }
+        }
+        end = skip_whitespace_back(chars, end);
+    }
+    let start = identifier_start(chars, end);
+    let name: String = chars[start..end].iter().collect();
+    if name.is_empty()
+        || name.starts_with(|c: char| c.is_ascii_digit())
+        || KEYWORDS.contains(&name.as_str())
+    {
+        return None;
+    }
+    let module = if start >= 2 && chars[start - 2] == ':' && chars[start - 1] == ':' {
+        let module_start = identifier_start(chars, start - 2);
+        if module_start < start - 2 {
+            Some(chars[module_start..start - 2].iter().collect())
+        } else {
+            None
+        }
+    } else {
+        None
+    };
+    Some(CallSite {
+        module,
+        name,
+        name_start: Position {
+            line: line as u32,
+            character: start as u32,
+        },
+        active_parameter,
+    })
+}
+
+fn skip_whitespace_back(chars: &[char], mut end: usize) -> usize {
+    while end > 0 && chars[end - 1].is_whitespace() {
+        end -= 1;
+    }
+    end
+}
+
+fn identifier_start(chars: &[char], mut end: usize) -> usize {
+    while end > 0 && (chars[end - 1].is_ascii_alphanumeric() || chars[end - 1] == '_') {
+        end -= 1;
+    }
+    end
+}
+
+/// Checks if a name can be used as a Move identifier
+fn is_valid_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    let first_valid = match chars.next() {
+        Some(c) => c.is_ascii_alphabetic() || c == '_',
+        None => false,
+    };
+    first_valid
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && name != "_"
+        && !KEYWORDS.contains(&name)
+}
+
 impl SymbolicatorRunner {
     /// Create a new idle runner (one that does not actually symbolicate)
     pub fn idle() -> Self {
@@ -533,6 +779,7 @@ impl UseDef {
             def_loc,
             type_def_loc,
             doc_string,
+            inferred_type: false,
         }
     }
 }
@@ -575,6 +822,17 @@ impl UseDefMap {
     fn extend(&mut self, use_defs: BTreeMap<u32, BTreeSet<UseDef>>) {
         self.0.extend(use_defs);
     }
+
+    /// Marks the definition starting at a given location as having an inferred type
+    fn mark_inferred_type(&mut self, line: u32, col_start: u32) {
+        if let Some(uses) = self.0.get_mut(&line) {
+            if let Some(u) = uses.iter().find(|u| u.col_start == col_start) {
+                let mut u = u.clone();
+                u.inferred_type = true;
+                uses.replace(u);
+            }
+        }
+    }
 }
 
 impl FunctionIdentTypeMap {
@@ -607,6 +865,173 @@ impl Symbols {
     pub fn file_mods(&self) -> &BTreeMap<PathBuf, BTreeSet<ModuleDefs>> {
         &self.file_mods
     }
+
+    /// Finds the use-def pair for the identifier at a given location
+    fn use_def_at(&self, use_fpath: &Path, use_line: u32, use_col: u32) -> Option<UseDef> {
+        self.file_use_defs
+            .get(use_fpath)
+            .and_then(|mod_symbols| mod_symbols.get(use_line))
+            .and_then(|uses| {
+                uses.into_iter()
+                    .find(|u| use_col >= u.col_start && use_col <= u.col_end)
+            })
+    }
+
+    /// Checks if the definition of a given use can be renamed - definitions outside of the package
+    /// containing the use (i.e., in dependencies) cannot
+    fn is_renamable(&self, use_fpath: &Path, u: &UseDef) -> bool {
+        let def_path = match self.file_name_mapping.get(&u.def_loc.fhash) {
+            Some(p) => Path::new(p.as_str()),
+            None => return false,
+        };
+        match SymbolicatorRunner::root_dir(use_fpath) {
+            Some(root) => {
+                let root = dunce::canonicalize(&root).unwrap_or(root);
+                let def_path =
+                    dunce::canonicalize(def_path).unwrap_or_else(|_| def_path.to_path_buf());
+                def_path.starts_with(root)
+            },
+            None => false,
+        }
+    }
+
+    /// Finds the name of the struct field defined at a given location
+    fn field_name(&self, def_loc: &DefLoc) -> Option<Symbol> {
+        self.file_mods
+            .values()
+            .flatten()
+            .filter(|m| m.fhash == def_loc.fhash)
+            .flat_map(|m| m.structs.values())
+            .flat_map(|s| s.field_defs.iter())
+            .find(|f| f.start == def_loc.start)
+            .map(|f| f.name)
+    }
+
+    /// Finds the name of the struct field bound using the field shorthand (e.g., `f` in
+    /// `let S { f } = s;` or `S { f }`) at a given reference of a definition. The same identifier
+    /// is then a reference of both the field and the local variable.
+    fn shorthand_field(&self, ref_loc: &UseLoc, def_loc: &DefLoc) -> Option<Symbol> {
+        let def_field = self.field_name(def_loc);
+        self.references
+            .iter()
+            .filter(|(other_loc, refs)| *other_loc != def_loc && refs.contains(ref_loc))
+            .find_map(
+                |(other_loc, _)| match (def_field, self.field_name(other_loc)) {
+                    (Some(name), None) | (None, Some(name)) => Some(name),
+                    _ => None,
+                },
+            )
+    }
+
+    /// Returns the naming rule of Move a new name for the definition at a given location breaks,
+    /// if any (the names of structs and constants must start with an uppercase letter, and the
+    /// names of functions cannot start with an underscore)
+    fn broken_naming_rule(&self, def_loc: &DefLoc, new_name: &str) -> Option<&'static str> {
+        let starts_uppercase = new_name.starts_with(|c: char| c.is_ascii_uppercase());
+        for m in self
+            .file_mods
+            .values()
+            .flatten()
+            .filter(|m| m.fhash == def_loc.fhash)
+        {
+            if m.structs.values().any(|s| s.name_start == def_loc.start) && !starts_uppercase {
+                return Some("struct names must start with 'A'..'Z'");
+            }
+            if m.constants.values().any(|c| *c == def_loc.start) && !starts_uppercase {
+                return Some("constant names must start with 'A'..'Z'");
+            }
+            if m.functions.values().any(|f| f.start == def_loc.start) && new_name.starts_with('_') {
+                return Some("function names cannot start with '_'");
+            }
+        }
+        None
+    }
+
+    /// Computes edits renaming the definition of a given use and all its references
+    fn rename_edits(&self, u: &UseDef, new_name: &str) -> HashMap<Url, Vec<TextEdit>> {
+        let mut changes: HashMap<Url, Vec<TextEdit>> = HashMap::new();
+        let is_field = self.field_name(&u.def_loc).is_some();
+        if let Some(refs) = self.references.get(&u.def_loc) {
+            for ref_loc in refs {
+                let path = match self.file_name_mapping.get(&ref_loc.fhash) {
+                    Some(p) => p,
+                    None => continue,
+                };
+                let range = Range {
+                    start: ref_loc.start,
+                    end: Position {
+                        line: ref_loc.start.line,
+                        character: ref_loc.col_end,
+                    },
+                };
+                // a field shorthand has to be expanded so that the field and the local variable
+                // it binds keep matching after only one of them gets renamed
+                let new_text = match self.shorthand_field(ref_loc, &u.def_loc) {
+                    Some(field) if is_field => format!("{}: {}", new_name, field),
+                    Some(field) => format!("{}: {}", field, new_name),
+                    None => new_name.to_string(),
+                };
+                changes
+                    .entry(Url::from_file_path(path.as_str()).unwrap())
+                    .or_default()
+                    .push(TextEdit::new(range, new_text));
+            }
+        }
+        changes
+    }
+
+    /// Finds the signature (and doc string, if available) of the function called at a given call
+    /// site
+    fn call_signature(&self, fpath: &Path, call: &CallSite) -> Option<(IdentType, String)> {
+        if let Some(u) = self.use_def_at(fpath, call.name_start.line, call.name_start.character) {
+            if matches!(u.use_type, IdentType::FunctionType(..)) {
+                return Some((u.use_type, u.doc_string));
+            }
+        }
+        // symbols are only recomputed on successful compilation so they may not reflect the call
+        // being currently typed - in this case look up the function by name instead
+        let name = Symbol::from(call.name.as_str());
+        let local_mods = self.file_mods.get(fpath);
+        self.file_mods
+            .values()
+            .flatten()
+            .filter(|m| match &call.module {
+                Some(module) => m.name.module.value().as_str() == module.as_str(),
+                None => local_mods.map_or(false, |mods| mods.contains(*m)),
+            })
+            .find_map(|m| m.functions.get(&name))
+            .map(|f| (f.ident_type.clone(), String::new()))
+    }
+
+    /// Computes type hints for local variables whose types were inferred within a given range of
+    /// lines in a file
+    fn inlay_hints(&self, fpath: &Path, range: &Range) -> Vec<InlayHint> {
+        let mut hints = vec![];
+        let mod_symbols = match self.file_use_defs.get(fpath) {
+            Some(m) if range.start.line <= range.end.line => m,
+            _ => return hints,
+        };
+        for (line, uses) in mod_symbols.0.range(range.start.line..=range.end.line) {
+            for u in uses {
+                if !u.inferred_type {
+                    continue;
+                }
+                if let IdentType::RegularType(t) = &u.use_type {
+                    if is_fully_inferred(t) {
+                        hints.push(InlayHint {
+                            position: Position {
+                                line: *line,
+                                character: u.col_end,
+                            },
+                            label: format!(": {}", type_to_ide_string(t)),
+                            kind: INLAY_HINT_KIND_TYPE,
+                        });
+                    }
+                }
+            }
+        }
+        hints
+    }
 }
 
 impl Symbolicator {
@@ -1228,10 +1653,26 @@ impl Symbolicator {
                     }
                 }
                 self.lvalue_list_symbols(true, lvalues, scope, references, use_defs);
+                if !matches!(e.exp.value, UnannotatedExp_::Annotate(..)) {
+                    self.inferred_type_symbols(lvalues, use_defs);
+                }
             },
         }
     }
 
+    /// Marks variables bound without a type annotation as having an inferred type
+    fn inferred_type_symbols(&self, lvalues: &LValueList, use_defs: &mut UseDefMap) {
+        for lval in &lvalues.value {
+            if let LValue_::Var(var, _) = &lval.value {
+                if let Some(name_start) =
+                    Self::get_start_loc(&var.loc(), &self.files, &self.file_id_mapping)
+                {
+                    use_defs.mark_inferred_type(name_start.line, name_start.character);
+                }
+            }
+        }
+    }
+
     /// Get symbols for a list of lvalues
     fn lvalue_list_symbols(
         &self,
@@ -2249,6 +2690,153 @@ pub fn on_use_request(
     }
 }
 
+/// Handles prepare-rename request of the language server
+pub fn on_prepare_rename_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<TextDocumentPositionParams>(request.params.clone())
+        .expect("could not deserialize prepare-rename request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let line = parameters.position.line;
+    let col = parameters.position.character;
+
+    on_use_request(
+        context,
+        symbols,
+        &fpath,
+        line,
+        col,
+        request.id.clone(),
+        |u| {
+            if symbols.is_renamable(&fpath, u) {
+                let range = Range {
+                    start: Position {
+                        line,
+                        character: u.col_start,
+                    },
+                    end: Position {
+                        line,
+                        character: u.col_end,
+                    },
+                };
+                Some(serde_json::to_value(PrepareRenameResponse::Range(range)).unwrap())
+            } else {
+                Some(serde_json::to_value(Option::<PrepareRenameResponse>::None).unwrap())
+            }
+        },
+    );
+}
+
+/// Handles rename request of the language server
+pub fn on_rename_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<RenameParams>(request.params.clone())
+        .expect("could not deserialize rename request");
+
+    let fpath = parameters
+        .text_document_position
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let loc = parameters.text_document_position.position;
+    let new_name = parameters.new_name;
+
+    let response = if !is_valid_identifier(&new_name) {
+        lsp_server::Response::new_err(
+            request.id.clone(),
+            lsp_server::ErrorCode::InvalidParams as i32,
+            format!("'{}' is not a valid Move identifier", new_name),
+        )
+    } else {
+        match symbols.use_def_at(&fpath, loc.line, loc.character) {
+            Some(u) if symbols.is_renamable(&fpath, &u) => {
+                match symbols.broken_naming_rule(&u.def_loc, &new_name) {
+                    Some(rule) => lsp_server::Response::new_err(
+                        request.id.clone(),
+                        lsp_server::ErrorCode::InvalidParams as i32,
+                        format!("'{}' cannot be used here: {}", new_name, rule),
+                    ),
+                    None => {
+                        let edit = WorkspaceEdit::new(symbols.rename_edits(&u, &new_name));
+                        lsp_server::Response::new_ok(request.id.clone(), edit)
+                    },
+                }
+            },
+            Some(_) => lsp_server::Response::new_err(
+                request.id.clone(),
+                lsp_server::ErrorCode::InvalidRequest as i32,
+                "definitions outside of the current package cannot be renamed".to_string(),
+            ),
+            None => lsp_server::Response::new_ok(request.id.clone(), Option::<WorkspaceEdit>::None),
+        }
+    };
+
+    eprintln!("about to send rename response");
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send rename response: {:?}", err);
+    }
+}
+
+/// Handles signature help request of the language server
+pub fn on_signature_help_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<SignatureHelpParams>(request.params.clone())
+        .expect("could not deserialize signature help request");
+
+    let fpath = parameters
+        .text_document_position_params
+        .text_document
+        .uri
+        .to_file_path()
+        .unwrap();
+    let position = parameters.text_document_position_params.position;
+
+    let signature_help = context
+        .files
+        .get(&fpath)
+        .and_then(|buffer| call_site(buffer, &position))
+        .and_then(|call| {
+            let (ident_type, doc_string) = symbols.call_signature(&fpath, &call)?;
+            let signature = signature_information(&ident_type, doc_string)?;
+            Some(SignatureHelp {
+                signatures: vec![signature],
+                active_signature: Some(0),
+                active_parameter: Some(call.active_parameter),
+            })
+        });
+
+    eprintln!("about to send signature help response");
+    let response = lsp_server::Response::new_ok(request.id.clone(), signature_help);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send signature help response: {:?}", err);
+    }
+}
+
+/// Handles inlay hint request of the language server
+pub fn on_inlay_hint_request(context: &Context, request: &Request, symbols: &Symbols) {
+    let parameters = serde_json::from_value::<InlayHintParams>(request.params.clone())
+        .expect("could not deserialize inlay hint request");
+
+    let fpath = parameters.text_document.uri.to_file_path().unwrap();
+    let hints = symbols.inlay_hints(&fpath, &parameters.range);
+
+    eprintln!("about to send inlay hint response");
+    let response = lsp_server::Response::new_ok(request.id.clone(), hints);
+    if let Err(err) = context
+        .connection
+        .sender
+        .send(lsp_server::Message::Response(response))
+    {
+        eprintln!("could not send inlay hint response: {:?}", err);
+    }
+}
+
 /// Handles document symbol request of the language server
 #[allow(deprecated)]
 pub fn on_document_symbol_request(context: &Context, request: &Request, symbols: &Symbols) {
@@ -3677,3 +4265,200 @@ fn symbols_test() {
         None,
     );
 }
+
+#[test]
+/// Tests if type hints are computed for variables with inferred types only.
+fn inlay_hints_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    let lines = |start, end| Range {
+        start: Position {
+            line: start,
+            character: 0,
+        },
+        end: Position {
+            line: end,
+            character: 0,
+        },
+    };
+
+    // unannotated local
+    assert_eq!(symbols.inlay_hints(&cpath, &lines(15, 15)), vec![
+        InlayHint {
+            position: Position {
+                line: 15,
+                character: 15,
+            },
+            label: ": u64".to_string(),
+            kind: INLAY_HINT_KIND_TYPE,
+        }
+    ]);
+    // fields of an unpacked struct are not hinted
+    assert!(symbols.inlay_hints(&cpath, &lines(10, 10)).is_empty());
+    // annotated local
+    assert!(symbols.inlay_hints(&cpath, &lines(113, 113)).is_empty());
+    // local initialized with an annotated expression
+    assert!(symbols.inlay_hints(&cpath, &lines(118, 118)).is_empty());
+}
+
+#[test]
+/// Tests if renaming a local variable edits its definition and all its uses, expanding struct
+/// field shorthands.
+fn rename_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+
+    // use of a local variable in function cp
+    let u = symbols.use_def_at(&cpath, 16, 9).unwrap();
+    assert!(symbols.is_renamable(&cpath, &u));
+    let changes = symbols.rename_edits(&u, "res");
+    assert_eq!(changes.len(), 1);
+    let mut edits = changes
+        .get(&Url::from_file_path(&cpath).unwrap())
+        .unwrap()
+        .iter()
+        .map(|e| {
+            (
+                e.range.start.line,
+                e.range.start.character,
+                e.range.end.character,
+            )
+        })
+        .collect::<Vec<_>>();
+    edits.sort();
+    assert_eq!(edits, vec![(15, 12, 15), (16, 8, 11)]);
+
+    let shorthand_edits = |u: &UseDef, new_name| {
+        let mut edits = symbols
+            .rename_edits(u, new_name)
+            .remove(&Url::from_file_path(&cpath).unwrap())
+            .unwrap()
+            .into_iter()
+            .filter(|e| (136..=138).contains(&e.range.start.line))
+            .map(|e| {
+                (
+                    e.range.start.line,
+                    e.range.start.character,
+                    e.range.end.character,
+                    e.new_text,
+                )
+            })
+            .collect::<Vec<_>>();
+        edits.sort();
+        edits
+    };
+
+    // local variable bound using field shorthands (shorthand function)
+    let u = symbols.use_def_at(&cpath, 137, 9).unwrap();
+    assert_eq!(shorthand_edits(&u, "val"), vec![
+        (136, 25, 35, "some_field: val".to_string()),
+        (137, 8, 18, "val".to_string()),
+        (137, 21, 31, "val".to_string()),
+        (138, 21, 31, "some_field: val".to_string()),
+    ]);
+    // struct field used in field shorthands (shorthand function)
+    let u = symbols.use_def_at(&cpath, 136, 26).unwrap();
+    assert_eq!(shorthand_edits(&u, "fld"), vec![
+        (136, 25, 35, "fld: some_field".to_string()),
+        (138, 21, 31, "fld: some_field".to_string()),
+    ]);
+
+    // struct (SomeStruct), constant (SOME_CONST), function (cp) and local (value in cp)
+    let u = symbols.use_def_at(&cpath, 2, 12).unwrap();
+    assert!(symbols
+        .broken_naming_rule(&u.def_loc, "some_struct")
+        .is_some());
+    assert!(symbols
+        .broken_naming_rule(&u.def_loc, "OtherStruct")
+        .is_none());
+    let u = symbols.use_def_at(&cpath, 6, 10).unwrap();
+    assert!(symbols
+        .broken_naming_rule(&u.def_loc, "some_const")
+        .is_some());
+    assert!(symbols
+        .broken_naming_rule(&u.def_loc, "OTHER_CONST")
+        .is_none());
+    let u = symbols.use_def_at(&cpath, 14, 8).unwrap();
+    assert!(symbols.broken_naming_rule(&u.def_loc, "_cp").is_some());
+    assert!(symbols.broken_naming_rule(&u.def_loc, "Cp").is_none());
+    let u = symbols.use_def_at(&cpath, 14, 11).unwrap();
+    assert!(symbols.broken_naming_rule(&u.def_loc, "_value").is_none());
+
+    assert!(is_valid_identifier("res"));
+    assert!(is_valid_identifier("_res"));
+    assert!(!is_valid_identifier("_"));
+    assert!(!is_valid_identifier("1res"));
+    assert!(!is_valid_identifier("re-s"));
+    assert!(!is_valid_identifier("let"));
+}
+
+#[test]
+/// Tests if the function call surrounding the cursor and its signature are found correctly.
+fn signature_help_test() {
+    let mut path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+
+    path.push("tests/symbols");
+
+    let (symbols_opt, _) = Symbolicator::get_symbols(path.as_path()).unwrap();
+    let symbols = symbols_opt.unwrap();
+
+    let mut fpath = path.clone();
+    fpath.push("sources/M1.move");
+    let cpath = dunce::canonicalize(&fpath).unwrap();
+    let buffer = std::fs::read_to_string(&cpath).unwrap();
+
+    // second argument of M2::multi_arg(SOME_CONST, SOME_CONST)
+    let call = call_site(&buffer, &Position {
+        line: 40,
+        character: 34,
+    })
+    .unwrap();
+    assert_eq!(call, CallSite {
+        module: Some("M2".to_string()),
+        name: "multi_arg".to_string(),
+        name_start: Position {
+            line: 40,
+            character: 12,
+        },
+        active_parameter: 1,
+    });
+    let (ident_type, _) = symbols.call_signature(&cpath, &call).unwrap();
+    let signature = signature_information(&ident_type, String::new()).unwrap();
+    assert_eq!(signature.label, "fun multi_arg(p1: u64, p2: u64): u64");
+    assert_eq!(
+        signature.parameters.unwrap()[1].label,
+        ParameterLabel::LabelOffsets([23, 30])
+    );
+
+    // nested calls and type arguments
+    let call = call_site("f<u64>(g(1, 2), ", &Position {
+        line: 0,
+        character: 16,
+    })
+    .unwrap();
+    assert_eq!(call.name, "f");
+    assert_eq!(call.active_parameter, 1);
+    // not in an argument list
+    assert!(call_site("if (x) { y", &Position {
+        line: 0,
+        character: 10,
+    })
+    .is_none());
+}
@@ -133,4 +133,10 @@ module Symbols::M1 {
         }
     }
 
+    fun shorthand(s: SomeStruct): SomeStruct {
+        let SomeStruct { some_field } = s;
+        some_field = some_field + 1;
+        SomeStruct { some_field }
+    }
+
 }
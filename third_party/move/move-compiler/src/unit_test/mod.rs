@@ -34,7 +34,11 @@ pub struct ModuleTestPlan {
 #[derive(Debug, Clone)]
 pub struct TestCase {
     pub test_name: TestName,
+    /// The arguments assigned in the `#[test]` attribute
     pub arguments: Vec<MoveValue>,
+    /// The positions of the parameters which are not assigned in the `#[test]` attribute. Their
+    /// arguments are generated for each case of a property-based test.
+    pub generated_parameters: Vec<usize>,
     pub expected_failure: Option<ExpectedFailure>,
 }
 
@@ -73,6 +77,31 @@ impl ModuleTestPlan {
     }
 }
 
+impl TestCase {
+    /// Whether this is a property-based test, i.e., some of its arguments are generated
+    pub fn is_property_test(&self) -> bool {
+        !self.generated_parameters.is_empty()
+    }
+
+    /// Returns the arguments of the test, with the generated arguments (in the order of the
+    /// generated parameters) placed among the assigned ones.
+    pub fn arguments_with_generated(&self, generated: Vec<MoveValue>) -> Vec<MoveValue> {
+        assert_eq!(generated.len(), self.generated_parameters.len());
+        let num_params = self.arguments.len() + generated.len();
+        let mut assigned = self.arguments.iter().cloned();
+        let mut generated = generated.into_iter();
+        (0..num_params)
+            .map(|idx| {
+                if self.generated_parameters.contains(&idx) {
+                    generated.next().unwrap()
+                } else {
+                    assigned.next().unwrap()
+                }
+            })
+            .collect()
+    }
+}
+
 impl TestPlan {
     pub fn new(
         tests: Vec<ModuleTestPlan>,
@@ -8,6 +8,8 @@ use crate::{
     expansion::ast::{
         self as E, Address, Attribute, AttributeValue, ModuleAccess_, ModuleIdent, ModuleIdent_,
     },
+    hlir::ast as H,
+    naming::ast as N,
     parser::ast::ConstantName,
     shared::{
         known_attributes::{AttributeKind, KnownAttribute, TestingAttribute},
@@ -159,9 +161,15 @@ fn build_test_info<'func>(
 
     let test_annotation_params = parse_test_attribute(context, test_attribute, 0);
     let mut arguments = Vec::new();
-    for (var, _) in &function.signature.parameters {
+    let mut generated_parameters = Vec::new();
+    for (idx, (var, ty)) in function.signature.parameters.iter().enumerate() {
         match test_annotation_params.get(&var.value()) {
             Some(value) => arguments.push(value.clone()),
+            // Arguments of parameters not assigned in the attribute are generated, which makes
+            // the test a property-based test
+            None if function.signature.type_parameters.is_empty() && is_generatable(ty) => {
+                generated_parameters.push(idx)
+            },
             None => {
                 let missing_param_msg = "Missing test parameter assignment in test. Expected a \
                                          parameter to be assigned in this attribute";
@@ -183,10 +191,35 @@ fn build_test_info<'func>(
     Some(TestCase {
         test_name: fn_name.to_string(),
         arguments,
+        generated_parameters,
         expected_failure,
     })
 }
 
+// Whether arguments of the type can be generated for a property-based test. The fields of
+// structs are only checked when the test is run.
+fn is_generatable(sp!(_, ty): &H::SingleType) -> bool {
+    match ty {
+        H::SingleType_::Base(base_type) => is_generatable_base(base_type),
+        H::SingleType_::Ref(_, _) => false,
+    }
+}
+
+fn is_generatable_base(sp!(_, ty): &H::BaseType) -> bool {
+    match ty {
+        H::BaseType_::Apply(_, sp!(_, H::TypeName_::Builtin(sp!(_, builtin))), ty_args) => {
+            !matches!(
+                builtin,
+                N::BuiltinTypeName_::Signer | N::BuiltinTypeName_::Fun
+            ) && ty_args.iter().all(is_generatable_base)
+        },
+        H::BaseType_::Apply(_, sp!(_, H::TypeName_::ModuleType(_, _)), ty_args) => {
+            ty_args.iter().all(is_generatable_base)
+        },
+        H::BaseType_::Param(_) | H::BaseType_::Unreachable | H::BaseType_::UnresolvedError => false,
+    }
+}
+
 //***************************************************************************
 // Attribute parsers
 //***************************************************************************
@@ -61,7 +61,7 @@ fn native_print(
             single_line,
             include_int_types,
         )?;
-        println!("{}", out);
+        move_vm_runtime::debug_output::print_debug_output(&out);
     }
 
     Ok(NativeResult::ok(gas_params.base_cost, smallvec![]))
@@ -103,7 +103,7 @@ fn native_print_stack_trace(
     {
         let mut s = String::new();
         context.print_stack_trace(&mut s)?;
-        println!("{}", s);
+        move_vm_runtime::debug_output::print_debug_output(&s);
     }
 
     Ok(NativeResult::ok(gas_params.base_cost, smallvec![]))
@@ -0,0 +1,46 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Output of the debugging natives (e.g., `std::debug::print`). It is printed to stdout, unless
+//! captured on the executing thread, e.g., to report it with the result of a unit test.
+
+use std::cell::RefCell;
+
+thread_local! {
+    static CAPTURED_OUTPUT: RefCell<Option<String>> = RefCell::new(None);
+}
+
+/// Prints a line of debug output, or appends it to the output captured on this thread
+pub fn print_debug_output(line: &str) {
+    CAPTURED_OUTPUT.with(|captured| match &mut *captured.borrow_mut() {
+        Some(output) => {
+            output.push_str(line);
+            output.push('\n');
+        },
+        None => println!("{}", line),
+    })
+}
+
+/// Runs `f`, and returns the debug output printed on this thread while it ran
+pub fn capture_debug_output<R>(f: impl FnOnce() -> R) -> (R, String) {
+    let previous = CAPTURED_OUTPUT.with(|captured| captured.replace(Some(String::new())));
+    let result = f();
+    let output = CAPTURED_OUTPUT.with(|captured| captured.replace(previous));
+    (result, output.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capture_debug_output() {
+        let ((), output) = capture_debug_output(|| {
+            print_debug_output("outer");
+            let ((), inner_output) = capture_debug_output(|| print_debug_output("inner"));
+            assert_eq!(inner_output, "inner\n");
+            print_debug_output("outer again");
+        });
+        assert_eq!(output, "outer\nouter again\n");
+    }
+}
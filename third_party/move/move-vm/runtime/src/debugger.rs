@@ -0,0 +1,89 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Hooks for driving the interpreter from an external debugger (e.g., a Debug Adapter
+//! Protocol server). Unlike the stdin-driven debugger in `debug.rs`, the debugger is
+//! installed programmatically and is notified before every instruction is executed.
+
+use move_binary_format::file_format::FunctionDefinitionIndex;
+use move_core_types::language_storage::ModuleId;
+use once_cell::sync::Lazy;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+/// The position of a frame on the call stack
+#[derive(Clone, Debug)]
+pub struct DebugLocation<'a> {
+    /// The module of the function, if the function is not a script
+    pub module_id: Option<&'a ModuleId>,
+    /// The name of the function
+    pub function_name: &'a str,
+    /// The index of the function definition in the module (or script)
+    pub function_index: FunctionDefinitionIndex,
+    /// The code offset of the instruction being executed
+    pub pc: u16,
+}
+
+/// A frame on the call stack, with the values of its locals
+#[derive(Clone, Debug)]
+pub struct DebugFrame {
+    pub module_id: Option<ModuleId>,
+    pub function_name: String,
+    pub function_index: FunctionDefinitionIndex,
+    pub pc: u16,
+    /// The (formatted) values of the locals. Uninitialized locals are printed as `-`.
+    pub locals: Vec<String>,
+}
+
+/// The full state of the interpreter, used to inspect a paused execution
+#[derive(Clone, Debug)]
+pub struct DebugState {
+    /// The frames of the call stack, from the outermost to the current one
+    pub frames: Vec<DebugFrame>,
+    /// The (formatted) values on the operand stack, from the bottom to the top
+    pub operand_stack: Vec<String>,
+}
+
+/// A debugger that is notified before every instruction executed by the interpreter
+pub trait Debugger: Send {
+    /// Called before executing the instruction at the top of the call stack. The call
+    /// stack is ordered from the outermost frame to the current one. Execution stays
+    /// paused for as long as this call blocks. As building the full state is expensive,
+    /// `inspect` should only be called when pausing execution.
+    fn on_instruction(
+        &mut self,
+        call_stack: &[DebugLocation<'_>],
+        inspect: &dyn Fn() -> DebugState,
+    );
+}
+
+static DEBUGGER_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+static DEBUGGER: Lazy<Mutex<Option<Box<dyn Debugger>>>> = Lazy::new(|| Mutex::new(None));
+
+/// Installs the debugger, replacing any previously installed debugger. Note that the
+/// debugger is global, so it should only be installed when code is executed on a
+/// single thread.
+pub fn set_debugger(debugger: Box<dyn Debugger>) {
+    *DEBUGGER.lock().unwrap() = Some(debugger);
+    DEBUGGER_INSTALLED.store(true, Ordering::Release);
+}
+
+/// Removes the installed debugger (if any)
+pub fn clear_debugger() {
+    DEBUGGER_INSTALLED.store(false, Ordering::Release);
+    *DEBUGGER.lock().unwrap() = None;
+}
+
+pub(crate) fn debugger_installed() -> bool {
+    DEBUGGER_INSTALLED.load(Ordering::Acquire)
+}
+
+/// Notifies the installed debugger (if any) of the instruction about to be executed
+pub(crate) fn notify_debugger(call_stack: &[DebugLocation<'_>], inspect: &dyn Fn() -> DebugState) {
+    if let Some(debugger) = DEBUGGER.lock().unwrap().as_mut() {
+        debugger.on_instruction(call_stack, inspect);
+    }
+}
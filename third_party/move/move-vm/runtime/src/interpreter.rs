@@ -2,6 +2,8 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
+#[cfg(any(debug_assertions, feature = "debugging"))]
+use crate::debugger::{DebugFrame, DebugLocation, DebugState};
 use crate::{
     data_cache::TransactionDataCache,
     loader::{Function, Loader, Resolver},
@@ -792,6 +794,72 @@ impl Interpreter {
         Ok(())
     }
 
+    /// Returns the positions of the frames on the call stack, from the outermost frame to the
+    /// current one.
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn debug_call_stack<'a>(
+        &'a self,
+        current_function: &'a Function,
+        pc: u16,
+    ) -> Vec<DebugLocation<'a>> {
+        self.call_stack
+            .0
+            .iter()
+            .map(|frame| (frame.function.as_ref(), frame.pc))
+            .chain(std::iter::once((current_function, pc)))
+            .map(|(function, pc)| DebugLocation {
+                module_id: function.module_id(),
+                function_name: function.name(),
+                function_index: function.index(),
+                pc,
+            })
+            .collect()
+    }
+
+    /// Returns the call stack, with the values of the locals of each frame, and the values on
+    /// the operand stack.
+    #[cfg(any(debug_assertions, feature = "debugging"))]
+    pub(crate) fn debug_state(
+        &self,
+        current_function: &Function,
+        current_locals: &Locals,
+        pc: u16,
+    ) -> DebugState {
+        let debug_string = |print: &dyn Fn(&mut String) -> PartialVMResult<()>| {
+            let mut s = String::new();
+            match print(&mut s) {
+                Ok(()) => s,
+                Err(err) => format!("<{:?}>", err),
+            }
+        };
+        let frames = self
+            .call_stack
+            .0
+            .iter()
+            .map(|frame| (frame.function.as_ref(), &frame.locals, frame.pc))
+            .chain(std::iter::once((current_function, current_locals, pc)))
+            .map(|(function, locals, pc)| DebugFrame {
+                module_id: function.module_id().cloned(),
+                function_name: function.name().to_string(),
+                function_index: function.index(),
+                pc,
+                locals: (0..function.local_count())
+                    .map(|idx| debug_string(&|s| values::debug::print_local(s, locals, idx)))
+                    .collect(),
+            })
+            .collect();
+        let operand_stack = self
+            .operand_stack
+            .value
+            .iter()
+            .map(|val| debug_string(&|s| values::debug::print_value(s, val)))
+            .collect();
+        DebugState {
+            frames,
+            operand_stack,
+        }
+    }
+
     /// Generate a string which is the status of the interpreter: call stack, current bytecode
     /// stream, locals and operand stack.
     ///
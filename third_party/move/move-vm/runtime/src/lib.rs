@@ -11,6 +11,7 @@
 //! soon.
 
 pub mod data_cache;
+pub mod debug_output;
 mod interpreter;
 mod loader;
 pub mod logging;
@@ -26,6 +27,8 @@ pub mod config;
 // Only include debugging functionality in debug builds
 #[cfg(any(debug_assertions, feature = "debugging"))]
 mod debug;
+#[cfg(any(debug_assertions, feature = "debugging"))]
+pub mod debugger;
 
 #[cfg(test)]
 mod unit_tests;
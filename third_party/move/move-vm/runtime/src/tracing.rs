@@ -3,7 +3,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
 #[cfg(any(debug_assertions, feature = "debugging"))]
-use crate::debug::DebugContext;
+use crate::{debug::DebugContext, debugger};
 #[cfg(any(debug_assertions, feature = "debugging"))]
 use crate::{
     interpreter::Interpreter,
@@ -78,7 +78,12 @@ pub(crate) fn trace(
     if *TRACING_ENABLED {
         let buf_writer = &mut *LOGGING_FILE_WRITER.lock().unwrap();
         buf_writer
-            .write_fmt(format_args!("{},{}\n", function_desc.pretty_string(), pc,))
+            .write_fmt(format_args!(
+                "{},{},{:?}\n",
+                function_desc.pretty_string(),
+                pc,
+                std::thread::current().id()
+            ))
             .unwrap();
         if *SINGLE_STEP_FLUSHING {
             buf_writer.flush().unwrap();
@@ -90,6 +95,11 @@ pub(crate) fn trace(
             .unwrap()
             .debug_loop(function_desc, locals, pc, instr, loader, interp);
     }
+    if debugger::debugger_installed() {
+        debugger::notify_debugger(&interp.debug_call_stack(function_desc, pc), &|| {
+            interp.debug_state(function_desc, locals, pc)
+        });
+    }
 }
 
 #[macro_export]
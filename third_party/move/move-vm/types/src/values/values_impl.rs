@@ -2888,6 +2888,16 @@ pub mod debug {
     pub fn print_value<B: Write>(buf: &mut B, val: &Value) -> PartialVMResult<()> {
         print_value_impl(buf, &val.0)
     }
+
+    pub fn print_local<B: Write>(buf: &mut B, locals: &Locals, idx: usize) -> PartialVMResult<()> {
+        match locals.0.borrow().get(idx) {
+            Some(val) => print_value_impl(buf, val),
+            None => Err(
+                PartialVMError::new(StatusCode::VERIFIER_INVARIANT_VIOLATION)
+                    .with_message(format!("local index out of bounds: {}", idx)),
+            ),
+        }
+    }
 }
 
 /***************************************************************************************
@@ -2,7 +2,11 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use super::reroot_path;
-use crate::{base::test_validation, NativeFunctionRecord};
+use crate::{
+    base::test_validation,
+    debug_adapter::{DebugAdapter, SourceIndex},
+    NativeFunctionRecord,
+};
 use anyhow::{bail, Result};
 use clap::*;
 use codespan_reporting::term::{termcolor, termcolor::StandardStream};
@@ -19,7 +23,7 @@ use move_package::{
     compilation::{build_plan::BuildPlan, compiled_package::build_and_report_v2_driver},
     BuildConfig,
 };
-use move_unit_test::UnitTestingConfig;
+use move_unit_test::{property_test::DEFAULT_NUM_FUZZ_CASES, TestReportFormat, UnitTestingConfig};
 use move_vm_runtime::tracing::{LOGGING_FILE_WRITER, TRACING_ENABLED};
 use move_vm_test_utils::gas_schedule::CostTable;
 // if unix
@@ -88,6 +92,32 @@ pub struct Test {
     #[cfg(feature = "evm-backend")]
     #[clap(long = "evm")]
     pub evm: bool,
+
+    /// Run the tests under a Debug Adapter Protocol server listening on this port. The tests
+    /// start once a debugger (e.g., an editor) attaches, and are run on a single thread.
+    #[clap(long = "debug-adapter-port")]
+    pub debug_adapter_port: Option<u16>,
+
+    /// Format of the test report. The JSON and JUnit reports are written to the report file.
+    #[clap(long = "format", value_enum, default_value = "human")]
+    pub report_format: TestReportFormat,
+    /// Write the JSON or JUnit test report to this file. Required by these formats.
+    #[clap(
+        long = "report-file",
+        required_if_eq_any = [("report_format", "json"), ("report_format", "junit")]
+    )]
+    pub report_file: Option<PathBuf>,
+
+    /// Fuzz the property-based tests, i.e., the tests with parameters which are not assigned in
+    /// the `#[test]` attribute, with randomly seeded arguments
+    #[clap(long = "fuzz")]
+    pub fuzz: bool,
+    /// Number of cases each property-based test is run with when fuzzing
+    #[clap(long = "fuzz-cases", default_value_t = DEFAULT_NUM_FUZZ_CASES)]
+    pub fuzz_cases: u64,
+    /// Seed the arguments of property-based tests are generated from
+    #[clap(long = "fuzz-seed")]
+    pub fuzz_seed: Option<u64>,
 }
 
 impl Test {
@@ -112,6 +142,12 @@ impl Test {
             compute_coverage,
             #[cfg(feature = "evm-backend")]
             evm,
+            debug_adapter_port,
+            report_format,
+            report_file,
+            fuzz,
+            fuzz_cases,
+            fuzz_seed,
         } = self;
         let unit_test_config = UnitTestingConfig {
             gas_limit,
@@ -123,20 +159,36 @@ impl Test {
             check_stackless_vm,
             verbose: verbose_mode,
             ignore_compile_warnings,
+            report_format,
+            report_file,
+            fuzz,
+            fuzz_cases,
+            fuzz_seed,
             #[cfg(feature = "evm-backend")]
             evm,
 
             ..UnitTestingConfig::default_with_bound(None)
         };
-        let result = run_move_unit_tests(
-            &rerooted_path,
-            config,
-            unit_test_config,
-            natives,
-            cost_table,
-            compute_coverage,
-            &mut std::io::stdout(),
-        )?;
+        let result = match debug_adapter_port {
+            Some(debug_adapter_port) => debug_move_unit_tests(
+                &rerooted_path,
+                config,
+                unit_test_config,
+                natives,
+                cost_table,
+                debug_adapter_port,
+                &mut std::io::stdout(),
+            )?,
+            None => run_move_unit_tests(
+                &rerooted_path,
+                config,
+                unit_test_config,
+                natives,
+                cost_table,
+                compute_coverage,
+                &mut std::io::stdout(),
+            )?,
+        };
 
         // Return a non-zero exit code if any test failed
         if let UnitTestResult::Failure = result {
@@ -154,12 +206,58 @@ pub enum UnitTestResult {
 }
 
 pub fn run_move_unit_tests<W: Write + Send>(
+    pkg_path: &Path,
+    build_config: move_package::BuildConfig,
+    unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    cost_table: Option<CostTable>,
+    compute_coverage: bool,
+    writer: &mut W,
+) -> Result<UnitTestResult> {
+    run_move_unit_tests_impl(
+        pkg_path,
+        build_config,
+        unit_test_config,
+        natives,
+        cost_table,
+        compute_coverage,
+        None,
+        writer,
+    )
+}
+
+/// Runs the Move unit tests under a Debug Adapter Protocol server listening on the given port.
+/// The tests start once a debugger attaches, and are run on a single thread.
+pub fn debug_move_unit_tests<W: Write + Send>(
+    pkg_path: &Path,
+    build_config: move_package::BuildConfig,
+    unit_test_config: UnitTestingConfig,
+    natives: Vec<NativeFunctionRecord>,
+    cost_table: Option<CostTable>,
+    debug_adapter_port: u16,
+    writer: &mut W,
+) -> Result<UnitTestResult> {
+    run_move_unit_tests_impl(
+        pkg_path,
+        build_config,
+        unit_test_config,
+        natives,
+        cost_table,
+        false,
+        Some(debug_adapter_port),
+        writer,
+    )
+}
+
+#[allow(clippy::too_many_arguments)]
+fn run_move_unit_tests_impl<W: Write + Send>(
     pkg_path: &Path,
     mut build_config: move_package::BuildConfig,
     mut unit_test_config: UnitTestingConfig,
     natives: Vec<NativeFunctionRecord>,
     cost_table: Option<CostTable>,
     compute_coverage: bool,
+    debug_adapter_port: Option<u16>,
     writer: &mut W,
 ) -> Result<UnitTestResult> {
     let mut test_plan = None;
@@ -331,11 +429,27 @@ pub fn run_move_unit_tests<W: Write + Send>(
 
     // Run the tests. If any of the tests fail, then we don't produce a coverage report, so cleanup
     // the trace files.
-    if !unit_test_config
-        .run_and_report_unit_tests(test_plan, Some(natives), cost_table, writer)
-        .unwrap()
-        .1
-    {
+    let (_, all_tests_passed) = match debug_adapter_port {
+        Some(debug_adapter_port) => {
+            // The VM debugger is global, so the tests must be run one at a time
+            unit_test_config.num_threads = 1;
+            let debug_adapter =
+                DebugAdapter::listen(debug_adapter_port, SourceIndex::from_test_plan(&test_plan))?;
+            debug_adapter.run(|| {
+                unit_test_config.run_and_report_unit_tests(
+                    test_plan,
+                    Some(natives),
+                    cost_table,
+                    writer,
+                )
+            })
+        },
+        None => {
+            unit_test_config.run_and_report_unit_tests(test_plan, Some(natives), cost_table, writer)
+        },
+    }
+    .unwrap();
+    if !all_tests_passed {
         cleanup_trace();
         return Ok(UnitTestResult::Failure);
     }
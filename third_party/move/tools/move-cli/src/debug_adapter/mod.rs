@@ -0,0 +1,590 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! A Debug Adapter Protocol (DAP) server for the Move VM.
+//!
+//! The server listens on a TCP port for an editor to attach, and then drives the interpreter
+//! (through `move_vm_runtime::debugger`) with source-level breakpoints, step in/over/out,
+//! and inspection of the locals and the operand stack. Instructions are mapped to source lines
+//! through the source maps of the modules (see `SourceIndex`).
+//!
+//! As the VM debugger is global, the code being debugged must be executed on a single thread.
+
+mod protocol;
+mod source_index;
+
+use self::{
+    protocol::{read_request, MessageWriter, Request},
+    source_index::SourceLine,
+};
+use anyhow::{Context, Result};
+use move_vm_runtime::debugger::{self, DebugLocation, DebugState, Debugger};
+use serde_json::{json, Value};
+pub use source_index::SourceIndex;
+use std::{
+    collections::BTreeSet,
+    io::BufReader,
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    thread,
+};
+
+/// The id of the (only) thread reported to the client
+const THREAD_ID: u64 = 1;
+
+/// How execution proceeds until it pauses next
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum RunMode {
+    /// Run until a breakpoint is hit
+    Continue,
+    /// Pause at the next instruction
+    Pause,
+    /// Pause at the next line, in any frame
+    StepIn,
+    /// Pause at the next line in the frame at the given depth (or a caller)
+    StepOver(usize),
+    /// Pause at the next line in a caller of the frame at the given depth
+    StepOut(usize),
+}
+
+/// The state shared by the configuration phase and the execution phase of a session
+struct Session {
+    writer: Arc<MessageWriter<TcpStream>>,
+    requests: Receiver<Request>,
+    source_index: SourceIndex,
+    breakpoints: BTreeSet<SourceLine>,
+    /// Whether the client has disconnected (or detached), in which case the code runs freely
+    detached: bool,
+}
+
+impl Session {
+    /// Handles the requests that are valid at any time. Returns false if the request
+    /// isn't one of them.
+    fn handle_common_request(&mut self, request: &Request) -> bool {
+        match request.command.as_str() {
+            "setBreakpoints" => self.set_breakpoints(request),
+            "setExceptionBreakpoints" => self.writer.respond(request, json!({})),
+            "threads" => self.writer.respond(
+                request,
+                json!({ "threads": [{ "id": THREAD_ID, "name": "main" }] }),
+            ),
+            "disconnect" => {
+                self.writer.respond(request, json!({}));
+                self.detached = true;
+            },
+            _ => return false,
+        }
+        true
+    }
+
+    /// Replaces the breakpoints of the source file in the request. Breakpoints on lines
+    /// without instructions move to the next line with instructions.
+    fn set_breakpoints(&mut self, request: &Request) {
+        let path = match request.arguments["source"]["path"].as_str() {
+            Some(path) => Path::new(path),
+            None => return self.writer.respond_error(request, "Missing source path"),
+        };
+        if let Some(file) = self.source_index.file_of(path) {
+            self.breakpoints
+                .retain(|(breakpoint_file, _)| *breakpoint_file != file);
+        }
+
+        let mut breakpoints = vec![];
+        for breakpoint in request.arguments["breakpoints"]
+            .as_array()
+            .into_iter()
+            .flatten()
+        {
+            let line = breakpoint["line"].as_u64().unwrap_or_default() as u32;
+            match self.source_index.resolve_breakpoint(path, line) {
+                Some(source_line) => {
+                    self.breakpoints.insert(source_line);
+                    breakpoints.push(json!({ "verified": true, "line": source_line.1 }));
+                },
+                None => breakpoints.push(json!({
+                    "verified": false,
+                    "line": line,
+                    "message": "No Move code at this line",
+                })),
+            }
+        }
+        self.writer
+            .respond(request, json!({ "breakpoints": breakpoints }));
+    }
+}
+
+/// A DAP session with an attached client, ready to debug code
+pub struct DebugAdapter {
+    session: Session,
+    stop_on_entry: bool,
+}
+
+impl DebugAdapter {
+    /// Waits for a client to attach on the given port of the local host, and handles its
+    /// configuration requests (e.g., the initial breakpoints) until the client is done.
+    pub fn listen(port: u16, source_index: SourceIndex) -> Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port))
+            .with_context(|| format!("Failed to listen for a debugger on port {}", port))?;
+        eprintln!(
+            "Waiting for a debugger to attach on {}...",
+            listener.local_addr()?
+        );
+        let (stream, _) = listener.accept()?;
+        let writer = Arc::new(MessageWriter::new(stream.try_clone()?));
+
+        // Requests are read on a separate thread, so that execution can poll for them
+        let (sender, requests) = mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stream);
+            while let Ok(Some(request)) = read_request(&mut reader) {
+                if sender.send(request).is_err() {
+                    break;
+                }
+            }
+        });
+
+        let mut adapter = Self {
+            session: Session {
+                writer,
+                requests,
+                source_index,
+                breakpoints: BTreeSet::new(),
+                detached: false,
+            },
+            stop_on_entry: false,
+        };
+        adapter.configure();
+        Ok(adapter)
+    }
+
+    fn configure(&mut self) {
+        let session = &mut self.session;
+        while !session.detached {
+            let request = match session.requests.recv() {
+                Ok(request) => request,
+                Err(_) => {
+                    session.detached = true;
+                    break;
+                },
+            };
+            match request.command.as_str() {
+                "initialize" => {
+                    session.writer.respond(
+                        &request,
+                        json!({ "supportsConfigurationDoneRequest": true }),
+                    );
+                    session.writer.send_event("initialized", json!({}));
+                },
+                "launch" | "attach" => {
+                    self.stop_on_entry = request.arguments["stopOnEntry"]
+                        .as_bool()
+                        .unwrap_or_default();
+                    session.writer.respond(&request, json!({}));
+                },
+                "configurationDone" => {
+                    session.writer.respond(&request, json!({}));
+                    break;
+                },
+                _ => {
+                    if !session.handle_common_request(&request) {
+                        session.writer.respond_error(
+                            &request,
+                            format!("Unsupported request before execution: {}", request.command),
+                        );
+                    }
+                },
+            }
+        }
+    }
+
+    /// Installs the debugger in the VM, so that the code executed from now on is debugged
+    /// (until the session is finished).
+    pub fn start(self) -> RunningDebugAdapter {
+        let writer = self.session.writer.clone();
+        debugger::set_debugger(Box::new(DapDebugger {
+            session: self.session,
+            mode: if self.stop_on_entry {
+                RunMode::Pause
+            } else {
+                RunMode::Continue
+            },
+            last_line: None,
+        }));
+        RunningDebugAdapter { writer }
+    }
+
+    /// Executes the code under the debugger, and finishes the session once it is done
+    pub fn run<T>(self, execute: impl FnOnce() -> T) -> T {
+        let running = self.start();
+        let result = execute();
+        running.finish();
+        result
+    }
+}
+
+/// A DAP session whose debugger is installed in the VM
+pub struct RunningDebugAdapter {
+    writer: Arc<MessageWriter<TcpStream>>,
+}
+
+impl RunningDebugAdapter {
+    /// Removes the debugger from the VM, and notifies the client that the debugged code
+    /// has terminated
+    pub fn finish(self) {
+        debugger::clear_debugger();
+        self.writer.send_event("terminated", json!({}));
+        self.writer.send_event("exited", json!({ "exitCode": 0 }));
+    }
+}
+
+/// The VM debugger, pausing execution at breakpoints and steps to serve the client
+struct DapDebugger {
+    session: Session,
+    mode: RunMode,
+    /// The depth of the call stack and the source line of the last executed instruction
+    last_line: Option<(usize, SourceLine)>,
+}
+
+impl DapDebugger {
+    /// Handles the requests sent while the code is running
+    fn handle_running_requests(&mut self) {
+        loop {
+            let request = match self.session.requests.try_recv() {
+                Ok(request) => request,
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => {
+                    self.session.detached = true;
+                    return;
+                },
+            };
+            if request.command == "pause" {
+                self.mode = RunMode::Pause;
+                self.session.writer.respond(&request, json!({}));
+            } else if !self.session.handle_common_request(&request) {
+                self.session
+                    .writer
+                    .respond_error(&request, "Execution isn't paused");
+            }
+        }
+    }
+
+    /// Returns the reason to pause before the instruction (if any)
+    fn pause_reason(&self, depth: usize, line: Option<SourceLine>) -> Option<&'static str> {
+        if self.mode == RunMode::Pause {
+            return Some("pause");
+        }
+        let line = line?;
+        if self.last_line == Some((depth, line)) {
+            return None;
+        }
+        match self.mode {
+            RunMode::StepIn => return Some("step"),
+            RunMode::StepOver(step_depth) if depth <= step_depth => return Some("step"),
+            RunMode::StepOut(step_depth) if depth < step_depth => return Some("step"),
+            _ => {},
+        }
+        if self.session.breakpoints.contains(&line) {
+            return Some("breakpoint");
+        }
+        None
+    }
+
+    /// Serves the client while paused, until it resumes execution
+    fn pause(&mut self, reason: &str, depth: usize, inspect: &dyn Fn() -> DebugState) {
+        let state = inspect();
+        self.session.writer.send_event(
+            "stopped",
+            json!({ "reason": reason, "threadId": THREAD_ID, "allThreadsStopped": true }),
+        );
+
+        while !self.session.detached {
+            let request = match self.session.requests.recv() {
+                Ok(request) => request,
+                Err(_) => {
+                    self.session.detached = true;
+                    break;
+                },
+            };
+            let mode = match request.command.as_str() {
+                "continue" => RunMode::Continue,
+                "next" => RunMode::StepOver(depth),
+                "stepIn" => RunMode::StepIn,
+                "stepOut" => RunMode::StepOut(depth),
+                "stackTrace" => {
+                    let body = self.stack_trace(&state);
+                    self.session.writer.respond(&request, body);
+                    continue;
+                },
+                "scopes" => {
+                    let body = scopes(&request, &state);
+                    self.session.writer.respond(&request, body);
+                    continue;
+                },
+                "variables" => {
+                    let body = self.variables(&request, &state);
+                    self.session.writer.respond(&request, body);
+                    continue;
+                },
+                "pause" => {
+                    self.session.writer.respond(&request, json!({}));
+                    continue;
+                },
+                _ => {
+                    if !self.session.handle_common_request(&request) {
+                        self.session.writer.respond_error(
+                            &request,
+                            format!("Unsupported request: {}", request.command),
+                        );
+                    }
+                    continue;
+                },
+            };
+            self.mode = mode;
+            self.session
+                .writer
+                .respond(&request, json!({ "allThreadsContinued": true }));
+            break;
+        }
+    }
+
+    /// Lists the frames of the call stack, from the current one to the outermost
+    fn stack_trace(&self, state: &DebugState) -> Value {
+        let stack_frames: Vec<_> = state
+            .frames
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(frame_id, frame)| {
+                let name = match &frame.module_id {
+                    Some(module_id) => format!(
+                        "0x{}::{}::{}",
+                        module_id.address().short_str_lossless(),
+                        module_id.name(),
+                        frame.function_name
+                    ),
+                    None => frame.function_name.clone(),
+                };
+                let mut stack_frame = json!({
+                    "id": frame_id,
+                    "name": name,
+                    "line": 0,
+                    "column": 0,
+                });
+                if let Some((file, line)) = self.session.source_index.source_line(
+                    frame.module_id.as_ref(),
+                    frame.function_index,
+                    frame.pc,
+                ) {
+                    let path = self.session.source_index.file_path(file);
+                    stack_frame["source"] = json!({
+                        "name": path.file_name().map(|name| name.to_string_lossy()),
+                        "path": path,
+                    });
+                    stack_frame["line"] = json!(line);
+                    stack_frame["column"] = json!(1);
+                }
+                stack_frame
+            })
+            .collect();
+        json!({ "stackFrames": stack_frames, "totalFrames": state.frames.len() })
+    }
+
+    /// Lists the locals of a frame, or the values on the operand stack
+    fn variables(&self, request: &Request, state: &DebugState) -> Value {
+        let reference = request.arguments["variablesReference"]
+            .as_u64()
+            .unwrap_or_default() as usize;
+        let variables: Vec<_> = match variables_of_reference(reference) {
+            Some(Variables::Locals(frame_id)) => match state.frames.get(frame_id) {
+                Some(frame) => frame
+                    .locals
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, value)| {
+                        let name = self
+                            .session
+                            .source_index
+                            .local_name(frame.module_id.as_ref(), frame.function_index, idx)
+                            .map(|name| name.to_string())
+                            .unwrap_or_else(|| format!("local#{}", idx));
+                        json!({ "name": name, "value": value, "variablesReference": 0 })
+                    })
+                    .collect(),
+                None => vec![],
+            },
+            Some(Variables::OperandStack) => state
+                .operand_stack
+                .iter()
+                .enumerate()
+                .rev()
+                .map(|(idx, value)| {
+                    json!({ "name": format!("[{}]", idx), "value": value, "variablesReference": 0 })
+                })
+                .collect(),
+            None => vec![],
+        };
+        json!({ "variables": variables })
+    }
+}
+
+impl Debugger for DapDebugger {
+    fn on_instruction(
+        &mut self,
+        call_stack: &[DebugLocation<'_>],
+        inspect: &dyn Fn() -> DebugState,
+    ) {
+        self.handle_running_requests();
+        if self.session.detached {
+            return;
+        }
+
+        let depth = call_stack.len();
+        let line = call_stack.last().and_then(|location| {
+            self.session.source_index.source_line(
+                location.module_id,
+                location.function_index,
+                location.pc,
+            )
+        });
+        if let Some(reason) = self.pause_reason(depth, line) {
+            self.pause(reason, depth, inspect);
+        }
+        if let Some(line) = line {
+            self.last_line = Some((depth, line));
+        }
+    }
+}
+
+/// The variables a variables reference refers to. References 0 is reserved by the protocol.
+enum Variables {
+    Locals(usize),
+    OperandStack,
+}
+
+const OPERAND_STACK_REFERENCE: usize = 1;
+
+fn variables_of_reference(reference: usize) -> Option<Variables> {
+    match reference {
+        0 => None,
+        OPERAND_STACK_REFERENCE => Some(Variables::OperandStack),
+        _ => Some(Variables::Locals(reference - OPERAND_STACK_REFERENCE - 1)),
+    }
+}
+
+/// Lists the scopes of a frame: its locals, and the operand stack for the current frame
+fn scopes(request: &Request, state: &DebugState) -> Value {
+    let frame_id = request.arguments["frameId"].as_u64().unwrap_or_default() as usize;
+    let mut scopes = vec![json!({
+        "name": "Locals",
+        "variablesReference": frame_id + OPERAND_STACK_REFERENCE + 1,
+        "expensive": false,
+    })];
+    if frame_id + 1 == state.frames.len() {
+        scopes.push(json!({
+            "name": "Operand Stack",
+            "variablesReference": OPERAND_STACK_REFERENCE,
+            "expensive": false,
+        }));
+    }
+    json!({ "scopes": scopes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates a debugger in the given mode, with a client connected over a local socket
+    fn create_debugger(
+        mode: RunMode,
+        last_line: Option<(usize, SourceLine)>,
+        breakpoints: &[SourceLine],
+    ) -> (DapDebugger, TcpStream) {
+        let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0)).unwrap();
+        let client = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        let (stream, _) = listener.accept().unwrap();
+        let (_, requests) = mpsc::channel();
+        let debugger = DapDebugger {
+            session: Session {
+                writer: Arc::new(MessageWriter::new(stream)),
+                requests,
+                source_index: SourceIndex::default(),
+                breakpoints: breakpoints.iter().copied().collect(),
+                detached: false,
+            },
+            mode,
+            last_line,
+        };
+        (debugger, client)
+    }
+
+    #[test]
+    fn test_pause_reason_pause() {
+        let (debugger, _client) = create_debugger(RunMode::Pause, Some((1, (0, 3))), &[]);
+
+        // Pausing doesn't wait for a new line (or for a line at all)
+        assert_eq!(debugger.pause_reason(1, Some((0, 3))), Some("pause"));
+        assert_eq!(debugger.pause_reason(2, None), Some("pause"));
+    }
+
+    #[test]
+    fn test_pause_reason_step_in() {
+        let (debugger, _client) = create_debugger(RunMode::StepIn, Some((1, (0, 3))), &[]);
+
+        // Stepping in pauses at the next line, in any frame
+        assert_eq!(debugger.pause_reason(1, Some((0, 3))), None);
+        assert_eq!(debugger.pause_reason(1, None), None);
+        assert_eq!(debugger.pause_reason(1, Some((0, 4))), Some("step"));
+        assert_eq!(debugger.pause_reason(2, Some((0, 3))), Some("step"));
+        assert_eq!(debugger.pause_reason(2, Some((1, 10))), Some("step"));
+    }
+
+    #[test]
+    fn test_pause_reason_step_over() {
+        let (debugger, _client) =
+            create_debugger(RunMode::StepOver(2), Some((2, (0, 3))), &[(1, 10)]);
+
+        // Stepping over skips the lines of callees, unless they hold a breakpoint
+        assert_eq!(debugger.pause_reason(3, Some((1, 9))), None);
+        assert_eq!(debugger.pause_reason(3, Some((1, 10))), Some("breakpoint"));
+
+        // Stepping over pauses at the next line of the frame, or of a caller
+        assert_eq!(debugger.pause_reason(2, Some((0, 3))), None);
+        assert_eq!(debugger.pause_reason(2, Some((0, 4))), Some("step"));
+        assert_eq!(debugger.pause_reason(1, Some((0, 8))), Some("step"));
+    }
+
+    #[test]
+    fn test_pause_reason_step_out() {
+        let (debugger, _client) =
+            create_debugger(RunMode::StepOut(2), Some((2, (0, 3))), &[(0, 5)]);
+
+        // Stepping out skips the lines of the frame and its callees
+        assert_eq!(debugger.pause_reason(2, Some((0, 4))), None);
+        assert_eq!(debugger.pause_reason(3, Some((1, 9))), None);
+        assert_eq!(debugger.pause_reason(2, Some((0, 5))), Some("breakpoint"));
+
+        // Stepping out pauses at the next line of a caller
+        assert_eq!(debugger.pause_reason(1, Some((0, 8))), Some("step"));
+    }
+
+    #[test]
+    fn test_pause_reason_breakpoints() {
+        let (debugger, _client) =
+            create_debugger(RunMode::Continue, Some((1, (0, 3))), &[(0, 3), (0, 5)]);
+
+        // Breakpoints are hit when execution enters their line
+        assert_eq!(debugger.pause_reason(1, Some((0, 4))), None);
+        assert_eq!(debugger.pause_reason(1, Some((0, 5))), Some("breakpoint"));
+        assert_eq!(debugger.pause_reason(2, Some((0, 5))), Some("breakpoint"));
+        assert_eq!(debugger.pause_reason(1, Some((1, 5))), None);
+
+        // Instructions on the line of the last instruction don't hit its breakpoint again,
+        // unless the line is entered from another frame (e.g., on recursion)
+        assert_eq!(debugger.pause_reason(1, Some((0, 3))), None);
+        assert_eq!(debugger.pause_reason(2, Some((0, 3))), Some("breakpoint"));
+    }
+}
@@ -0,0 +1,160 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! The wire format of the Debug Adapter Protocol: JSON messages, each preceded by a
+//! `Content-Length` header.
+
+use anyhow::{anyhow, bail, Result};
+use serde_json::{json, Value};
+use std::{
+    io::{BufRead, Read, Write},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
+
+const CONTENT_LENGTH_HEADER: &str = "Content-Length:";
+
+/// A request sent by the client (i.e., the editor)
+#[derive(Clone, Debug)]
+pub struct Request {
+    pub seq: u64,
+    pub command: String,
+    pub arguments: Value,
+}
+
+/// Reads the next request from the client. Returns none once the client disconnects.
+/// Messages other than requests (i.e., responses to reverse requests) are skipped.
+pub fn read_request<R: BufRead>(reader: &mut R) -> Result<Option<Request>> {
+    loop {
+        let mut content_length = None;
+        loop {
+            let mut header = String::new();
+            if reader.read_line(&mut header)? == 0 {
+                return Ok(None);
+            }
+            let header = header.trim();
+            if header.is_empty() {
+                break;
+            }
+            if let Some(length) = header.strip_prefix(CONTENT_LENGTH_HEADER) {
+                content_length = Some(length.trim().parse::<usize>()?);
+            }
+        }
+        let content_length =
+            content_length.ok_or_else(|| anyhow!("Message without a content length"))?;
+
+        let mut content = vec![0; content_length];
+        reader.read_exact(&mut content)?;
+        let message: Value = serde_json::from_slice(&content)?;
+        if message["type"] != "request" {
+            continue;
+        }
+
+        let seq = match message["seq"].as_u64() {
+            Some(seq) => seq,
+            None => bail!("Request without a sequence number: {}", message),
+        };
+        let command = match message["command"].as_str() {
+            Some(command) => command.to_string(),
+            None => bail!("Request without a command: {}", message),
+        };
+        return Ok(Some(Request {
+            seq,
+            command,
+            arguments: message["arguments"].clone(),
+        }));
+    }
+}
+
+/// Writes responses and events to the client
+pub struct MessageWriter<W> {
+    writer: Mutex<W>,
+    next_seq: AtomicU64,
+}
+
+impl<W: Write> MessageWriter<W> {
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+            next_seq: AtomicU64::new(1),
+        }
+    }
+
+    /// Sends a successful response to the request
+    pub fn respond(&self, request: &Request, body: Value) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "success": true,
+            "command": request.command,
+            "body": body,
+        }));
+    }
+
+    /// Sends an error response to the request
+    pub fn respond_error(&self, request: &Request, message: impl Into<String>) {
+        self.send(json!({
+            "type": "response",
+            "request_seq": request.seq,
+            "success": false,
+            "command": request.command,
+            "message": message.into(),
+        }));
+    }
+
+    /// Sends an event
+    pub fn send_event(&self, event: &str, body: Value) {
+        self.send(json!({
+            "type": "event",
+            "event": event,
+            "body": body,
+        }));
+    }
+
+    /// Sends the message. Errors are ignored, as the client disconnecting is detected (and
+    /// handled) when reading requests.
+    fn send(&self, mut message: Value) {
+        message["seq"] = json!(self.next_seq.fetch_add(1, Ordering::Relaxed));
+        let content = message.to_string();
+        let mut writer = self.writer.lock().unwrap();
+        let _ = write!(
+            writer,
+            "{} {}\r\n\r\n{}",
+            CONTENT_LENGTH_HEADER,
+            content.len(),
+            content
+        )
+        .and_then(|_| writer.flush());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_request_round_trip() {
+        let writer = MessageWriter::new(vec![]);
+        writer.send(json!({
+            "type": "request",
+            "command": "setBreakpoints",
+            "arguments": { "source": { "path": "sources/m.move" } },
+        }));
+        writer.send_event("stopped", json!({ "reason": "step" }));
+        writer.send(json!({ "type": "request", "command": "continue" }));
+        let bytes = writer.writer.into_inner().unwrap();
+
+        // Events (and responses) are skipped
+        let mut reader = bytes.as_slice();
+        let request = read_request(&mut reader).unwrap().unwrap();
+        assert_eq!(request.seq, 1);
+        assert_eq!(request.command, "setBreakpoints");
+        assert_eq!(request.arguments["source"]["path"], "sources/m.move");
+        let request = read_request(&mut reader).unwrap().unwrap();
+        assert_eq!(request.seq, 3);
+        assert_eq!(request.command, "continue");
+        assert!(read_request(&mut reader).unwrap().is_none());
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CompiledModule, FunctionDefinitionIndex},
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_command_line_common::files::FileHash;
+use move_compiler::{compiled_unit::CompiledUnit, unit_test::TestPlan};
+use move_core_types::language_storage::ModuleId;
+use move_package::compilation::compiled_package::CompiledPackage;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+/// A line in a source file, identified by the index of the file in the `SourceIndex`
+pub type SourceLine = (usize, u32);
+
+/// The source of a function: the line of each instruction, and the names of the locals
+struct FunctionSource {
+    file: usize,
+    code_lines: Vec<Option<u32>>,
+    local_names: Vec<String>,
+}
+
+/// A source file, with the byte offsets at which its lines start
+struct SourceFile {
+    path: PathBuf,
+    line_starts: Vec<u32>,
+    /// The lines holding at least one instruction, i.e., the lines a breakpoint can be set at
+    code_lines: BTreeSet<u32>,
+}
+
+impl SourceFile {
+    fn new(path: PathBuf, contents: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(
+                contents
+                    .match_indices('\n')
+                    .map(|(offset, _)| offset as u32 + 1),
+            )
+            .collect();
+        Self {
+            path,
+            line_starts,
+            code_lines: BTreeSet::new(),
+        }
+    }
+
+    /// Returns the (1-based) line holding the given byte offset
+    fn line_of(&self, offset: u32) -> u32 {
+        self.line_starts.partition_point(|start| *start <= offset) as u32
+    }
+}
+
+/// Maps the instructions of compiled functions to lines of their source files (through the
+/// source maps of the modules), and source lines back to instructions for breakpoints.
+#[derive(Default)]
+pub struct SourceIndex {
+    files: Vec<SourceFile>,
+    file_hashes: HashMap<FileHash, usize>,
+    file_paths: HashMap<PathBuf, usize>,
+    functions: HashMap<(ModuleId, FunctionDefinitionIndex), FunctionSource>,
+}
+
+impl SourceIndex {
+    /// Indexes the modules (and their sources) of a unit test plan
+    pub fn from_test_plan(test_plan: &TestPlan) -> Self {
+        let mut index = Self::default();
+        for (file_hash, (path, contents)) in &test_plan.files {
+            index.add_file(*file_hash, Path::new(path.as_str()), contents);
+        }
+        for module in test_plan.module_info.values() {
+            index.add_module(&module.module, &module.source_map);
+        }
+        index
+    }
+
+    /// Indexes the modules (and their sources) of a compiled package and its dependencies
+    pub fn add_compiled_package(&mut self, package: &CompiledPackage) {
+        for unit in package.all_compiled_units_with_source() {
+            if let CompiledUnit::Module(module) = &unit.unit {
+                if let Ok(contents) = fs::read_to_string(&unit.source_path) {
+                    self.add_file(FileHash::new(&contents), &unit.source_path, &contents);
+                    self.add_module(&module.module, &module.source_map);
+                }
+            }
+        }
+    }
+
+    /// Adds a source file, so that the modules defined in it can be indexed
+    pub fn add_file(&mut self, file_hash: FileHash, path: &Path, contents: &str) {
+        if self.file_hashes.contains_key(&file_hash) {
+            return;
+        }
+        let path = canonical_path(path);
+        let file = self.files.len();
+        self.files.push(SourceFile::new(path.clone(), contents));
+        self.file_hashes.insert(file_hash, file);
+        self.file_paths.insert(path, file);
+    }
+
+    /// Indexes the functions of the module. The source file of the module must have been added.
+    pub fn add_module(&mut self, module: &CompiledModule, source_map: &SourceMap) {
+        let file = match self
+            .file_hashes
+            .get(&source_map.definition_location.file_hash())
+        {
+            Some(file) => *file,
+            None => return,
+        };
+
+        for (idx, function_def) in module.function_defs().iter().enumerate() {
+            let function_index = FunctionDefinitionIndex(idx as u16);
+            let (function_source_map, code) = match (
+                source_map.get_function_source_map(function_index),
+                &function_def.code,
+            ) {
+                (Ok(function_source_map), Some(code)) => (function_source_map, code),
+                _ => continue,
+            };
+
+            let code_lines: Vec<_> = (0..code.code.len())
+                .map(|offset| {
+                    function_source_map
+                        .get_code_location(offset as u16)
+                        .map(|loc| self.files[file].line_of(loc.start()))
+                })
+                .collect();
+            self.files[file]
+                .code_lines
+                .extend(code_lines.iter().flatten().copied());
+
+            let local_names = function_source_map
+                .parameters
+                .iter()
+                .chain(&function_source_map.locals)
+                .map(|(name, _)| name.clone())
+                .collect();
+            self.functions
+                .insert((module.self_id(), function_index), FunctionSource {
+                    file,
+                    code_lines,
+                    local_names,
+                });
+        }
+    }
+
+    /// Returns the source line of the instruction, if the function has a source map
+    pub fn source_line(
+        &self,
+        module_id: Option<&ModuleId>,
+        function_index: FunctionDefinitionIndex,
+        pc: u16,
+    ) -> Option<SourceLine> {
+        let function = self.functions.get(&(module_id?.clone(), function_index))?;
+        let line = (*function.code_lines.get(pc as usize)?)?;
+        Some((function.file, line))
+    }
+
+    /// Returns the name of the local of the function, if the function has a source map
+    pub fn local_name(
+        &self,
+        module_id: Option<&ModuleId>,
+        function_index: FunctionDefinitionIndex,
+        local_index: usize,
+    ) -> Option<&str> {
+        self.functions
+            .get(&(module_id?.clone(), function_index))?
+            .local_names
+            .get(local_index)
+            .map(|name| name.as_str())
+    }
+
+    /// Returns the path of the file with the given index
+    pub fn file_path(&self, file: usize) -> &Path {
+        &self.files[file].path
+    }
+
+    /// Returns the index of the source file at the given path (if indexed)
+    pub fn file_of(&self, path: &Path) -> Option<usize> {
+        self.file_paths.get(&canonical_path(path)).copied()
+    }
+
+    /// Resolves a breakpoint at the given line of the file to the first line (at or after the
+    /// given line) holding an instruction. Returns none if there is no such line.
+    pub fn resolve_breakpoint(&self, path: &Path, line: u32) -> Option<SourceLine> {
+        let file = self.file_of(path)?;
+        let line = *self.files[file].code_lines.range(line..).next()?;
+        Some((file, line))
+    }
+}
+
+fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
@@ -9,6 +9,7 @@ use base::{
 use move_package::BuildConfig;
 
 pub mod base;
+pub mod debug_adapter;
 pub mod sandbox;
 pub mod utils;
 
@@ -0,0 +1,307 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{CompiledModule, FunctionDefinitionIndex},
+};
+use move_cli::{
+    base::test::{debug_move_unit_tests, UnitTestResult},
+    debug_adapter::SourceIndex,
+};
+use move_compiler::compiled_unit::CompiledUnit;
+use move_package::BuildConfig;
+use move_unit_test::UnitTestingConfig;
+use serde_json::{json, Value};
+use std::{
+    fs,
+    io::{BufRead, BufReader, Read, Write},
+    net::{Ipv4Addr, TcpListener, TcpStream},
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant},
+};
+use tempfile::TempDir;
+
+const FIXTURE_PATH: &str = "tests/debug_adapter_tests";
+const SOURCE_PATH: &str = "sources/m.move";
+const CONNECT_TIMEOUT_SECS: u64 = 120;
+
+/// Copies the fixture package to a temporary directory, so that building it
+/// doesn't write to the source tree
+fn copy_fixture() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let package_path = temp_dir.path().join("package");
+    fs::create_dir_all(package_path.join("sources")).unwrap();
+    for file in ["Move.toml", SOURCE_PATH] {
+        fs::copy(Path::new(FIXTURE_PATH).join(file), package_path.join(file)).unwrap();
+    }
+    let package_path = fs::canonicalize(package_path).unwrap();
+    (temp_dir, package_path)
+}
+
+fn function_index(module: &CompiledModule, name: &str) -> FunctionDefinitionIndex {
+    let idx = module
+        .function_defs()
+        .iter()
+        .position(|def| {
+            module
+                .identifier_at(module.function_handle_at(def.function).name)
+                .as_str()
+                == name
+        })
+        .unwrap();
+    FunctionDefinitionIndex(idx as u16)
+}
+
+#[test]
+fn test_source_index() {
+    let (_temp_dir, package_path) = copy_fixture();
+    let package = BuildConfig::default()
+        .compile_package(&package_path, &mut Vec::new())
+        .unwrap();
+    let mut source_index = SourceIndex::default();
+    source_index.add_compiled_package(&package);
+
+    let source_path = package_path.join(SOURCE_PATH);
+    let file = source_index.file_of(&source_path).unwrap();
+    assert_eq!(source_index.file_path(file), source_path);
+
+    // Breakpoints on lines with code stay there
+    assert_eq!(
+        source_index.resolve_breakpoint(&source_path, 4),
+        Some((file, 4))
+    );
+    assert_eq!(
+        source_index.resolve_breakpoint(&source_path, 9),
+        Some((file, 9))
+    );
+
+    // Breakpoints on blank lines and comments move to the next line with code
+    assert_eq!(
+        source_index.resolve_breakpoint(&source_path, 3),
+        Some((file, 4))
+    );
+    assert_eq!(
+        source_index.resolve_breakpoint(&source_path, 8),
+        Some((file, 9))
+    );
+
+    // Breakpoints after the last line with code (or in unknown files) can't be resolved
+    assert_eq!(source_index.resolve_breakpoint(&source_path, 100), None);
+    assert_eq!(
+        source_index.resolve_breakpoint(&package_path.join("sources/unknown.move"), 4),
+        None
+    );
+
+    // Instructions map back to the lines of their function
+    let module = package
+        .root_compiled_units
+        .iter()
+        .find_map(|unit| match &unit.unit {
+            CompiledUnit::Module(module) => Some(module.module.clone()),
+            CompiledUnit::Script(_) => None,
+        })
+        .unwrap();
+    let module_id = module.self_id();
+    let inc = function_index(&module, "inc");
+    let add_twice = function_index(&module, "add_twice");
+    assert_eq!(
+        source_index.source_line(Some(&module_id), inc, 0),
+        Some((file, 4))
+    );
+    assert_eq!(
+        source_index.source_line(Some(&module_id), add_twice, 0),
+        Some((file, 9))
+    );
+    assert_eq!(source_index.source_line(Some(&module_id), inc, 1000), None);
+    assert_eq!(source_index.source_line(None, inc, 0), None);
+    assert_eq!(source_index.local_name(Some(&module_id), inc, 0), Some("x"));
+}
+
+/// A DAP client, talking to the debug adapter over a socket
+struct Client {
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+    next_seq: u64,
+}
+
+impl Client {
+    /// Connects to the debug adapter, retrying until it listens (i.e., the tests are built)
+    fn connect(port: u16) -> Self {
+        let start = Instant::now();
+        let stream = loop {
+            match TcpStream::connect((Ipv4Addr::LOCALHOST, port)) {
+                Ok(stream) => break stream,
+                Err(_) if start.elapsed() < Duration::from_secs(CONNECT_TIMEOUT_SECS) => {
+                    thread::sleep(Duration::from_millis(100))
+                },
+                Err(error) => panic!("Failed to connect to the debug adapter: {}", error),
+            }
+        };
+        Self {
+            reader: BufReader::new(stream.try_clone().unwrap()),
+            writer: stream,
+            next_seq: 1,
+        }
+    }
+
+    /// Sends the request, and returns the body of its (successful) response
+    fn request(&mut self, command: &str, arguments: Value) -> Value {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let content = json!({
+            "seq": seq,
+            "type": "request",
+            "command": command,
+            "arguments": arguments,
+        })
+        .to_string();
+        write!(
+            self.writer,
+            "Content-Length: {}\r\n\r\n{}",
+            content.len(),
+            content
+        )
+        .unwrap();
+
+        let response = self.read_until(|message| message["request_seq"] == json!(seq));
+        assert_eq!(response["success"], json!(true), "{}", response);
+        response["body"].clone()
+    }
+
+    /// Waits for the event, and returns its body
+    fn expect_event(&mut self, event: &str) -> Value {
+        self.read_until(|message| message["type"] == "event" && message["event"] == event)["body"]
+            .clone()
+    }
+
+    /// Returns the top frame of the stack trace, and the number of frames
+    fn top_frame(&mut self) -> (Value, u64) {
+        let body = self.request("stackTrace", json!({ "threadId": 1 }));
+        (
+            body["stackFrames"][0].clone(),
+            body["totalFrames"].as_u64().unwrap(),
+        )
+    }
+
+    /// Reads messages until one matches the predicate
+    fn read_until(&mut self, predicate: impl Fn(&Value) -> bool) -> Value {
+        loop {
+            let mut content_length = 0;
+            loop {
+                let mut header = String::new();
+                assert_ne!(self.reader.read_line(&mut header).unwrap(), 0);
+                let header = header.trim();
+                if header.is_empty() {
+                    break;
+                }
+                if let Some(length) = header.strip_prefix("Content-Length:") {
+                    content_length = length.trim().parse().unwrap();
+                }
+            }
+            let mut content = vec![0; content_length];
+            self.reader.read_exact(&mut content).unwrap();
+            let message: Value = serde_json::from_slice(&content).unwrap();
+            if predicate(&message) {
+                return message;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_debug_session() {
+    let (temp_dir, package_path) = copy_fixture();
+    let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+
+    // Run the tests under the debug adapter
+    let install_dir = temp_dir.path().join("build");
+    let tests_package_path = package_path.clone();
+    let tests = thread::spawn(move || {
+        let build_config = BuildConfig {
+            install_dir: Some(install_dir),
+            ..Default::default()
+        };
+        debug_move_unit_tests(
+            &tests_package_path,
+            build_config,
+            UnitTestingConfig::default_with_bound(None),
+            vec![],
+            None,
+            port,
+            &mut Vec::new(),
+        )
+        .unwrap()
+    });
+
+    // Configure the session, with a breakpoint on the comment before `let y = inc(x);`
+    let mut client = Client::connect(port);
+    client.request("initialize", json!({ "adapterID": "move" }));
+    client.expect_event("initialized");
+    client.request("launch", json!({}));
+    let source_path = package_path.join(SOURCE_PATH);
+    let body = client.request(
+        "setBreakpoints",
+        json!({ "source": { "path": source_path }, "breakpoints": [{ "line": 8 }, { "line": 100 }] }),
+    );
+    assert_eq!(body["breakpoints"][0]["verified"], json!(true));
+    assert_eq!(body["breakpoints"][0]["line"], json!(9));
+    assert_eq!(body["breakpoints"][1]["verified"], json!(false));
+    client.request("configurationDone", json!({}));
+
+    // The breakpoint is hit in `add_twice`, called by the test
+    let stopped = client.expect_event("stopped");
+    assert_eq!(stopped["reason"], json!("breakpoint"));
+    let (frame, num_frames) = client.top_frame();
+    assert_eq!(frame["name"], json!("0x42::m::add_twice"));
+    assert_eq!(frame["line"], json!(9));
+    assert_eq!(frame["source"]["path"], json!(source_path));
+    assert_eq!(num_frames, 2);
+
+    // The locals of the frame are named after the source
+    let body = client.request("scopes", json!({ "frameId": frame["id"] }));
+    let locals_reference = body["scopes"][0]["variablesReference"].clone();
+    let body = client.request(
+        "variables",
+        json!({ "variablesReference": locals_reference }),
+    );
+    assert_eq!(body["variables"][0]["name"], json!("x"));
+
+    // Stepping in enters `inc`
+    client.request("stepIn", json!({ "threadId": 1 }));
+    assert_eq!(client.expect_event("stopped")["reason"], json!("step"));
+    let (frame, num_frames) = client.top_frame();
+    assert_eq!(frame["name"], json!("0x42::m::inc"));
+    assert_eq!(frame["line"], json!(4));
+    assert_eq!(num_frames, 3);
+
+    // Stepping out returns to `add_twice`
+    client.request("stepOut", json!({ "threadId": 1 }));
+    assert_eq!(client.expect_event("stopped")["reason"], json!("step"));
+    let (frame, num_frames) = client.top_frame();
+    assert_eq!(frame["name"], json!("0x42::m::add_twice"));
+    assert_eq!(num_frames, 2);
+
+    // Stepping over the next line doesn't enter the second call to `inc`
+    client.request("next", json!({ "threadId": 1 }));
+    assert_eq!(client.expect_event("stopped")["reason"], json!("step"));
+    let (frame, num_frames) = client.top_frame();
+    assert_eq!(frame["name"], json!("0x42::m::add_twice"));
+    assert_eq!(frame["line"], json!(10));
+    assert_eq!(num_frames, 2);
+    client.request("next", json!({ "threadId": 1 }));
+    assert_eq!(client.expect_event("stopped")["reason"], json!("step"));
+    let (frame, num_frames) = client.top_frame();
+    assert!(num_frames <= 2);
+    assert_ne!(frame["name"], json!("0x42::m::inc"));
+
+    // Continuing runs the tests to completion
+    client.request("continue", json!({ "threadId": 1 }));
+    client.expect_event("terminated");
+    assert_eq!(tests.join().unwrap(), UnitTestResult::Success);
+}
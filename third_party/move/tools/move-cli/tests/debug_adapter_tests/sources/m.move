@@ -0,0 +1,18 @@
+module 0x42::m {
+    public fun inc(x: u64): u64 {
+
+        x + 1
+    }
+
+    public fun add_twice(x: u64): u64 {
+        // Calls inc twice
+        let y = inc(x);
+        inc(y)
+    }
+
+    #[test]
+    fun test_add_twice() {
+        let z = add_twice(1);
+        assert!(z == 3, 0);
+    }
+}
@@ -0,0 +1,117 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use move_cli::base::test::{run_move_unit_tests, UnitTestResult};
+use move_command_line_common::testing::{
+    add_update_baseline_fix, format_diff, read_env_update_baseline, EXP_EXT,
+};
+use move_compiler::compiled_unit::{CompiledUnit, NamedCompiledModule};
+use move_coverage::{coverage_map::CoverageMap, line_coverage::LineCoverage};
+use move_package::BuildConfig;
+use move_unit_test::UnitTestingConfig;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use tempfile::TempDir;
+
+const FIXTURE_PATH: &str = "tests/line_coverage_tests";
+const SOURCE_PATH: &str = "sources/m.move";
+
+/// Copies the fixture package to a temporary directory, so that testing it doesn't write the
+/// trace and coverage map to the source tree
+fn copy_fixture() -> (TempDir, PathBuf) {
+    let temp_dir = TempDir::new().unwrap();
+    let package_path = temp_dir.path().join("package");
+    fs::create_dir_all(package_path.join("sources")).unwrap();
+    for file in ["Move.toml", SOURCE_PATH] {
+        fs::copy(Path::new(FIXTURE_PATH).join(file), package_path.join(file)).unwrap();
+    }
+    let package_path = fs::canonicalize(package_path).unwrap();
+    (temp_dir, package_path)
+}
+
+/// Compares the output with the baseline in the fixture directory, or updates the baseline
+fn check_baseline(name: &str, output: &str) {
+    let exp_path = Path::new(FIXTURE_PATH).join(format!("{}.{}", name, EXP_EXT));
+    if read_env_update_baseline() {
+        fs::write(&exp_path, output).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&exp_path).unwrap_or_default();
+    if expected != output {
+        panic!(
+            "{}",
+            add_update_baseline_fix(format!(
+                "Expected output of {} differs:\n{}",
+                exp_path.display(),
+                format_diff(expected, output)
+            ))
+        );
+    }
+}
+
+// The VM writes its trace to a single file per process, so this is the only test of this binary
+#[test]
+fn test_line_coverage() {
+    let (temp_dir, package_path) = copy_fixture();
+    let build_config = BuildConfig {
+        install_dir: Some(temp_dir.path().join("build")),
+        ..Default::default()
+    };
+
+    // Run the tests, recording the coverage map in the package
+    let result = run_move_unit_tests(
+        &package_path,
+        build_config.clone(),
+        UnitTestingConfig::default_with_bound(None),
+        vec![],
+        None,
+        /* compute_coverage */ true,
+        &mut Vec::new(),
+    )
+    .unwrap();
+    assert_eq!(result, UnitTestResult::Success);
+
+    let coverage_map = CoverageMap::from_binary_file(package_path.join(".coverage_map.mvcov"))
+        .unwrap()
+        .to_unified_exec_map();
+    let package = build_config
+        .compile_package(&package_path, &mut Vec::new())
+        .unwrap();
+    let mut line_coverage = LineCoverage::new();
+    for unit in package.root_modules() {
+        if let CompiledUnit::Module(NamedCompiledModule {
+            module, source_map, ..
+        }) = &unit.unit
+        {
+            line_coverage
+                .add_module(module, source_map, &unit.source_path, &coverage_map)
+                .unwrap();
+        }
+    }
+
+    let package_path = package_path.display().to_string();
+    let mut lcov = vec![];
+    line_coverage.write_lcov(&mut lcov).unwrap();
+    let lcov = String::from_utf8(lcov)
+        .unwrap()
+        .replace(&package_path, "$PACKAGE");
+    check_baseline("coverage.lcov", &lcov);
+
+    let mut cobertura = vec![];
+    line_coverage
+        .write_cobertura(&mut cobertura, Path::new(&package_path))
+        .unwrap();
+    let cobertura = String::from_utf8(cobertura)
+        .unwrap()
+        .replace(&package_path, "$PACKAGE")
+        .lines()
+        .map(|line| match line.find(r#"timestamp=""#) {
+            Some(start) => format!(r#"{}timestamp="$TIMESTAMP">"#, &line[..start]),
+            None => line.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    check_baseline("coverage.cobertura", &(cobertura + "\n"));
+}
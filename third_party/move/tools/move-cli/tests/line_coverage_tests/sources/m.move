@@ -0,0 +1,28 @@
+module 0x42::m {
+    public fun max(x: u64, y: u64): u64 {
+        if (x > y) {
+            x
+        } else {
+            y
+        }
+    }
+
+    public fun count_down(n: u64): u64 {
+        let steps = 0;
+        while (n > 0) {
+            n = n - 1;
+            steps = steps + 1;
+        };
+        steps
+    }
+
+    public fun unused(x: u64): u64 {
+        x + 1
+    }
+
+    #[test]
+    fun test_m() {
+        assert!(max(2, 1) == 2, 0);
+        assert!(count_down(3) == 3, 1);
+    }
+}
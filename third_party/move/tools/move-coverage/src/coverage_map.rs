@@ -20,6 +20,9 @@ use std::{
 
 pub type FunctionCoverage = BTreeMap<u64, u64>;
 
+/// How often each (from, to) pair of offsets was executed in a row within a function
+pub type SuccessorCoverage = BTreeMap<(u64, u64), u64>;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CoverageMap {
     pub exec_maps: BTreeMap<String, ExecCoverageMap>,
@@ -30,6 +33,7 @@ pub struct ModuleCoverageMap {
     pub module_addr: AccountAddress,
     pub module_name: Identifier,
     pub function_maps: BTreeMap<Identifier, FunctionCoverage>,
+    pub successor_maps: BTreeMap<Identifier, SuccessorCoverage>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -59,12 +63,16 @@ pub struct TraceMap {
 
 impl CoverageMap {
     /// Takes in a file containing a raw VM trace, and returns an updated coverage map.
+    ///
+    /// Besides the executions of each instruction, this records which instruction followed
+    /// which within a function, pairing up the lines of the same thread (if the trace has them).
     pub fn update_coverage_from_trace_file<P: AsRef<Path> + std::fmt::Debug>(
         mut self,
         filename: P,
     ) -> Self {
         let file = File::open(&filename)
             .unwrap_or_else(|_| panic!("Unable to open coverage trace file '{:?}'", filename));
+        let mut last_lines: BTreeMap<String, (String, u64)> = BTreeMap::new();
         for line in BufReader::new(file).lines() {
             let line = line.unwrap();
             let mut splits = line.split(',');
@@ -72,6 +80,8 @@ impl CoverageMap {
             let exec_id = "dummy_exec_id";
             let context = splits.next().unwrap();
             let pc = splits.next().unwrap().parse::<u64>().unwrap();
+            let thread = splits.next().unwrap_or_default();
+            let last_line = last_lines.insert(thread.to_owned(), (context.to_owned(), pc));
 
             let mut context_segs: Vec<_> = context.split("::").collect();
             let is_script = context_segs.len() == 2;
@@ -80,6 +90,18 @@ impl CoverageMap {
                 let module_name = Identifier::new(context_segs.pop().unwrap()).unwrap();
                 let module_addr =
                     AccountAddress::from_hex_literal(context_segs.pop().unwrap()).unwrap();
+                if let Some((last_context, last_pc)) = last_line {
+                    if last_context == context {
+                        self.insert_successor(
+                            exec_id,
+                            module_addr,
+                            module_name.clone(),
+                            func_name.clone(),
+                            last_pc,
+                            pc,
+                        );
+                    }
+                }
                 self.insert(exec_id, module_addr, module_name, func_name, pc);
             } else {
                 // Don't count scripts (for now)
@@ -125,6 +147,22 @@ impl CoverageMap {
         exec_entry.insert(module_addr, module_name, func_name, pc);
     }
 
+    pub fn insert_successor(
+        &mut self,
+        exec_id: &str,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+    ) {
+        let exec_entry = self
+            .exec_maps
+            .entry(exec_id.to_owned())
+            .or_insert_with(|| ExecCoverageMap::new(exec_id.to_owned()));
+        exec_entry.insert_successor_multi(module_addr, module_name, func_name, from_pc, to_pc, 1);
+    }
+
     pub fn to_unified_exec_map(&self) -> ExecCoverageMap {
         let mut unified_map = ExecCoverageMap::new(String::new());
         for (_, exec_map) in self.exec_maps.iter() {
@@ -140,6 +178,18 @@ impl CoverageMap {
                         );
                     }
                 }
+                for (func_name, successor_map) in module_map.successor_maps.iter() {
+                    for ((from_pc, to_pc), count) in successor_map.iter() {
+                        unified_map.insert_successor_multi(
+                            *module_addr,
+                            module_name.clone(),
+                            func_name.clone(),
+                            *from_pc,
+                            *to_pc,
+                            *count,
+                        );
+                    }
+                }
             }
         }
         unified_map
@@ -152,6 +202,7 @@ impl ModuleCoverageMap {
             module_addr,
             module_name,
             function_maps: BTreeMap::new(),
+            successor_maps: BTreeMap::new(),
         }
     }
 
@@ -168,6 +219,21 @@ impl ModuleCoverageMap {
         self.insert_multi(func_name, pc, 1);
     }
 
+    pub fn insert_successor_multi(
+        &mut self,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+        count: u64,
+    ) {
+        let func_entry = self
+            .successor_maps
+            .entry(func_name)
+            .or_insert_with(SuccessorCoverage::new);
+        let successor_entry = func_entry.entry((from_pc, to_pc)).or_insert(0);
+        *successor_entry += count;
+    }
+
     pub fn merge(&mut self, another: ModuleCoverageMap) {
         for (key, val) in another.function_maps {
             self.function_maps
@@ -175,11 +241,21 @@ impl ModuleCoverageMap {
                 .or_insert_with(FunctionCoverage::new)
                 .extend(val);
         }
+        for (key, val) in another.successor_maps {
+            self.successor_maps
+                .entry(key)
+                .or_insert_with(SuccessorCoverage::new)
+                .extend(val);
+        }
     }
 
     pub fn get_function_coverage(&self, func_name: &IdentStr) -> Option<&FunctionCoverage> {
         self.function_maps.get(func_name)
     }
+
+    pub fn get_successor_coverage(&self, func_name: &IdentStr) -> Option<&SuccessorCoverage> {
+        self.successor_maps.get(func_name)
+    }
 }
 
 impl ExecCoverageMap {
@@ -215,6 +291,22 @@ impl ExecCoverageMap {
         self.insert_multi(module_addr, module_name, func_name, pc, 1);
     }
 
+    pub fn insert_successor_multi(
+        &mut self,
+        module_addr: AccountAddress,
+        module_name: Identifier,
+        func_name: Identifier,
+        from_pc: u64,
+        to_pc: u64,
+        count: u64,
+    ) {
+        let module_entry = self
+            .module_maps
+            .entry((module_addr, module_name.clone()))
+            .or_insert_with(|| ModuleCoverageMap::new(module_addr, module_name));
+        module_entry.insert_successor_multi(func_name, from_pc, to_pc, count);
+    }
+
     pub fn into_coverage_map_with_modules(
         self,
         modules: BTreeMap<AccountAddress, BTreeMap<Identifier, (String, CompiledModule)>>,
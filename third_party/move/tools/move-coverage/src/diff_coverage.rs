@@ -0,0 +1,215 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Coverage of the lines changed relative to a git base, e.g., the lines changed by a PR.
+
+use crate::line_coverage::{CoverageCounts, LineCoverage};
+use anyhow::{bail, Result};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+/// The (1-based) lines added or modified in each file
+pub type ChangedLines = BTreeMap<PathBuf, BTreeSet<u32>>;
+
+/// Returns the lines of the working tree (of the git repository containing `dir`) that were
+/// added or modified since `base`, which is any git revision. Files are keyed by their
+/// canonical paths.
+pub fn changed_lines_since(base: &str, dir: &Path) -> Result<ChangedLines> {
+    let top_level = run_git(dir, &["rev-parse", "--show-toplevel"])?;
+    let top_level = PathBuf::from(top_level.trim());
+    let diff = run_git(dir, &[
+        "diff",
+        "--no-color",
+        "--no-ext-diff",
+        "--unified=0",
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+        base,
+        "--",
+    ])?;
+    Ok(parse_unified_diff(&diff)
+        .into_iter()
+        .map(|(path, lines)| {
+            let path = top_level.join(path);
+            (fs::canonicalize(&path).unwrap_or(path), lines)
+        })
+        .collect())
+}
+
+fn run_git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").current_dir(dir).args(args).output()?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Parses the added or modified lines of each file out of a unified diff without context lines
+/// (i.e., `git diff --unified=0`). Paths are relative to the root of the repository.
+pub fn parse_unified_diff(diff: &str) -> ChangedLines {
+    let mut changed_lines = ChangedLines::new();
+    let mut current_file = None;
+    // Whether the line is part of the header of a file diff, so an added line starting with
+    // `++ ` is not taken for the path of a file
+    let mut in_header = false;
+    for line in diff.lines() {
+        if line.starts_with("diff ") {
+            in_header = true;
+        } else if let (true, Some(path)) = (in_header, line.strip_prefix("+++ ")) {
+            // Deleted files have no lines left to cover
+            current_file = path.strip_prefix("b/").map(PathBuf::from);
+        } else if let Some(hunk) = line.strip_prefix("@@ ") {
+            in_header = false;
+            let file = match &current_file {
+                Some(file) => file,
+                None => continue,
+            };
+            // The hunk header is `@@ -<start>[,<count>] +<start>[,<count>] @@`
+            let added = match hunk.split(' ').find_map(|range| range.strip_prefix('+')) {
+                Some(added) => added,
+                None => continue,
+            };
+            let mut range = added.splitn(2, ',');
+            let start = range.next().and_then(|start| start.parse::<u32>().ok());
+            let count = match range.next() {
+                Some(count) => count.parse::<u32>().ok(),
+                None => Some(1),
+            };
+            if let (Some(start), Some(count)) = (start, count) {
+                changed_lines
+                    .entry(file.clone())
+                    .or_default()
+                    .extend(start..start + count);
+            }
+        }
+    }
+    changed_lines.retain(|_, lines| !lines.is_empty());
+    changed_lines
+}
+
+/// Prints the coverage of the changed lines of each source file (as computed by
+/// `LineCoverage::restrict_to_lines`) and lists the changed lines which are not covered. File
+/// paths are printed relative to `root` where possible. Returns the total counts.
+pub fn format_diff_summary<W: Write>(
+    coverage: &LineCoverage,
+    root: &Path,
+    summary_writer: &mut W,
+) -> io::Result<CoverageCounts> {
+    writeln!(summary_writer, "+-------------------------+")?;
+    writeln!(summary_writer, "| Move Diff Coverage      |")?;
+    writeln!(summary_writer, "+-------------------------+")?;
+
+    for (path, (lines, _)) in coverage.files() {
+        let covered = lines.values().filter(|hits| **hits > 0).count();
+        writeln!(
+            summary_writer,
+            "{}: {}/{} changed lines covered",
+            path.strip_prefix(root).unwrap_or(path).display(),
+            covered,
+            lines.len()
+        )?;
+        let uncovered: Vec<_> = lines
+            .iter()
+            .filter(|(_, hits)| **hits == 0)
+            .map(|(line, _)| *line)
+            .collect();
+        if !uncovered.is_empty() {
+            writeln!(
+                summary_writer,
+                "    uncovered lines: {}",
+                format_line_ranges(&uncovered)
+            )?;
+        }
+    }
+
+    let counts = coverage.counts();
+    writeln!(summary_writer, "+-------------------------+")?;
+    if counts.lines_valid == 0 {
+        writeln!(summary_writer, "| No changed Move code    |")?;
+    } else {
+        writeln!(
+            summary_writer,
+            "| % Diff Coverage: {:.2}  |",
+            counts.line_rate() * 100f64
+        )?;
+    }
+    writeln!(summary_writer, "+-------------------------+")?;
+    Ok(counts)
+}
+
+/// Formats sorted lines as comma separated ranges, e.g., `3, 7-9`
+fn format_line_ranges(lines: &[u32]) -> String {
+    let mut ranges: Vec<(u32, u32)> = vec![];
+    for line in lines {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == *line => *end = *line,
+            _ => ranges.push((*line, *line)),
+        }
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end)| {
+            if start == end {
+                start.to_string()
+            } else {
+                format!("{}-{}", start, end)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_unified_diff() {
+        let diff = "\
+diff --git a/sources/coin.move b/sources/coin.move
+index 1111111..2222222 100644
+--- a/sources/coin.move
++++ b/sources/coin.move
+@@ -10 +10 @@ module 0x1::coin {
+-        x + 1
++        x + 2
+@@ -20,0 +21,3 @@ module 0x1::coin {
++    fun f() {
++    }
++
+@@ -30,2 +33,0 @@ module 0x1::coin {
+-    fun g() {
+-    }
+diff --git a/sources/old.move b/sources/old.move
+deleted file mode 100644
+--- a/sources/old.move
++++ /dev/null
+@@ -1,2 +0,0 @@
+-module 0x1::old {
+-}
+";
+        let changed_lines = parse_unified_diff(diff);
+        assert_eq!(changed_lines.len(), 1);
+        assert_eq!(
+            changed_lines[Path::new("sources/coin.move")],
+            BTreeSet::from([10, 21, 22, 23])
+        );
+    }
+
+    #[test]
+    fn test_format_line_ranges() {
+        assert_eq!(format_line_ranges(&[]), "");
+        assert_eq!(format_line_ranges(&[3, 7, 8, 9, 12]), "3, 7-9, 12");
+    }
+}
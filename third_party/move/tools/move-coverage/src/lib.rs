@@ -7,6 +7,8 @@ use move_binary_format::CompiledModule;
 use std::io::Write;
 
 pub mod coverage_map;
+pub mod diff_coverage;
+pub mod line_coverage;
 pub mod source_coverage;
 pub mod summary;
 
@@ -0,0 +1,514 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+#![forbid(unsafe_code)]
+
+//! Line and branch coverage of Move source files, computed from a `CoverageMap` through the
+//! source maps of the modules, and its export in the LCOV and Cobertura XML formats.
+
+use crate::coverage_map::{ExecCoverageMap, FunctionCoverage, SuccessorCoverage};
+use anyhow::{bail, format_err, Result};
+use codespan::Files;
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{Bytecode, CodeOffset, FunctionDefinitionIndex},
+    CompiledModule,
+};
+use move_bytecode_source_map::source_map::SourceMap;
+use move_core_types::language_storage::ModuleId;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The coverage of a conditional branch instruction (`BrTrue` or `BrFalse`)
+#[derive(Clone, Debug)]
+pub struct BranchCoverage {
+    /// The (1-based) source line of the branch instruction
+    pub line: u32,
+    pub offset: CodeOffset,
+    /// How often the branch jumped and fell through, or none if the branch instruction was
+    /// never executed. Unless the next instruction is also the target of a jump (in which case
+    /// the recorded successors of the branch are used), falling through is counted by the
+    /// executions of the next instruction, and jumping by the remaining executions.
+    pub outcomes: Option<(u64, u64)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct FunctionLineCoverage {
+    pub name: String,
+    /// The (1-based) line the function is defined at
+    pub line: u32,
+    /// How often the function was called
+    pub hits: u64,
+    /// The execution count of each (1-based) line holding code of the function
+    pub lines: BTreeMap<u32, u64>,
+    pub branches: Vec<BranchCoverage>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ModuleLineCoverage {
+    pub module_id: ModuleId,
+    pub source_path: PathBuf,
+    pub functions: Vec<FunctionLineCoverage>,
+}
+
+/// Counts of valid (i.e., coverable) and covered lines and branch outcomes
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CoverageCounts {
+    pub lines_valid: u64,
+    pub lines_covered: u64,
+    pub branches_valid: u64,
+    pub branches_covered: u64,
+}
+
+impl CoverageCounts {
+    fn of(lines: &BTreeMap<u32, u64>, branches: &[&BranchCoverage]) -> Self {
+        Self {
+            lines_valid: lines.len() as u64,
+            lines_covered: lines.values().filter(|hits| **hits > 0).count() as u64,
+            branches_valid: 2 * branches.len() as u64,
+            branches_covered: branches
+                .iter()
+                .filter_map(|branch| branch.outcomes)
+                .map(|(jumped, fell_through)| (jumped > 0) as u64 + (fell_through > 0) as u64)
+                .sum(),
+        }
+    }
+
+    fn add(&mut self, other: Self) {
+        self.lines_valid += other.lines_valid;
+        self.lines_covered += other.lines_covered;
+        self.branches_valid += other.branches_valid;
+        self.branches_covered += other.branches_covered;
+    }
+
+    /// The fraction of covered lines, which is 1 if there are no lines
+    pub fn line_rate(&self) -> f64 {
+        rate(self.lines_covered, self.lines_valid)
+    }
+
+    /// The fraction of covered branch outcomes, which is 1 if there are no branches
+    pub fn branch_rate(&self) -> f64 {
+        rate(self.branches_covered, self.branches_valid)
+    }
+}
+
+/// The line and branch coverage of a set of modules
+#[derive(Clone, Debug, Default)]
+pub struct LineCoverage {
+    pub modules: Vec<ModuleLineCoverage>,
+}
+
+impl FunctionLineCoverage {
+    pub fn counts(&self) -> CoverageCounts {
+        CoverageCounts::of(&self.lines, &self.branches.iter().collect::<Vec<_>>())
+    }
+}
+
+impl ModuleLineCoverage {
+    pub fn counts(&self) -> CoverageCounts {
+        let (lines, branches) = merge_functions(self.functions.iter());
+        CoverageCounts::of(&lines, &branches)
+    }
+}
+
+impl LineCoverage {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Computes the coverage of the module, whose source is at `source_path`, from the (unified)
+    /// coverage map
+    pub fn add_module(
+        &mut self,
+        module: &CompiledModule,
+        source_map: &SourceMap,
+        source_path: &Path,
+        coverage_map: &ExecCoverageMap,
+    ) -> Result<()> {
+        let file_contents = fs::read_to_string(source_path)?;
+        if !source_map.check(&file_contents) {
+            bail!(
+                "File contents of {} out of sync with source map",
+                source_path.display()
+            );
+        }
+        let mut files = Files::new();
+        let file_id = files.add(source_path.as_os_str().to_os_string(), file_contents);
+        let line_of = |offset: u32| -> Result<u32> {
+            Ok(files
+                .location(file_id, offset)
+                .map_err(|err| format_err!("{}", err))?
+                .line
+                .0
+                + 1)
+        };
+
+        let module_id = module.self_id();
+        let module_map = coverage_map
+            .module_maps
+            .get(&(*module_id.address(), module_id.name().to_owned()));
+        let empty_coverage = FunctionCoverage::new();
+        let empty_successors = SuccessorCoverage::new();
+
+        let mut functions = vec![];
+        for (function_def_idx, function_def) in module.function_defs().iter().enumerate() {
+            let code_unit = match &function_def.code {
+                Some(code_unit) => code_unit,
+                None => continue,
+            };
+            let function_def_idx = FunctionDefinitionIndex(function_def_idx as u16);
+            let fn_handle = module.function_handle_at(function_def.function);
+            let fn_name = module.identifier_at(fn_handle.name);
+            let function_coverage = module_map
+                .and_then(|module_map| module_map.get_function_coverage(fn_name))
+                .unwrap_or(&empty_coverage);
+            let executions =
+                |offset: CodeOffset| *function_coverage.get(&(offset as u64)).unwrap_or(&0);
+            let successor_coverage = module_map
+                .and_then(|module_map| module_map.get_successor_coverage(fn_name))
+                .unwrap_or(&empty_successors);
+            let followed_by = |offset: CodeOffset, successor: CodeOffset| {
+                *successor_coverage
+                    .get(&(offset as u64, successor as u64))
+                    .unwrap_or(&0)
+            };
+            let jump_targets: BTreeSet<_> = code_unit
+                .code
+                .iter()
+                .filter_map(|instruction| match instruction {
+                    Bytecode::BrTrue(target)
+                    | Bytecode::BrFalse(target)
+                    | Bytecode::Branch(target) => Some(*target),
+                    _ => None,
+                })
+                .collect();
+
+            let function_map = source_map.get_function_source_map(function_def_idx)?;
+            let mut lines = BTreeMap::new();
+            let mut branches = vec![];
+            for (offset, instruction) in code_unit.code.iter().enumerate() {
+                let offset = offset as CodeOffset;
+                let line = line_of(
+                    source_map
+                        .get_code_location(function_def_idx, offset)?
+                        .start(),
+                )?;
+                let hits = lines.entry(line).or_insert(0);
+                *hits = executions(offset).max(*hits);
+
+                if let Bytecode::BrTrue(target) | Bytecode::BrFalse(target) = instruction {
+                    let outcomes = (executions(offset) > 0).then(|| {
+                        if jump_targets.contains(&(offset + 1)) {
+                            // The next instruction is a join point, so its executions don't
+                            // tell how often this branch fell through
+                            (
+                                followed_by(offset, *target),
+                                followed_by(offset, offset + 1),
+                            )
+                        } else {
+                            let fell_through = executions(offset + 1);
+                            (
+                                executions(offset).saturating_sub(fell_through),
+                                fell_through,
+                            )
+                        }
+                    });
+                    branches.push(BranchCoverage {
+                        line,
+                        offset,
+                        outcomes,
+                    });
+                }
+            }
+
+            functions.push(FunctionLineCoverage {
+                name: fn_name.to_string(),
+                line: line_of(function_map.definition_location.start())?,
+                hits: executions(0),
+                lines,
+                branches,
+            });
+        }
+
+        self.modules.push(ModuleLineCoverage {
+            module_id,
+            source_path: fs::canonicalize(source_path)?,
+            functions,
+        });
+        Ok(())
+    }
+
+    pub fn counts(&self) -> CoverageCounts {
+        let mut counts = CoverageCounts::default();
+        for module in &self.modules {
+            counts.add(module.counts());
+        }
+        counts
+    }
+
+    /// Restricts the coverage to the given (1-based) lines of each source file, keyed by their
+    /// canonical paths. Functions and modules left without lines are dropped.
+    pub fn restrict_to_lines(&mut self, lines: &BTreeMap<PathBuf, BTreeSet<u32>>) {
+        let empty_lines = BTreeSet::new();
+        for module in &mut self.modules {
+            let kept_lines = lines.get(&module.source_path).unwrap_or(&empty_lines);
+            for function in &mut module.functions {
+                function.lines.retain(|line, _| kept_lines.contains(line));
+                function
+                    .branches
+                    .retain(|branch| kept_lines.contains(&branch.line));
+            }
+            module
+                .functions
+                .retain(|function| !function.lines.is_empty());
+        }
+        self.modules.retain(|module| !module.functions.is_empty());
+    }
+
+    /// Returns the coverage of each source file: the execution count of each line, and the
+    /// branches
+    pub fn files(&self) -> BTreeMap<&Path, (BTreeMap<u32, u64>, Vec<&BranchCoverage>)> {
+        let mut files: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for module in &self.modules {
+            files
+                .entry(module.source_path.as_path())
+                .or_default()
+                .extend(&module.functions);
+        }
+        files
+            .into_iter()
+            .map(|(path, functions)| (path, merge_functions(functions.into_iter())))
+            .collect()
+    }
+
+    /// Writes the coverage as an LCOV tracefile, with one record per source file
+    pub fn write_lcov<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let files = self.files();
+        for (path, (lines, branches)) in &files {
+            writeln!(writer, "TN:")?;
+            writeln!(writer, "SF:{}", path.display())?;
+
+            let functions: Vec<_> = self
+                .modules
+                .iter()
+                .filter(|module| module.source_path == *path)
+                .flat_map(|module| {
+                    module.functions.iter().map(move |function| {
+                        (
+                            format!("{}::{}", module.module_id.name(), function.name),
+                            function,
+                        )
+                    })
+                })
+                .collect();
+            for (name, function) in &functions {
+                writeln!(writer, "FN:{},{}", function.line, name)?;
+            }
+            for (name, function) in &functions {
+                writeln!(writer, "FNDA:{},{}", function.hits, name)?;
+            }
+            writeln!(writer, "FNF:{}", functions.len())?;
+            writeln!(
+                writer,
+                "FNH:{}",
+                functions
+                    .iter()
+                    .filter(|(_, function)| function.hits > 0)
+                    .count()
+            )?;
+
+            for (block, branch) in branches.iter().enumerate() {
+                let (jumped, fell_through) = match branch.outcomes {
+                    Some((jumped, fell_through)) => (jumped.to_string(), fell_through.to_string()),
+                    None => ("-".to_string(), "-".to_string()),
+                };
+                writeln!(writer, "BRDA:{},{},0,{}", branch.line, block, jumped)?;
+                writeln!(writer, "BRDA:{},{},1,{}", branch.line, block, fell_through)?;
+            }
+            let counts = CoverageCounts::of(lines, branches);
+            writeln!(writer, "BRF:{}", counts.branches_valid)?;
+            writeln!(writer, "BRH:{}", counts.branches_covered)?;
+
+            for (line, hits) in lines {
+                writeln!(writer, "DA:{},{}", line, hits)?;
+            }
+            writeln!(writer, "LF:{}", counts.lines_valid)?;
+            writeln!(writer, "LH:{}", counts.lines_covered)?;
+            writeln!(writer, "end_of_record")?;
+        }
+        Ok(())
+    }
+
+    /// Writes the coverage as a Cobertura XML report, with one package per address and one
+    /// class per module. File names are written relative to `source_root` where possible.
+    pub fn write_cobertura<W: Write>(&self, writer: &mut W, source_root: &Path) -> io::Result<()> {
+        let counts = self.counts();
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default();
+        writeln!(writer, r#"<?xml version="1.0" ?>"#)?;
+        writeln!(
+            writer,
+            r#"<!DOCTYPE coverage SYSTEM "http://cobertura.sourceforge.net/xml/coverage-04.dtd">"#
+        )?;
+        writeln!(
+            writer,
+            r#"<coverage {} complexity="0" version="1.9" timestamp="{}">"#,
+            xml_counts(&counts),
+            timestamp
+        )?;
+        writeln!(writer, "  <sources>")?;
+        writeln!(
+            writer,
+            "    <source>{}</source>",
+            xml_escape(&source_root.display().to_string())
+        )?;
+        writeln!(writer, "  </sources>")?;
+
+        let mut packages: BTreeMap<_, Vec<_>> = BTreeMap::new();
+        for module in &self.modules {
+            packages
+                .entry(module.module_id.address())
+                .or_default()
+                .push(module);
+        }
+        writeln!(writer, "  <packages>")?;
+        for (address, modules) in packages {
+            let mut package_counts = CoverageCounts::default();
+            for module in &modules {
+                package_counts.add(module.counts());
+            }
+            writeln!(
+                writer,
+                r#"    <package name="0x{}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+                address.short_str_lossless(),
+                package_counts.line_rate(),
+                package_counts.branch_rate()
+            )?;
+            writeln!(writer, "      <classes>")?;
+            for module in modules {
+                let file_name = module
+                    .source_path
+                    .strip_prefix(source_root)
+                    .unwrap_or(&module.source_path);
+                let module_counts = module.counts();
+                writeln!(
+                    writer,
+                    r#"        <class name="{}" filename="{}" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+                    module.module_id.name(),
+                    xml_escape(&file_name.display().to_string()),
+                    module_counts.line_rate(),
+                    module_counts.branch_rate()
+                )?;
+                writeln!(writer, "          <methods>")?;
+                for function in &module.functions {
+                    let function_counts = function.counts();
+                    writeln!(
+                        writer,
+                        r#"            <method name="{}" signature="" line-rate="{:.4}" branch-rate="{:.4}" complexity="0">"#,
+                        function.name,
+                        function_counts.line_rate(),
+                        function_counts.branch_rate()
+                    )?;
+                    let branches: Vec<_> = function.branches.iter().collect();
+                    write_cobertura_lines(writer, "              ", &function.lines, &branches)?;
+                    writeln!(writer, "            </method>")?;
+                }
+                writeln!(writer, "          </methods>")?;
+                let (lines, branches) = merge_functions(module.functions.iter());
+                write_cobertura_lines(writer, "          ", &lines, &branches)?;
+                writeln!(writer, "        </class>")?;
+            }
+            writeln!(writer, "      </classes>")?;
+            writeln!(writer, "    </package>")?;
+        }
+        writeln!(writer, "  </packages>")?;
+        writeln!(writer, "</coverage>")
+    }
+}
+
+/// Merges the line and branch coverage of the functions
+fn merge_functions<'a>(
+    functions: impl Iterator<Item = &'a FunctionLineCoverage>,
+) -> (BTreeMap<u32, u64>, Vec<&'a BranchCoverage>) {
+    let mut lines = BTreeMap::new();
+    let mut branches = vec![];
+    for function in functions {
+        for (line, hits) in &function.lines {
+            let merged_hits = lines.entry(*line).or_insert(0);
+            *merged_hits = (*hits).max(*merged_hits);
+        }
+        branches.extend(&function.branches);
+    }
+    branches.sort_by_key(|branch| branch.line);
+    (lines, branches)
+}
+
+fn write_cobertura_lines<W: Write>(
+    writer: &mut W,
+    indent: &str,
+    lines: &BTreeMap<u32, u64>,
+    branches: &[&BranchCoverage],
+) -> io::Result<()> {
+    writeln!(writer, "{}<lines>", indent)?;
+    for (line, hits) in lines {
+        let line_branches: Vec<_> = branches
+            .iter()
+            .filter(|branch| branch.line == *line)
+            .copied()
+            .collect();
+        if line_branches.is_empty() {
+            writeln!(
+                writer,
+                r#"{}  <line number="{}" hits="{}" branch="false"/>"#,
+                indent, line, hits
+            )?;
+        } else {
+            let counts = CoverageCounts::of(&BTreeMap::new(), &line_branches);
+            writeln!(
+                writer,
+                r#"{}  <line number="{}" hits="{}" branch="true" condition-coverage="{:.0}% ({}/{})"/>"#,
+                indent,
+                line,
+                hits,
+                counts.branch_rate() * 100f64,
+                counts.branches_covered,
+                counts.branches_valid
+            )?;
+        }
+    }
+    writeln!(writer, "{}</lines>", indent)
+}
+
+fn xml_counts(counts: &CoverageCounts) -> String {
+    format!(
+        r#"line-rate="{:.4}" branch-rate="{:.4}" lines-covered="{}" lines-valid="{}" branches-covered="{}" branches-valid="{}""#,
+        counts.line_rate(),
+        counts.branch_rate(),
+        counts.lines_covered,
+        counts.lines_valid,
+        counts.branches_covered,
+        counts.branches_valid
+    )
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn rate(covered: u64, valid: u64) -> f64 {
+    if valid == 0 {
+        1f64
+    } else {
+        covered as f64 / valid as f64
+    }
+}
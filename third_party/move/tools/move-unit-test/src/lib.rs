@@ -4,10 +4,14 @@
 
 pub mod cargo_runner;
 pub mod extensions;
+pub mod property_test;
 pub mod test_reporter;
 pub mod test_runner;
 
-use crate::test_runner::TestRunner;
+use crate::{
+    property_test::{PropertyTestConfig, DEFAULT_NUM_CASES},
+    test_runner::TestRunner,
+};
 use clap::*;
 use move_command_line_common::files::verify_and_create_named_address_mapping;
 use move_compiler::{
@@ -22,8 +26,10 @@ use move_vm_runtime::native_functions::NativeFunctionTable;
 use move_vm_test_utils::gas_schedule::CostTable;
 use std::{
     collections::BTreeMap,
-    io::{Result, Write},
+    fs::File,
+    io::{Error, ErrorKind, Result, Write},
     marker::Send,
+    path::PathBuf,
     sync::Mutex,
 };
 
@@ -107,6 +113,33 @@ pub struct UnitTestingConfig {
     #[clap(short = 'v', long = "verbose")]
     pub verbose: bool,
 
+    /// Format of the test report. The JSON and JUnit reports capture the debug output of each
+    /// test, and are written to the report file.
+    #[clap(long = "format", value_enum, default_value = "human")]
+    pub report_format: TestReportFormat,
+
+    /// Write the JSON or JUnit report to this file. Required by these formats.
+    #[clap(
+        long = "report-file",
+        required_if_eq_any = [("report_format", "json"), ("report_format", "junit")]
+    )]
+    pub report_file: Option<PathBuf>,
+
+    /// Fuzz the property-based tests, i.e., the tests with parameters which are not assigned in
+    /// the `#[test]` attribute, with randomly seeded arguments. Without this flag, property-based
+    /// tests run a few cases from a fixed seed, so that their results are deterministic.
+    #[clap(long = "fuzz")]
+    pub fuzz: bool,
+
+    /// Number of cases each property-based test is run with when fuzzing
+    #[clap(long = "fuzz-cases", default_value_t = property_test::DEFAULT_NUM_FUZZ_CASES)]
+    pub fuzz_cases: u64,
+
+    /// Seed the arguments of property-based tests are generated from, e.g., to reproduce a
+    /// failure
+    #[clap(long = "fuzz-seed")]
+    pub fuzz_seed: Option<u64>,
+
     /// Use the EVM-based execution backend.
     /// Does not work with --stackless.
     #[cfg(feature = "evm-backend")]
@@ -114,6 +147,17 @@ pub struct UnitTestingConfig {
     pub evm: bool,
 }
 
+/// The format of the report of a test run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TestReportFormat {
+    /// Human readable output, with the result of each test as it completes
+    Human,
+    /// A JSON document with the result of each test
+    Json,
+    /// A JUnit XML report, as consumed by CI systems
+    Junit,
+}
+
 fn format_module_id(module_id: &ModuleId) -> String {
     format!(
         "0x{}::{}",
@@ -137,6 +181,11 @@ impl UnitTestingConfig {
             dep_files: vec![],
             check_stackless_vm: false,
             verbose: false,
+            report_format: TestReportFormat::Human,
+            report_file: None,
+            fuzz: false,
+            fuzz_cases: property_test::DEFAULT_NUM_FUZZ_CASES,
+            fuzz_seed: None,
             list: false,
             named_address_values: vec![],
 
@@ -154,6 +203,21 @@ impl UnitTestingConfig {
         self
     }
 
+    /// How the arguments of property-based tests are generated
+    pub fn property_test_config(&self) -> PropertyTestConfig {
+        if self.fuzz {
+            PropertyTestConfig {
+                num_cases: self.fuzz_cases,
+                seed: self.fuzz_seed.unwrap_or_else(rand::random),
+            }
+        } else {
+            PropertyTestConfig {
+                num_cases: DEFAULT_NUM_CASES,
+                seed: self.fuzz_seed.unwrap_or_default(),
+            }
+        }
+    }
+
     fn compile_to_test_plan(
         &self,
         source_files: Vec<String>,
@@ -234,7 +298,29 @@ impl UnitTestingConfig {
             return Ok((shared_writer.into_inner().unwrap(), true));
         }
 
+        // The structured reports are written to a file, so that they aren't mixed up with the
+        // human readable output (or anything else the caller prints)
+        let report_file = match (self.report_format, &self.report_file) {
+            (TestReportFormat::Human, _) => None,
+            (_, Some(report_file)) => Some(report_file),
+            (_, None) => {
+                return Err(Error::new(
+                    ErrorKind::InvalidInput,
+                    "The JSON and JUnit test reports require a report file",
+                ))
+            },
+        };
+
+        let property_test_config = self.property_test_config();
         writeln!(shared_writer.lock().unwrap(), "Running Move unit tests")?;
+        if self.fuzz {
+            writeln!(
+                shared_writer.lock().unwrap(),
+                "Fuzzing property-based tests with {} cases from seed {}",
+                property_test_config.num_cases,
+                property_test_config.seed
+            )?;
+        }
         let mut test_runner = TestRunner::new(
             self.gas_limit.unwrap_or(DEFAULT_EXECUTION_BOUND),
             self.num_threads,
@@ -244,6 +330,8 @@ impl UnitTestingConfig {
             native_function_table,
             cost_table,
             self.verbose,
+            /* capture_debug_output */ report_file.is_some(),
+            property_test_config,
             #[cfg(feature = "evm-backend")]
             self.evm,
         )
@@ -254,6 +342,10 @@ impl UnitTestingConfig {
         }
 
         let test_results = test_runner.run(&shared_writer).unwrap();
+        if let Some(report_file) = report_file {
+            test_results.report(self.report_format, &mut File::create(report_file)?)?;
+        }
+
         if self.report_statistics {
             test_results.report_statistics(&shared_writer)?;
         }
@@ -0,0 +1,538 @@
+// Copyright (c) The Move Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+//! Generation and shrinking of the arguments of property-based tests, i.e., of `#[test]`
+//! functions with parameters which are not assigned in the test attribute.
+
+use anyhow::{anyhow, bail, Result};
+use move_binary_format::{
+    access::ModuleAccess,
+    file_format::{
+        CompiledModule, FunctionDefinitionIndex, SignatureToken, StructFieldInformation,
+        StructHandleIndex, TableIndex,
+    },
+};
+use move_compiler::{compiled_unit::NamedCompiledModule, unit_test::TestCase};
+use move_core_types::{
+    account_address::AccountAddress,
+    language_storage::ModuleId,
+    u256::U256,
+    value::{MoveStruct, MoveValue},
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+/// The number of cases a property-based test is run with, unless fuzzing
+pub const DEFAULT_NUM_CASES: u64 = 16;
+
+/// The number of cases a property-based test is run with when fuzzing, unless specified
+pub const DEFAULT_NUM_FUZZ_CASES: u64 = 256;
+
+/// The maximum length of generated vectors and strings
+const MAX_VECTOR_LENGTH: usize = 16;
+
+/// The maximum number of executions spent on shrinking the arguments of a failing test
+pub const MAX_SHRINK_RUNS: usize = 1024;
+
+/// How the arguments of property-based tests are generated
+#[derive(Debug, Clone, Copy)]
+pub struct PropertyTestConfig {
+    /// The number of cases each property-based test is run with
+    pub num_cases: u64,
+    /// The seed the arguments of all tests are generated from
+    pub seed: u64,
+}
+
+impl Default for PropertyTestConfig {
+    fn default() -> Self {
+        Self {
+            num_cases: DEFAULT_NUM_CASES,
+            seed: 0,
+        }
+    }
+}
+
+impl PropertyTestConfig {
+    /// The generator for the cases of a test. It only depends on the seed and the name of the
+    /// test, so a failing case is reproduced by the same seed regardless of which other tests
+    /// are run, and in which order.
+    pub fn rng_for_test(&self, module_id: &ModuleId, function_name: &str) -> StdRng {
+        // FNV-1a, as the hashers of the standard library are not guaranteed to be stable
+        let hash = format!("{}::{}", module_id, function_name)
+            .bytes()
+            .fold(0xCBF2_9CE4_8422_2325u64, |hash, byte| {
+                (hash ^ byte as u64).wrapping_mul(0x0100_0000_01B3)
+            });
+        StdRng::seed_from_u64(self.seed ^ hash)
+    }
+}
+
+/// The type of a generated argument
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgumentType {
+    Bool,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    U256,
+    Address,
+    Vector(Box<ArgumentType>),
+    /// `std::string::String` or `std::ascii::String`, which are generated as printable ASCII
+    String,
+    /// `std::option::Option`, whose vector has at most one element
+    Option(Box<ArgumentType>),
+    Struct {
+        name: String,
+        fields: Vec<(String, ArgumentType)>,
+    },
+}
+
+/// A parameter of a test whose argument is generated
+#[derive(Debug, Clone)]
+pub struct GeneratedParameter {
+    pub name: String,
+    pub ty: ArgumentType,
+}
+
+/// Resolves the names and types of the generated parameters of the test `function_name` in
+/// `module_id`. Fails if arguments of one of the types (e.g., a struct with a native field) can
+/// not be generated.
+pub fn generated_parameters(
+    module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
+    module_id: &ModuleId,
+    function_name: &str,
+    test_case: &TestCase,
+) -> Result<Vec<GeneratedParameter>> {
+    let named_module = module_info
+        .get(module_id)
+        .ok_or_else(|| anyhow!("Module {} not found", module_id))?;
+    let module = &named_module.module;
+    let (fdef_idx, fdef) = module
+        .function_defs()
+        .iter()
+        .enumerate()
+        .find(|(_, fdef)| {
+            let handle = module.function_handle_at(fdef.function);
+            module.identifier_at(handle.name).as_str() == function_name
+        })
+        .ok_or_else(|| anyhow!("Function {}::{} not found", module_id, function_name))?;
+    let handle = module.function_handle_at(fdef.function);
+    let parameters = &module.signature_at(handle.parameters).0;
+    let parameter_names = named_module
+        .source_map
+        .get_function_source_map(FunctionDefinitionIndex(fdef_idx as TableIndex))
+        .ok()
+        .map(|function_source_map| function_source_map.parameters.clone())
+        .unwrap_or_default();
+
+    test_case
+        .generated_parameters
+        .iter()
+        .map(|idx| {
+            let token = parameters
+                .get(*idx)
+                .ok_or_else(|| anyhow!("Parameter {} of {} not found", idx, function_name))?;
+            let name = parameter_names
+                .get(*idx)
+                .map(|(name, _)| name.clone())
+                .unwrap_or_else(|| format!("arg{}", idx));
+            Ok(GeneratedParameter {
+                name,
+                ty: ArgumentType::resolve(module_info, module, token, &[])?,
+            })
+        })
+        .collect()
+}
+
+impl ArgumentType {
+    fn resolve(
+        module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
+        module: &CompiledModule,
+        token: &SignatureToken,
+        ty_args: &[ArgumentType],
+    ) -> Result<Self> {
+        Ok(match token {
+            SignatureToken::Bool => Self::Bool,
+            SignatureToken::U8 => Self::U8,
+            SignatureToken::U16 => Self::U16,
+            SignatureToken::U32 => Self::U32,
+            SignatureToken::U64 => Self::U64,
+            SignatureToken::U128 => Self::U128,
+            SignatureToken::U256 => Self::U256,
+            SignatureToken::Address => Self::Address,
+            SignatureToken::Vector(elem) => {
+                Self::Vector(Box::new(Self::resolve(module_info, module, elem, ty_args)?))
+            },
+            SignatureToken::Struct(idx) => Self::resolve_struct(module_info, module, *idx, vec![])?,
+            SignatureToken::StructInstantiation(idx, tokens) => {
+                let struct_ty_args = tokens
+                    .iter()
+                    .map(|token| Self::resolve(module_info, module, token, ty_args))
+                    .collect::<Result<Vec<_>>>()?;
+                Self::resolve_struct(module_info, module, *idx, struct_ty_args)?
+            },
+            SignatureToken::TypeParameter(idx) => ty_args
+                .get(*idx as usize)
+                .cloned()
+                .ok_or_else(|| anyhow!("Unbound type parameter {}", idx))?,
+            SignatureToken::Signer
+            | SignatureToken::Reference(_)
+            | SignatureToken::MutableReference(_) => {
+                bail!("Cannot generate arguments of type {:?}", token)
+            },
+        })
+    }
+
+    fn resolve_struct(
+        module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
+        module: &CompiledModule,
+        idx: StructHandleIndex,
+        ty_args: Vec<ArgumentType>,
+    ) -> Result<Self> {
+        let handle = module.struct_handle_at(idx);
+        let module_id = module.module_id_for_handle(module.module_handle_at(handle.module));
+        let name = module.identifier_at(handle.name);
+
+        // Structs of the standard library with invariants on their fields
+        if module_id.address() == &AccountAddress::ONE {
+            match (module_id.name().as_str(), name.as_str(), ty_args.as_slice()) {
+                ("string", "String", []) | ("ascii", "String", []) => return Ok(Self::String),
+                ("option", "Option", [ty]) => return Ok(Self::Option(Box::new(ty.clone()))),
+                _ => (),
+            }
+        }
+
+        let defining_module = &module_info
+            .get(&module_id)
+            .ok_or_else(|| anyhow!("Module {} not found", module_id))?
+            .module;
+        let struct_def = defining_module
+            .struct_defs()
+            .iter()
+            .find(|def| {
+                defining_module
+                    .identifier_at(defining_module.struct_handle_at(def.struct_handle).name)
+                    == name
+            })
+            .ok_or_else(|| anyhow!("Struct {}::{} not found", module_id, name))?;
+        let fields = match &struct_def.field_information {
+            StructFieldInformation::Native => bail!(
+                "Cannot generate arguments of native struct {}::{}",
+                module_id,
+                name
+            ),
+            StructFieldInformation::Declared(fields) => fields,
+        };
+        let fields = fields
+            .iter()
+            .map(|field| {
+                Ok((
+                    defining_module.identifier_at(field.name).to_string(),
+                    Self::resolve(module_info, defining_module, &field.signature.0, &ty_args)?,
+                ))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self::Struct {
+            name: format!("{}::{}", module_id, name),
+            fields,
+        })
+    }
+
+    /// The number of bits of an integer type
+    fn int_bits(&self) -> Option<u32> {
+        match self {
+            Self::U8 => Some(8),
+            Self::U16 => Some(16),
+            Self::U32 => Some(32),
+            Self::U64 => Some(64),
+            Self::U128 => Some(128),
+            Self::U256 => Some(256),
+            _ => None,
+        }
+    }
+
+    /// The maximum of an integer of `bits` bits
+    fn int_max(bits: u32) -> U256 {
+        U256::max_value() >> (256 - bits) as u8
+    }
+
+    fn int_value(&self, value: U256) -> MoveValue {
+        match self {
+            Self::U8 => MoveValue::U8(value.unchecked_as_u8()),
+            Self::U16 => MoveValue::U16(value.unchecked_as_u16()),
+            Self::U32 => MoveValue::U32(value.unchecked_as_u32()),
+            Self::U64 => MoveValue::U64(value.unchecked_as_u64()),
+            Self::U128 => MoveValue::U128(value.unchecked_as_u128()),
+            _ => MoveValue::U256(value),
+        }
+    }
+
+    fn as_u256(value: &MoveValue) -> Option<U256> {
+        Some(match value {
+            MoveValue::U8(x) => U256::from(*x),
+            MoveValue::U16(x) => U256::from(*x),
+            MoveValue::U32(x) => U256::from(*x),
+            MoveValue::U64(x) => U256::from(*x),
+            MoveValue::U128(x) => U256::from(*x),
+            MoveValue::U256(x) => *x,
+            _ => return None,
+        })
+    }
+
+    /// Generates a random value of this type. Boundary values (e.g., zero and the maximum of
+    /// integers, empty vectors) and small values are generated more often than their share of
+    /// the domain, as they are more likely to hit edge cases.
+    pub fn generate<R: Rng>(&self, rng: &mut R) -> MoveValue {
+        if let Some(bits) = self.int_bits() {
+            let max = Self::int_max(bits);
+            let value = match rng.gen_range(0..8) {
+                0 => U256::zero(),
+                1 => U256::one(),
+                2 => max,
+                3 => max - U256::one(),
+                4 | 5 => U256::from(rng.gen_range(0u8..=16)),
+                _ => {
+                    // Uniform among a random number of bits, so that values of all magnitudes
+                    // are generated
+                    let mut bytes = [0u8; 32];
+                    rng.fill(&mut bytes);
+                    let width = rng.gen_range(1..=bits);
+                    U256::from_le_bytes(&bytes) & Self::int_max(width)
+                },
+            };
+            return self.int_value(value);
+        }
+        match self {
+            Self::Bool => MoveValue::Bool(rng.gen()),
+            Self::Address => MoveValue::Address(match rng.gen_range(0..4) {
+                0 => AccountAddress::ZERO,
+                1 => AccountAddress::ONE,
+                _ => AccountAddress::new(rng.gen()),
+            }),
+            Self::Vector(elem) => {
+                let len = if rng.gen_range(0..8) == 0 {
+                    0
+                } else {
+                    rng.gen_range(0..=MAX_VECTOR_LENGTH)
+                };
+                MoveValue::Vector((0..len).map(|_| elem.generate(rng)).collect())
+            },
+            Self::String => {
+                let len = rng.gen_range(0..=MAX_VECTOR_LENGTH);
+                let bytes = (0..len)
+                    .map(|_| MoveValue::U8(rng.gen_range(b' '..=b'~')))
+                    .collect();
+                MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(bytes)]))
+            },
+            Self::Option(elem) => {
+                let value = if rng.gen() {
+                    vec![elem.generate(rng)]
+                } else {
+                    vec![]
+                };
+                MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(value)]))
+            },
+            Self::Struct { fields, .. } => MoveValue::Struct(MoveStruct::Runtime(
+                fields.iter().map(|(_, ty)| ty.generate(rng)).collect(),
+            )),
+            _ => unreachable!("integer types are handled above"),
+        }
+    }
+
+    /// Returns values of this type which are "simpler" than `value`, the simplest first. A
+    /// failing test is retried with these to find a minimal failing input.
+    pub fn shrink(&self, value: &MoveValue) -> Vec<MoveValue> {
+        if self.int_bits().is_some() {
+            let x = match Self::as_u256(value) {
+                Some(x) => x,
+                None => return vec![],
+            };
+            let mut candidates: Vec<U256> = vec![];
+            for candidate in [
+                Some(U256::zero()),
+                x.checked_div(U256::from(2u8)),
+                x.checked_sub(U256::one()),
+            ]
+            .into_iter()
+            .flatten()
+            {
+                if candidate < x && !candidates.contains(&candidate) {
+                    candidates.push(candidate)
+                }
+            }
+            return candidates
+                .into_iter()
+                .map(|candidate| self.int_value(candidate))
+                .collect();
+        }
+        match (self, value) {
+            (Self::Bool, MoveValue::Bool(true)) => vec![MoveValue::Bool(false)],
+            (Self::Address, MoveValue::Address(address)) if *address != AccountAddress::ZERO => {
+                vec![MoveValue::Address(AccountAddress::ZERO)]
+            },
+            (Self::Vector(elem), MoveValue::Vector(values)) => Self::shrink_vector(elem, values)
+                .into_iter()
+                .map(MoveValue::Vector)
+                .collect(),
+            (Self::String, MoveValue::Struct(MoveStruct::Runtime(fields))) => match &fields[..] {
+                // Only shrink by removing characters, so the string stays printable
+                [MoveValue::Vector(bytes)] => Self::shrink_vector_length(bytes)
+                    .into_iter()
+                    .map(|bytes| {
+                        MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(bytes)]))
+                    })
+                    .collect(),
+                _ => vec![],
+            },
+            (Self::Option(elem), MoveValue::Struct(MoveStruct::Runtime(fields))) => {
+                match &fields[..] {
+                    [MoveValue::Vector(values)] => Self::shrink_vector(elem, values)
+                        .into_iter()
+                        .map(|values| {
+                            MoveValue::Struct(MoveStruct::Runtime(vec![MoveValue::Vector(values)]))
+                        })
+                        .collect(),
+                    _ => vec![],
+                }
+            },
+            (
+                Self::Struct { fields: types, .. },
+                MoveValue::Struct(MoveStruct::Runtime(fields)),
+            ) => {
+                let types: Vec<_> = types.iter().map(|(_, ty)| ty.clone()).collect();
+                shrink_arguments(&types, fields)
+                    .into_iter()
+                    .map(|fields| MoveValue::Struct(MoveStruct::Runtime(fields)))
+                    .collect()
+            },
+            _ => vec![],
+        }
+    }
+
+    /// Shrinks a vector by removing elements
+    fn shrink_vector_length(values: &[MoveValue]) -> Vec<Vec<MoveValue>> {
+        if values.is_empty() {
+            return vec![];
+        }
+        let mut candidates = vec![vec![]];
+        if values.len() > 1 {
+            candidates.push(values[..values.len() / 2].to_vec());
+            candidates.extend((0..values.len()).map(|idx| {
+                let mut shorter = values.to_vec();
+                shorter.remove(idx);
+                shorter
+            }));
+        }
+        candidates
+    }
+
+    /// Shrinks a vector by removing elements, then by shrinking the elements
+    fn shrink_vector(elem: &ArgumentType, values: &[MoveValue]) -> Vec<Vec<MoveValue>> {
+        let mut candidates = Self::shrink_vector_length(values);
+        let types = vec![elem.clone(); values.len()];
+        candidates.extend(shrink_arguments(&types, values));
+        candidates
+    }
+}
+
+/// Returns the argument lists obtained by shrinking one of `values`, which are of `types`
+pub fn shrink_arguments(types: &[ArgumentType], values: &[MoveValue]) -> Vec<Vec<MoveValue>> {
+    types
+        .iter()
+        .zip(values)
+        .enumerate()
+        .flat_map(|(idx, (ty, value))| {
+            ty.shrink(value).into_iter().map(move |shrunk| {
+                let mut shrunk_values = values.to_vec();
+                shrunk_values[idx] = shrunk;
+                shrunk_values
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_in_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..1000 {
+            match ArgumentType::U8.generate(&mut rng) {
+                MoveValue::U8(_) => (),
+                value => panic!("unexpected value {}", value),
+            }
+            match ArgumentType::Option(Box::new(ArgumentType::U64)).generate(&mut rng) {
+                MoveValue::Struct(MoveStruct::Runtime(fields)) => match &fields[..] {
+                    [MoveValue::Vector(values)] => assert!(values.len() <= 1),
+                    _ => panic!("unexpected option fields"),
+                },
+                value => panic!("unexpected value {}", value),
+            }
+            match ArgumentType::String.generate(&mut rng) {
+                MoveValue::Struct(MoveStruct::Runtime(fields)) => match &fields[..] {
+                    [MoveValue::Vector(bytes)] => assert!(bytes
+                        .iter()
+                        .all(|byte| matches!(byte, MoveValue::U8(b' '..=b'~')))),
+                    _ => panic!("unexpected string fields"),
+                },
+                value => panic!("unexpected value {}", value),
+            }
+        }
+    }
+
+    #[test]
+    fn test_rng_for_test_is_deterministic() {
+        let module_id = ModuleId::new(
+            AccountAddress::ONE,
+            move_core_types::identifier::Identifier::new("m").unwrap(),
+        );
+        let config = PropertyTestConfig {
+            num_cases: 1,
+            seed: 42,
+        };
+        let generate = |function_name| {
+            let mut rng = config.rng_for_test(&module_id, function_name);
+            ArgumentType::U128.generate(&mut rng)
+        };
+        assert_eq!(generate("f"), generate("f"));
+    }
+
+    #[test]
+    fn test_shrink_int() {
+        assert_eq!(ArgumentType::U64.shrink(&MoveValue::U64(0)), vec![]);
+        assert_eq!(ArgumentType::U64.shrink(&MoveValue::U64(1)), vec![
+            MoveValue::U64(0)
+        ]);
+        assert_eq!(ArgumentType::U8.shrink(&MoveValue::U8(255)), vec![
+            MoveValue::U8(0),
+            MoveValue::U8(127),
+            MoveValue::U8(254)
+        ]);
+    }
+
+    #[test]
+    fn test_shrink_to_minimal() {
+        // Shrink to the minimal vector with an element of at least 10, as a test failing for
+        // such vectors would
+        let ty = ArgumentType::Vector(Box::new(ArgumentType::U64));
+        let fails = |value: &MoveValue| match value {
+            MoveValue::Vector(values) => values
+                .iter()
+                .any(|x| matches!(x, MoveValue::U64(x) if *x >= 10)),
+            _ => false,
+        };
+        let mut value = MoveValue::Vector(vec![
+            MoveValue::U64(3),
+            MoveValue::U64(1000),
+            MoveValue::U64(7),
+        ]);
+        while let Some(shrunk) = ty.shrink(&value).into_iter().find(|value| fails(value)) {
+            value = shrunk;
+        }
+        assert_eq!(value, MoveValue::Vector(vec![MoveValue::U64(10)]));
+    }
+}
@@ -2,12 +2,13 @@
 // Copyright (c) The Move Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::format_module_id;
+use crate::{format_module_id, TestReportFormat};
 use codespan_reporting::files::{Files, SimpleFiles};
 use colored::{control, Colorize};
 use move_binary_format::{
     access::ModuleAccess,
     errors::{ExecutionState, Location, VMError, VMResult},
+    file_format::CodeOffset,
 };
 use move_command_line_common::{env::read_bool_env_var, files::FileHash};
 pub use move_compiler::unit_test::ExpectedMoveError as MoveError;
@@ -15,10 +16,15 @@ use move_compiler::{
     diagnostics::{self, Diagnostic, Diagnostics},
     unit_test::{ModuleTestPlan, TestName, TestPlan},
 };
-use move_core_types::{effects::ChangeSet, language_storage::ModuleId, vm_status::StatusType};
+use move_core_types::{
+    effects::ChangeSet,
+    language_storage::ModuleId,
+    vm_status::{StatusCode, StatusType},
+};
 use move_ir_types::location::Loc;
 use move_symbol_pool::Symbol;
 use once_cell::sync::Lazy;
+use serde::Serialize;
 use std::{
     collections::{BTreeMap, BTreeSet, HashMap},
     io::{Result, Write},
@@ -47,6 +53,14 @@ pub enum FailureReason {
     },
     // Property checking failed
     Property(String),
+    // A property-based test failed with the (shrunk) generated arguments, rendered as
+    // `name = value` pairs
+    GeneratedArguments {
+        seed: u64,
+        num_cases: u64,
+        arguments: String,
+        reason: Box<FailureReason>,
+    },
 
     // Failed to compile Move code into EVM bytecode.
     #[cfg(feature = "evm-backend")]
@@ -66,6 +80,49 @@ pub struct TestRunInfo {
     pub function_ident: String,
     pub elapsed_time: Duration,
     pub instructions_executed: u64,
+    pub gas_used: u64,
+    /// The output of the debugging natives (e.g., `std::debug::print`), if it was captured
+    pub debug_output: String,
+}
+
+/// Where a failing test aborted
+#[derive(Debug, Clone, Serialize)]
+pub struct AbortLocation {
+    pub module: String,
+    pub function: String,
+    pub code_offset: CodeOffset,
+    pub file: Option<String>,
+    pub line: Option<usize>,
+}
+
+/// The result of a single test in a structured (JSON) report
+#[derive(Debug, Serialize)]
+struct TestCaseReport {
+    module: String,
+    name: String,
+    passed: bool,
+    duration_secs: f64,
+    instructions_executed: u64,
+    gas_used: u64,
+    failure: Option<TestFailureReport>,
+    output: String,
+}
+
+#[derive(Debug, Serialize)]
+struct TestFailureReport {
+    kind: &'static str,
+    message: String,
+    abort_code: Option<u64>,
+    location: Option<AbortLocation>,
+}
+
+/// A structured (JSON) report of a test run
+#[derive(Debug, Serialize)]
+struct TestRunReport {
+    total: usize,
+    passed: usize,
+    failed: usize,
+    tests: Vec<TestCaseReport>,
 }
 
 #[derive(Debug, Clone)]
@@ -82,13 +139,25 @@ pub struct TestResults {
 }
 
 impl TestRunInfo {
-    pub fn new(function_ident: String, elapsed_time: Duration, instructions_executed: u64) -> Self {
+    pub fn new(
+        function_ident: String,
+        elapsed_time: Duration,
+        instructions_executed: u64,
+        gas_used: u64,
+    ) -> Self {
         Self {
             function_ident,
             elapsed_time,
             instructions_executed,
+            gas_used,
+            debug_output: String::new(),
         }
     }
+
+    pub fn with_debug_output(mut self, debug_output: String) -> Self {
+        self.debug_output = debug_output;
+        self
+    }
 }
 
 impl FailureReason {
@@ -138,6 +207,20 @@ impl FailureReason {
         FailureReason::Property(details)
     }
 
+    pub fn generated_arguments(
+        seed: u64,
+        num_cases: u64,
+        arguments: String,
+        reason: FailureReason,
+    ) -> Self {
+        FailureReason::GeneratedArguments {
+            seed,
+            num_cases,
+            arguments,
+            reason: Box::new(reason),
+        }
+    }
+
     #[cfg(feature = "evm-backend")]
     pub fn move_to_evm_error(diagnostics: String) -> Self {
         FailureReason::MoveToEVMError(diagnostics)
@@ -160,7 +243,35 @@ impl TestFailure {
     }
 
     pub fn render_error(&self, test_plan: &TestPlan) -> String {
-        let error_string = match &self.failure_reason {
+        self.render(test_plan, control::SHOULD_COLORIZE.should_colorize())
+    }
+
+    fn render(&self, test_plan: &TestPlan, colorize: bool) -> String {
+        let error_string = self.render_reason(&self.failure_reason, test_plan, colorize);
+
+        match &self.storage_state {
+            None => error_string,
+            Some(storage_state) => {
+                format!(
+                    "{}\n────── Storage state at point of failure ──────\n{}",
+                    error_string,
+                    if storage_state.is_empty() {
+                        "<empty>"
+                    } else {
+                        storage_state
+                    }
+                )
+            },
+        }
+    }
+
+    fn render_reason(
+        &self,
+        failure_reason: &FailureReason,
+        test_plan: &TestPlan,
+        colorize: bool,
+    ) -> String {
+        match failure_reason {
             FailureReason::NoError(message) => message.to_string(),
             FailureReason::Timeout(message) => message.to_string(),
             FailureReason::WrongError(message, expected, actual) => {
@@ -169,7 +280,7 @@ impl TestFailure {
                     expected.verbiage(/* is_past_tense */ false),
                     actual.verbiage(/* is_past_tense */ true),
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, colorize)
             },
             FailureReason::WrongAbortDEPRECATED(message, expected_code, actual) => {
                 let base_message = format!(
@@ -179,7 +290,7 @@ impl TestFailure {
                     expected_code,
                     actual.verbiage(/* is_past_tense */ true),
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, colorize)
             },
             FailureReason::UnexpectedError(message, error) => {
                 let prefix = match error.0.status_type() {
@@ -203,7 +314,7 @@ impl TestFailure {
                     message,
                     error.verbiage(/* is_past_tense */ true)
                 );
-                Self::report_error_with_location(test_plan, base_message, &self.vm_error)
+                Self::report_error_with_location(test_plan, base_message, &self.vm_error, colorize)
             },
             FailureReason::Mismatch {
                 move_vm_return_values,
@@ -225,6 +336,19 @@ impl TestFailure {
                 )
             },
             FailureReason::Property(message) => message.clone(),
+            FailureReason::GeneratedArguments {
+                seed,
+                num_cases,
+                arguments,
+                reason,
+            } => format!(
+                "{}\nFailing generated arguments: {}\n\
+                Rerun with `--fuzz --fuzz-seed {} --fuzz-cases {}` to reproduce",
+                self.render_reason(reason, test_plan, colorize),
+                arguments,
+                seed,
+                num_cases
+            ),
 
             #[cfg(feature = "evm-backend")]
             FailureReason::MoveToEVMError(diagnostics) => {
@@ -233,24 +357,70 @@ impl TestFailure {
                     diagnostics
                 )
             },
-        };
+        }
+    }
 
-        match &self.storage_state {
-            None => error_string,
-            Some(storage_state) => {
-                format!(
-                    "{}\n────── Storage state at point of failure ──────\n{}",
-                    error_string,
-                    if storage_state.is_empty() {
-                        "<empty>"
-                    } else {
-                        storage_state
-                    }
-                )
-            },
+    /// A short identifier of the kind of failure, e.g., for the `type` of a JUnit failure
+    pub fn failure_kind(&self) -> &'static str {
+        Self::reason_kind(&self.failure_reason)
+    }
+
+    fn reason_kind(failure_reason: &FailureReason) -> &'static str {
+        match failure_reason {
+            FailureReason::NoError(_) => "no_error",
+            FailureReason::WrongError(..) => "wrong_error",
+            FailureReason::WrongAbortDEPRECATED(..) => "wrong_abort",
+            FailureReason::UnexpectedError(..) => "unexpected_error",
+            FailureReason::Timeout(_) => "timeout",
+            FailureReason::Mismatch { .. } => "mismatch",
+            FailureReason::Property(_) => "property",
+            FailureReason::GeneratedArguments { reason, .. } => Self::reason_kind(reason),
+            #[cfg(feature = "evm-backend")]
+            FailureReason::MoveToEVMError(_) => "move_to_evm_error",
         }
     }
 
+    /// The location the test aborted (or otherwise failed) at, if it failed with a VM error in
+    /// a module
+    pub fn abort_location(&self, test_plan: &TestPlan) -> Option<AbortLocation> {
+        let vm_error = self.vm_error.as_ref()?;
+        let module_id = match vm_error.location() {
+            Location::Module(module_id) => module_id,
+            _ => return None,
+        };
+        let (fdef_idx, offset) = *vm_error.offsets().first()?;
+        let named_module = test_plan.module_info.get(module_id)?;
+        let fn_handle_idx = named_module.module.function_def_at(fdef_idx).function;
+        let fn_id_idx = named_module.module.function_handle_at(fn_handle_idx).name;
+
+        let loc = named_module
+            .source_map
+            .get_function_source_map(fdef_idx)
+            .ok()
+            .and_then(|function_source_map| function_source_map.get_code_location(offset));
+        let (file, line) =
+            match loc.and_then(|loc| Some((loc, test_plan.files.get(&loc.file_hash())?))) {
+                Some((loc, (file_name, source))) => {
+                    let mut files = SimpleFiles::new();
+                    let id = files.add(*file_name, source.as_str());
+                    let line = files
+                        .line_index(id, loc.start() as usize)
+                        .and_then(|line_index| files.line_number(id, line_index))
+                        .ok();
+                    (Some(file_name.to_string()), line)
+                },
+                None => (None, None),
+            };
+
+        Some(AbortLocation {
+            module: format_module_id(module_id),
+            function: named_module.module.identifier_at(fn_id_idx).to_string(),
+            code_offset: offset,
+            file,
+            line,
+        })
+    }
+
     fn get_line_number(
         loc: &Loc,
         files: &SimpleFiles<Symbol, &str>,
@@ -333,8 +503,9 @@ impl TestFailure {
         test_plan: &TestPlan,
         base_message: String,
         vm_error: &Option<VMError>,
+        colorize: bool,
     ) -> String {
-        let report_diagnostics = if control::SHOULD_COLORIZE.should_colorize() {
+        let report_diagnostics = if colorize {
             diagnostics::report_diagnostics_to_color_buffer
         } else {
             diagnostics::report_diagnostics_to_buffer
@@ -485,7 +656,7 @@ impl TestResults {
                 stats.push((
                     qualified_function_name,
                     test_result.elapsed_time.as_secs_f32(),
-                    test_result.instructions_executed,
+                    test_result.gas_used,
                 ))
             }
         }
@@ -502,7 +673,7 @@ impl TestResults {
                 stats.push((
                     qualified_function_name,
                     test_failure.test_run_info.elapsed_time.as_secs_f32(),
-                    test_failure.test_run_info.instructions_executed,
+                    test_failure.test_run_info.gas_used,
                 ));
             }
         }
@@ -518,14 +689,14 @@ impl TestResults {
             )?;
             writeln!(
                 writer.lock().unwrap(),
-                "│ {name:^width$} │ {time:^10} │ {instructions:^25} │",
+                "│ {name:^width$} │ {time:^10} │ {gas_used:^25} │",
                 width = max_function_name_size,
                 name = "Test Name",
                 time = "Time",
-                instructions = "Gas Used"
+                gas_used = "Gas Used"
             )?;
 
-            for (qualified_function_name, time, instructions) in stats {
+            for (qualified_function_name, time, gas_used) in stats {
                 writeln!(
                     writer.lock().unwrap(),
                     "├─{:─^width$}─┼─{:─^10}─┼─{:─^25}─┤",
@@ -536,11 +707,11 @@ impl TestResults {
                 )?;
                 writeln!(
                     writer.lock().unwrap(),
-                    "│ {name:<width$} │ {time:^10.3} │ {instructions:^25} │",
+                    "│ {name:<width$} │ {time:^10.3} │ {gas_used:^25} │",
                     name = qualified_function_name,
                     width = max_function_name_size,
                     time = time,
-                    instructions = instructions,
+                    gas_used = gas_used,
                 )?;
             }
 
@@ -557,7 +728,167 @@ impl TestResults {
         writeln!(writer.lock().unwrap())
     }
 
-    /// Returns `true` if all tests passed, `false` if there was a test failure/timeout
+    /// Collects the result of each test, ordered by module and test name
+    fn test_case_reports(&self) -> Vec<TestCaseReport> {
+        let mut reports = vec![];
+        for (module_id, test_results) in &self.final_statistics.passed {
+            for test_result in test_results {
+                reports.push(Self::test_case_report(module_id, test_result, None));
+            }
+        }
+        for (module_id, test_failures) in &self.final_statistics.failed {
+            for test_failure in test_failures {
+                let abort_code = test_failure
+                    .vm_error
+                    .as_ref()
+                    .filter(|vm_error| vm_error.major_status() == StatusCode::ABORTED)
+                    .and_then(|vm_error| vm_error.sub_status());
+                let failure = TestFailureReport {
+                    kind: test_failure.failure_kind(),
+                    message: test_failure.render(&self.test_plan, /* colorize */ false),
+                    abort_code,
+                    location: test_failure.abort_location(&self.test_plan),
+                };
+                reports.push(Self::test_case_report(
+                    module_id,
+                    &test_failure.test_run_info,
+                    Some(failure),
+                ));
+            }
+        }
+        reports.sort_by(|a, b| (&a.module, &a.name).cmp(&(&b.module, &b.name)));
+        reports
+    }
+
+    fn test_case_report(
+        module_id: &ModuleId,
+        test_run_info: &TestRunInfo,
+        failure: Option<TestFailureReport>,
+    ) -> TestCaseReport {
+        TestCaseReport {
+            module: format_module_id(module_id),
+            name: test_run_info.function_ident.clone(),
+            passed: failure.is_none(),
+            duration_secs: test_run_info.elapsed_time.as_secs_f64(),
+            instructions_executed: test_run_info.instructions_executed,
+            gas_used: test_run_info.gas_used,
+            failure,
+            output: test_run_info.debug_output.clone(),
+        }
+    }
+
+    /// Writes the report in the given format. Nothing is written for the human readable format,
+    /// which is printed by `summarize`.
+    pub fn report<W: Write>(&self, format: TestReportFormat, writer: &mut W) -> Result<()> {
+        match format {
+            TestReportFormat::Human => Ok(()),
+            TestReportFormat::Json => self.report_json(writer),
+            TestReportFormat::Junit => self.report_junit(writer),
+        }
+    }
+
+    /// Writes the result of each test as a JSON document
+    pub fn report_json<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let tests = self.test_case_reports();
+        let passed = tests.iter().filter(|test| test.passed).count();
+        let report = TestRunReport {
+            total: tests.len(),
+            passed,
+            failed: tests.len() - passed,
+            tests,
+        };
+        serde_json::to_writer_pretty(&mut *writer, &report)?;
+        writeln!(writer)
+    }
+
+    /// Writes the result of each test as a JUnit XML report, with one test suite per module
+    pub fn report_junit<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let tests = self.test_case_reports();
+        let mut suites: BTreeMap<&str, Vec<&TestCaseReport>> = BTreeMap::new();
+        for test in &tests {
+            suites.entry(test.module.as_str()).or_default().push(test);
+        }
+        let count_failures =
+            |tests: &[&TestCaseReport]| tests.iter().filter(|test| !test.passed).count();
+        let total_time =
+            |tests: &[&TestCaseReport]| tests.iter().map(|test| test.duration_secs).sum::<f64>();
+
+        let all_tests: Vec<_> = tests.iter().collect();
+        writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+        writeln!(
+            writer,
+            r#"<testsuites name="Move unit tests" tests="{}" failures="{}" time="{:.3}">"#,
+            all_tests.len(),
+            count_failures(&all_tests),
+            total_time(&all_tests)
+        )?;
+        for (module, tests) in suites {
+            writeln!(
+                writer,
+                r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="0" time="{:.3}">"#,
+                xml_escape(module),
+                tests.len(),
+                count_failures(&tests),
+                total_time(&tests)
+            )?;
+            for test in tests {
+                writeln!(
+                    writer,
+                    r#"    <testcase name="{}" classname="{}" time="{:.3}">"#,
+                    xml_escape(&test.name),
+                    xml_escape(module),
+                    test.duration_secs
+                )?;
+                writeln!(writer, "      <properties>")?;
+                writeln!(
+                    writer,
+                    r#"        <property name="instructions_executed" value="{}"/>"#,
+                    test.instructions_executed
+                )?;
+                writeln!(
+                    writer,
+                    r#"        <property name="gas_used" value="{}"/>"#,
+                    test.gas_used
+                )?;
+                writeln!(writer, "      </properties>")?;
+                if let Some(failure) = &test.failure {
+                    let summary = match &failure.location {
+                        Some(location) => format!(
+                            "{} in {}::{}{}",
+                            failure.kind,
+                            location.module,
+                            location.function,
+                            match (&location.file, location.line) {
+                                (Some(file), Some(line)) => format!(" ({}:{})", file, line),
+                                _ => String::new(),
+                            }
+                        ),
+                        None => failure.kind.to_string(),
+                    };
+                    writeln!(
+                        writer,
+                        r#"      <failure type="{}" message="{}">{}</failure>"#,
+                        failure.kind,
+                        xml_escape(&summary),
+                        xml_escape(&failure.message)
+                    )?;
+                }
+                if !test.output.is_empty() {
+                    writeln!(
+                        writer,
+                        "      <system-out>{}</system-out>",
+                        xml_escape(&test.output)
+                    )?;
+                }
+                writeln!(writer, "    </testcase>")?;
+            }
+            writeln!(writer, "  </testsuite>")?;
+        }
+        writeln!(writer, "</testsuites>")
+    }
+
+    /// Prints the test failures and a summary of the results. Returns `true` if all tests
+    /// passed, `false` if there was a test failure/timeout
     pub fn summarize<W: Write>(self, writer: &Mutex<W>) -> Result<bool> {
         let num_failed_tests = self
             .final_statistics
@@ -610,3 +941,23 @@ impl TestResults {
         Ok(num_failed_tests == 0)
     }
 }
+
+/// Escapes the characters with a special meaning in XML and replaces the ones XML 1.0 does not
+/// allow at all (e.g., control characters in printed byte strings) with U+FFFD.
+fn xml_escape(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            '\u{0}'..='\u{1F}' | '\u{FFFE}' | '\u{FFFF}' => {
+                escaped.push(char::REPLACEMENT_CHARACTER)
+            },
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -4,33 +4,45 @@
 
 use crate::{
     extensions, format_module_id,
+    property_test::{self, GeneratedParameter, PropertyTestConfig, MAX_SHRINK_RUNS},
     test_reporter::{
         FailureReason, MoveError, TestFailure, TestResults, TestRunInfo, TestStatistics,
     },
 };
 use anyhow::Result;
 use colored::*;
+use itertools::Itertools;
 use move_binary_format::{errors::VMResult, file_format::CompiledModule};
 use move_bytecode_utils::Modules;
-use move_compiler::unit_test::{ExpectedFailure, ModuleTestPlan, TestCase, TestPlan};
+use move_compiler::{
+    compiled_unit::NamedCompiledModule,
+    unit_test::{ExpectedFailure, ModuleTestPlan, TestCase, TestPlan},
+};
 use move_core_types::{
     account_address::AccountAddress,
     effects::{ChangeSet, Op},
     identifier::IdentStr,
-    value::serialize_values,
+    language_storage::ModuleId,
+    value::{serialize_values, MoveValue},
     vm_status::StatusCode,
 };
 use move_resource_viewer::MoveValueAnnotator;
 use move_vm_runtime::{
-    move_vm::MoveVM, native_extensions::NativeContextExtensions,
-    native_functions::NativeFunctionTable,
+    debug_output::capture_debug_output, move_vm::MoveVM,
+    native_extensions::NativeContextExtensions, native_functions::NativeFunctionTable,
 };
 use move_vm_test_utils::{
     gas_schedule::{zero_cost_schedule, CostTable, Gas, GasCost, GasStatus},
     InMemoryStorage,
 };
 use rayon::prelude::*;
-use std::{io::Write, marker::Send, sync::Mutex, time::Instant};
+use std::{
+    collections::BTreeMap,
+    io::Write,
+    marker::Send,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
 #[cfg(feature = "evm-backend")]
 use {
     evm::{backend::MemoryVicinity, ExitReason},
@@ -38,7 +50,6 @@ use {
     move_to_yul,
     primitive_types::{H160, U256},
     std::convert::TryInto,
-    std::time::Duration,
 };
 
 /// Test state common to all tests
@@ -52,6 +63,8 @@ pub struct SharedTestingConfig {
     #[allow(dead_code)] // used by some features
     source_files: Vec<String>,
     record_writeset: bool,
+    capture_debug_output: bool,
+    property_test_config: PropertyTestConfig,
 
     #[cfg(feature = "evm-backend")]
     evm: bool,
@@ -132,6 +145,10 @@ impl TestRunner {
         native_function_table: Option<NativeFunctionTable>,
         cost_table: Option<CostTable>,
         record_writeset: bool,
+        // Whether to capture the debug output of each test, instead of printing it
+        capture_debug_output: bool,
+        // How the arguments of property-based tests are generated
+        property_test_config: PropertyTestConfig,
         #[cfg(feature = "evm-backend")] evm: bool,
     ) -> Result<Self> {
         let source_files = tests
@@ -162,6 +179,8 @@ impl TestRunner {
                 cost_table: cost_table.unwrap_or_else(unit_cost_table),
                 source_files,
                 record_writeset,
+                capture_debug_output,
+                property_test_config,
                 #[cfg(feature = "evm-backend")]
                 evm,
             },
@@ -180,7 +199,13 @@ impl TestRunner {
                     .tests
                     .module_tests
                     .par_iter()
-                    .map(|(_, test_plan)| self.testing_config.exec_module_tests(test_plan, writer))
+                    .map(|(_, test_plan)| {
+                        self.testing_config.exec_module_tests(
+                            test_plan,
+                            &self.tests.module_info,
+                            writer,
+                        )
+                    })
                     .reduce(TestStatistics::new, |acc, stats| acc.combine(stats));
 
                 Ok(TestResults::new(final_statistics, self.tests))
@@ -252,7 +277,7 @@ impl SharedTestingConfig {
         &self,
         test_plan: &ModuleTestPlan,
         function_name: &str,
-        test_info: &TestCase,
+        arguments: &[MoveValue],
     ) -> (
         VMResult<ChangeSet>,
         VMResult<NativeContextExtensions>,
@@ -267,13 +292,20 @@ impl SharedTestingConfig {
         // TODO: collect VM logs if the verbose flag (i.e, `self.verbose`) is set
 
         let now = Instant::now();
-        let serialized_return_values_result = session.execute_function_bypass_visibility(
-            &test_plan.module_id,
-            IdentStr::new(function_name).unwrap(),
-            vec![], // no ty args, at least for now
-            serialize_values(test_info.arguments.iter()),
-            &mut gas_meter,
-        );
+        let mut execute = || {
+            session.execute_function_bypass_visibility(
+                &test_plan.module_id,
+                IdentStr::new(function_name).unwrap(),
+                vec![], // no ty args, at least for now
+                serialize_values(arguments),
+                &mut gas_meter,
+            )
+        };
+        let (serialized_return_values_result, debug_output) = if self.capture_debug_output {
+            capture_debug_output(execute)
+        } else {
+            (execute(), String::new())
+        };
         let mut return_result = serialized_return_values_result.map(|res| {
             res.return_values
                 .into_iter()
@@ -288,13 +320,13 @@ impl SharedTestingConfig {
         let test_run_info = TestRunInfo::new(
             function_name.to_string(),
             now.elapsed(),
-            // TODO(Gas): This doesn't look quite right...
-            //            We're not computing the number of instructions executed even with a unit gas schedule.
+            gas_meter.instructions_executed(),
             Gas::new(self.execution_bound)
                 .checked_sub(gas_meter.remaining_gas())
                 .unwrap()
                 .into(),
-        );
+        )
+        .with_debug_output(debug_output);
         match session.finish_with_extensions() {
             Ok((cs, extensions)) => (Ok(cs), Ok(extensions), return_result, test_run_info),
             Err(err) => (Err(err.clone()), Err(err), return_result, test_run_info),
@@ -304,140 +336,264 @@ impl SharedTestingConfig {
     fn exec_module_tests_move_vm_and_stackless_vm(
         &self,
         test_plan: &ModuleTestPlan,
+        module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
         output: &TestOutput<impl Write>,
     ) -> TestStatistics {
         let mut stats = TestStatistics::new();
 
         for (function_name, test_info) in &test_plan.tests {
-            let (cs_result, ext_result, exec_result, test_run_info) =
-                self.execute_via_move_vm(test_plan, function_name, test_info);
+            let result = if test_info.is_property_test() {
+                self.run_property_test(test_plan, module_info, function_name, test_info)
+            } else {
+                let (result, writeset) =
+                    self.run_test_case(test_plan, function_name, test_info, &test_info.arguments);
+                if let Some(writeset) = writeset {
+                    stats.test_output(function_name.to_string(), test_plan, writeset);
+                }
+                result
+            };
 
-            if self.record_writeset {
-                stats.test_output(
-                    function_name.to_string(),
-                    test_plan,
-                    format!("{:?}", cs_result),
-                );
+            match result {
+                Ok(test_run_info) => {
+                    output.pass(function_name);
+                    stats.test_success(test_run_info, test_plan);
+                },
+                Err(test_failure) => {
+                    if let FailureReason::Timeout(_) = test_failure.failure_reason {
+                        output.timeout(function_name);
+                    } else {
+                        output.fail(function_name);
+                    }
+                    stats.test_failure(test_failure, test_plan)
+                },
             }
+        }
 
-            let save_session_state = || {
-                if self.save_storage_state_on_failure {
-                    cs_result.ok().and_then(|changeset| {
-                        ext_result.ok().and_then(|extensions| {
-                            print_resources_and_extensions(
-                                &changeset,
-                                extensions,
-                                &self.starting_storage_state,
-                            )
-                            .ok()
-                        })
+        stats
+    }
+
+    /// Runs a test with the given arguments. Returns the information on the run if the test
+    /// passed, and the change set of the run if `record_writeset` is set.
+    fn run_test_case(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        arguments: &[MoveValue],
+    ) -> (
+        std::result::Result<TestRunInfo, TestFailure>,
+        Option<String>,
+    ) {
+        let (cs_result, ext_result, exec_result, test_run_info) =
+            self.execute_via_move_vm(test_plan, function_name, arguments);
+
+        let writeset = if self.record_writeset {
+            Some(format!("{:?}", cs_result))
+        } else {
+            None
+        };
+
+        let save_session_state = || {
+            if self.save_storage_state_on_failure {
+                cs_result.ok().and_then(|changeset| {
+                    ext_result.ok().and_then(|extensions| {
+                        print_resources_and_extensions(
+                            &changeset,
+                            extensions,
+                            &self.starting_storage_state,
+                        )
+                        .ok()
                     })
+                })
+            } else {
+                None
+            }
+        };
+        let result = match exec_result {
+            Err(err) => {
+                let actual_err =
+                    MoveError(err.major_status(), err.sub_status(), err.location().clone());
+                assert!(err.major_status() != StatusCode::EXECUTED);
+                match test_info.expected_failure.as_ref() {
+                    Some(ExpectedFailure::Expected) => Ok(test_run_info),
+                    Some(ExpectedFailure::ExpectedWithError(expected_err))
+                        if expected_err == &actual_err =>
+                    {
+                        Ok(test_run_info)
+                    },
+                    Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
+                        if actual_err.0 == StatusCode::ABORTED
+                            && actual_err.1.is_some()
+                            && actual_err.1.unwrap() == *code =>
+                    {
+                        Ok(test_run_info)
+                    },
+                    // incorrect cases
+                    Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
+                        Err(TestFailure::new(
+                            FailureReason::wrong_error(expected_err.clone(), actual_err),
+                            test_run_info,
+                            Some(err),
+                            save_session_state(),
+                        ))
+                    },
+                    Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
+                        Err(TestFailure::new(
+                            FailureReason::wrong_abort_deprecated(*expected_code, actual_err),
+                            test_run_info,
+                            Some(err),
+                            save_session_state(),
+                        ))
+                    },
+                    None if err.major_status() == StatusCode::OUT_OF_GAS => {
+                        // Ran out of ticks, report a test timeout and log a test failure
+                        Err(TestFailure::new(
+                            FailureReason::timeout(),
+                            test_run_info,
+                            Some(err),
+                            save_session_state(),
+                        ))
+                    },
+                    None => Err(TestFailure::new(
+                        FailureReason::unexpected_error(actual_err),
+                        test_run_info,
+                        Some(err),
+                        save_session_state(),
+                    )),
+                }
+            },
+            Ok(_) => {
+                // Expected the test to fail, but it executed
+                if test_info.expected_failure.is_some() {
+                    Err(TestFailure::new(
+                        FailureReason::no_error(),
+                        test_run_info,
+                        None,
+                        save_session_state(),
+                    ))
                 } else {
-                    None
+                    // Expected the test to execute fully and it did
+                    Ok(test_run_info)
                 }
-            };
-            match exec_result {
-                Err(err) => {
-                    let actual_err =
-                        MoveError(err.major_status(), err.sub_status(), err.location().clone());
-                    assert!(err.major_status() != StatusCode::EXECUTED);
-                    match test_info.expected_failure.as_ref() {
-                        Some(ExpectedFailure::Expected) => {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithError(expected_err))
-                            if expected_err == &actual_err =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(code))
-                            if actual_err.0 == StatusCode::ABORTED
-                                && actual_err.1.is_some()
-                                && actual_err.1.unwrap() == *code =>
-                        {
-                            output.pass(function_name);
-                            stats.test_success(test_run_info, test_plan);
-                        },
-                        // incorrect cases
-                        Some(ExpectedFailure::ExpectedWithError(expected_err)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_error(expected_err.clone(), actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        Some(ExpectedFailure::ExpectedWithCodeDEPRECATED(expected_code)) => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::wrong_abort_deprecated(
-                                        *expected_code,
-                                        actual_err,
-                                    ),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None if err.major_status() == StatusCode::OUT_OF_GAS => {
-                            // Ran out of ticks, report a test timeout and log a test failure
-                            output.timeout(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::timeout(),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                        None => {
-                            output.fail(function_name);
-                            stats.test_failure(
-                                TestFailure::new(
-                                    FailureReason::unexpected_error(actual_err),
-                                    test_run_info,
-                                    Some(err),
-                                    save_session_state(),
-                                ),
-                                test_plan,
-                            )
-                        },
-                    }
+            },
+        };
+
+        (result, writeset)
+    }
+
+    /// Runs a property-based test for each of the configured number of cases, generating the
+    /// arguments which are not assigned in the test attribute. The generated arguments of the
+    /// first failing case are shrunk to a minimal failing input.
+    fn run_property_test(
+        &self,
+        test_plan: &ModuleTestPlan,
+        module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
+        function_name: &str,
+        test_info: &TestCase,
+    ) -> std::result::Result<TestRunInfo, TestFailure> {
+        let mut total_run_info = TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0, 0);
+        let parameters = match property_test::generated_parameters(
+            module_info,
+            &test_plan.module_id,
+            function_name,
+            test_info,
+        ) {
+            Ok(parameters) => parameters,
+            Err(err) => {
+                return Err(TestFailure::new(
+                    FailureReason::property(format!(
+                        "Cannot generate the arguments of the test: {}",
+                        err
+                    )),
+                    total_run_info,
+                    None,
+                    None,
+                ))
+            },
+        };
+
+        let mut rng = self
+            .property_test_config
+            .rng_for_test(&test_plan.module_id, function_name);
+        for _ in 0..self.property_test_config.num_cases {
+            let generated: Vec<_> = parameters
+                .iter()
+                .map(|parameter| parameter.ty.generate(&mut rng))
+                .collect();
+            let arguments = test_info.arguments_with_generated(generated.clone());
+            match self.run_test_case(test_plan, function_name, test_info, &arguments) {
+                (Ok(test_run_info), _) => {
+                    total_run_info.elapsed_time += test_run_info.elapsed_time;
+                    total_run_info.instructions_executed += test_run_info.instructions_executed;
+                    total_run_info.gas_used += test_run_info.gas_used;
+                    total_run_info
+                        .debug_output
+                        .push_str(&test_run_info.debug_output);
                 },
-                Ok(_) => {
-                    // Expected the test to fail, but it executed
-                    if test_info.expected_failure.is_some() {
-                        output.fail(function_name);
-                        stats.test_failure(
-                            TestFailure::new(
-                                FailureReason::no_error(),
-                                test_run_info,
-                                None,
-                                save_session_state(),
-                            ),
-                            test_plan,
-                        )
-                    } else {
-                        // Expected the test to execute fully and it did
-                        output.pass(function_name);
-                        stats.test_success(test_run_info, test_plan);
-                    }
+                (Err(test_failure), _) => {
+                    return Err(self.shrink_failure(
+                        test_plan,
+                        function_name,
+                        test_info,
+                        &parameters,
+                        generated,
+                        test_failure,
+                    ))
                 },
             }
         }
+        Ok(total_run_info)
+    }
 
-        stats
+    /// Shrinks the generated arguments of a failing case of a property-based test, as long as
+    /// the test keeps failing, and reports the failure of the smallest arguments found.
+    fn shrink_failure(
+        &self,
+        test_plan: &ModuleTestPlan,
+        function_name: &str,
+        test_info: &TestCase,
+        parameters: &[GeneratedParameter],
+        mut generated: Vec<MoveValue>,
+        mut test_failure: TestFailure,
+    ) -> TestFailure {
+        let types: Vec<_> = parameters
+            .iter()
+            .map(|parameter| parameter.ty.clone())
+            .collect();
+        let mut runs = 0;
+        'shrink: while runs < MAX_SHRINK_RUNS {
+            for candidate in property_test::shrink_arguments(&types, &generated) {
+                if runs == MAX_SHRINK_RUNS {
+                    break 'shrink;
+                }
+                runs += 1;
+                let arguments = test_info.arguments_with_generated(candidate.clone());
+                if let (Err(candidate_failure), _) =
+                    self.run_test_case(test_plan, function_name, test_info, &arguments)
+                {
+                    generated = candidate;
+                    test_failure = candidate_failure;
+                    continue 'shrink;
+                }
+            }
+            // None of the simpler arguments fail the test
+            break;
+        }
+
+        let arguments = parameters
+            .iter()
+            .zip(&generated)
+            .map(|(parameter, value)| format!("{} = {}", parameter.name, value))
+            .join(", ");
+        TestFailure {
+            failure_reason: FailureReason::generated_arguments(
+                self.property_test_config.seed,
+                self.property_test_config.num_cases,
+                arguments,
+                test_failure.failure_reason,
+            ),
+            ..test_failure
+        }
     }
 
     #[cfg(feature = "evm-backend")]
@@ -506,6 +662,22 @@ impl SharedTestingConfig {
 
         let gen_options = move_to_yul::options::Options::default();
         for (function_name, test_info) in &test_plan.tests {
+            if test_info.is_property_test() {
+                output.fail(function_name);
+                stats.test_failure(
+                    TestFailure::new(
+                        FailureReason::property(
+                            "Property-based tests are not supported by the EVM backend".to_string(),
+                        ),
+                        TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0, 0),
+                        None,
+                        None,
+                    ),
+                    test_plan,
+                );
+                continue;
+            }
+
             let yul_code = match move_to_yul::generator::Generator::run_for_unit_test(
                 &gen_options,
                 &model,
@@ -521,7 +693,7 @@ impl SharedTestingConfig {
                     stats.test_failure(
                         TestFailure::new(
                             FailureReason::move_to_evm_error(diagnostics),
-                            TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0),
+                            TestRunInfo::new(function_name.to_string(), Duration::ZERO, 0, 0),
                             None,
                             None,
                         ),
@@ -540,7 +712,7 @@ impl SharedTestingConfig {
             };
 
             let test_run_info =
-                || -> TestRunInfo { TestRunInfo::new(function_name.to_string(), duration, 0) };
+                || -> TestRunInfo { TestRunInfo::new(function_name.to_string(), duration, 0, 0) };
 
             // TODO: gas/timeout
             // TODO: arguments
@@ -660,6 +832,7 @@ impl SharedTestingConfig {
     fn exec_module_tests(
         &self,
         test_plan: &ModuleTestPlan,
+        module_info: &BTreeMap<ModuleId, NamedCompiledModule>,
         writer: &Mutex<impl Write>,
     ) -> TestStatistics {
         let output = TestOutput { test_plan, writer };
@@ -669,6 +842,6 @@ impl SharedTestingConfig {
             return self.exec_module_tests_evm(test_plan, &output);
         }
 
-        self.exec_module_tests_move_vm_and_stackless_vm(test_plan, &output)
+        self.exec_module_tests_move_vm_and_stackless_vm(test_plan, module_info, &output)
     }
 }
@@ -5,17 +5,20 @@
 use move_command_line_common::testing::{
     add_update_baseline_fix, format_diff, read_env_update_baseline, EXP_EXT,
 };
-use move_unit_test::{self, UnitTestingConfig};
+use move_unit_test::{self, TestReportFormat, UnitTestingConfig};
 use regex::RegexBuilder;
 use std::{
     fs,
     path::{Path, PathBuf},
 };
+use tempfile::NamedTempFile;
 
 // We don't support statistics tests as that includes times which are variable and will make these
-// tests flaky.
+// tests flaky. The durations in the JSON and JUnit reports are zeroed out instead.
 const TEST_MODIFIER_STRS: &[&str] = &[
     "storage",
+    "json",
+    "junit",
     #[cfg(feature = "evm-backend")]
     "evm",
 ];
@@ -24,6 +27,8 @@ pub fn modify(mut base_config: UnitTestingConfig, modifier_str: &str) -> Option<
     // Add future test modifiers here
     match modifier_str {
         "storage" => base_config.report_storage_on_error = true,
+        "json" => base_config.report_format = TestReportFormat::Json,
+        "junit" => base_config.report_format = TestReportFormat::Junit,
         #[cfg(feature = "evm-backend")]
         "evm" => base_config.evm = true,
         _ => return None,
@@ -39,7 +44,7 @@ fn run_test_with_modifiers(
 
     for modifier in TEST_MODIFIER_STRS.iter() {
         let modified_exp_path = path.with_extension(format!("{}.{}", modifier, EXP_EXT));
-        if let (Some(test_config), true) = (
+        if let (Some(mut test_config), true) = (
             modify(unit_test_config.clone(), modifier),
             modified_exp_path.exists(),
         ) {
@@ -53,10 +58,17 @@ fn run_test_with_modifiers(
                 );
             }
 
-            results.push((
-                test_config.run_and_report_unit_tests(test_plan.unwrap(), None, None, buffer)?,
-                modified_exp_path,
-            ))
+            // The structured reports are written to a file, and appended to the output
+            let report_file = (test_config.report_format != TestReportFormat::Human)
+                .then(NamedTempFile::new)
+                .transpose()?;
+            test_config.report_file = report_file.as_ref().map(|file| file.path().to_path_buf());
+            let (mut buffer, ok) =
+                test_config.run_and_report_unit_tests(test_plan.unwrap(), None, None, buffer)?;
+            if let Some(report_file) = report_file {
+                buffer.extend(fs::read(report_file.path())?);
+            }
+            results.push(((buffer, ok), modified_exp_path))
         }
     }
 
@@ -98,10 +110,14 @@ fn run_test_impl(path: &Path) -> anyhow::Result<()> {
         .multi_line(true)
         .build()
         .unwrap();
+    let duration_regex = RegexBuilder::new(r#"("duration_secs": |time=")[0-9.e-]+"#)
+        .build()
+        .unwrap();
 
     for ((buffer, _), exp_path) in run_test_with_modifiers(unit_test_config, path)? {
         let base_output = String::from_utf8(buffer)?;
         let cleaned_output = regex.replacen(&base_output, 0, r"$1$2");
+        let cleaned_output = duration_regex.replacen(&cleaned_output, 0, "${1}0");
         if update_baseline {
             fs::write(&exp_path, &*cleaned_output)?
         }
@@ -0,0 +1,60 @@
+address 0x1 {
+module M {
+    use std::option::{Self, Option};
+    use std::signer;
+    use std::string::{Self, String};
+    use std::vector;
+
+    struct Point has drop {
+        x: u64,
+        y: u64,
+    }
+
+    #[test]
+    fun add_commutes(a: u32, b: u32) {
+        assert!((a as u64) + (b as u64) == (b as u64) + (a as u64), 0)
+    }
+
+    #[test]
+    fun reverse_twice(v: vector<u64>) {
+        let w = copy v;
+        vector::reverse(&mut w);
+        vector::reverse(&mut w);
+        assert!(w == v, 0)
+    }
+
+    #[test(s = @0x1)]
+    fun assigned_and_generated(s: signer, flag: bool) {
+        assert!(signer::address_of(&s) == @0x1 || flag || !flag, 0)
+    }
+
+    #[test]
+    fun string_length(s: String) {
+        assert!(string::length(&s) == vector::length(string::bytes(&s)), 0)
+    }
+
+    #[test]
+    fun option_is_some_or_none(o: Option<u8>) {
+        assert!(option::is_some(&o) != option::is_none(&o), 0)
+    }
+
+    #[test]
+    fun point_fields(p: Point) {
+        let Point { x, y } = p;
+        assert!((x as u128) + (y as u128) >= (x as u128), 0)
+    }
+
+    #[test]
+    #[expected_failure(abort_code = 7, location = Self)]
+    fun always_aborts(x: u256, a: address) {
+        let _ = x;
+        let _ = a;
+        abort 7
+    }
+
+    #[test]
+    fun bounded(x: u64) {
+        assert!(x < 100, 0)
+    }
+}
+}
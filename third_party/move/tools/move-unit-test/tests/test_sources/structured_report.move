@@ -0,0 +1,21 @@
+address 0x1 {
+module M {
+    #[test]
+    public fun aborts() {
+        abort 7
+    }
+
+    #[test]
+    public fun passes() {}
+
+    #[test]
+    public fun prints() {
+        std::debug::print(&42);
+    }
+
+    #[test]
+    public fun prints_control_character() {
+        std::debug::print(&std::string::utf8(b"\x00"));
+    }
+}
+}
@@ -74,7 +74,7 @@ impl fmt::Debug for StateKeyInner {
 }
 
 #[repr(u8)]
-#[derive(Clone, Debug, FromPrimitive, ToPrimitive)]
+#[derive(Clone, Debug, Deserialize, Eq, FromPrimitive, Hash, PartialEq, Serialize, ToPrimitive)]
 pub enum StateKeyTag {
     AccessPath,
     TableItem,